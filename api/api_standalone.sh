@@ -12,6 +12,8 @@ fi
 DB_PATH="$DB_FOLDER"/knowledge-focus.db
 # echo $DB_PATH
 
+# 配合 Tauri 端使用时，以 KNOWLEDGE_FOCUS_EXTERNAL_API=1 启动应用即可连接此进程而不再启动sidecar
+
 
 uv run main.py \
 --port 60315 \
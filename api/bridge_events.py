@@ -2,7 +2,8 @@
 桥接事件发送工具模块
 
 此模块提供统一的方法来向Tauri前端发送事件通知。
-事件通过标准输出发送，Rust桥接层会捕获并转发给TypeScript前端。
+Rust桥接层通过 /events/stream (SSE) 订阅事件；没有订阅者时（例如Rust尚未连接）
+事件退回到标准输出发送，由Rust解析 EVENT_NOTIFY_JSON 前缀后转发给TypeScript前端。
 
 使用方法:
     from bridge_events import BridgeEventSender
@@ -15,6 +16,8 @@ import json
 import time
 import logging
 import sys
+import asyncio
+import threading
 from typing import Dict, Any, List
 from enum import Enum
 
@@ -37,6 +40,75 @@ def test_stdout_accessibility():
         logger.error(f"原始stdout不可访问: {e}")
         return False
 
+# SSE订阅者列表，元素为 (事件循环, asyncio.Queue)
+# send_event 可能在任务线程中调用，因此通过 call_soon_threadsafe 投递到订阅者所在的事件循环
+_subscribers_lock = threading.Lock()
+_subscribers: List[tuple] = []
+
+def subscribe_events(maxsize: int = 1000) -> asyncio.Queue:
+    """注册一个SSE订阅者，必须在事件循环中调用"""
+    loop = asyncio.get_running_loop()
+    queue: asyncio.Queue = asyncio.Queue(maxsize=maxsize)
+    with _subscribers_lock:
+        _subscribers.append((loop, queue))
+    logger.info(f"桥接事件订阅者已连接，当前订阅者数量: {len(_subscribers)}")
+    return queue
+
+def unsubscribe_events(queue: asyncio.Queue):
+    """移除SSE订阅者"""
+    with _subscribers_lock:
+        _subscribers[:] = [(loop, q) for loop, q in _subscribers if q is not queue]
+    logger.info(f"桥接事件订阅者已断开，当前订阅者数量: {len(_subscribers)}")
+
+# 订阅队列已满、改为通过stdout发送的事件数
+_overflow_count = 0
+_overflow_lock = threading.Lock()
+
+def _write_to_stdout(event_data: Dict[str, Any]):
+    """直接写入原始stdout，绕过任何可能的重定向，确保Rust端能够捕获到输出"""
+    _ORIGINAL_STDOUT.write(f"EVENT_NOTIFY_JSON:{json.dumps(event_data)}\n")
+    _ORIGINAL_STDOUT.flush()
+
+def _put_nowait_or_fallback(queue: asyncio.Queue, event_data: Dict[str, Any]):
+    """在订阅者的事件循环中入队；队列已满时退回stdout发送，不丢弃事件"""
+    global _overflow_count
+    try:
+        queue.put_nowait(event_data)
+    except asyncio.QueueFull:
+        with _overflow_lock:
+            _overflow_count += 1
+            overflow_count = _overflow_count
+        logger.warning(
+            f"桥接事件订阅队列已满，改为通过stdout发送: {event_data.get('event')}（累计 {overflow_count} 个）"
+        )
+        try:
+            _write_to_stdout(event_data)
+        except Exception as e:
+            logger.error(f"桥接事件退回stdout发送失败，事件丢失 [{event_data.get('event')}]: {e}")
+
+def get_overflow_count() -> int:
+    """订阅队列已满、改为通过stdout发送的事件数"""
+    return _overflow_count
+
+def _publish_to_subscribers(event_data: Dict[str, Any]) -> bool:
+    """投递事件到所有SSE订阅者，返回是否至少投递给一个订阅者
+
+    队列已满的订阅者不计入；投递后才变满的由 _put_nowait_or_fallback 退回stdout发送。
+    """
+    with _subscribers_lock:
+        subscribers = list(_subscribers)
+    delivered = False
+    for loop, queue in subscribers:
+        if queue.full():
+            continue
+        try:
+            loop.call_soon_threadsafe(_put_nowait_or_fallback, queue, event_data)
+            delivered = True
+        except RuntimeError:
+            # 事件循环已关闭，订阅者失效
+            unsubscribe_events(queue)
+    return delivered
+
 class BridgeEventSender:
     """桥接事件发送器"""
     
@@ -101,10 +173,9 @@ class BridgeEventSender:
                 "payload": self._enrich_payload(payload or {})
             }
             
-            # 直接写入原始stdout，绕过任何可能的重定向
-            # 使用原始stdout确保Rust端能够捕获到输出
-            _ORIGINAL_STDOUT.write(f"EVENT_NOTIFY_JSON:{json.dumps(event_data)}\n")
-            _ORIGINAL_STDOUT.flush()
+            # 优先通过SSE通道发送，没有订阅者时退回stdout
+            if not _publish_to_subscribers(event_data):
+                _write_to_stdout(event_data)
             
             logger.debug(f"桥接事件已发送: {event_name} from {self.source}")
             
@@ -0,0 +1,80 @@
+from fastapi import APIRouter, Depends, Body
+from sqlalchemy import Engine
+from typing import Dict, Any, Callable
+from clipboard_mgr import ClipboardManager
+import logging
+logger = logging.getLogger()
+
+def get_router(get_engine: Callable[[], Engine]) -> APIRouter:
+    router = APIRouter()
+
+    def get_clipboard_manager(engine: Engine = Depends(get_engine)) -> ClipboardManager:
+        return ClipboardManager(engine=engine)
+
+    @router.post("/clipboard/items")
+    def add_clipboard_items(
+        request: Dict[str, Any] = Body(...),
+        clipboard_mgr: ClipboardManager = Depends(get_clipboard_manager)
+    ):
+        """保存Rust端捕获的剪贴板内容
+        
+        请求体:
+        - items: [{"kind": "text"|"files", "text", "file_paths", "source_app", "content_hash", "captured_at"}, ...]
+        """
+        try:
+            saved_count = clipboard_mgr.add_items(request.get("items", []))
+            return {
+                "success": True,
+                "saved_count": saved_count,
+                "message": f"Saved {saved_count} clipboard items"
+            }
+        except Exception as e:
+            logger.error(f"保存剪贴板记录失败: {str(e)}")
+            return {
+                "success": False,
+                "message": f"save clipboard items failed: {str(e)}"
+            }
+
+    @router.get("/clipboard/items")
+    def get_clipboard_items(
+        limit: int = 100,
+        clipboard_mgr: ClipboardManager = Depends(get_clipboard_manager)
+    ):
+        """按最近复制时间倒序获取剪贴板记录"""
+        try:
+            items = clipboard_mgr.get_recent_items(limit)
+            return {
+                "success": True,
+                "data": [item.model_dump() for item in items],
+                "count": len(items)
+            }
+        except Exception as e:
+            logger.error(f"获取剪贴板记录失败: {str(e)}")
+            return {
+                "success": False,
+                "message": f"get clipboard items failed: {str(e)}"
+            }
+
+    @router.delete("/clipboard/items/{item_id}")
+    def delete_clipboard_item(
+        item_id: int,
+        clipboard_mgr: ClipboardManager = Depends(get_clipboard_manager)
+    ):
+        """删除一条剪贴板记录"""
+        if clipboard_mgr.delete_item(item_id):
+            return {"success": True, "message": f"Deleted clipboard item {item_id}"}
+        return {"success": False, "message": f"Clipboard item {item_id} not found"}
+
+    @router.delete("/clipboard/items")
+    def clear_clipboard_items(
+        clipboard_mgr: ClipboardManager = Depends(get_clipboard_manager)
+    ):
+        """清空所有剪贴板记录"""
+        try:
+            deleted_count = clipboard_mgr.clear_items()
+            return {"success": True, "deleted_count": deleted_count}
+        except Exception as e:
+            logger.error(f"清空剪贴板记录失败: {str(e)}")
+            return {"success": False, "message": f"clear clipboard items failed: {str(e)}"}
+
+    return router
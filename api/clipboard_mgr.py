@@ -0,0 +1,83 @@
+from sqlmodel import Session, select, delete
+from sqlalchemy import Engine
+from db_mgr import ClipboardItem
+from datetime import datetime
+from typing import List, Dict, Any
+import logging
+
+logger = logging.getLogger()
+
+class ClipboardManager:
+    """剪贴板记录管理类
+    
+    保存Rust端捕获的剪贴板文本和文件，内容相同的复制合并为一条记录并累计次数。
+    """
+    def __init__(self, engine: Engine) -> None:
+        self.engine = engine
+
+    def add_items(self, items: List[Dict[str, Any]]) -> int:
+        """保存捕获的剪贴板内容
+        
+        Args:
+            items: [{"kind", "text", "file_paths", "source_app", "content_hash", "captured_at": 秒级时间戳}, ...]
+            
+        Returns:
+            保存的记录数
+        """
+        saved_count = 0
+        with Session(self.engine) as session:
+            try:
+                for item in items:
+                    content_hash = item.get("content_hash")
+                    if not content_hash or item.get("kind") not in ("text", "files"):
+                        continue
+                    captured_at = item.get("captured_at")
+                    captured_at = datetime.fromtimestamp(captured_at) if captured_at else datetime.now()
+                    existing = session.exec(
+                        select(ClipboardItem).where(ClipboardItem.content_hash == content_hash)
+                    ).first()
+                    if existing:
+                        existing.copy_count += 1
+                        existing.captured_at = captured_at
+                        existing.source_app = item.get("source_app") or existing.source_app
+                        session.add(existing)
+                    else:
+                        session.add(ClipboardItem(
+                            kind=item["kind"],
+                            text=item.get("text"),
+                            file_paths=item.get("file_paths"),
+                            source_app=item.get("source_app"),
+                            content_hash=content_hash,
+                            captured_at=captured_at,
+                        ))
+                    saved_count += 1
+                session.commit()
+                return saved_count
+            except Exception as e:
+                session.rollback()
+                logger.error(f"Failed to save clipboard items: {str(e)}")
+                raise
+
+    def get_recent_items(self, limit: int = 100) -> List[ClipboardItem]:
+        """按最近复制时间倒序获取剪贴板记录"""
+        with Session(self.engine) as session:
+            return session.exec(
+                select(ClipboardItem).order_by(ClipboardItem.captured_at.desc()).limit(limit)
+            ).all()
+
+    def delete_item(self, item_id: int) -> bool:
+        """删除一条剪贴板记录"""
+        with Session(self.engine) as session:
+            item = session.get(ClipboardItem, item_id)
+            if not item:
+                return False
+            session.delete(item)
+            session.commit()
+            return True
+
+    def clear_items(self) -> int:
+        """清空所有剪贴板记录"""
+        with Session(self.engine) as session:
+            result = session.exec(delete(ClipboardItem))
+            session.commit()
+            return result.rowcount
@@ -0,0 +1,107 @@
+"""
+控制命令通道模块
+
+与 bridge_events 的单向 stdout 事件桥接互补，Rust 通过 sidecar 的标准输入向 Python 发送控制命令。
+每条命令占一行，格式为:
+    CONTROL_COMMAND_JSON:{"id": "...", "command": "pause_workers", "args": {}}
+
+命令执行结果通过桥接事件 control-command-result 返回，payload 包含 id、command、status、message。
+外部API模式下没有可写的标准输入，Rust 改为调用 POST /control，使用同一个分发函数。
+
+支持的命令:
+    pause_workers    暂停后台任务处理线程（正在处理的任务会继续完成）
+    resume_workers   恢复后台任务处理线程
+    set_log_level    调整日志级别，args: {"level": "DEBUG" | "INFO" | "WARNING" | "ERROR"}
+    trigger_reindex  创建批量打标签任务，重新处理待处理的文件
+"""
+
+import json
+import logging
+import sys
+import threading
+from typing import Any, Callable, Dict
+
+from bridge_events import BridgeEventSender
+
+logger = logging.getLogger()
+
+CONTROL_PREFIX = "CONTROL_COMMAND_JSON:"
+
+# 设置时表示后台任务处理线程暂停领取新任务
+_workers_paused = threading.Event()
+
+def workers_paused() -> bool:
+    """后台任务处理线程是否被暂停"""
+    return _workers_paused.is_set()
+
+def handle_control_command(command: str, args: Dict[str, Any], get_engine: Callable) -> Dict[str, Any]:
+    """执行一条控制命令，返回 {"status": ..., "message": ...}"""
+    try:
+        if command == "pause_workers":
+            _workers_paused.set()
+            logger.info("Background task workers paused by control command")
+            return {"status": "success", "message": "后台任务处理已暂停"}
+
+        if command == "resume_workers":
+            _workers_paused.clear()
+            logger.info("Background task workers resumed by control command")
+            return {"status": "success", "message": "后台任务处理已恢复"}
+
+        if command == "set_log_level":
+            level_name = str(args.get("level", "")).upper()
+            level = logging.getLevelName(level_name)
+            if not isinstance(level, int):
+                return {"status": "error", "message": f"无效的日志级别: {level_name}"}
+            root_logger = logging.getLogger()
+            root_logger.setLevel(level)
+            for handler in root_logger.handlers:
+                handler.setLevel(level)
+            logger.info(f"Log level changed to {level_name} by control command")
+            return {"status": "success", "message": f"日志级别已设置为 {level_name}"}
+
+        if command == "trigger_reindex":
+            from task_mgr import TaskManager
+            from db_mgr import TaskType, TaskPriority
+            task_mgr = TaskManager(engine=get_engine())
+            task = task_mgr.add_task(
+                task_name="reindex pending files",
+                task_type=TaskType.TAGGING,
+                priority=TaskPriority.MEDIUM,
+                extra_data={"trigger": "control_command"},
+            )
+            logger.info(f"Reindex task created by control command: ID={task.id}")
+            return {"status": "success", "message": f"已创建重新处理任务: {task.id}", "task_id": task.id}
+
+        return {"status": "error", "message": f"未知的控制命令: {command}"}
+    except Exception as e:
+        logger.error(f"执行控制命令 {command} 失败: {e}", exc_info=True)
+        return {"status": "error", "message": str(e)}
+
+def _listen_stdin(get_engine: Callable):
+    """逐行读取标准输入中的控制命令，标准输入关闭时退出"""
+    sender = BridgeEventSender(source="control-channel")
+    for line in sys.stdin:
+        line = line.strip()
+        if not line.startswith(CONTROL_PREFIX):
+            continue
+        try:
+            request = json.loads(line[len(CONTROL_PREFIX):])
+        except json.JSONDecodeError as e:
+            logger.warning(f"无法解析控制命令: {e} - {line}")
+            continue
+
+        command = request.get("command", "")
+        result = handle_control_command(command, request.get("args") or {}, get_engine)
+        sender.send_event("control-command-result", {
+            "id": request.get("id"),
+            "command": command,
+            **result,
+        })
+    logger.info("Control channel stdin closed, listener exiting")
+
+def start_stdin_listener(get_engine: Callable):
+    """启动标准输入控制命令监听线程"""
+    thread = threading.Thread(target=_listen_stdin, args=(get_engine,), daemon=True, name="control-channel")
+    thread.start()
+    logger.info("Control channel stdin listener has started")
+    return thread
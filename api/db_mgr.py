@@ -285,6 +285,30 @@ class FileScreeningResult(SQLModel, table=True):
             datetime: lambda v: v.strftime("%Y-%m-%d %H:%M:%S"),
         }
 
+# 文件文本表 - 存储Rust端从文本、Markdown、代码和PDF中提取的规范化文本
+class FileContent(SQLModel, table=True):
+    __tablename__ = "t_file_contents"
+    id: int = Field(default=None, primary_key=True)
+    file_path: str = Field(index=True, unique=True)  # 文件完整路径
+    content_kind: str  # 内容类型: text, markdown, code, pdf, image（图片识别出的文字）
+    text: str  # 规范化后的文本
+    char_count: int  # 文本字符数
+    truncated: bool = Field(default=False)  # 文本是否因超出长度上限被截断
+    modified_time: datetime | None = Field(default=None)  # 提取时文件的修改时间
+    extracted_at: datetime = Field(default_factory=datetime.now)  # 提取时间
+
+# 剪贴板记录表 - 存储Rust端捕获的剪贴板文本和文件（用户开启剪贴板捕获后）
+class ClipboardItem(SQLModel, table=True):
+    __tablename__ = "t_clipboard_items"
+    id: int = Field(default=None, primary_key=True)
+    kind: str  # 内容类型: text 或 files
+    text: str | None = Field(default=None)  # 复制的文本
+    file_paths: List[str] | None = Field(default=None, sa_column=Column(JSON))  # 复制的文件路径列表
+    source_app: str | None = Field(default=None)  # 复制时的前台应用（可获取时）
+    content_hash: str = Field(index=True)  # 内容哈希，用于合并重复复制
+    copy_count: int = Field(default=1)  # 同一内容被复制的次数
+    captured_at: datetime = Field(default_factory=datetime.now, index=True)  # 最近一次复制的时间
+
 # 文档表
 # 用于记录被处理的原始文件信息。
 # 设计意图: 管理最原始的入口文件，file_hash能避免重复处理未变更的文件，status字段则可以支持异步处理和失败重试机制。
@@ -570,6 +594,14 @@ class DBManager:
                 # 创建索引 - 为tags_display_ids创建索引，便于统计已打标签的文件
                 session.exec(text(f'CREATE INDEX IF NOT EXISTS idx_tags_display_ids ON {FileScreeningResult.__tablename__} (tags_display_ids);'))
 
+            # 创建文件文本表
+            if not inspector.has_table(FileContent.__tablename__):
+                FileContent.__table__.create(self.engine, checkfirst=True)
+
+            # 创建剪贴板记录表
+            if not inspector.has_table(ClipboardItem.__tablename__):
+                ClipboardItem.__table__.create(self.engine, checkfirst=True)
+
             # 创建 FTS5 虚拟表和触发器
             if not inspector.has_table('t_files_fts'):
                 session.exec(text("""
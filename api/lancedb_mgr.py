@@ -1,5 +1,6 @@
 from config import singleton, EMBEDDING_DIMENSIONS
 import lancedb
+import pyarrow as pa
 from lancedb.pydantic import LanceModel, Vector
 from typing import List
 import os
@@ -34,6 +35,7 @@ class LanceDBMgr:
         self.db = lancedb.connect(self.uri)
         self.tags_tbl = None
         self.vectors_tbl = None
+        self.file_embeddings_tbl = None
 
     def init_tags_table(self, table_name: str = "tags"):
         """Initializes the LanceDB table for tags."""
@@ -83,6 +85,43 @@ class LanceDBMgr:
             logger.error(f"Failed to initialize LanceDB vectors table: {e}")
             raise
 
+    def init_file_embeddings_table(self, dimensions: int, table_name: str = "file_embeddings"):
+        """Initializes the LanceDB table for embeddings computed locally by the Rust side.
+
+        向量维度取决于本地模型，换用维度不同的模型时删除旧表重建。
+        """
+        if table_name in self.db.table_names():
+            tbl = self.db.open_table(table_name)
+            if tbl.schema.field("vector").type.list_size == dimensions:
+                self.file_embeddings_tbl = tbl
+                return
+            logger.warning(f"Embedding dimensions changed to {dimensions}. Dropping existing table '{table_name}' and recreating...")
+            self.db.drop_table(table_name)
+        schema = pa.schema([
+            pa.field("file_path", pa.string()),
+            pa.field("chunk_index", pa.int32()),
+            pa.field("model", pa.string()),
+            pa.field("text", pa.string()),
+            pa.field("vector", pa.list_(pa.float32(), dimensions)),
+        ])
+        self.file_embeddings_tbl = self.db.create_table(table_name, schema=schema)
+        logger.info(f"LanceDB file embeddings table '{table_name}' created with {dimensions} dimensions at {self.uri}")
+
+    def replace_file_embeddings(self, file_path: str, records: List[dict]):
+        """
+        Replaces all embeddings of a file with the given records.
+
+        Args:
+            file_path: The file the embeddings belong to.
+            records: A list of dictionaries with 'chunk_index', 'model', 'text' and 'vector'.
+        """
+        if not records:
+            return
+        self.init_file_embeddings_table(len(records[0]["vector"]))
+        escaped_path = file_path.replace("'", "''")
+        self.file_embeddings_tbl.delete(f"file_path = '{escaped_path}'")
+        self.file_embeddings_tbl.add([{**record, "file_path": file_path} for record in records])
+
     def add_tags(self, tags_data: List[dict]):
         """
         Adds or updates tags in the LanceDB table.
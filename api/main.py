@@ -5,12 +5,16 @@ import logging
 import time
 import threading
 import signal
+import socket
 import asyncio
+import hmac
 from datetime import datetime
-from typing import Dict, Any
+from typing import Dict, Any, Optional
 from pathlib import Path
 from contextlib import asynccontextmanager
-from fastapi import FastAPI, Body, Depends
+import json
+from fastapi import FastAPI, Body, Depends, Request, Header, HTTPException
+from fastapi.responses import StreamingResponse
 from fastapi.middleware.cors import CORSMiddleware
 import uvicorn
 from utils import kill_process_on_port, monitor_parent, kill_orphaned_processes
@@ -32,6 +36,9 @@ from lancedb_mgr import LanceDBMgr
 from file_tagging_mgr import FileTaggingMgr, configure_parsing_warnings
 from multivector_mgr import MultiVectorMgr
 from task_mgr import TaskManager
+from bridge_events import subscribe_events, unsubscribe_events
+from control_channel import handle_control_command, start_stdin_listener, workers_paused
+from request_decompression import RequestDecompressionMiddleware
 # API路由导入将在lifespan函数中进行
 
 # # 初始化logger
@@ -246,6 +253,12 @@ async def lifespan(app: FastAPI):
         except Exception as monitor_err:
             logger.error(f"启动父进程监控线程失败: {str(monitor_err)}", exc_info=True)
 
+        # 监听Tauri通过标准输入发送的控制命令
+        try:
+            start_stdin_listener(get_engine)
+        except Exception as control_err:
+            logger.error(f"启动控制命令监听线程失败: {str(control_err)}", exc_info=True)
+
         # 配置解析库的警告和日志级别
         try:
             configure_parsing_warnings()
@@ -267,6 +280,7 @@ async def lifespan(app: FastAPI):
             from unified_tools_api import get_router as get_tools_router
             from documents_api import get_router as get_documents_router
             from user_api import get_router as get_user_router
+            from clipboard_api import get_router as get_clipboard_router
             
             # 注册各个API路由
             models_router = get_models_router(get_engine=get_engine, base_dir=app.state.db_directory)
@@ -293,6 +307,9 @@ async def lifespan(app: FastAPI):
             documents_router = get_documents_router(get_engine=get_engine, base_dir=app.state.db_directory)
             app.include_router(documents_router, prefix="", tags=["documents"])
             
+            clipboard_router = get_clipboard_router(get_engine=get_engine)
+            app.include_router(clipboard_router, prefix="", tags=["clipboard"])
+            
             # 用户认证相关路由
             user_router = get_user_router(get_engine=get_engine)
             app.include_router(user_router, prefix="", tags=["user", "auth"])
@@ -432,6 +449,8 @@ app.add_middleware(
     allow_methods=["*"],    # Allows all methods (GET, POST, PUT, DELETE, etc.)
     allow_headers=["*"],    # Allows all headers
 )
+# 解压 Rust 端压缩发送的大批量请求体（Content-Encoding: gzip / zstd）
+app.add_middleware(RequestDecompressionMiddleware)
 
 def get_engine():
     """FastAPI依赖函数，用于获取数据库引擎"""
@@ -559,6 +578,11 @@ def _generic_task_processor(engine, db_directory: str, stop_event: threading.Eve
         task_id = None
         task_to_process = None
 
+        # 收到 pause_workers 控制命令后暂停领取新任务
+        if workers_paused():
+            time.sleep(1)
+            continue
+
         try:
             # --- 获取并锁定任务 ---
             # 获取任务并标记为处理中
@@ -891,9 +915,73 @@ def health_check():
     """API健康检查端点，用于验证API服务是否正常运行"""
     return {
         "status": "ok", 
+        "service": "knowledge-focus-api",  # Tauri外部API模式据此识别本服务
+        "timestamp": datetime.now().isoformat(),
+    }
+
+# API接口版本，请求/响应结构发生不兼容变化时递增，需与 Tauri 端 EXPECTED_API_SCHEMA_VERSION 保持一致
+API_SCHEMA_VERSION = 1
+
+@app.get("/version")
+def get_version():
+    """API版本信息，Tauri启动时据此检查前后端兼容性"""
+    try:
+        import tomllib
+        with open(Path(__file__).parent / "pyproject.toml", "rb") as f:
+            api_version = tomllib.load(f)["project"]["version"]
+    except Exception:
+        api_version = "unknown"
+    return {
+        "api_version": api_version,
+        "schema_version": API_SCHEMA_VERSION,
+    }
+
+# 本次启动的关闭令牌，由Tauri通过环境变量传入；未传入时（如独立运行）不接受关闭请求
+SHUTDOWN_TOKEN = os.environ.get("KF_SHUTDOWN_TOKEN")
+
+# 优雅关闭端点，供Tauri退出时在终止进程前调用
+@app.post("/shutdown")
+def shutdown_api(x_shutdown_token: Optional[str] = Header(default=None)):
+    """在返回响应后向自身发送SIGTERM，由uvicorn完成lifespan收尾，避免数据库写入被截断
+
+    请求需携带 X-Shutdown-Token 头；自定义请求头也使浏览器跨域请求必须先经过CORS预检，网页无法直接关闭API
+    """
+    if not SHUTDOWN_TOKEN or not x_shutdown_token or not hmac.compare_digest(x_shutdown_token, SHUTDOWN_TOKEN):
+        logger.warning("拒绝关闭请求：关闭令牌无效")
+        raise HTTPException(status_code=403, detail="关闭令牌无效")
+    logger.info("收到关闭请求，准备优雅关闭API服务")
+    threading.Timer(0.5, lambda: os.kill(os.getpid(), signal.SIGTERM)).start()
+    return {
+        "status": "shutting_down",
         "timestamp": datetime.now().isoformat(),
     }
 
+# 控制命令端点，外部API模式下Tauri无法写入标准输入时使用
+@app.post("/control")
+def control_command(request: Dict[str, Any] = Body(...)):
+    """执行控制命令，请求体为 {"command": ..., "args": {...}}，命令列表见 control_channel.py"""
+    return handle_control_command(request.get("command", ""), request.get("args") or {}, get_engine)
+
+# 桥接事件SSE通道，Tauri通过此端点订阅事件，替代解析stdout
+@app.get("/events/stream")
+async def events_stream(request: Request):
+    """以 Server-Sent Events 推送桥接事件，每条消息的 data 为 {"event": ..., "payload": ...}"""
+    queue = subscribe_events()
+
+    async def event_generator():
+        try:
+            while not await request.is_disconnected():
+                try:
+                    event_data = await asyncio.wait_for(queue.get(), timeout=15)
+                    yield f"data: {json.dumps(event_data)}\n\n"
+                except asyncio.TimeoutError:
+                    # 心跳，便于双方发现断开的连接
+                    yield ": keepalive\n\n"
+        finally:
+            unsubscribe_events(queue)
+
+    return StreamingResponse(event_generator(), media_type="text/event-stream")
+
 @app.get("/system-config/{config_key}")
 def get_system_config(config_key: str, engine: Engine = Depends(get_engine)):
     """获取系统配置
@@ -1090,6 +1178,15 @@ def signal_handler(signum, frame):
         print(f"信号处理器清理进程失败: {e}")
     sys.exit(0)
 
+def bind_unix_socket(path: str) -> socket.socket:
+    """绑定Unix套接字，只允许当前用户访问；上次运行残留的套接字文件先删除"""
+    if os.path.exists(path):
+        os.remove(path)
+    sock = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+    sock.bind(path)
+    os.chmod(path, 0o600)
+    return sock
+
 if __name__ == "__main__":
     try:
         # 注册信号处理器
@@ -1100,6 +1197,7 @@ if __name__ == "__main__":
         parser.add_argument("--port", type=int, default=60315, help="API服务监听端口")
         parser.add_argument("--host", type=str, default="127.0.0.1", help="API服务监听地址")
         parser.add_argument("--db-path", type=str, default="knowledge-focus.db", help="数据库文件路径")
+        parser.add_argument("--uds", type=str, default=None, help="额外监听的Unix套接字路径（可选，仅Unix系统）")
         args = parser.parse_args()
 
         print("API服务程序启动")
@@ -1121,14 +1219,25 @@ if __name__ == "__main__":
         # 启动服务器
         print(f"API服务启动在: http://{args.host}:{args.port}")
         # 配置uvicorn日志，防止覆盖我们的日志配置
-        uvicorn.run(
-            app, 
-            host=args.host, 
-            port=args.port, 
+        config = uvicorn.Config(
+            app,
+            host=args.host,
+            port=args.port,
             log_level="info",
             access_log=False,  # 禁用uvicorn的访问日志，使用我们自己的
             use_colors=False   # 禁用颜色输出，保持日志文件的整洁
         )
+        if args.uds:
+            # 同时监听TCP端口（前端页面使用）和Unix套接字（Tauri的Rust端使用）
+            print(f"API服务同时监听Unix套接字: {args.uds}")
+            sockets = [config.bind_socket(), bind_unix_socket(args.uds)]
+            try:
+                uvicorn.Server(config).run(sockets=sockets)
+            finally:
+                if os.path.exists(args.uds):
+                    os.remove(args.uds)
+        else:
+            uvicorn.Server(config).run()
     
     except Exception as e:
         print(f"API服务启动失败: {str(e)}")
@@ -0,0 +1,166 @@
+"""
+请求体解压中间件
+
+Rust 端在初始扫描时向 /file-screening/batch 推送的 JSON 可达数十 MB，超过阈值时会压缩后发送，
+并通过 Content-Encoding 请求头标明编码。本中间件在请求进入路由之前解压请求体，
+路由函数无需感知压缩。
+
+支持的编码:
+    gzip  标准库 gzip
+    zstd  Python 3.14 的 compression.zstd，或已安装的 zstandard 包；两者都没有时返回 415
+
+不支持的编码返回 415，解压失败返回 400。解压采用流式读取，
+压缩或解压后的大小超过 MAX_DECOMPRESSED_BYTES 时立即停止并返回 413，防止压缩炸弹耗尽内存。
+"""
+
+import gzip
+import io
+import logging
+
+logger = logging.getLogger()
+
+# 解压后请求体的大小上限
+MAX_DECOMPRESSED_BYTES = 256 * 1024 * 1024
+# 每次从解压流读取的字节数
+_READ_SIZE = 1024 * 1024
+
+try:
+    from compression import zstd as _zstd  # Python 3.14+
+
+    def _open_zstd(fileobj):
+        return _zstd.ZstdFile(fileobj)
+except ImportError:
+    try:
+        import zstandard as _zstandard
+
+        def _open_zstd(fileobj):
+            # 流式压缩的帧头中可能没有原始大小，使用流式解压
+            return _zstandard.ZstdDecompressor().stream_reader(fileobj, read_across_frames=True)
+    except ImportError:
+        _open_zstd = None
+
+
+class DecompressedTooLarge(Exception):
+    """解压后的请求体超过大小上限"""
+
+
+def _decompress(encoding: str, body: bytes, max_size: int) -> bytes:
+    """流式解压，输出超过 max_size 时抛出 DecompressedTooLarge"""
+    if encoding == "gzip":
+        reader = gzip.GzipFile(fileobj=io.BytesIO(body))
+    elif encoding == "zstd":
+        reader = _open_zstd(io.BytesIO(body))
+    else:
+        raise ValueError(f"unsupported encoding: {encoding}")
+
+    chunks = []
+    total = 0
+    with reader:
+        while True:
+            chunk = reader.read(_READ_SIZE)
+            if not chunk:
+                break
+            total += len(chunk)
+            if total > max_size:
+                raise DecompressedTooLarge()
+            chunks.append(chunk)
+    return b"".join(chunks)
+
+
+def supported_encodings() -> list:
+    encodings = ["gzip"]
+    if _open_zstd is not None:
+        encodings.append("zstd")
+    return encodings
+
+
+class RequestDecompressionMiddleware:
+    """解压带有 Content-Encoding 的请求体（纯 ASGI 中间件，不影响流式响应）"""
+
+    def __init__(self, app, max_size: int = MAX_DECOMPRESSED_BYTES):
+        self.app = app
+        self.max_size = max_size
+
+    async def __call__(self, scope, receive, send):
+        if scope["type"] != "http":
+            await self.app(scope, receive, send)
+            return
+
+        headers = scope.get("headers", [])
+        encoding = None
+        for name, value in headers:
+            if name == b"content-encoding":
+                encoding = value.decode("latin-1").strip().lower()
+                break
+        if not encoding or encoding == "identity":
+            await self.app(scope, receive, send)
+            return
+
+        if encoding not in supported_encodings():
+            logger.warning(f"Unsupported request Content-Encoding: {encoding}")
+            await self._reject(send, 415, f"Unsupported Content-Encoding: {encoding}")
+            return
+
+        # 读取完整的压缩请求体，压缩数据本身超过上限时不再继续读取
+        chunks = []
+        received = 0
+        more_body = True
+        while more_body:
+            message = await receive()
+            if message["type"] == "http.disconnect":
+                return
+            chunk = message.get("body", b"")
+            received += len(chunk)
+            if received > self.max_size:
+                logger.warning(f"Compressed {encoding} request body exceeds {self.max_size} bytes")
+                await self._reject(send, 413, "Request body too large")
+                return
+            chunks.append(chunk)
+            more_body = message.get("more_body", False)
+        compressed = b"".join(chunks)
+
+        try:
+            body = _decompress(encoding, compressed, self.max_size)
+        except DecompressedTooLarge:
+            logger.warning(f"Decompressed {encoding} request body exceeds {self.max_size} bytes")
+            await self._reject(send, 413, "Request body too large")
+            return
+        except Exception as e:
+            logger.warning(f"Failed to decompress {encoding} request body: {e}")
+            await self._reject(send, 400, f"Invalid {encoding} request body")
+            return
+
+        logger.debug(f"Decompressed {encoding} request body: {len(compressed)} -> {len(body)} bytes")
+
+        # 去掉编码头并更新长度，下游按普通 JSON 请求处理
+        new_headers = [
+            (name, value)
+            for name, value in headers
+            if name not in (b"content-encoding", b"content-length")
+        ]
+        new_headers.append((b"content-length", str(len(body)).encode("latin-1")))
+        scope = dict(scope, headers=new_headers)
+
+        body_sent = False
+
+        async def receive_decompressed():
+            nonlocal body_sent
+            if body_sent:
+                return await receive()
+            body_sent = True
+            return {"type": "http.request", "body": body, "more_body": False}
+
+        await self.app(scope, receive_decompressed, send)
+
+    @staticmethod
+    async def _reject(send, status: int, message: str):
+        payload = f'{{"detail": "{message}"}}'.encode("utf-8")
+        await send({
+            "type": "http.response.start",
+            "status": status,
+            "headers": [
+                (b"content-type", b"application/json"),
+                (b"content-length", str(len(payload)).encode("latin-1")),
+            ],
+        })
+        await send({"type": "http.response.body", "body": payload})
@@ -1,4 +1,6 @@
-from fastapi import APIRouter, Depends, Body
+from fastapi import APIRouter, Depends, Body, Request
+from starlette.concurrency import run_in_threadpool
+import json
 from sqlalchemy import Engine
 from typing import Dict, Any, Callable
 from datetime import datetime
@@ -11,6 +13,46 @@ from db_mgr import (
 import logging
 logger = logging.getLogger()
 
+# NDJSON 流式提交时每累积多少条记录写入一次数据库
+STREAM_CHUNK_SIZE = 500
+
+def _prepare_screening_records(data_list):
+    """把 Rust 端发送的文件记录规范化：时间戳转换为 datetime，metadata 字段兼容为 extra_metadata"""
+    # 预处理每个文件记录中的时间戳，转换为Python datetime对象
+    for data in data_list:
+        # 处理Unix时间戳的转换 (从Rust发送的秒数转换为Python datetime)
+        if "created_time" in data and isinstance(data["created_time"], (int, float)):
+            data["created_time"] = datetime.fromtimestamp(data["created_time"])
+            
+        if "modified_time" in data and isinstance(data["modified_time"], (int, float)):
+            data["modified_time"] = datetime.fromtimestamp(data["modified_time"])
+            
+        if "accessed_time" in data and isinstance(data["accessed_time"], (int, float)):
+            data["accessed_time"] = datetime.fromtimestamp(data["accessed_time"])
+    
+    # 处理字符串格式的时间字段（处理之前已经先处理了整数时间戳）
+    for data in data_list:
+        for time_field in ["created_time", "modified_time", "accessed_time"]:
+            # 只处理仍然是字符串格式的时间字段（整数时间戳已在前一步转换）
+            if time_field in data and isinstance(data[time_field], str):
+                try:
+                    data[time_field] = datetime.fromisoformat(data[time_field].replace("Z", "+00:00"))
+                except Exception as e:
+                    logger.warning(f"Failed to convert string time field {time_field}: {str(e)}")
+                    # 如果是修改时间字段转换失败，设置为当前时间
+                    if time_field == "modified_time":
+                        data[time_field] = datetime.now()
+            
+            # 确保每个时间字段都有值，对于必填字段
+            if time_field == "modified_time" and (time_field not in data or data[time_field] is None):
+                logger.warning(f"Missing required time field {time_field}, using current time")
+                data[time_field] = datetime.now()
+                        
+        # Ensure 'extra_metadata' is used, but allow 'metadata' for backward compatibility from client
+        if "metadata" in data and "extra_metadata" not in data:
+            data["extra_metadata"] = data.pop("metadata")
+
+
 def get_router(get_engine: Callable[[], Engine]) -> APIRouter:
     router = APIRouter()
 
@@ -47,39 +89,7 @@ def get_router(get_engine: Callable[[], Engine]) -> APIRouter:
             if not data_list:
                 return {"success": True, "processed_count": 0, "failed_count": 0, "message": "No files to process"}
 
-            # 预处理每个文件记录中的时间戳，转换为Python datetime对象
-            for data in data_list:
-                # 处理Unix时间戳的转换 (从Rust发送的秒数转换为Python datetime)
-                if "created_time" in data and isinstance(data["created_time"], (int, float)):
-                    data["created_time"] = datetime.fromtimestamp(data["created_time"])
-                    
-                if "modified_time" in data and isinstance(data["modified_time"], (int, float)):
-                    data["modified_time"] = datetime.fromtimestamp(data["modified_time"])
-                    
-                if "accessed_time" in data and isinstance(data["accessed_time"], (int, float)):
-                    data["accessed_time"] = datetime.fromtimestamp(data["accessed_time"])
-            
-            # 处理字符串格式的时间字段（处理之前已经先处理了整数时间戳）
-            for data in data_list:
-                for time_field in ["created_time", "modified_time", "accessed_time"]:
-                    # 只处理仍然是字符串格式的时间字段（整数时间戳已在前一步转换）
-                    if time_field in data and isinstance(data[time_field], str):
-                        try:
-                            data[time_field] = datetime.fromisoformat(data[time_field].replace("Z", "+00:00"))
-                        except Exception as e:
-                            logger.warning(f"Failed to convert string time field {time_field}: {str(e)}")
-                            # 如果是修改时间字段转换失败，设置为当前时间
-                            if time_field == "modified_time":
-                                data[time_field] = datetime.now()
-                    
-                    # 确保每个时间字段都有值，对于必填字段
-                    if time_field == "modified_time" and (time_field not in data or data[time_field] is None):
-                        logger.warning(f"Missing required time field {time_field}, using current time")
-                        data[time_field] = datetime.now()
-                                
-                # Ensure 'extra_metadata' is used, but allow 'metadata' for backward compatibility from client
-                if "metadata" in data and "extra_metadata" not in data:
-                    data["extra_metadata"] = data.pop("metadata")
+            _prepare_screening_records(data_list)
 
             # 1. 先创建任务，获取 task_id
             task_name = f"batch processing files: {len(data_list)} files"
@@ -116,6 +126,144 @@ def get_router(get_engine: Callable[[], Engine]) -> APIRouter:
                 "message": f"batch processing failed: {str(e)}"
             }
 
+    @router.post("/file-screening/stream")
+    async def add_streamed_file_screening_results(
+        request: Request,
+        screening_mgr: ScreeningManager = Depends(get_screening_manager),
+        task_mgr: TaskManager = Depends(get_task_manager)
+    ):
+        """以 NDJSON 流接收文件粗筛结果
+
+        请求体每行一条文件记录（格式同 /file-screening/batch 的 data_list 元素），
+        边接收边按 STREAM_CHUNK_SIZE 分块写入数据库，整次提交关联同一个打标签任务。
+        无法解析的行计入 failed_count，不影响其余记录。
+        """
+        processed_count = 0
+        failed_count = 0
+        errors = []
+        task = None
+        chunk = []
+        buffer = b""
+
+        async def flush():
+            nonlocal task, processed_count, failed_count, chunk
+            if not chunk:
+                return
+            records, chunk = chunk, []
+            _prepare_screening_records(records)
+            if task is None:
+                task = await run_in_threadpool(
+                    task_mgr.add_task,
+                    task_name="streamed processing files",
+                    task_type=TaskType.TAGGING,
+                    priority=TaskPriority.MEDIUM,
+                    extra_data={"streamed": True},
+                )
+                logger.info(f"Created tagging task ID: {task.id} for streamed screening results")
+            result = await run_in_threadpool(
+                screening_mgr.add_batch_screening_results, records, task_id=task.id
+            )
+            processed_count += result["success"]
+            failed_count += result["failed"]
+            if result.get("errors"):
+                errors.extend(result["errors"])
+
+        def parse_line(line: bytes):
+            nonlocal failed_count
+            line = line.strip()
+            if not line:
+                return
+            try:
+                chunk.append(json.loads(line))
+            except json.JSONDecodeError as e:
+                failed_count += 1
+                errors.append(f"invalid NDJSON line: {str(e)}")
+
+        try:
+            async for data in request.stream():
+                buffer += data
+                *lines, buffer = buffer.split(b"\n")
+                for line in lines:
+                    parse_line(line)
+                if len(chunk) >= STREAM_CHUNK_SIZE:
+                    await flush()
+            # 最后一行可能没有换行符
+            parse_line(buffer)
+            await flush()
+        except Exception as e:
+            logger.error(f"streamed processing files failed: {str(e)}")
+            return {
+                "success": False,
+                "processed_count": processed_count,
+                "failed_count": failed_count,
+                "message": f"streamed processing failed: {str(e)}"
+            }
+
+        logger.info(f"Streamed screening results: processed {processed_count}, failed {failed_count}")
+        return {
+            "success": processed_count > 0 or failed_count == 0,
+            "processed_count": processed_count,
+            "failed_count": failed_count,
+            "errors": errors[:100] or None,
+            "task_id": task.id if task else None,
+            "message": f"Created processing tasks for {processed_count} files, failed {failed_count} files"
+        }
+
+    @router.post("/file-screening/hashes")
+    def update_file_screening_hashes(
+        request: Dict[str, Any] = Body(...),
+        screening_mgr: ScreeningManager = Depends(get_screening_manager)
+    ):
+        """补充延迟计算的文件哈希
+        
+        延迟哈希模式下 Rust 端先发送不带哈希的粗筛结果，随后在后台计算哈希并通过此端点补充，
+        不会把记录重置为待处理状态。
+        
+        请求体:
+        - updates: [{"file_path": ..., "file_hash": ...}, ...]
+        """
+        try:
+            updates = request.get("updates", [])
+            updated_count = screening_mgr.update_file_hashes(updates)
+            return {
+                "success": True,
+                "updated_count": updated_count,
+                "message": f"Updated hashes for {updated_count} files"
+            }
+        except Exception as e:
+            logger.error(f"补充文件哈希失败: {str(e)}")
+            return {
+                "success": False,
+                "message": f"update hashes failed: {str(e)}"
+            }
+
+    @router.post("/file-content/batch")
+    def save_file_contents(
+        request: Dict[str, Any] = Body(...),
+        screening_mgr: ScreeningManager = Depends(get_screening_manager)
+    ):
+        """保存Rust端提取的文件文本
+        
+        Rust 端从文本、Markdown、源代码和带文本层的 PDF 中提取规范化文本，按块通过此端点发送。
+        
+        请求体:
+        - items: [{"file_path", "modified_time", "content_kind", "text", "char_count", "truncated"}, ...]
+        """
+        try:
+            items = request.get("items", [])
+            saved_count = screening_mgr.upsert_file_contents(items)
+            return {
+                "success": True,
+                "saved_count": saved_count,
+                "message": f"Saved text for {saved_count} files"
+            }
+        except Exception as e:
+            logger.error(f"保存文件文本失败: {str(e)}")
+            return {
+                "success": False,
+                "message": f"save file contents failed: {str(e)}"
+            }
+
     @router.get("/file-screening/results")
     def get_file_screening_results(
         limit: int = 1000,
@@ -213,6 +361,60 @@ def get_router(get_engine: Callable[[], Engine]) -> APIRouter:
                 "message": f"搜索失败: {str(e)}"
             }
 
+    @router.get("/file-screening/provenance")
+    def get_download_provenance(
+        file_path: str,
+        screening_mgr: ScreeningManager = Depends(get_screening_manager)
+    ):
+        """获取文件的下载来源（下载地址、来源页面、下载程序）
+        
+        参数:
+        - file_path: 文件路径
+        """
+        try:
+            provenance = screening_mgr.get_download_provenance(file_path)
+            if provenance is None:
+                return {
+                    "success": False,
+                    "message": f"File not found in screening results: {file_path}"
+                }
+            return {
+                "success": True,
+                "data": provenance
+            }
+        except Exception as e:
+            logger.error(f"获取文件下载来源失败: {str(e)}")
+            return {
+                "success": False,
+                "message": f"获取失败: {str(e)}"
+            }
+
+    @router.get("/file-screening/downloads")
+    def find_files_by_download_host(
+        host: str,
+        limit: int = 100,
+        screening_mgr: ScreeningManager = Depends(get_screening_manager)
+    ):
+        """查找从指定网站下载的文件（包含子域名）
+        
+        参数:
+        - host: 网站主机名，如 example.com
+        - limit: 最大返回结果数
+        """
+        try:
+            results_dict = screening_mgr.find_files_by_download_host(host, limit)
+            return {
+                "success": True,
+                "count": len(results_dict),
+                "data": results_dict
+            }
+        except Exception as e:
+            logger.error(f"按下载来源查找文件失败: {str(e)}")
+            return {
+                "success": False,
+                "message": f"查找失败: {str(e)}"
+            }
+
 
     @router.post("/screening/clean-by-path")
     def clean_screening_results_by_path(
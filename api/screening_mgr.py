@@ -1,8 +1,8 @@
 from typing import List, Dict, Any
 from sqlmodel import Session, select, delete, update
 from sqlalchemy import Engine
-from sqlalchemy import text
-from db_mgr import FileScreeningResult, FileScreenResult
+from sqlalchemy import text, func, or_
+from db_mgr import FileScreeningResult, FileScreenResult, FileContent
 from datetime import datetime, timedelta
 import logging
 import os
@@ -34,6 +34,15 @@ class ScreeningManager:
         existing_record = self.get_by_path(file_path)
         if existing_record:
             # 检查文件内容是否有变化
+            if file_hash is None and existing_record.file_hash is not None:
+                # 延迟哈希模式下发送的记录不带哈希，改按修改时间和大小判断，未变化时保留已有哈希
+                content_changed = (
+                    existing_record.modified_time != data.get("modified_time")
+                    or existing_record.file_size != data.get("file_size")
+                )
+                if not content_changed:
+                    file_hash = existing_record.file_hash
+                    data["file_hash"] = file_hash
             if existing_record.file_hash != file_hash:
                 # 文件内容已变化，需要重新处理
                 logger.info(f"File content has changed: {file_path}, Old hash: {existing_record.file_hash}, New hash: {file_hash}, Updating record and resetting to pending")
@@ -323,6 +332,80 @@ class ScreeningManager:
                 logger.error(f"Failed to update file screening result: {str(e)}")
                 return None
     
+    def update_file_hashes(self, updates: List[Dict[str, Any]]) -> int:
+        """补充延迟计算的文件哈希，不改变记录的处理状态
+        
+        Args:
+            updates: [{"file_path": ..., "file_hash": ...}, ...]
+            
+        Returns:
+            更新的记录数
+        """
+        updated_count = 0
+        with Session(self.engine) as session:
+            try:
+                for item in updates:
+                    file_path = item.get("file_path")
+                    file_hash = item.get("file_hash")
+                    if not file_path or not file_hash:
+                        continue
+                    result = session.exec(
+                        update(FileScreeningResult)
+                        .where(FileScreeningResult.file_path == file_path)
+                        .values(file_hash=file_hash)
+                    )
+                    updated_count += result.rowcount
+                session.commit()
+                logger.info(f"Updated deferred hashes for {updated_count} files")
+                return updated_count
+            except Exception as e:
+                session.rollback()
+                logger.error(f"Failed to update deferred hashes: {str(e)}")
+                raise
+
+    def upsert_file_contents(self, items: List[Dict[str, Any]]) -> int:
+        """保存Rust端提取的文件文本，同一路径的旧文本被替换
+        
+        Args:
+            items: [{"file_path": ..., "modified_time": 秒级时间戳, "content_kind": ..., "text": ..., "char_count": ..., "truncated": ...}, ...]
+            
+        Returns:
+            保存的记录数
+        """
+        saved_count = 0
+        with Session(self.engine) as session:
+            try:
+                for item in items:
+                    file_path = item.get("file_path")
+                    if not file_path or item.get("text") is None:
+                        continue
+                    modified_time = item.get("modified_time")
+                    values = {
+                        "content_kind": item.get("content_kind", "text"),
+                        "text": item["text"],
+                        "char_count": item.get("char_count", len(item["text"])),
+                        "truncated": bool(item.get("truncated", False)),
+                        "modified_time": datetime.fromtimestamp(modified_time) if modified_time else None,
+                        "extracted_at": datetime.now(),
+                    }
+                    existing = session.exec(
+                        select(FileContent).where(FileContent.file_path == file_path)
+                    ).first()
+                    if existing:
+                        for key, value in values.items():
+                            setattr(existing, key, value)
+                        session.add(existing)
+                    else:
+                        session.add(FileContent(file_path=file_path, **values))
+                    saved_count += 1
+                session.commit()
+                logger.info(f"Saved extracted text for {saved_count} files")
+                return saved_count
+            except Exception as e:
+                session.rollback()
+                logger.error(f"Failed to save extracted text: {str(e)}")
+                raise
+
     def update_status(self, result_id: int, status: FileScreenResult, error_message: str = None) -> bool:
         """更新粗筛结果状态
         
@@ -475,6 +558,13 @@ class ScreeningManager:
                 
                 matching_rows = query_result.fetchall()
                 matching_count = len(matching_rows)
+
+                # 同时删除这些文件提取的文本
+                session.exec(
+                    text("DELETE FROM t_file_contents WHERE file_path LIKE :path_prefix || '%' ESCAPE '\\'"),
+                    params={"path_prefix": escaped_path},
+                )
+                session.commit()
                 
                 if matching_count > 0:
                     # 记录前5个匹配的路径，帮助调试
@@ -788,6 +878,58 @@ class ScreeningManager:
             logger.error(f"按路径子字符串搜索文件失败: {e}")
             return []
 
+    def get_download_provenance(self, file_path: str) -> Dict[str, Any] | None:
+        """获取文件的下载来源（Rust端从扩展属性读取，保存在 extra_metadata 中）
+        
+        Args:
+            file_path: 文件路径
+            
+        Returns:
+            下载来源字典，文件不在粗筛结果中时返回 None；文件没有下载来源时各字段为 None
+        """
+        result = self.get_by_path(file_path)
+        if result is None:
+            return None
+        extra = result.extra_metadata or {}
+        quarantine = extra.get("quarantine") or {}
+        return {
+            "file_path": result.file_path,
+            "download_url": extra.get("download_url"),
+            "referrer_url": extra.get("referrer_url"),
+            "download_host": extra.get("download_host"),
+            "downloaded_by": quarantine.get("agent"),
+            "zone_id": extra.get("zone_id"),
+        }
+
+    def find_files_by_download_host(self, host: str, limit: int = 100) -> List[Dict[str, Any]]:
+        """查找从指定网站下载的文件（按 extra_metadata 中的 download_host 匹配，包含子域名）
+        
+        Args:
+            host: 网站主机名，如 example.com
+            limit: 最大返回结果数量
+            
+        Returns:
+            匹配的文件字典列表，附带下载地址
+        """
+        host = host.strip().lower()
+        if host.startswith("www."):
+            host = host[4:]
+        if not host:
+            return []
+        download_host = func.json_extract(FileScreeningResult.extra_metadata, "$.download_host")
+        statement = select(FileScreeningResult)\
+            .where(or_(download_host == host, download_host.like(f"%.{host}")))\
+            .order_by(FileScreeningResult.modified_time.desc())\
+            .limit(limit)
+        with Session(self.engine) as session:
+            results = session.exec(statement).all()
+            result_dicts = []
+            for result in results:
+                file_info = self._result_to_dict(result)
+                file_info["download_url"] = (result.extra_metadata or {}).get("download_url")
+                result_dicts.append(file_info)
+            return result_dicts
+
     def is_path_in_blacklist_hierarchy(self, path: str, myfolders_mgr=None) -> bool:
         """检查路径是否在层级黑名单中
         
@@ -20,6 +20,45 @@ def get_router(get_engine: Callable[[], Engine], base_dir: str) -> APIRouter:
     def get_search_manager(engine: Engine = Depends(get_engine)) -> SearchManager:
         return SearchManager(engine=engine, lancedb_mgr=get_lancedb_manager(), models_mgr=get_models_manager(engine))
 
+    @router.post("/file-content/embeddings")
+    def save_file_embeddings(
+        request: Dict[str, Any] = Body(...),
+        lancedb_mgr: LanceDBMgr = Depends(get_lancedb_manager)
+    ):
+        """保存Rust端用本地模型计算的文件文本向量，替换该文件原有的向量
+        
+        请求体:
+        - file_path: 文件路径
+        - model: 本地模型名称
+        - items: [{"chunk_index", "text", "vector"}, ...]
+        """
+        try:
+            file_path = request.get("file_path")
+            model = request.get("model", "")
+            items = request.get("items", [])
+            if not file_path:
+                return {"success": False, "message": "file_path is required"}
+            lancedb_mgr.replace_file_embeddings(file_path, [
+                {
+                    "chunk_index": item["chunk_index"],
+                    "model": model,
+                    "text": item.get("text", ""),
+                    "vector": item["vector"],
+                }
+                for item in items
+            ])
+            return {
+                "success": True,
+                "saved_count": len(items),
+                "message": f"Saved {len(items)} embeddings for {file_path}"
+            }
+        except Exception as e:
+            logger.error(f"[SEARCH API] Save file embeddings failed: {e}")
+            return {
+                "success": False,
+                "message": f"save file embeddings failed: {str(e)}"
+            }
+
     # =============================================================================
     # 📊 向量内容搜索API端点
     # =============================================================================
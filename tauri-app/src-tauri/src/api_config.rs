@@ -0,0 +1,186 @@
+//! # API 地址配置 (API Endpoint Configuration)
+//!
+//! 解析 Python API 的主机、端口和数据库路径，优先级从高到低：
+//! 1. 命令行参数：`--api-host`、`--api-port`、`--db-path`（支持 `--api-port 60315` 和 `--api-port=60315`）
+//! 2. 环境变量：`KNOWLEDGE_FOCUS_API_HOST`、`KNOWLEDGE_FOCUS_API_PORT`、`KNOWLEDGE_FOCUS_DB_PATH`
+//! 3. settings.json 中的 `api_host`、`api_port`、`api_db_path`（与前端共用同一个 store 文件）
+//! 4. 默认值：`127.0.0.1:60315`，数据库位于应用数据目录下的 knowledge-focus.db
+//!
+//! 可选的本地套接字（`--api-socket`、`KNOWLEDGE_FOCUS_API_SOCKET`、`api_socket`，值为套接字文件路径）
+//! 开启后 API 额外监听该 Unix 套接字，Rust 端的请求改走套接字，不再经过 TCP 回环端口；
+//! 前端页面仍通过 TCP 端口访问。只在 Unix 系统上支持，Windows 上忽略该配置。
+
+use log::{error, info, warn};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+pub const DEFAULT_API_HOST: &str = "127.0.0.1";
+pub const DEFAULT_API_PORT: u16 = 60315;
+const DEFAULT_DB_FILE_NAME: &str = "knowledge-focus.db";
+const SETTINGS_STORE_FILE: &str = "settings.json";
+
+/// 解析后的API地址配置
+#[derive(Debug, Clone)]
+pub struct ApiEndpointConfig {
+    pub host: String,
+    pub port: u16,
+    pub db_path: String,
+    /// API 额外监听的 Unix 套接字路径，未配置时只使用 TCP
+    pub socket_path: Option<String>,
+}
+
+/// 从命令行参数中读取指定选项的值
+fn cli_arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let prefix = format!("{}=", name);
+    for (i, arg) in args.iter().enumerate() {
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+        if arg == name {
+            return args.get(i + 1).cloned();
+        }
+    }
+    None
+}
+
+/// 读取非空的环境变量
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// 从 settings.json 中读取字符串或数字配置
+fn store_value(app_handle: &AppHandle, key: &str) -> Option<String> {
+    let store = match app_handle.store(SETTINGS_STORE_FILE) {
+        Ok(store) => store,
+        Err(e) => {
+            error!("[API_CONFIG] 打开 {} 失败: {}", SETTINGS_STORE_FILE, e);
+            return None;
+        }
+    };
+    match store.get(key)? {
+        serde_json::Value::String(value) if !value.trim().is_empty() => Some(value),
+        serde_json::Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// 按 命令行 > 环境变量 > settings.json 的顺序查找配置值，并记录来源
+fn lookup(
+    app_handle: &AppHandle,
+    cli_name: &str,
+    env_name: &str,
+    store_key: &str,
+) -> Option<(String, &'static str)> {
+    if let Some(value) = cli_arg_value(cli_name) {
+        return Some((value, "命令行参数"));
+    }
+    if let Some(value) = env_value(env_name) {
+        return Some((value, "环境变量"));
+    }
+    store_value(app_handle, store_key).map(|value| (value, "settings.json"))
+}
+
+/// 解析API地址配置，无效的值会被忽略并回退到默认值
+pub fn resolve_api_endpoint(app_handle: &AppHandle) -> Result<ApiEndpointConfig, String> {
+    let host = match lookup(
+        app_handle,
+        "--api-host",
+        "KNOWLEDGE_FOCUS_API_HOST",
+        "api_host",
+    ) {
+        Some((host, source)) => {
+            info!("[API_CONFIG] 使用{}中的API主机: {}", source, host);
+            host
+        }
+        None => DEFAULT_API_HOST.to_string(),
+    };
+
+    let port = match lookup(
+        app_handle,
+        "--api-port",
+        "KNOWLEDGE_FOCUS_API_PORT",
+        "api_port",
+    ) {
+        Some((value, source)) => match value.trim().parse::<u16>() {
+            Ok(port) if port > 0 => {
+                info!("[API_CONFIG] 使用{}中的API端口: {}", source, port);
+                port
+            }
+            _ => {
+                warn!(
+                    "[API_CONFIG] {}中的API端口无效: {}，使用默认端口 {}",
+                    source, value, DEFAULT_API_PORT
+                );
+                DEFAULT_API_PORT
+            }
+        },
+        None => DEFAULT_API_PORT,
+    };
+
+    let db_path = match lookup(
+        app_handle,
+        "--db-path",
+        "KNOWLEDGE_FOCUS_DB_PATH",
+        "api_db_path",
+    ) {
+        Some((db_path, source)) => {
+            info!("[API_CONFIG] 使用{}中的数据库路径: {}", source, db_path);
+            db_path
+        }
+        None => app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| e.to_string())?
+            .join(DEFAULT_DB_FILE_NAME)
+            .to_string_lossy()
+            .to_string(),
+    };
+
+    // 自定义数据库路径的父目录可能不存在，提前创建以免API启动失败
+    if let Some(parent) = PathBuf::from(&db_path).parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("创建数据库目录失败 {}: {}", parent.display(), e))?;
+        }
+    }
+
+    let socket_path = match lookup(
+        app_handle,
+        "--api-socket",
+        "KNOWLEDGE_FOCUS_API_SOCKET",
+        "api_socket",
+    ) {
+        Some((socket_path, source)) if cfg!(unix) => {
+            info!(
+                "[API_CONFIG] 使用{}中的API本地套接字: {}",
+                source, socket_path
+            );
+            if let Some(parent) = PathBuf::from(&socket_path).parent() {
+                if !parent.as_os_str().is_empty() && !parent.exists() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|e| format!("创建套接字目录失败 {}: {}", parent.display(), e))?;
+                }
+            }
+            Some(socket_path)
+        }
+        Some((_, source)) => {
+            warn!(
+                "[API_CONFIG] {}中配置了API本地套接字，当前系统不支持，继续使用 TCP",
+                source
+            );
+            None
+        }
+        None => None,
+    };
+
+    Ok(ApiEndpointConfig {
+        host,
+        port,
+        db_path,
+        socket_path,
+    })
+}
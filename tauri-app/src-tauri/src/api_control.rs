@@ -0,0 +1,112 @@
+//! # API 控制命令通道 (API Control Channel)
+//!
+//! 桥接事件只能从 Python 流向 Rust，该模块补上反方向的通道：通过 sidecar 的标准输入
+//! 向 Python 写入 `CONTROL_COMMAND_JSON:` 前缀的命令行（暂停任务处理、调整日志级别、触发重新处理等），
+//! 执行结果由 Python 以 `control-command-result` 桥接事件返回。
+//! 外部API模式下进程不由应用启动、没有可写的标准输入，改为调用 `POST /control` 并直接返回结果。
+//! 支持的命令见 api/control_channel.py。
+
+use log::info;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 控制命令行前缀，与 api/control_channel.py 中的 CONTROL_PREFIX 保持一致
+const CONTROL_PREFIX: &str = "CONTROL_COMMAND_JSON:";
+/// HTTP 控制端点请求超时
+const CONTROL_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 控制命令序号，用于把 `control-command-result` 事件与请求对应起来
+static NEXT_COMMAND_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 向 Python API 发送控制命令
+///
+/// 通过标准输入发送时只返回命令 id，结果稍后以 `control-command-result` 事件到达；
+/// 外部API模式下通过 HTTP 发送，返回值中包含执行结果。
+pub async fn send_control_command(
+    api_state_mutex: &Arc<Mutex<crate::ApiProcessState>>,
+    command: &str,
+    args: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let id = format!(
+        "{}-{}",
+        command,
+        NEXT_COMMAND_ID.fetch_add(1, Ordering::SeqCst)
+    );
+
+    let control_url = {
+        let api_state = api_state_mutex.lock().unwrap();
+        let line = format!(
+            "{}{}\n",
+            CONTROL_PREFIX,
+            serde_json::json!({ "id": id, "command": command, "args": args })
+        );
+        let written = api_state
+            .supervisor
+            .with_child(crate::sidecar_supervisor::API_NAME, |child| {
+                child.write(line.as_bytes())
+            });
+        if let Some(written) = written {
+            written.map_err(|e| format!("写入API标准输入失败: {}", e))?;
+            info!(
+                "[API_CONTROL] 已通过标准输入发送控制命令: {} ({})",
+                command, id
+            );
+            return Ok(serde_json::json!({
+                "id": id,
+                "transport": "stdin"
+            }));
+        }
+        if !api_state.external {
+            return Err("API服务未运行，无法发送控制命令".to_string());
+        }
+        format!("http://{}:{}/control", api_state.host, api_state.port)
+    };
+
+    let response = crate::http_client::shared()
+        .post(&control_url)
+        .timeout(CONTROL_REQUEST_TIMEOUT)
+        .json(&serde_json::json!({ "command": command, "args": args }))
+        .send()
+        .await
+        .map_err(|e| format!("发送控制命令失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("控制命令被拒绝，状态码: {}", response.status()));
+    }
+    let result: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("解析控制命令结果失败: {}", e))?;
+    info!("[API_CONTROL] 已通过HTTP发送控制命令: {} ({})", command, id);
+
+    Ok(serde_json::json!({
+        "id": id,
+        "transport": "http",
+        "result": result
+    }))
+}
+
+/// 向 Python API 发送控制命令（pause_workers / resume_workers / set_log_level / trigger_reindex）
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn send_api_command(
+    command: String,
+    args: Option<serde_json::Value>,
+    api_state: tauri::State<'_, crate::ApiState>,
+) -> Result<serde_json::Value, String> {
+    info!("[CMD] send_api_command 被调用: {} {:?}", command, args);
+
+    let sent = send_control_command(
+        &api_state.0,
+        &command,
+        args.unwrap_or_else(|| serde_json::json!({})),
+    )
+    .await?;
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("控制命令 {} 已发送", command),
+        "id": sent["id"],
+        "transport": sent["transport"],
+        "result": sent.get("result").cloned()
+    }))
+}
@@ -1,3 +1,5 @@
+use log::{error, info, warn};
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use tauri::path::BaseDirectory;
 use tauri::{AppHandle, Emitter, Manager};
@@ -6,6 +8,7 @@ use tokio::sync::oneshot;
 
 // 引入事件缓冲器
 use crate::event_buffer::{BridgeEventData, EventBuffer};
+use crate::startup_stages::{report_stage, StageStatus, StartupStage};
 
 /// 解析Python stdout输出中的桥接事件
 ///
@@ -23,7 +26,7 @@ fn parse_bridge_event(line: &str) -> Option<BridgeEventData> {
                 return Some(event_data);
             }
             Err(e) => {
-                eprintln!("解析桥接事件JSON失败: {} - 原始内容: {}", e, json_part);
+                error!("解析桥接事件JSON失败: {} - 原始内容: {}", e, json_part);
                 return None;
             }
         }
@@ -33,6 +36,20 @@ fn parse_bridge_event(line: &str) -> Option<BridgeEventData> {
     None
 }
 
+/// uv sync 失败时保留的最近输出行数
+const UV_SYNC_OUTPUT_TAIL_LINES: usize = 20;
+
+/// 查找之前 uv sync 构建的虚拟环境中的 Python 解释器，不存在时返回 None
+fn existing_venv_python(venv_parent_path: &std::path::Path) -> Option<std::path::PathBuf> {
+    let venv_dir = venv_parent_path.join(".venv");
+    let python_path = if cfg!(windows) {
+        venv_dir.join("Scripts").join("python.exe")
+    } else {
+        venv_dir.join("bin").join("python")
+    };
+    python_path.exists().then_some(python_path)
+}
+
 // Helper function to start the Python API service
 // 返回一个oneshot channel的接收端，当API成功启动且可访问后会发送信号
 pub fn start_python_api(
@@ -45,13 +62,14 @@ pub fn start_python_api(
     // oneshot发送端不能克隆，但我们可以在开始健康检查前保存它
     let tx = std::sync::Arc::new(std::sync::Mutex::new(Some(tx)));
 
-    // 创建事件缓冲器
-    let event_buffer = Arc::new(EventBuffer::new(app_handle.clone()));
+    // 获取共享的事件缓冲器（重启API时复用同一个实例）
+    let event_buffer = EventBuffer::shared(&app_handle);
 
     tauri::async_runtime::spawn(async move {
         let port_to_use: u16;
         let host_to_use: String;
         let db_path_to_use: String;
+        let socket_path_to_use: Option<String>;
 
         {
             // Scope to ensure lock is released
@@ -59,13 +77,14 @@ pub fn start_python_api(
             port_to_use = api_state_guard.port;
             host_to_use = api_state_guard.host.clone();
             db_path_to_use = api_state_guard.db_path.clone();
+            socket_path_to_use = api_state_guard.socket_path.clone();
         }
 
         // 获取当前工作目录，用于调试
         let current_dir = std::env::current_dir()
             .map(|p| p.to_string_lossy().to_string())
             .unwrap_or_else(|_| "无法获取当前工作目录".to_string());
-        println!("当前工作目录: {}", current_dir);
+        info!("当前工作目录: {}", current_dir);
 
         // According to dev/production environment, choose different venv_parent_path: ../api or /path/to/app/app_data_dir
         let venv_parent_path = if cfg!(debug_assertions) {
@@ -78,10 +97,11 @@ pub fn start_python_api(
                     path
                 }
                 Err(e) => {
-                    eprintln!("无法获取当前工作目录: {}", e);
+                    error!("无法获取当前工作目录: {}", e);
                     if let Some(window) = app_handle.get_webview_window("main") {
                         if window.is_visible().unwrap_or(false) {
-                            let _ = window.emit("api-error", Some(format!("无法获取当前工作目录: {}", e)));
+                            let _ = window
+                                .emit("api-error", Some(format!("无法获取当前工作目录: {}", e)));
                         }
                     }
                     return;
@@ -91,17 +111,18 @@ pub fn start_python_api(
             match app_handle.path().app_data_dir() {
                 Ok(path) => path,
                 Err(e) => {
-                    eprintln!("无法获取应用数据目录: {}", e);
+                    error!("无法获取应用数据目录: {}", e);
                     if let Some(window) = app_handle.get_webview_window("main") {
                         if window.is_visible().unwrap_or(false) {
-                            let _ = window.emit("api-error", Some(format!("无法获取应用数据目录: {}", e)));
+                            let _ = window
+                                .emit("api-error", Some(format!("无法获取应用数据目录: {}", e)));
                         }
                     }
                     return;
                 }
             }
         };
-        println!("venv_parent_path: {:?}", venv_parent_path);
+        info!("venv_parent_path: {:?}", venv_parent_path);
 
         // 如果是生产环境，复制BaseDirectory::Resource/api/pyproject.toml到app_data_dir
         if !cfg!(debug_assertions) {
@@ -109,10 +130,11 @@ pub fn start_python_api(
             {
                 Ok(path) => path,
                 Err(e) => {
-                    eprintln!("无法解析资源路径: {}", e);
+                    error!("无法解析资源路径: {}", e);
                     if let Some(window) = app_handle.get_webview_window("main") {
                         if window.is_visible().unwrap_or(false) {
-                            let _ = window.emit("api-error", Some(format!("无法解析资源路径: {}", e)));
+                            let _ =
+                                window.emit("api-error", Some(format!("无法解析资源路径: {}", e)));
                         }
                     }
                     return;
@@ -120,11 +142,11 @@ pub fn start_python_api(
             };
             let pyproject_src_path = resource_api_path.join("pyproject.toml");
             let pyproject_dest_path = venv_parent_path.join("pyproject.toml");
-            println!("pyproject_src_path: {:?}", pyproject_src_path);
-            println!("pyproject_dest_path: {:?}", pyproject_dest_path);
+            info!("pyproject_src_path: {:?}", pyproject_src_path);
+            info!("pyproject_dest_path: {:?}", pyproject_dest_path);
             // 总是复制文件，以便在部署新版本后能自动更新虚拟环境
             if let Err(e) = std::fs::copy(&pyproject_src_path, &pyproject_dest_path) {
-                eprintln!("复制pyproject.toml失败: {}", e);
+                error!("复制pyproject.toml失败: {}", e);
                 if let Some(window) = app_handle.get_webview_window("main") {
                     let _ = window.emit(
                         "api-error",
@@ -136,6 +158,12 @@ pub fn start_python_api(
         }
 
         // 创建或更新虚拟环境
+        report_stage(
+            &app_handle,
+            StartupStage::UvSync,
+            StageStatus::Started,
+            None,
+        );
         let sidecar_command = app_handle.shell().sidecar("uv").unwrap().args([
             "sync",
             "--index-strategy",
@@ -144,12 +172,12 @@ pub fn start_python_api(
             "--directory",
             venv_parent_path.to_str().unwrap(),
         ]);
-        println!("Running command: {:?}", sidecar_command);
+        info!("Running command: {:?}", sidecar_command);
 
-        // 捕获 uv sync 的输出并发送到前端
-        match sidecar_command.spawn() {
+        // 捕获 uv sync 的输出并发送到前端，只有同步成功后才继续启动API
+        let sync_result: Result<(), String> = match sidecar_command.spawn() {
             Ok((mut sync_rx, _sync_child)) => {
-                println!("uv sync 进程已启动");
+                info!("uv sync 进程已启动");
                 if let Some(window) = app_handle.get_webview_window("main") {
                     let _ = window.emit(
                         "api-log",
@@ -157,92 +185,143 @@ pub fn start_python_api(
                     );
                 }
 
-                // 监听 uv sync 的输出
+                // 监听 uv sync 的输出，必须读到进程终止才能拿到退出码，窗口不可见时只是不再转发日志
                 let app_handle_for_sync = app_handle.clone();
                 let sync_task = tauri::async_runtime::spawn(async move {
+                    let mut output_tail: VecDeque<String> = VecDeque::new();
+                    let mut exit_code: Option<i32> = None;
                     while let Some(event) = sync_rx.recv().await {
-                        // 检查窗口是否仍然存在，避免向已销毁的窗口发送事件
+                        let (line_str, stream) = match event {
+                            CommandEvent::Stdout(line) => {
+                                (String::from_utf8_lossy(&line).to_string(), "stdout")
+                            }
+                            CommandEvent::Stderr(line) => {
+                                (String::from_utf8_lossy(&line).to_string(), "stderr")
+                            }
+                            CommandEvent::Error(e) => (e, "error"),
+                            CommandEvent::Terminated(status) => {
+                                exit_code = status.code;
+                                info!("uv sync 进程终止，状态码: {}", status.code.unwrap_or(-1));
+                                break;
+                            }
+                            _ => continue,
+                        };
+
+                        // 保留最近的输出，同步失败时作为错误详情
+                        if output_tail.len() >= UV_SYNC_OUTPUT_TAIL_LINES {
+                            output_tail.pop_front();
+                        }
+                        output_tail.push_back(line_str.trim_end().to_string());
+
+                        let _ = app_handle_for_sync.emit(
+                            "uv-sync-progress",
+                            serde_json::json!({ "line": line_str.trim_end(), "stream": stream }),
+                        );
+
+                        // 检查窗口是否真的可用（可能已经被销毁但引用仍存在）
                         if let Some(window) = app_handle_for_sync.get_webview_window("main") {
-                            // 检查窗口是否真的可用（可能已经被销毁但引用仍存在）
                             if window.is_visible().unwrap_or(false) {
-                                match event {
-                                    CommandEvent::Stdout(line) => {
-                                        let line_str = String::from_utf8_lossy(&line);
-                                        if window.is_visible().unwrap_or(false) {
-                                            let _ = window.emit("api-log", Some(line_str.to_string()));
-                                        }
-                                    }
-                                    CommandEvent::Stderr(line) => {
-                                        let line_str = String::from_utf8_lossy(&line);
-                                        // uv 命令将正常的进度信息输出到 stderr，所以我们需要区分真正的错误
-                                        // 只有包含明确错误关键词的才当作错误处理
-                                        if line_str.contains("error")
-                                            || line_str.contains("Error")
-                                            || line_str.contains("ERROR")
-                                            || line_str.contains("failed")
-                                            || line_str.contains("Failed")
-                                            || line_str.contains("FAILED")
-                                        {
-                                            if window.is_visible().unwrap_or(false) {
-                                                let _ = window.emit("api-error", Some(line_str.to_string()));
-                                            }
-                                        } else {
-                                            // 其他 stderr 输出当作正常日志处理（如下载进度等）
-                                            if window.is_visible().unwrap_or(false) {
-                                                let _ = window.emit("api-log", Some(line_str.to_string()));
-                                            }
-                                        }
-                                    }
-                                    CommandEvent::Terminated(status) => {
-                                        println!(
-                                            "uv sync 进程终止，状态码: {}",
-                                            status.code.unwrap_or(-1)
-                                        );
-                                        if status.code.unwrap_or(-1) != 0 {
-                                            let _ = window.emit(
-                                                "api-error",
-                                                Some(format!(
-                                                    "uv sync failed，exit code: {}",
-                                                    status.code.unwrap_or(-1)
-                                                )),
-                                            );
-                                        } else {
-                                            let _ = window.emit(
-                                                "api-log",
-                                                Some(
-                                                    "Python virtual environment sync completed"
-                                                        .to_string(),
-                                                ),
-                                            );
-                                        }
-                                        break;
-                                    }
-                                    _ => {}
-                                }
-                            } else {
-                                // 窗口不可见，可能已被销毁，停止发送事件
-                                println!("主窗口不可见，停止发送 uv sync 日志事件");
-                                break;
+                                // uv 命令将正常的进度信息输出到 stderr，所以我们需要区分真正的错误
+                                // 只有包含明确错误关键词的才当作错误处理
+                                let is_error = stream == "error"
+                                    || line_str.contains("error")
+                                    || line_str.contains("Error")
+                                    || line_str.contains("ERROR")
+                                    || line_str.contains("failed")
+                                    || line_str.contains("Failed")
+                                    || line_str.contains("FAILED");
+                                let event_name = if is_error { "api-error" } else { "api-log" };
+                                let _ = window.emit(event_name, Some(line_str.clone()));
                             }
-                        } else {
-                            // 窗口不存在，停止发送事件
-                            println!("主窗口不存在，停止发送 uv sync 日志事件");
-                            break;
                         }
                     }
+                    (exit_code, output_tail.into_iter().collect::<Vec<_>>())
                 });
 
                 // 等待 uv sync 完成
-                sync_task.await.expect("uv sync 任务失败");
+                match sync_task.await {
+                    Ok((Some(0), _)) => Ok(()),
+                    Ok((exit_code, output_tail)) => {
+                        let exit_code_str = exit_code
+                            .map(|code| code.to_string())
+                            .unwrap_or_else(|| "unknown".to_string());
+                        let _ = app_handle.emit(
+                            "uv-sync-failed",
+                            serde_json::json!({
+                                "exit_code": exit_code,
+                                "output_tail": output_tail
+                            }),
+                        );
+                        Err(format!(
+                            "uv sync failed，exit code: {}\n{}",
+                            exit_code_str,
+                            output_tail.join("\n")
+                        ))
+                    }
+                    Err(e) => Err(format!("uv sync 任务失败: {}", e)),
+                }
+            }
+            Err(e) => Err(format!("启动 uv sync 失败: {}", e)),
+        };
+
+        match sync_result {
+            Ok(()) => {
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    let _ = window.emit(
+                        "api-log",
+                        Some("Python virtual environment sync completed".to_string()),
+                    );
+                }
+                report_stage(
+                    &app_handle,
+                    StartupStage::UvSync,
+                    StageStatus::Completed,
+                    None,
+                );
+            }
+            Err(e) if existing_venv_python(&venv_parent_path).is_some() => {
+                // 同步失败（通常是断网）但已有之前构建的虚拟环境，降级为离线运行
+                error!("{}", e);
+                warn!("uv sync 失败，使用已有虚拟环境离线启动API");
+                report_stage(
+                    &app_handle,
+                    StartupStage::UvSync,
+                    StageStatus::Degraded,
+                    Some("依赖同步失败，使用已有虚拟环境离线运行".to_string()),
+                );
+                let _ = app_handle.emit(
+                    "running-offline",
+                    serde_json::json!({
+                        "reason": e,
+                        "venv_path": venv_parent_path.join(".venv").to_string_lossy()
+                    }),
+                );
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    let _ = window.emit(
+                        "api-log",
+                        Some(
+                            "Dependency sync failed, starting with the existing virtual environment (offline)"
+                                .to_string(),
+                        ),
+                    );
+                }
             }
             Err(e) => {
-                eprintln!("启动 uv sync 失败: {}", e);
+                warn!("{}", e);
+                report_stage(
+                    &app_handle,
+                    StartupStage::UvSync,
+                    StageStatus::Failed,
+                    Some(e.clone()),
+                );
                 if let Some(window) = app_handle.get_webview_window("main") {
-                    if window.is_visible().unwrap_or(false) {
-                        let _ = window.emit("api-error", Some(format!("uv sync failed: {}", e)));
-                    }
+                    let _ = window.emit("api-error", Some(e));
                 }
-                // return; 如果异常，比如断网，继续尝试启动API服务
+                // 虚拟环境不完整时 uv run 只会启动失败或卡住，直接结束启动流程
+                if let Some(sender) = tx.lock().unwrap().take() {
+                    let _ = sender.send(false);
+                }
+                return;
             }
         }
 
@@ -257,20 +336,21 @@ pub fn start_python_api(
             {
                 Ok(path) => path,
                 Err(e) => {
-                    eprintln!("无法解析main.py路径: {}", e);
+                    error!("无法解析main.py路径: {}", e);
                     if let Some(window) = app_handle.get_webview_window("main") {
                         if window.is_visible().unwrap_or(false) {
-                            let _ = window.emit("api-error", Some(format!("无法解析main.py路径: {}", e)));
+                            let _ = window
+                                .emit("api-error", Some(format!("无法解析main.py路径: {}", e)));
                         }
                     }
                     return;
                 }
             }
         };
-        println!("main_py_path: {:?}", script_path);
+        info!("main_py_path: {:?}", script_path);
 
         // 通过uv运行Python脚本
-        let sidecar_command = app_handle.shell().sidecar("uv").unwrap().args([
+        let mut sidecar_command = app_handle.shell().sidecar("uv").unwrap().args([
             "run",
             "--offline", // 离线模式运行，因为之前已经进行过uv sync了
             "--directory",
@@ -283,17 +363,34 @@ pub fn start_python_api(
             "--db-path",
             db_path_to_use.as_str(),
         ]);
+        // 配置了本地套接字时API额外监听该套接字，Rust 端请求经套接字发送
+        if let Some(socket_path) = socket_path_to_use.as_deref() {
+            sidecar_command = sidecar_command.args(["--uds", socket_path]);
+        }
 
-        println!("Running command: {:?}", sidecar_command);
+        info!("Running command: {:?}", sidecar_command);
+        // 关闭令牌在打印命令后再设置，避免写入日志
+        let sidecar_command = sidecar_command.env("KF_SHUTDOWN_TOKEN", crate::shutdown_token());
+        report_stage(
+            &app_handle,
+            StartupStage::ApiBoot,
+            StageStatus::Started,
+            None,
+        );
 
         match sidecar_command.spawn() {
             Ok((mut rx, child)) => {
-                {
-                    // Scope to ensure lock is released
-                    let mut api_state_guard = api_state_mutex.lock().unwrap();
-                    api_state_guard.process_child = Some(child);
-                }
-                println!(
+                // 记录本次启动的进程PID，重启后旧进程的终止事件不应清除新进程
+                let child_pid = child.pid();
+                crate::record_api_pid(child_pid);
+                let supervisor = api_state_mutex.lock().unwrap().supervisor.clone();
+                supervisor.attach(
+                    &app_handle,
+                    crate::sidecar_supervisor::API_NAME,
+                    "uv",
+                    child,
+                );
+                info!(
                     "API服务已启动. Port: {}, Host: {}",
                     port_to_use, host_to_use
                 );
@@ -312,100 +409,95 @@ pub fn start_python_api(
                 }
 
                 let app_handle_clone = app_handle.clone();
-                let api_state_mutex_clone = api_state_mutex.clone();
+                let supervisor_clone = supervisor.clone();
 
                 // 监听API进程事件
                 let event_buffer_clone = event_buffer.clone();
                 tauri::async_runtime::spawn(async move {
                     while let Some(event) = rx.recv().await {
-                        if let Some(window) = app_handle_clone.get_webview_window("main") {
-                            // 检查窗口是否仍然可见/有效
-                            if !window.is_visible().unwrap_or(false) {
-                                println!("FastAPI事件处理: 窗口已不可见，停止发送事件");
-                                break;
+                        // 窗口不可见（如隐藏到托盘）时只是不再发送日志，桥接事件和进程退出仍要处理
+                        let emit_to_window = |event_name: &str, message: String| {
+                            if let Some(window) = app_handle_clone.get_webview_window("main") {
+                                if window.is_visible().unwrap_or(false) {
+                                    let _ = window.emit(event_name, Some(message));
+                                }
                             }
+                        };
 
-                            match event {
-                                CommandEvent::Stdout(line) => {
-                                    let line_str = String::from_utf8_lossy(&line);
-
-                                    // 检查是否是桥接事件通知
-                                    if let Some(event_data) = parse_bridge_event(&line_str) {
-                                        // 使用事件缓冲器处理桥接事件
-                                        println!(
-                                            "收到桥接事件: {} (通过缓冲器处理)",
-                                            event_data.event
-                                        );
-                                        event_buffer_clone.handle_event(event_data).await;
-                                    } else {
-                                        // 普通的Python日志输出
-                                        // println!("Python API: {}", line_str);
-                                        if window.is_visible().unwrap_or(false) {
-                                            let _ = window.emit("api-log", Some(line_str.to_string()));
-                                        }
-                                    }
-                                }
-                                CommandEvent::Stderr(line) => {
-                                    let line_str = String::from_utf8_lossy(&line);
-                                    // Python/FastAPI 的 stderr 输出需要区分错误和正常信息
-                                    // 只有包含明确错误关键词的才当作错误处理
-                                    if line_str.contains("error")
-                                        || line_str.contains("Error")
-                                        || line_str.contains("ERROR")
-                                        || line_str.contains("failed")
-                                        || line_str.contains("Failed")
-                                        || line_str.contains("FAILED")
-                                        || line_str.contains("exception")
-                                        || line_str.contains("Exception")
-                                        || line_str.contains("EXCEPTION")
-                                        || line_str.contains("traceback")
-                                        || line_str.contains("Traceback")
-                                    {
-                                        if window.is_visible().unwrap_or(false) {
-                                            let _ = window.emit("api-error", Some(line_str.to_string()));
-                                        }
-                                    } else {
-                                        // 其他 stderr 输出当作正常日志处理（如启动信息等）
-                                        if window.is_visible().unwrap_or(false) {
-                                            let _ = window.emit("api-log", Some(line_str.to_string()));
-                                        }
-                                    }
+                        match event {
+                            CommandEvent::Stdout(line) => {
+                                let line_str = String::from_utf8_lossy(&line);
+
+                                // 检查是否是桥接事件通知
+                                if let Some(event_data) = parse_bridge_event(&line_str) {
+                                    // 使用事件缓冲器处理桥接事件
+                                    info!("收到桥接事件: {} (通过缓冲器处理)", event_data.event);
+                                    event_buffer_clone.handle_raw_event(event_data).await;
+                                } else {
+                                    // 普通的Python日志输出
+                                    // println!("Python API: {}", line_str);
+                                    emit_to_window("api-log", line_str.to_string());
                                 }
-                                CommandEvent::Error(err) => {
-                                    eprintln!("Python API进程错误: {}", err);
-                                    if window.is_visible().unwrap_or(false) {
-                                        let _ = window.emit("api-error", Some(err.to_string()));
-                                    }
-                                    if let Ok(mut state) = api_state_mutex_clone.lock() {
-                                        state.process_child = None;
-                                    }
+                            }
+                            CommandEvent::Stderr(line) => {
+                                let line_str = String::from_utf8_lossy(&line);
+                                // Python/FastAPI 的 stderr 输出需要区分错误和正常信息
+                                // 只有包含明确错误关键词的才当作错误处理
+                                if line_str.contains("error")
+                                    || line_str.contains("Error")
+                                    || line_str.contains("ERROR")
+                                    || line_str.contains("failed")
+                                    || line_str.contains("Failed")
+                                    || line_str.contains("FAILED")
+                                    || line_str.contains("exception")
+                                    || line_str.contains("Exception")
+                                    || line_str.contains("EXCEPTION")
+                                    || line_str.contains("traceback")
+                                    || line_str.contains("Traceback")
+                                {
+                                    emit_to_window("api-error", line_str.to_string());
+                                } else {
+                                    // 其他 stderr 输出当作正常日志处理（如启动信息等）
+                                    emit_to_window("api-log", line_str.to_string());
                                 }
-                                CommandEvent::Terminated(status) => {
-                                    println!(
-                                        "API进程已终止，状态码: {}",
+                            }
+                            CommandEvent::Error(err) => {
+                                error!("Python API进程错误: {}", err);
+                                emit_to_window("api-error", err.to_string());
+                                supervisor_clone.detach(
+                                    &app_handle_clone,
+                                    crate::sidecar_supervisor::API_NAME,
+                                    child_pid,
+                                );
+                            }
+                            CommandEvent::Terminated(status) => {
+                                info!("API进程已终止，状态码: {}", status.code.unwrap_or(-1));
+                                emit_to_window(
+                                    "api-log",
+                                    format!(
+                                        "API process terminated with exit code: {}",
                                         status.code.unwrap_or(-1)
-                                    );
-                                    if window.is_visible().unwrap_or(false) {
-                                        let _ = window.emit(
-                                            "api-log",
-                                            Some(format!(
-                                                "API process terminated with exit code: {}",
-                                                status.code.unwrap_or(-1)
-                                            ))
-                                        );
-                                    }
-                                    if let Ok(mut state) = api_state_mutex_clone.lock() {
-                                        state.process_child = None;
-                                    }
-                                }
-                                _ => {}
+                                    ),
+                                );
+                                supervisor_clone.detach(
+                                    &app_handle_clone,
+                                    crate::sidecar_supervisor::API_NAME,
+                                    child_pid,
+                                );
                             }
+                            _ => {}
                         }
                     }
                 });
             }
             Err(e) => {
-                eprintln!("启动API服务失败: {}", e);
+                error!("启动API服务失败: {}", e);
+                report_stage(
+                    &app_handle,
+                    StartupStage::ApiBoot,
+                    StageStatus::Failed,
+                    Some(format!("启动API服务失败: {}", e)),
+                );
                 if let Some(window) = app_handle.get_webview_window("main") {
                     if window.is_visible().unwrap_or(false) {
                         let _ = window.emit("api-error", Some(format!("启动API服务失败: {}", e)));
@@ -421,3 +513,223 @@ pub fn start_python_api(
 
     rx // 返回接收端
 }
+
+/// 等待旧API进程树自行退出的最长时间，超时后强制终止
+const RESTART_EXIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+/// 等待旧API释放端口的最长时间
+const PORT_RELEASE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// 终止当前API进程并重新启动
+///
+/// 供看门狗在连续健康检查失败后调用。uv 启动的 Python 进程是 uv 的子进程，只终止 uv 时可能残留并占用端口，
+/// 因此按本次启动的 uv 进程 PID 终止整个进程树，确认进程退出、端口释放后再启动新进程。
+pub async fn restart_python_api(
+    app_handle: AppHandle,
+    api_state_mutex: Arc<Mutex<crate::ApiProcessState>>,
+) -> oneshot::Receiver<bool> {
+    let (old_child, host, port) = {
+        let api_state_guard = api_state_mutex.lock().unwrap();
+        (
+            api_state_guard
+                .supervisor
+                .take_child(crate::sidecar_supervisor::API_NAME),
+            api_state_guard.host.clone(),
+            api_state_guard.port,
+        )
+    };
+
+    if let Some(child) = old_child {
+        let child_pid = child.pid();
+        // 先记下整个进程树：uv 退出后 Python 进程会被过继，无法再按父进程查找
+        let pids = crate::process_tree::tree_pids(child_pid);
+        info!(
+            "[API_RESTART] 终止旧的API进程树, uv PID: {}, 进程: {:?}",
+            child_pid, pids
+        );
+        crate::process_tree::terminate(&pids, false);
+        if !crate::process_tree::wait_for_exit(&pids, RESTART_EXIT_TIMEOUT).await {
+            warn!(
+                "[API_RESTART] 旧的API进程未在 {:?} 内退出，强制终止",
+                RESTART_EXIT_TIMEOUT
+            );
+            crate::process_tree::terminate(&pids, true);
+            if let Err(e) = child.kill() {
+                error!("[API_RESTART] 终止旧的 uv 进程失败: {}", e);
+            }
+            crate::process_tree::wait_for_exit(&pids, RESTART_EXIT_TIMEOUT).await;
+        }
+    }
+
+    if !crate::process_tree::wait_for_port_free(&host, port, PORT_RELEASE_TIMEOUT).await {
+        warn!(
+            "[API_RESTART] 端口 {}:{} 在 {:?} 内未释放，仍尝试启动新进程",
+            host, port, PORT_RELEASE_TIMEOUT
+        );
+    }
+
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.emit(
+            "api-log",
+            Some("Restarting Python API service...".to_string()),
+        );
+    }
+
+    start_python_api(app_handle, api_state_mutex)
+}
+
+/// 开启外部API模式的环境变量（值为 `1` 或 `true`）
+///
+/// 开发者在调试器中手动运行 main.py 时设置，应用会连接已运行的API而不是启动sidecar。
+pub const EXTERNAL_API_ENV: &str = "KNOWLEDGE_FOCUS_EXTERNAL_API";
+
+/// 是否请求了外部API模式
+pub fn external_api_requested() -> bool {
+    matches!(
+        std::env::var(EXTERNAL_API_ENV).as_deref(),
+        Ok("1") | Ok("true")
+    )
+}
+
+/// 尝试连接配置的主机/端口上已运行的 Knowledge Focus API
+///
+/// 只有 `/health` 返回本应用API的标识时才会连接，成功后将 ApiProcessState 标记为外部模式，
+/// 此后退出清理和看门狗都不会终止或重启该进程。返回 false 时调用方应照常启动sidecar。
+pub async fn try_attach_external_api(
+    app_handle: &AppHandle,
+    api_state_mutex: &Arc<Mutex<crate::ApiProcessState>>,
+) -> bool {
+    let (host, port) = {
+        let api_state_guard = api_state_mutex.lock().unwrap();
+        (api_state_guard.host.clone(), api_state_guard.port)
+    };
+    let health_url = format!("http://{}:{}/health", host, port);
+
+    let health_body = match crate::http_client::shared()
+        .get(&health_url)
+        .timeout(std::time::Duration::from_secs(2))
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => {
+            response.json::<serde_json::Value>().await.ok()
+        }
+        _ => None,
+    };
+
+    let is_knowledge_focus_api = health_body
+        .as_ref()
+        .and_then(|body| body.get("service"))
+        .and_then(|service| service.as_str())
+        == Some("knowledge-focus-api");
+    if !is_knowledge_focus_api {
+        info!(
+            "[API_EXTERNAL] {} 上没有可连接的 Knowledge Focus API，将启动内置API",
+            health_url
+        );
+        return false;
+    }
+
+    api_state_mutex.lock().unwrap().external = true;
+    crate::EXTERNAL_API_ATTACHED.store(true, std::sync::atomic::Ordering::SeqCst);
+
+    info!(
+        "[API_EXTERNAL] 已连接外部API: {}:{}（桥接事件通过SSE通道接收）",
+        host, port
+    );
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.emit(
+            "api-log",
+            Some(format!(
+                "Attached to external Python API at {}:{}",
+                host, port
+            )),
+        );
+    }
+    true
+}
+
+/// Rust 端期望的API接口版本，请求/响应结构发生不兼容变化时需与 Python 端 `API_SCHEMA_VERSION` 同步递增
+pub const EXPECTED_API_SCHEMA_VERSION: u64 = 1;
+
+/// 通过 `/version` 检查API接口版本是否与当前应用兼容
+///
+/// 版本不一致（或API缺少 `/version` 端点）时发出 `api-version-mismatch` 事件并返回错误；
+/// 网络异常等无法判断的情况只记录警告，不阻止启动。
+pub async fn verify_api_version(
+    app_handle: &AppHandle,
+    api_state_mutex: &Arc<Mutex<crate::ApiProcessState>>,
+) -> Result<(), String> {
+    let version_url = {
+        let api_state_guard = api_state_mutex.lock().unwrap();
+        format!(
+            "http://{}:{}/version",
+            api_state_guard.host, api_state_guard.port
+        )
+    };
+
+    let response = match crate::http_client::shared()
+        .get(&version_url)
+        .timeout(std::time::Duration::from_secs(3))
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("[API_VERSION] 获取API版本失败，跳过兼容性检查: {}", e);
+            return Ok(());
+        }
+    };
+
+    let (api_schema_version, api_version) = if response.status().is_success() {
+        match response.json::<serde_json::Value>().await {
+            Ok(body) => (
+                body.get("schema_version").and_then(|v| v.as_u64()),
+                body.get("api_version")
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string()),
+            ),
+            Err(e) => {
+                warn!("[API_VERSION] 解析API版本信息失败，跳过兼容性检查: {}", e);
+                return Ok(());
+            }
+        }
+    } else if response.status() == reqwest::StatusCode::NOT_FOUND {
+        // 没有 /version 端点的API早于版本握手机制，视为不兼容
+        (None, None)
+    } else {
+        warn!(
+            "[API_VERSION] 获取API版本返回状态码 {}，跳过兼容性检查",
+            response.status()
+        );
+        return Ok(());
+    };
+
+    if api_schema_version == Some(EXPECTED_API_SCHEMA_VERSION) {
+        info!(
+            "[API_VERSION] API版本兼容: {} (schema {})",
+            api_version.as_deref().unwrap_or("unknown"),
+            EXPECTED_API_SCHEMA_VERSION
+        );
+        return Ok(());
+    }
+
+    let message = format!(
+        "API接口版本不兼容: 应用期望 schema {}，API 提供 {}",
+        EXPECTED_API_SCHEMA_VERSION,
+        api_schema_version
+            .map(|v| format!("schema {}", v))
+            .unwrap_or_else(|| "未知版本".to_string())
+    );
+    warn!("[API_VERSION] {}", message);
+    let _ = app_handle.emit(
+        "api-version-mismatch",
+        serde_json::json!({
+            "expected_schema_version": EXPECTED_API_SCHEMA_VERSION,
+            "api_schema_version": api_schema_version,
+            "api_version": api_version,
+            "app_version": app_handle.package_info().version.to_string(),
+            "message": message
+        }),
+    );
+    Err(message)
+}
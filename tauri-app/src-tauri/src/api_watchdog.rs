@@ -0,0 +1,156 @@
+//! # API 看门狗 (API Watchdog)
+//!
+//! 启动阶段的健康检查只在 lib.rs 中执行一次，API 就绪后如果 Python 进程卡死或崩溃，
+//! 前端只能看到各种请求失败。该模块在 API 就绪后持续探测 `/health`：
+//! - 首次失败时发出 `api-degraded` 事件
+//! - 恢复健康时发出 `api-recovered` 事件
+//! - 连续失败达到阈值后走重启流程（终止旧进程并重新启动 API），外部API模式下不重启
+
+use log::{error, info, warn};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 健康检查间隔
+const PROBE_INTERVAL: Duration = Duration::from_secs(10);
+/// 单次健康检查超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+/// 连续失败多少次后重启API
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+/// 重启后等待API重新就绪的最长时间
+const RESTART_GRACE_PERIOD: Duration = Duration::from_secs(120);
+
+/// 探测一次API健康状态
+async fn probe_health(client: &reqwest::Client, api_url: &str) -> bool {
+    match client.get(api_url).timeout(PROBE_TIMEOUT).send().await {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+/// 读取当前API健康检查地址（主机和端口可能在运行期间被修改）
+fn health_url(api_state_mutex: &Arc<Mutex<crate::ApiProcessState>>) -> String {
+    let api_state_guard = api_state_mutex.lock().unwrap();
+    format!(
+        "http://{}:{}/health",
+        api_state_guard.host, api_state_guard.port
+    )
+}
+
+/// 启动API看门狗，应在API首次就绪后调用
+pub fn start_api_watchdog(
+    app_handle: AppHandle,
+    api_state_mutex: Arc<Mutex<crate::ApiProcessState>>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let client = crate::http_client::shared();
+        let supervisor = api_state_mutex.lock().unwrap().supervisor.clone();
+        let mut consecutive_failures: u32 = 0;
+        let mut degraded = false;
+        let mut restart_count: u32 = 0;
+
+        info!(
+            "[WATCHDOG] API看门狗已启动，检查间隔: {:?}，失败阈值: {}",
+            PROBE_INTERVAL, MAX_CONSECUTIVE_FAILURES
+        );
+
+        loop {
+            tokio::time::sleep(PROBE_INTERVAL).await;
+
+            let api_url = health_url(&api_state_mutex);
+            let healthy = probe_health(&client, &api_url).await;
+            // 健康状态同步到监管器，前端的 sidecar 状态列表中可以看到API是否健康
+            supervisor.report_health(&app_handle, crate::sidecar_supervisor::API_NAME, healthy);
+            if healthy {
+                if degraded {
+                    info!(
+                        "[WATCHDOG] API已恢复健康 (此前连续失败 {} 次)",
+                        consecutive_failures
+                    );
+                    let _ = app_handle.emit(
+                        "api-recovered",
+                        serde_json::json!({
+                            "restart_count": restart_count,
+                            "timestamp": chrono::Utc::now().to_rfc3339()
+                        }),
+                    );
+                    crate::tray_status::set_api_down(&app_handle, false);
+                    degraded = false;
+                }
+                consecutive_failures = 0;
+                continue;
+            }
+
+            consecutive_failures += 1;
+            error!(
+                "[WATCHDOG] API健康检查失败 ({}/{}): {}",
+                consecutive_failures, MAX_CONSECUTIVE_FAILURES, api_url
+            );
+
+            if !degraded {
+                degraded = true;
+                let _ = app_handle.emit(
+                    "api-degraded",
+                    serde_json::json!({
+                        "consecutive_failures": consecutive_failures,
+                        "timestamp": chrono::Utc::now().to_rfc3339()
+                    }),
+                );
+                crate::tray_status::set_api_down(&app_handle, true);
+            }
+
+            if consecutive_failures < MAX_CONSECUTIVE_FAILURES {
+                continue;
+            }
+
+            // 外部API不由应用管理，只能等待开发者自行恢复
+            if api_state_mutex.lock().unwrap().external {
+                error!("[WATCHDOG] 外部API连续健康检查失败，外部模式下不自动重启");
+                consecutive_failures = 0;
+                continue;
+            }
+
+            // 连续失败达到阈值，重启API
+            restart_count += 1;
+            error!(
+                "[WATCHDOG] API连续 {} 次健康检查失败，开始第 {} 次重启",
+                consecutive_failures, restart_count
+            );
+            let _ =
+                crate::api_startup::restart_python_api(app_handle.clone(), api_state_mutex.clone())
+                    .await;
+            consecutive_failures = 0;
+
+            // 等待重启后的API重新就绪，期间不计入失败次数
+            let deadline = tokio::time::Instant::now() + RESTART_GRACE_PERIOD;
+            while tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                if probe_health(&client, &health_url(&api_state_mutex)).await {
+                    supervisor.report_health(
+                        &app_handle,
+                        crate::sidecar_supervisor::API_NAME,
+                        true,
+                    );
+                    info!("[WATCHDOG] 重启后API已重新就绪");
+                    let _ = app_handle.emit(
+                        "api-recovered",
+                        serde_json::json!({
+                            "restart_count": restart_count,
+                            "timestamp": chrono::Utc::now().to_rfc3339()
+                        }),
+                    );
+                    crate::tray_status::set_api_down(&app_handle, false);
+                    degraded = false;
+                    break;
+                }
+            }
+
+            if degraded {
+                warn!(
+                    "[WATCHDOG] 重启后 {:?} 内API仍未就绪，继续监测",
+                    RESTART_GRACE_PERIOD
+                );
+            }
+        }
+    });
+}
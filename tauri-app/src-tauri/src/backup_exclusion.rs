@@ -0,0 +1,155 @@
+//! # 备份位置排除 (Backup Location Exclusion)
+//!
+//! Time Machine 快照和备份卷、Windows 文件历史记录和系统映像备份、Linux 的 Timeshift 快照中
+//! 保存着大量与原始文件重复的副本，扫描它们会索引上百万个重复文件。默认把检测到的备份位置
+//! 当作黑名单处理（扫描和文件事件都跳过），可以通过 `set_backup_exclusion` 关闭，
+//! 选择保存在 settings.json 的 `exclude_backup_locations` 中。
+//!
+//! 备份位置在启动时检测，新卷挂载时（`volume-mounted` 事件）重新检测。
+
+use log::info;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use tauri::{AppHandle, Listener};
+use tauri_plugin_store::StoreExt;
+
+/// settings.json 中保存是否排除备份位置的键
+const EXCLUDE_STORE_KEY: &str = "exclude_backup_locations";
+
+/// 固定的系统备份位置
+#[cfg(target_os = "macos")]
+const FIXED_BACKUP_LOCATIONS: &[&str] = &[
+    "/Volumes/.timemachine",
+    "/Volumes/com.apple.TimeMachine.localsnapshots",
+    "/System/Volumes/Data/.MobileBackups",
+    "/.MobileBackups",
+    "/Backups.backupdb",
+];
+#[cfg(windows)]
+const FIXED_BACKUP_LOCATIONS: &[&str] = &[];
+#[cfg(target_os = "linux")]
+const FIXED_BACKUP_LOCATIONS: &[&str] = &["/timeshift", "/run/timeshift"];
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+const FIXED_BACKUP_LOCATIONS: &[&str] = &[];
+
+/// 卷根目录下表示备份数据的目录或标记文件：
+/// 存在标记文件时整个卷是备份卷，否则只排除该目录
+#[cfg(target_os = "macos")]
+const VOLUME_BACKUP_MARKERS: &[(&str, bool)] = &[
+    ("Backups.backupdb", false),
+    (".com.apple.timemachine.donotpresent", true),
+];
+#[cfg(windows)]
+const VOLUME_BACKUP_MARKERS: &[(&str, bool)] = &[
+    ("FileHistory", false),
+    ("WindowsImageBackup", false),
+    ("System Volume Information", false),
+];
+#[cfg(target_os = "linux")]
+const VOLUME_BACKUP_MARKERS: &[(&str, bool)] = &[("timeshift", false)];
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+const VOLUME_BACKUP_MARKERS: &[(&str, bool)] = &[];
+
+/// 是否排除备份位置，默认排除
+static EXCLUDE_BACKUPS: AtomicBool = AtomicBool::new(true);
+/// 最近一次检测到的备份位置
+static BACKUP_LOCATIONS: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+
+/// 检测当前存在的备份位置
+fn detect_backup_locations() -> Vec<PathBuf> {
+    let mut locations: Vec<PathBuf> = FIXED_BACKUP_LOCATIONS
+        .iter()
+        .map(PathBuf::from)
+        .filter(|path| path.exists())
+        .collect();
+    for mount_point in crate::volumes::mount_points() {
+        for (marker, whole_volume) in VOLUME_BACKUP_MARKERS {
+            let marker_path = mount_point.join(marker);
+            if marker_path.exists() {
+                locations.push(if *whole_volume {
+                    mount_point.clone()
+                } else {
+                    marker_path
+                });
+            }
+        }
+    }
+    locations.sort();
+    locations.dedup();
+    locations
+}
+
+/// 重新检测备份位置
+fn refresh() {
+    let locations = detect_backup_locations();
+    if !locations.is_empty() {
+        info!("[BACKUP_EXCLUSION] 检测到备份位置: {:?}", locations);
+    }
+    *BACKUP_LOCATIONS.write().unwrap() = locations;
+}
+
+/// 路径是否位于需要排除的备份位置中
+pub fn is_backup_path(path: &Path) -> bool {
+    EXCLUDE_BACKUPS.load(Ordering::SeqCst)
+        && BACKUP_LOCATIONS
+            .read()
+            .unwrap()
+            .iter()
+            .any(|location| crate::path_utils::path_starts_with(path, location))
+}
+
+/// 恢复保存的设置并检测备份位置，应在 `volumes::init` 之后、文件监控启动前调用
+pub fn init(app_handle: &AppHandle) {
+    let saved = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(EXCLUDE_STORE_KEY))
+        .and_then(|value| value.as_bool());
+    if let Some(exclude) = saved {
+        EXCLUDE_BACKUPS.store(exclude, Ordering::SeqCst);
+    }
+    refresh();
+
+    // 新挂载的卷可能是备份卷
+    app_handle.listen_any("volume-mounted", |_| refresh());
+}
+
+/// 设置是否在扫描和监控中排除备份位置
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_backup_exclusion(
+    enabled: bool,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    EXCLUDE_BACKUPS.store(enabled, Ordering::SeqCst);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(EXCLUDE_STORE_KEY, serde_json::json!(enabled));
+    store
+        .save()
+        .map_err(|e| format!("保存备份排除设置失败: {}", e))?;
+
+    info!("[BACKUP_EXCLUSION] 排除备份位置: {}", enabled);
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": if enabled { "已排除备份位置" } else { "不再排除备份位置" }
+    }))
+}
+
+/// 获取备份排除设置和检测到的备份位置
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_backup_exclusion() -> Result<serde_json::Value, String> {
+    let locations: Vec<String> = BACKUP_LOCATIONS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|location| location.to_string_lossy().to_string())
+        .collect();
+    Ok(serde_json::json!({
+        "status": "success",
+        "enabled": EXCLUDE_BACKUPS.load(Ordering::SeqCst),
+        "locations": locations
+    }))
+}
@@ -0,0 +1,227 @@
+//! # 桥接事件类型 (Typed Bridge Events)
+//!
+//! Python 端通过 `BridgeEventSender`（api/bridge_events.py）发送的事件在线上仍是
+//! `{"event": ..., "payload": ...}`。事件进入 Rust 时按事件名把已知事件的 payload 解析为对应结构，
+//! payload 格式不符的事件在桥接边界被拒绝，未列出的事件作为 `Unknown` 原样转发。
+//! payload 结构只声明 Rust 端用到的字段，Python 端新增字段不会导致解析失败。
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fmt;
+
+use crate::event_buffer::BridgeEventData;
+
+/// 任务ID，Python 端可能以字符串或整数发送
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum TaskId {
+    Text(String),
+    Number(i64),
+}
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskId::Text(id) => write!(f, "{}", id),
+            TaskId::Number(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaskCompletedPayload {
+    pub task_id: TaskId,
+    #[serde(default = "default_true")]
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelDownloadProgressPayload {
+    pub model_name: String,
+    pub current: f64,
+    pub total: f64,
+    #[serde(default)]
+    pub stage: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelDownloadCompletedPayload {
+    pub model_name: String,
+    #[serde(default)]
+    pub local_path: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelDownloadFailedPayload {
+    pub model_name: String,
+    #[serde(default)]
+    pub error_message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MultivectorProgressPayload {
+    pub file_path: String,
+    #[serde(default)]
+    pub task_id: Option<TaskId>,
+    pub current: f64,
+    pub total: f64,
+    #[serde(default)]
+    pub stage: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MultivectorCompletedPayload {
+    pub file_path: String,
+    #[serde(default)]
+    pub task_id: Option<TaskId>,
+    #[serde(default)]
+    pub parent_chunks_count: u64,
+    #[serde(default)]
+    pub child_chunks_count: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MultivectorFailedPayload {
+    pub file_path: String,
+    #[serde(default)]
+    pub task_id: Option<TaskId>,
+    pub error_message: String,
+    #[serde(default)]
+    pub error_code: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ErrorOccurredPayload {
+    pub error_type: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SystemStatusPayload {
+    pub status: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ControlCommandResultPayload {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub command: String,
+    pub status: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 已知桥接事件的类型化数据
+#[derive(Debug, Clone)]
+pub enum BridgeEventKind {
+    TaskCompleted(TaskCompletedPayload),
+    ModelDownloadProgress(ModelDownloadProgressPayload),
+    ModelDownloadCompleted(ModelDownloadCompletedPayload),
+    ModelDownloadFailed(ModelDownloadFailedPayload),
+    MultivectorProgress(MultivectorProgressPayload),
+    MultivectorCompleted(MultivectorCompletedPayload),
+    MultivectorFailed(MultivectorFailedPayload),
+    ErrorOccurred(ErrorOccurredPayload),
+    SystemStatus(SystemStatusPayload),
+    ControlCommandResult(ControlCommandResultPayload),
+    /// 未定义结构的事件，payload 不做校验
+    Unknown,
+}
+
+/// 经过校验的桥接事件：原始数据用于缓冲和发送，类型化数据供各处理逻辑使用
+#[derive(Debug, Clone)]
+pub struct BridgeEvent {
+    pub data: BridgeEventData,
+    pub kind: BridgeEventKind,
+}
+
+impl BridgeEvent {
+    /// 按事件名解析 payload，已知事件的 payload 格式不符时返回错误
+    pub fn parse(data: BridgeEventData) -> Result<Self, String> {
+        fn payload<T: DeserializeOwned>(data: &BridgeEventData) -> Result<T, String> {
+            T::deserialize(&data.payload)
+                .map_err(|e| format!("事件 {} 的payload格式错误: {}", data.event, e))
+        }
+
+        let kind = match data.event.as_str() {
+            "task-completed" => BridgeEventKind::TaskCompleted(payload(&data)?),
+            "model-download-progress" => BridgeEventKind::ModelDownloadProgress(payload(&data)?),
+            "model-download-completed" => BridgeEventKind::ModelDownloadCompleted(payload(&data)?),
+            "model-download-failed" => BridgeEventKind::ModelDownloadFailed(payload(&data)?),
+            "multivector-progress" => BridgeEventKind::MultivectorProgress(payload(&data)?),
+            "multivector-completed" => BridgeEventKind::MultivectorCompleted(payload(&data)?),
+            "multivector-failed" => BridgeEventKind::MultivectorFailed(payload(&data)?),
+            "error-occurred" => BridgeEventKind::ErrorOccurred(payload(&data)?),
+            "system-status" => BridgeEventKind::SystemStatus(payload(&data)?),
+            "control-command-result" => BridgeEventKind::ControlCommandResult(payload(&data)?),
+            _ => BridgeEventKind::Unknown,
+        };
+        Ok(Self { data, kind })
+    }
+
+    /// 事件摘要，用于日志
+    pub fn summary(&self) -> String {
+        match &self.kind {
+            BridgeEventKind::TaskCompleted(p) => {
+                format!("任务 {} 完成 (成功: {})", p.task_id, p.success)
+            }
+            BridgeEventKind::ModelDownloadProgress(p) => format!(
+                "模型 {} {} {}/{} {}",
+                p.model_name, p.stage, p.current, p.total, p.message
+            ),
+            BridgeEventKind::ModelDownloadCompleted(p) => {
+                format!("模型 {} 下载完成: {}", p.model_name, p.local_path)
+            }
+            BridgeEventKind::ModelDownloadFailed(p) => {
+                format!("模型 {} 下载失败: {}", p.model_name, p.error_message)
+            }
+            BridgeEventKind::MultivectorProgress(p) => format!(
+                "{} {} {}/{} (任务: {})",
+                p.file_path,
+                p.stage,
+                p.current,
+                p.total,
+                display_task_id(&p.task_id)
+            ),
+            BridgeEventKind::MultivectorCompleted(p) => format!(
+                "{} 向量化完成，父块 {} 个，子块 {} 个 (任务: {})",
+                p.file_path,
+                p.parent_chunks_count,
+                p.child_chunks_count,
+                display_task_id(&p.task_id)
+            ),
+            BridgeEventKind::MultivectorFailed(p) => format!(
+                "{} 向量化失败 [{}]: {} (任务: {})",
+                p.file_path,
+                p.error_code,
+                p.error_message,
+                display_task_id(&p.task_id)
+            ),
+            BridgeEventKind::ErrorOccurred(p) => format!("[{}] {}", p.error_type, p.message),
+            BridgeEventKind::SystemStatus(p) => format!("{} {}", p.status, p.message),
+            BridgeEventKind::ControlCommandResult(p) => format!(
+                "{} ({}) -> {}: {}",
+                p.command,
+                p.id.as_deref().unwrap_or("-"),
+                p.status,
+                p.message
+            ),
+            BridgeEventKind::Unknown => String::new(),
+        }
+    }
+}
+
+fn display_task_id(task_id: &Option<TaskId>) -> String {
+    task_id
+        .as_ref()
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
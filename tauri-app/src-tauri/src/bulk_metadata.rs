@@ -0,0 +1,406 @@
+//! # 目录批量元数据读取 (Bulk Metadata Reads)
+//!
+//! 初始扫描对每个文件调用一次 `fs::metadata`，目录中有上万个文件时就是上万次系统调用。
+//! 两个平台提供了一次读取整个目录元数据的接口：
+//! - macOS：`getattrlistbulk`，一次调用返回多个目录项的名称、类型、创建/修改时间和大小
+//! - Windows：`GetFileInformationByHandleEx(FileFullDirectoryInfo)`，一次调用填满一个缓冲区的目录项
+//!
+//! 扫描通过 [`scope`] 执行，在其中 [`lookup`] 按所在目录批量读取并缓存元数据；
+//! 缓存是一个目录栈，与深度优先遍历的顺序一致，离开目录后即释放。
+//! 其他平台、不支持批量读取的文件系统以及符号链接返回 None，调用方改用 `fs::metadata`。
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// 从目录项读取的元数据
+#[derive(Debug, Clone, Copy)]
+pub struct EntryStat {
+    pub is_dir: bool,
+    pub len: u64,
+    pub created: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+}
+
+impl From<&std::fs::Metadata> for EntryStat {
+    fn from(metadata: &std::fs::Metadata) -> EntryStat {
+        EntryStat {
+            is_dir: metadata.is_dir(),
+            len: metadata.len(),
+            created: metadata.created().ok(),
+            modified: metadata.modified().ok(),
+        }
+    }
+}
+
+// 当前遍历路径上各级目录的元数据（栈底为最外层目录）
+#[derive(Default)]
+struct DirStack {
+    dirs: Vec<(PathBuf, HashMap<OsString, EntryStat>)>,
+}
+
+impl DirStack {
+    fn lookup(&mut self, dir: &Path, name: &std::ffi::OsStr) -> Option<EntryStat> {
+        // 已离开的目录出栈
+        while self
+            .dirs
+            .last()
+            .is_some_and(|(top, _)| !dir.starts_with(top))
+        {
+            self.dirs.pop();
+        }
+        if self.dirs.last().is_none_or(|(top, _)| top != dir) {
+            // 读取失败时缓存空结果，同一目录下的文件不再重试
+            let entries = platform::read_dir(dir).unwrap_or_else(|e| {
+                log::debug!("[BULK_METADATA] 无法批量读取目录 {:?}: {}", dir, e);
+                HashMap::new()
+            });
+            self.dirs.push((dir.to_path_buf(), entries));
+        }
+        self.dirs.last()?.1.get(name).copied()
+    }
+}
+
+tokio::task_local! {
+    static DIR_STACK: RefCell<DirStack>;
+}
+
+/// 在批量元数据缓存中执行扫描
+pub async fn scope<F: Future>(scan: F) -> F::Output {
+    DIR_STACK
+        .scope(RefCell::new(DirStack::default()), scan)
+        .await
+}
+
+/// 查询文件的元数据；不在 [`scope`] 中、平台不支持或目录读取失败时返回 None
+pub fn lookup(path: &Path) -> Option<EntryStat> {
+    if !platform::SUPPORTED {
+        return None;
+    }
+    let (dir, name) = (path.parent()?, path.file_name()?);
+    DIR_STACK
+        .try_with(|stack| stack.borrow_mut().lookup(dir, name))
+        .ok()
+        .flatten()
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::EntryStat;
+    use std::collections::HashMap;
+    use std::ffi::{c_void, OsString};
+    use std::os::unix::ffi::OsStringExt;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    pub const SUPPORTED: bool = true;
+
+    // <sys/attr.h>
+    const ATTR_BIT_MAP_COUNT: u16 = 5;
+    const ATTR_CMN_NAME: u32 = 0x0000_0001;
+    const ATTR_CMN_OBJTYPE: u32 = 0x0000_0008;
+    const ATTR_CMN_CRTIME: u32 = 0x0000_0200;
+    const ATTR_CMN_MODTIME: u32 = 0x0000_0400;
+    const ATTR_CMN_RETURNED_ATTRS: u32 = 0x8000_0000;
+    const ATTR_FILE_DATALENGTH: u32 = 0x0000_0200;
+    const FSOPT_PACK_INVAL_ATTRS: u64 = 0x0000_0008;
+    // <sys/vnode.h>
+    const VREG: u32 = 1;
+    const VDIR: u32 = 2;
+
+    const BUFFER_SIZE: usize = 256 * 1024;
+
+    #[repr(C)]
+    struct AttrList {
+        bitmapcount: u16,
+        reserved: u16,
+        commonattr: u32,
+        volattr: u32,
+        dirattr: u32,
+        fileattr: u32,
+        forkattr: u32,
+    }
+
+    extern "C" {
+        fn getattrlistbulk(
+            dirfd: i32,
+            attr_list: *mut AttrList,
+            attr_buf: *mut c_void,
+            attr_buf_size: usize,
+            options: u64,
+        ) -> i32;
+    }
+
+    // 按顺序读取紧凑排列的属性（每个属性按 4 字节对齐）
+    struct Cursor<'a> {
+        entry: &'a [u8],
+        offset: usize,
+    }
+
+    impl Cursor<'_> {
+        fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+            let bytes = self
+                .entry
+                .get(self.offset..self.offset + N)?
+                .try_into()
+                .ok()?;
+            self.offset += N;
+            Some(bytes)
+        }
+
+        fn u32(&mut self) -> Option<u32> {
+            self.bytes().map(u32::from_ne_bytes)
+        }
+
+        fn i64(&mut self) -> Option<i64> {
+            self.bytes().map(i64::from_ne_bytes)
+        }
+
+        fn timespec(&mut self) -> Option<SystemTime> {
+            let (sec, nsec) = (self.i64()?, self.i64()?);
+            let sec = u64::try_from(sec).ok()?;
+            Some(UNIX_EPOCH + Duration::new(sec, nsec.clamp(0, 999_999_999) as u32))
+        }
+    }
+
+    fn parse_entry(entry: &[u8]) -> Option<(OsString, EntryStat)> {
+        let mut cursor = Cursor { entry, offset: 4 };
+        // attribute_set_t：实际返回的属性
+        let returned_common = cursor.u32()?;
+        let _ = (cursor.u32()?, cursor.u32()?);
+        let returned_file = cursor.u32()?;
+        let _ = cursor.u32()?;
+
+        // attrreference_t：名称相对于该结构的偏移和长度（含结尾的 NUL）
+        let name_ref = cursor.offset;
+        let name_offset = i32::from_ne_bytes(cursor.bytes()?);
+        let name_length = cursor.u32()? as usize;
+        let name_start = name_ref.checked_add_signed(name_offset as isize)?;
+        let name = entry.get(name_start..name_start + name_length.checked_sub(1)?)?;
+
+        let object_type = cursor.u32()?;
+        let created = cursor.timespec();
+        let modified = cursor.timespec();
+        let len = cursor.i64()?;
+
+        // 符号链接等其他类型交给 fs::metadata 处理（需要跟随链接）
+        if object_type != VREG && object_type != VDIR {
+            return None;
+        }
+        Some((
+            OsString::from_vec(name.to_vec()),
+            EntryStat {
+                is_dir: object_type == VDIR,
+                len: if returned_file & ATTR_FILE_DATALENGTH != 0 {
+                    len.max(0) as u64
+                } else {
+                    0
+                },
+                created: created.filter(|_| returned_common & ATTR_CMN_CRTIME != 0),
+                modified: modified.filter(|_| returned_common & ATTR_CMN_MODTIME != 0),
+            },
+        ))
+    }
+
+    pub fn read_dir(dir: &Path) -> std::io::Result<HashMap<OsString, EntryStat>> {
+        let handle = std::fs::File::open(dir)?;
+        let mut attributes = AttrList {
+            bitmapcount: ATTR_BIT_MAP_COUNT,
+            reserved: 0,
+            commonattr: ATTR_CMN_RETURNED_ATTRS
+                | ATTR_CMN_NAME
+                | ATTR_CMN_OBJTYPE
+                | ATTR_CMN_CRTIME
+                | ATTR_CMN_MODTIME,
+            volattr: 0,
+            dirattr: 0,
+            fileattr: ATTR_FILE_DATALENGTH,
+            forkattr: 0,
+        };
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        let mut entries = HashMap::new();
+        loop {
+            // SAFETY: attributes 和 buffer 在调用期间有效，buffer 长度与传入的大小一致
+            let count = unsafe {
+                getattrlistbulk(
+                    handle.as_raw_fd(),
+                    &mut attributes,
+                    buffer.as_mut_ptr().cast(),
+                    buffer.len(),
+                    FSOPT_PACK_INVAL_ATTRS,
+                )
+            };
+            if count < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if count == 0 {
+                break;
+            }
+            let mut offset = 0;
+            for _ in 0..count {
+                let Some(length) = buffer
+                    .get(offset..offset + 4)
+                    .map(|bytes| u32::from_ne_bytes(bytes.try_into().unwrap()) as usize)
+                else {
+                    break;
+                };
+                if let Some((name, stat)) =
+                    buffer.get(offset..offset + length).and_then(parse_entry)
+                {
+                    entries.insert(name, stat);
+                }
+                offset += length;
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::EntryStat;
+    use std::collections::HashMap;
+    use std::ffi::{c_void, OsString};
+    use std::os::windows::ffi::OsStringExt;
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    pub const SUPPORTED: bool = true;
+
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    // FILE_INFO_BY_HANDLE_CLASS::FileFullDirectoryInfo
+    const FILE_FULL_DIRECTORY_INFO: i32 = 14;
+    const ERROR_NO_MORE_FILES: i32 = 18;
+    const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+    const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+    // 1601-01-01 到 1970-01-01 之间的 100 纳秒数
+    const UNIX_EPOCH_FILETIME: i64 = 116_444_736_000_000_000;
+
+    // FILE_FULL_DIR_INFO 需要 8 字节对齐，使用 u64 缓冲区
+    const BUFFER_WORDS: usize = 8 * 1024;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetFileInformationByHandleEx(
+            file: *mut c_void,
+            class: i32,
+            info: *mut c_void,
+            size: u32,
+        ) -> i32;
+    }
+
+    fn filetime(value: i64) -> Option<SystemTime> {
+        let since_epoch = u64::try_from(value.checked_sub(UNIX_EPOCH_FILETIME)?).ok()?;
+        Some(UNIX_EPOCH + Duration::from_nanos(since_epoch.checked_mul(100)?))
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+        Some(u32::from_ne_bytes(
+            bytes.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    }
+
+    fn read_i64(bytes: &[u8], offset: usize) -> Option<i64> {
+        Some(i64::from_ne_bytes(
+            bytes.get(offset..offset + 8)?.try_into().ok()?,
+        ))
+    }
+
+    // FILE_FULL_DIR_INFO 布局：NextEntryOffset(0) FileIndex(4) CreationTime(8) LastAccessTime(16)
+    // LastWriteTime(24) ChangeTime(32) EndOfFile(40) AllocationSize(48) FileAttributes(56)
+    // FileNameLength(60) EaSize(64) FileName(68)
+    fn parse_entry(entry: &[u8]) -> Option<(OsString, EntryStat)> {
+        let attributes = read_u32(entry, 56)?;
+        let name_length = read_u32(entry, 60)? as usize;
+        let name: Vec<u16> = entry
+            .get(68..68 + name_length)?
+            .chunks_exact(2)
+            .map(|unit| u16::from_ne_bytes([unit[0], unit[1]]))
+            .collect();
+        let name = OsString::from_wide(&name);
+        // 符号链接和目录联接交给 fs::metadata 处理（需要跟随链接）
+        if name == "." || name == ".." || attributes & FILE_ATTRIBUTE_REPARSE_POINT != 0 {
+            return None;
+        }
+        let is_dir = attributes & FILE_ATTRIBUTE_DIRECTORY != 0;
+        Some((
+            name,
+            EntryStat {
+                is_dir,
+                len: if is_dir {
+                    0
+                } else {
+                    read_i64(entry, 40)?.max(0) as u64
+                },
+                created: filetime(read_i64(entry, 8)?),
+                modified: filetime(read_i64(entry, 24)?),
+            },
+        ))
+    }
+
+    pub fn read_dir(dir: &Path) -> std::io::Result<HashMap<OsString, EntryStat>> {
+        let handle = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+            .open(dir)?;
+        let mut buffer = vec![0u64; BUFFER_WORDS];
+        let mut entries = HashMap::new();
+        loop {
+            // SAFETY: buffer 在调用期间有效，传入的大小与 buffer 的字节数一致
+            let ok = unsafe {
+                GetFileInformationByHandleEx(
+                    handle.as_raw_handle(),
+                    FILE_FULL_DIRECTORY_INFO,
+                    buffer.as_mut_ptr().cast(),
+                    (buffer.len() * 8) as u32,
+                )
+            };
+            if ok == 0 {
+                let error = std::io::Error::last_os_error();
+                if error.raw_os_error() == Some(ERROR_NO_MORE_FILES) {
+                    break;
+                }
+                return Err(error);
+            }
+            // SAFETY: 以字节方式读取已初始化的 u64 缓冲区
+            let bytes = unsafe {
+                std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), buffer.len() * 8)
+            };
+            let mut offset = 0;
+            loop {
+                let Some(entry) = bytes.get(offset..) else {
+                    break;
+                };
+                if let Some((name, stat)) = parse_entry(entry) {
+                    entries.insert(name, stat);
+                }
+                match read_u32(entry, 0) {
+                    Some(0) | None => break,
+                    Some(next) => offset += next as usize,
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+mod platform {
+    use super::EntryStat;
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+    use std::path::Path;
+
+    // 其他平台没有批量读取接口，逐个 stat 与直接调用 fs::metadata 相同
+    pub const SUPPORTED: bool = false;
+
+    pub fn read_dir(_dir: &Path) -> std::io::Result<HashMap<OsString, EntryStat>> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+}
@@ -0,0 +1,91 @@
+//! # 包识别 (Package Detection)
+//!
+//! macOS 的 bundle（`.app`、`.photoslibrary`、`.fcpbundle` 以及各应用自定义的文档包）在访达中显示为单个文件。
+//! 以前只按目录名后缀判断，名字里恰好带有这些后缀的普通文件夹会被误判，
+//! 不在列表中的包格式（如 `.logicx`、`.scriv`）又识别不出来。
+//!
+//! 现在通过 Launch Services 查询目录的 `NSURLIsPackageKey`：目录的扩展名在 UTI 中声明为包
+//! （遵循 `com.apple.package`），或目录设置了包标志位时为 true，与访达的显示一致。
+//! 查询结果按目录缓存；非 macOS 平台或查询失败（如路径已不存在）时返回 None，由调用方回退到扩展名判断。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 缓存条目上限，超过后整体清空重新积累
+const MAX_ENTRIES: usize = 20_000;
+
+/// 目录路径 -> 是否为包
+static CACHE: Mutex<Option<HashMap<PathBuf, bool>>> = Mutex::new(None);
+
+#[cfg(target_os = "macos")]
+#[allow(unused_unsafe)]
+mod platform {
+    use objc2::rc::Retained;
+    use objc2::runtime::AnyObject;
+    use objc2_foundation::{NSNumber, NSString, NSURLIsPackageKey, NSURL};
+    use std::path::Path;
+
+    /// 向 Launch Services 查询目录是否为包
+    pub fn query_is_package(path: &Path) -> Option<bool> {
+        let path = path.to_str()?;
+        let url = unsafe { NSURL::fileURLWithPath_isDirectory(&NSString::from_str(path), true) };
+        let mut value: Option<Retained<AnyObject>> = None;
+        unsafe { url.getResourceValue_forKey_error(&mut value, NSURLIsPackageKey) }.ok()?;
+        let number = value?.downcast::<NSNumber>().ok()?;
+        Some(number.boolValue())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use std::path::Path;
+
+    pub fn query_is_package(_path: &Path) -> Option<bool> {
+        None
+    }
+}
+
+/// 当前平台能否通过 Launch Services 判断包
+pub fn is_available() -> bool {
+    cfg!(target_os = "macos")
+}
+
+/// 路径是否为包：文件一律不是包；无法判断时返回 None
+pub fn is_package(path: &Path) -> Option<bool> {
+    if !is_available() {
+        return None;
+    }
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_dir() {
+        return Some(false);
+    }
+
+    if let Some(&cached) = CACHE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|cache| cache.get(path))
+    {
+        return Some(cached);
+    }
+
+    let is_package = platform::query_is_package(path)?;
+    let mut cache = CACHE.lock().unwrap();
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if cache.len() >= MAX_ENTRIES {
+        cache.clear();
+    }
+    cache.insert(path.to_path_buf(), is_package);
+    Some(is_package)
+}
+
+/// 路径所在的最外层包（不含路径本身），不在包内时返回 None
+pub fn containing_package(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .filter(|ancestor| ancestor.parent().is_some())
+        .filter(|ancestor| is_package(ancestor) == Some(true))
+        .last()
+        .map(Path::to_path_buf)
+}
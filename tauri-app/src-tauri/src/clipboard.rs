@@ -0,0 +1,475 @@
+//! # 剪贴板捕获 (Clipboard Capture)
+//!
+//! 用户开启后，后台线程每秒检查一次剪贴板，把新复制的文本或文件列表（可获取时附带前台应用）
+//! 通过 `/clipboard/items` 发送到 API，纳入知识库。开关保存在 settings.json 的
+//! `clipboard_capture` 中，默认关闭；可以暂停一段时间或暂停到手动恢复（暂停状态不保存）。
+//!
+//! 敏感内容不会发送：
+//! - 密码管理器等应用复制的内容（按前台应用名），以及 macOS 上标记为隐藏或临时的剪贴板内容
+//!   （`org.nspasteboard.ConcealedType` 等）
+//! - 看起来像私钥、访问令牌、JWT、银行卡号或密码的文本
+//!
+//! 开启捕获时剪贴板中已有的内容不会发送，只记录之后的复制。
+
+use log::{error, info};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+/// settings.json 中保存开关的键
+const ENABLED_STORE_KEY: &str = "clipboard_capture";
+/// 剪贴板检查间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// 无法访问剪贴板时的重试间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+/// 少于此字符数的文本不记录
+const MIN_TEXT_CHARS: usize = 2;
+/// 文本最多保留的字符数
+const MAX_TEXT_CHARS: usize = 20_000;
+/// 一次最多记录的文件数
+const MAX_FILES: usize = 200;
+/// 这些应用复制的内容不记录（按应用名，不区分大小写）
+const IGNORED_APPS: &[&str] = &[
+    "1password",
+    "bitwarden",
+    "keepassxc",
+    "keychain access",
+    "钥匙串访问",
+    "lastpass",
+    "dashlane",
+    "enpass",
+    "passwords",
+    "密码",
+];
+
+/// 是否捕获剪贴板，默认关闭
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// 暂停到的时间（秒级时间戳），0 表示未暂停，u64::MAX 表示暂停到手动恢复
+static PAUSED_UNTIL: AtomicU64 = AtomicU64::new(0);
+/// 已发送到 API 的条数
+static CAPTURED: AtomicU64 = AtomicU64::new(0);
+/// 因敏感内容过滤掉的条数
+static FILTERED: AtomicU64 = AtomicU64::new(0);
+
+/// 一次复制的内容
+enum ClipboardContent {
+    Text(String),
+    Files(Vec<PathBuf>),
+}
+
+impl ClipboardContent {
+    fn hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        match self {
+            ClipboardContent::Text(text) => {
+                hasher.update(b"text\0");
+                hasher.update(text.as_bytes());
+            }
+            ClipboardContent::Files(paths) => {
+                hasher.update(b"files\0");
+                for path in paths {
+                    hasher.update(path.to_string_lossy().as_bytes());
+                    hasher.update(b"\0");
+                }
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn is_paused() -> bool {
+    now_secs() < PAUSED_UNTIL.load(Ordering::SeqCst)
+}
+
+/// 当前是否在捕获剪贴板（已开启且未暂停）
+pub fn is_capturing() -> bool {
+    ENABLED.load(Ordering::SeqCst) && !is_paused()
+}
+
+/// 恢复保存的开关并启动检查线程，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let saved = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(ENABLED_STORE_KEY))
+        .and_then(|value| value.as_bool());
+    if let Some(enabled) = saved {
+        ENABLED.store(enabled, Ordering::SeqCst);
+    }
+    let app_handle = app_handle.clone();
+    let spawned = std::thread::Builder::new()
+        .name("clipboard-capture".to_string())
+        .spawn(move || poll_loop(app_handle));
+    if let Err(e) = spawned {
+        error!("[CLIPBOARD] 无法启动剪贴板检查线程: {}", e);
+    }
+}
+
+// 检查线程：剪贴板内容变化时过滤并发送
+fn poll_loop(app_handle: AppHandle) {
+    let mut clipboard: Option<arboard::Clipboard> = None;
+    // 上次看到的内容哈希；暂停或关闭期间清空，恢复后先记下当时的内容，不发送
+    let mut last_hash: Option<String> = None;
+    let mut last_change_count: Option<isize> = None;
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        if !is_capturing() {
+            clipboard = None;
+            last_hash = None;
+            last_change_count = None;
+            continue;
+        }
+
+        // macOS 上剪贴板变化计数未变时不读取内容
+        let change_count = platform::change_count();
+        if change_count.is_some() && change_count == last_change_count {
+            continue;
+        }
+        last_change_count = change_count;
+
+        if clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(opened) => clipboard = Some(opened),
+                Err(e) => {
+                    error!("[CLIPBOARD] 无法访问剪贴板: {}", e);
+                    last_change_count = None;
+                    std::thread::sleep(RETRY_INTERVAL);
+                    continue;
+                }
+            }
+        }
+        let Some(content) = clipboard.as_mut().and_then(read_content) else {
+            // 内容被过滤（过短的文本、图片等）也算一次变化，之后再次复制之前的内容时仍会捕获；
+            // 空字符串不会与内容哈希相同
+            last_hash = Some(String::new());
+            continue;
+        };
+
+        let hash = content.hash();
+        let first_read = last_hash.is_none();
+        if last_hash.as_deref() == Some(hash.as_str()) {
+            continue;
+        }
+        last_hash = Some(hash.clone());
+        if first_read {
+            continue;
+        }
+
+        let source_app = platform::frontmost_app();
+        if let Some(reason) = sensitive_reason(&content, source_app.as_deref()) {
+            FILTERED.fetch_add(1, Ordering::Relaxed);
+            info!("[CLIPBOARD] 跳过敏感内容: {}", reason);
+            continue;
+        }
+
+        let item = match content {
+            ClipboardContent::Text(text) => serde_json::json!({
+                "kind": "text",
+                "text": text,
+                "source_app": source_app,
+                "content_hash": hash,
+                "captured_at": now_secs()
+            }),
+            ClipboardContent::Files(paths) => serde_json::json!({
+                "kind": "files",
+                "file_paths": paths
+                    .iter()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>(),
+                "source_app": source_app,
+                "content_hash": hash,
+                "captured_at": now_secs()
+            }),
+        };
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            match post_item(&app_handle, item).await {
+                Ok(()) => {
+                    CAPTURED.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => error!("[CLIPBOARD] 发送剪贴板内容失败: {}", e),
+            }
+        });
+    }
+}
+
+// 读取剪贴板：优先文件列表，其次文本
+fn read_content(clipboard: &mut arboard::Clipboard) -> Option<ClipboardContent> {
+    if let Ok(mut paths) = clipboard.get().file_list() {
+        if !paths.is_empty() {
+            paths.truncate(MAX_FILES);
+            return Some(ClipboardContent::Files(paths));
+        }
+    }
+    let text = clipboard.get_text().ok()?;
+    let text = text.trim();
+    if text.chars().count() < MIN_TEXT_CHARS {
+        return None;
+    }
+    Some(ClipboardContent::Text(
+        text.chars().take(MAX_TEXT_CHARS).collect(),
+    ))
+}
+
+// 判断内容是否敏感，返回原因
+fn sensitive_reason(content: &ClipboardContent, source_app: Option<&str>) -> Option<&'static str> {
+    if platform::is_concealed() {
+        return Some("剪贴板内容被标记为隐藏或临时");
+    }
+    if let Some(app) = source_app {
+        let app = app.to_lowercase();
+        if IGNORED_APPS.iter().any(|ignored| app.contains(ignored)) {
+            return Some("来自密码管理器");
+        }
+    }
+    match content {
+        ClipboardContent::Text(text) => sensitive_text_reason(text),
+        ClipboardContent::Files(_) => None,
+    }
+}
+
+fn sensitive_patterns() -> &'static [(regex::Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(regex::Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            (r"-----BEGIN [A-Z ]*PRIVATE KEY-----", "私钥"),
+            (r"\bAKIA[0-9A-Z]{16}\b", "AWS 访问密钥"),
+            (r"\b(sk|pk|rk)-[A-Za-z0-9_-]{20,}", "API 密钥"),
+            (r"\bgh[pousr]_[A-Za-z0-9]{36,}\b", "GitHub 令牌"),
+            (r"\bxox[abprs]-[A-Za-z0-9-]{10,}", "Slack 令牌"),
+            (
+                r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+",
+                "JWT",
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(pattern, reason)| {
+            regex::Regex::new(pattern).ok().map(|regex| (regex, reason))
+        })
+        .collect()
+    })
+}
+
+fn sensitive_text_reason(text: &str) -> Option<&'static str> {
+    for (regex, reason) in sensitive_patterns() {
+        if regex.is_match(text) {
+            return Some(reason);
+        }
+    }
+    if looks_like_card_number(text) {
+        return Some("银行卡号");
+    }
+    if looks_like_password(text) {
+        return Some("疑似密码");
+    }
+    None
+}
+
+// 去掉空格和连字符后为 13-19 位数字且通过 Luhn 校验
+fn looks_like_card_number(text: &str) -> bool {
+    let compact: String = text.chars().filter(|c| *c != ' ' && *c != '-').collect();
+    if !(13..=19).contains(&compact.len()) || !compact.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    let sum: u32 = compact
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, digit)| {
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+// 单个 8-64 字符、不含空白的词，同时包含大写、小写、数字和符号
+fn looks_like_password(text: &str) -> bool {
+    let length = text.chars().count();
+    if !(8..=64).contains(&length) || text.chars().any(char::is_whitespace) {
+        return false;
+    }
+    text.chars().any(|c| c.is_ascii_uppercase())
+        && text.chars().any(|c| c.is_ascii_lowercase())
+        && text.chars().any(|c| c.is_ascii_digit())
+        && text.chars().any(|c| c.is_ascii_punctuation())
+        && !text.contains("://")
+}
+
+async fn post_item(app_handle: &AppHandle, item: serde_json::Value) -> Result<(), String> {
+    let (api_host, api_port) = {
+        let api_state = app_handle.state::<crate::ApiState>();
+        let api_state_guard = api_state.0.lock().unwrap();
+        (api_state_guard.host.clone(), api_state_guard.port)
+    };
+    let url = format!("http://{}:{}/clipboard/items", api_host, api_port);
+    let response = crate::http_client::shared()
+        .post(&url)
+        .timeout(Duration::from_secs(10))
+        .json(&serde_json::json!({ "items": [item] }))
+        .send()
+        .await
+        .map_err(|e| format!("请求失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("API返回错误状态: {}", response.status()));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("解析响应失败: {}", e))?;
+    if body["success"].as_bool() != Some(true) {
+        return Err(format!("API未能保存剪贴板内容: {}", body["message"]));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+#[allow(unused_unsafe)]
+mod platform {
+    use objc2_app_kit::{NSPasteboard, NSWorkspace};
+
+    /// 密码管理器等应用用来标记不应被记录的剪贴板类型
+    const CONCEALED_TYPES: &[&str] = &[
+        "org.nspasteboard.ConcealedType",
+        "org.nspasteboard.TransientType",
+        "org.nspasteboard.AutoGeneratedType",
+    ];
+
+    /// 剪贴板变化计数，每次复制加一
+    pub fn change_count() -> Option<isize> {
+        Some(unsafe { NSPasteboard::generalPasteboard().changeCount() })
+    }
+
+    pub fn is_concealed() -> bool {
+        let pasteboard = unsafe { NSPasteboard::generalPasteboard() };
+        unsafe { pasteboard.types() }.is_some_and(|types| {
+            types.iter().any(|pasteboard_type| {
+                CONCEALED_TYPES.contains(&pasteboard_type.to_string().as_str())
+            })
+        })
+    }
+
+    /// 前台应用的名称
+    pub fn frontmost_app() -> Option<String> {
+        let workspace = unsafe { NSWorkspace::sharedWorkspace() };
+        let app = unsafe { workspace.frontmostApplication() }?;
+        unsafe { app.localizedName() }.map(|name| name.to_string())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    pub fn change_count() -> Option<isize> {
+        None
+    }
+
+    pub fn is_concealed() -> bool {
+        false
+    }
+
+    pub fn frontmost_app() -> Option<String> {
+        None
+    }
+}
+
+fn status_json() -> serde_json::Value {
+    let paused_until = PAUSED_UNTIL.load(Ordering::SeqCst);
+    serde_json::json!({
+        "enabled": ENABLED.load(Ordering::SeqCst),
+        "paused": is_paused(),
+        "paused_until": if is_paused() && paused_until != u64::MAX {
+            Some(paused_until)
+        } else {
+            None
+        },
+        "captured": CAPTURED.load(Ordering::Relaxed),
+        "filtered": FILTERED.load(Ordering::Relaxed)
+    })
+}
+
+/// 开启或关闭剪贴板捕获
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_clipboard_capture(
+    enabled: bool,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    ENABLED.store(enabled, Ordering::SeqCst);
+    if enabled {
+        PAUSED_UNTIL.store(0, Ordering::SeqCst);
+    }
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(ENABLED_STORE_KEY, serde_json::json!(enabled));
+    store
+        .save()
+        .map_err(|e| format!("保存剪贴板捕获设置失败: {}", e))?;
+
+    info!(
+        "[CLIPBOARD] 剪贴板捕获已{}",
+        if enabled { "开启" } else { "关闭" }
+    );
+    Ok(serde_json::json!({
+        "status": "success",
+        "clipboard": status_json()
+    }))
+}
+
+/// 暂停剪贴板捕获，不传分钟数时暂停到手动恢复
+#[tauri::command(rename_all = "snake_case")]
+pub fn pause_clipboard_capture(minutes: Option<u64>) -> Result<serde_json::Value, String> {
+    let until = match minutes {
+        Some(minutes) => now_secs().saturating_add(minutes.saturating_mul(60)),
+        None => u64::MAX,
+    };
+    PAUSED_UNTIL.store(until, Ordering::SeqCst);
+    match minutes {
+        Some(minutes) => info!("[CLIPBOARD] 剪贴板捕获暂停 {} 分钟", minutes),
+        None => info!("[CLIPBOARD] 剪贴板捕获已暂停，等待手动恢复"),
+    }
+    Ok(serde_json::json!({
+        "status": "success",
+        "clipboard": status_json()
+    }))
+}
+
+/// 恢复暂停的剪贴板捕获
+#[tauri::command(rename_all = "snake_case")]
+pub fn resume_clipboard_capture() -> Result<serde_json::Value, String> {
+    PAUSED_UNTIL.store(0, Ordering::SeqCst);
+    info!("[CLIPBOARD] 剪贴板捕获已恢复");
+    Ok(serde_json::json!({
+        "status": "success",
+        "clipboard": status_json()
+    }))
+}
+
+/// 获取剪贴板捕获的开关、暂停状态和统计
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_clipboard_capture() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "clipboard": status_json()
+    }))
+}
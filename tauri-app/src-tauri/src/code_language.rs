@@ -0,0 +1,132 @@
+//! # 源代码语言识别 (Source Language Detection)
+//!
+//! 根据扩展名识别代码文件的编程语言；没有扩展名或扩展名未知时读取首行的 shebang
+//! （如 `#!/usr/bin/env python3`）。识别结果以 `lang:<语言>` 标牌和 `extra_metadata.language`
+//! 附加到 FileMetadata，前端可以直接按语言筛选，无需 Python 端解析文件内容。
+
+use std::io::Read;
+use std::path::Path;
+
+/// 扩展名（小写，不带点）-> 语言
+const EXTENSION_LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("py", "python"),
+    ("pyw", "python"),
+    ("ipynb", "python"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("jsx", "javascript"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("scala", "scala"),
+    ("swift", "swift"),
+    ("m", "objective-c"),
+    ("mm", "objective-c"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cpp", "cpp"),
+    ("cxx", "cpp"),
+    ("hpp", "cpp"),
+    ("hh", "cpp"),
+    ("cs", "csharp"),
+    ("rb", "ruby"),
+    ("php", "php"),
+    ("pl", "perl"),
+    ("pm", "perl"),
+    ("lua", "lua"),
+    ("r", "r"),
+    ("jl", "julia"),
+    ("dart", "dart"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("erl", "erlang"),
+    ("hs", "haskell"),
+    ("clj", "clojure"),
+    ("ml", "ocaml"),
+    ("fs", "fsharp"),
+    ("zig", "zig"),
+    ("nim", "nim"),
+    ("sh", "shell"),
+    ("bash", "shell"),
+    ("zsh", "shell"),
+    ("fish", "shell"),
+    ("ps1", "powershell"),
+    ("bat", "batch"),
+    ("cmd", "batch"),
+    ("sql", "sql"),
+    ("html", "html"),
+    ("htm", "html"),
+    ("css", "css"),
+    ("scss", "css"),
+    ("less", "css"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+];
+
+/// shebang 中的解释器名前缀 -> 语言（`python3.11` 按前缀 `python` 匹配）
+const INTERPRETER_LANGUAGES: &[(&str, &str)] = &[
+    ("python", "python"),
+    ("node", "javascript"),
+    ("deno", "typescript"),
+    ("ruby", "ruby"),
+    ("perl", "perl"),
+    ("php", "php"),
+    ("lua", "lua"),
+    ("Rscript", "r"),
+    ("bash", "shell"),
+    ("zsh", "shell"),
+    ("sh", "shell"),
+    ("dash", "shell"),
+    ("fish", "shell"),
+    ("pwsh", "powershell"),
+];
+
+/// 读取 shebang 时最多读取的字节数
+const SHEBANG_MAX_BYTES: u64 = 256;
+
+/// 只按扩展名（小写，不带点）识别语言，不读取文件
+pub fn language_for_extension(extension: &str) -> Option<&'static str> {
+    EXTENSION_LANGUAGES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, language)| *language)
+}
+
+/// 解析 shebang 行：`#!/usr/bin/python3`、`#!/usr/bin/env -S node --flag`
+fn language_for_shebang(line: &str) -> Option<&'static str> {
+    let command = line.strip_prefix("#!")?;
+    let mut parts = command.split_whitespace();
+    let mut interpreter = parts.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = parts.find(|part| !part.starts_with('-'))?;
+    }
+    INTERPRETER_LANGUAGES
+        .iter()
+        .find(|(prefix, _)| interpreter.starts_with(prefix))
+        .map(|(_, language)| *language)
+}
+
+fn read_shebang(path: &Path) -> Option<String> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut buffer = Vec::new();
+    file.take(SHEBANG_MAX_BYTES).read_to_end(&mut buffer).ok()?;
+    if !buffer.starts_with(b"#!") {
+        return None;
+    }
+    let line = buffer.split(|&byte| byte == b'\n').next()?;
+    Some(String::from_utf8_lossy(line).trim_end().to_string())
+}
+
+/// 识别代码文件的编程语言，不是代码文件时返回 None
+pub fn detect(path: &Path, extension: Option<&str>) -> Option<&'static str> {
+    if let Some(language) = extension.and_then(language_for_extension) {
+        return Some(language);
+    }
+    read_shebang(path).and_then(|line| language_for_shebang(&line))
+}
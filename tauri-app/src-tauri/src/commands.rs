@@ -1,3 +1,5 @@
+use crate::event_buffer::{EventBuffer, EventBufferStrategy};
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
@@ -6,13 +8,14 @@ use tauri::{
     Manager,
     // Window,
 };
+use tauri_plugin_opener::OpenerExt;
 
 /// 刷新监控配置（重新获取文件夹配置和Bundle扩展名）
 #[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
 pub async fn refresh_monitoring_config(
     state: tauri::State<'_, crate::AppState>,
 ) -> Result<serde_json::Value, String> {
-    println!("[CMD] refresh_monitoring_config 被调用");
+    info!("[CMD] refresh_monitoring_config 被调用");
 
     // 获取文件监控器
     let monitor = {
@@ -27,7 +30,7 @@ pub async fn refresh_monitoring_config(
     match monitor.refresh_all_configurations().await {
         Ok(()) => {
             let summary = monitor.get_configuration_summary();
-            println!(
+            info!(
                 "[CMD] refresh_monitoring_config 成功，配置摘要: {:?}",
                 summary
             );
@@ -38,25 +41,151 @@ pub async fn refresh_monitoring_config(
             }))
         }
         Err(e) => {
-            eprintln!("[CMD] refresh_monitoring_config 失败: {}", e);
+            error!("[CMD] refresh_monitoring_config 失败: {}", e);
             Err(format!("配置刷新失败: {}", e))
         }
     }
 }
 
+/// 规则试运行的默认抽样文件数
+const DEFAULT_AUDIT_SAMPLE_LIMIT: usize = 2000;
+
+/// 规则试运行：抽样遍历目录并评估所有规则，返回每条规则匹配和排除的文件数，
+/// 用于在修改规则前预览效果，不向API发送任何数据
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn audit_rules(
+    directory: String,
+    sample_limit: Option<usize>,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<serde_json::Value, String> {
+    info!("[CMD] audit_rules 被调用，目录: {}", directory);
+
+    let monitor = {
+        let guard = state.file_monitor.lock().unwrap();
+        match &*guard {
+            Some(monitor) => monitor.clone(),
+            None => return Err("文件监控器未初始化".to_string()),
+        }
+    };
+
+    let report = monitor
+        .audit_rules(
+            &directory,
+            sample_limit.unwrap_or(DEFAULT_AUDIT_SAMPLE_LIMIT),
+        )
+        .await?;
+    Ok(serde_json::json!({
+        "status": "success",
+        "report": report
+    }))
+}
+
+/// 扫描基准测试的默认抽样文件数
+const DEFAULT_BENCHMARK_SAMPLE_LIMIT: usize = 5000;
+
+/// 扫描基准测试：分别测量遍历、元数据、哈希、规则评估和序列化的吞吐量，
+/// 用于比较不同版本在同一台机器上的扫描性能
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn run_scan_benchmark(
+    path: String,
+    sample_limit: Option<usize>,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<serde_json::Value, String> {
+    info!("[CMD] run_scan_benchmark 被调用，目录: {}", path);
+
+    let monitor = {
+        let guard = state.file_monitor.lock().unwrap();
+        match &*guard {
+            Some(monitor) => monitor.clone(),
+            None => return Err("文件监控器未初始化".to_string()),
+        }
+    };
+
+    let report = monitor
+        .run_scan_benchmark(
+            &path,
+            sample_limit.unwrap_or(DEFAULT_BENCHMARK_SAMPLE_LIMIT),
+        )
+        .await?;
+    Ok(serde_json::json!({
+        "status": "success",
+        "report": report
+    }))
+}
+
+/// 添加自定义Bundle扩展名（如 `.logicx`），立即生效并保存到本地规则文件，同时同步到API
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn add_custom_bundle_extension(
+    extension: String,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<serde_json::Value, String> {
+    info!(
+        "[CMD] add_custom_bundle_extension 被调用，扩展名: {}",
+        extension
+    );
+
+    let monitor = {
+        let guard = state.file_monitor.lock().unwrap();
+        match &*guard {
+            Some(monitor) => monitor.clone(),
+            None => return Err("文件监控器未初始化".to_string()),
+        }
+    };
+
+    let synced = monitor.add_custom_bundle_extension(&extension).await?;
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("已添加Bundle扩展名: {}", extension),
+        "synced_to_api": synced
+    }))
+}
+
+/// 移除自定义Bundle扩展名，同时从本地规则文件和API中移除
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn remove_custom_bundle_extension(
+    extension: String,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<serde_json::Value, String> {
+    info!(
+        "[CMD] remove_custom_bundle_extension 被调用，扩展名: {}",
+        extension
+    );
+
+    let monitor = {
+        let guard = state.file_monitor.lock().unwrap();
+        match &*guard {
+            Some(monitor) => monitor.clone(),
+            None => return Err("文件监控器未初始化".to_string()),
+        }
+    };
+
+    let synced = monitor.remove_custom_bundle_extension(&extension).await?;
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("已移除Bundle扩展名: {}", extension),
+        "synced_to_api": synced
+    }))
+}
+
 /// 刷新简化配置（重新获取扩展名映射和Bundle配置）
 #[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
 pub async fn refresh_simplified_config(
     state: tauri::State<'_, crate::AppState>,
+    api_state: tauri::State<'_, crate::ApiState>,
 ) -> Result<serde_json::Value, String> {
-    println!("[CMD] refresh_simplified_config 被调用");
+    info!("[CMD] refresh_simplified_config 被调用");
 
-    match state.refresh_simplified_config().await {
+    let (api_host, api_port) = {
+        let api_state_guard = api_state.0.lock().unwrap();
+        (api_state_guard.host.clone(), api_state_guard.port)
+    };
+
+    match state.refresh_simplified_config(api_host, api_port).await {
         Ok(()) => {
             // 获取更新后的配置摘要
             match state.get_simplified_config().await {
                 Ok(config) => {
-                    println!("[CMD] refresh_simplified_config 成功");
+                    info!("[CMD] refresh_simplified_config 成功");
                     Ok(serde_json::json!({
                         "status": "success",
                         "message": "简化配置刷新成功",
@@ -69,7 +198,7 @@ pub async fn refresh_simplified_config(
                     }))
                 }
                 Err(e) => {
-                    eprintln!("[CMD] 获取配置摘要失败: {}", e);
+                    error!("[CMD] 获取配置摘要失败: {}", e);
                     Ok(serde_json::json!({
                         "status": "success",
                         "message": "简化配置刷新成功，但无法获取摘要",
@@ -79,7 +208,7 @@ pub async fn refresh_simplified_config(
             }
         }
         Err(e) => {
-            eprintln!("[CMD] refresh_simplified_config 失败: {}", e);
+            error!("[CMD] refresh_simplified_config 失败: {}", e);
             Err(format!("简化配置刷新失败: {}", e))
         }
     }
@@ -94,7 +223,7 @@ pub struct DirectoryEntry {
 
 #[tauri::command]
 pub async fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, String> {
-    println!("[CMD] read_directory 被调用，路径: {}", path);
+    info!("[CMD] read_directory 被调用，路径: {}", path);
 
     let path_obj = Path::new(&path);
 
@@ -133,7 +262,7 @@ pub async fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, String>
                         }
                     }
                     Err(e) => {
-                        println!("[CMD] 读取目录项失败: {}", e);
+                        warn!("[CMD] 读取目录项失败: {}", e);
                         // 继续处理其他项，不中断整个过程
                     }
                 }
@@ -147,7 +276,7 @@ pub async fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, String>
     // 按名称排序
     entries.sort_by(|a, b| a.name.cmp(&b.name));
 
-    println!("[CMD] read_directory 成功读取 {} 个子目录", entries.len());
+    info!("[CMD] read_directory 成功读取 {} 个子目录", entries.len());
     Ok(entries)
 }
 
@@ -160,7 +289,7 @@ pub async fn queue_add_blacklist_folder(
     state: tauri::State<'_, crate::AppState>,
     _app_handle: tauri::AppHandle,
 ) -> Result<serde_json::Value, String> {
-    println!(
+    info!(
         "[CMD] queue_add_blacklist_folder 被调用，父ID: {}, 路径: {}",
         parent_id, folder_path
     );
@@ -175,7 +304,7 @@ pub async fn queue_add_blacklist_folder(
 
     // 检查初始扫描是否已完成
     if state.is_initial_scan_completed() {
-        println!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
+        info!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
         // 触发队列处理
         state.process_pending_config_changes();
 
@@ -184,7 +313,7 @@ pub async fn queue_add_blacklist_folder(
             "message": format!("黑名单文件夹 {} 已加入处理队列并即将执行", folder_path)
         }))
     } else {
-        println!("[CONFIG_QUEUE] 初始扫描未完成，将黑名单添加操作加入队列");
+        info!("[CONFIG_QUEUE] 初始扫描未完成，将黑名单添加操作加入队列");
         Ok(serde_json::json!({
             "status": "queued",
             "message": format!("黑名单文件夹 {} 已加入处理队列，将在初始扫描完成后处理", folder_path)
@@ -199,13 +328,19 @@ pub async fn queue_delete_folder(
     folder_path: String,
     is_blacklist: bool,
     state: tauri::State<'_, crate::AppState>,
-    _app_handle: tauri::AppHandle, // 使用下划线前缀表示故意不使用的参数
+    app_handle: tauri::AppHandle,
 ) -> Result<serde_json::Value, String> {
-    println!(
+    info!(
         "[CMD] queue_delete_folder 被调用，ID: {}, 路径: {}, 是否黑名单: {}",
         folder_id, folder_path, is_blacklist
     );
 
+    if !is_blacklist {
+        if let Err(e) = crate::security_bookmarks::remove_bookmark(&app_handle, &folder_path) {
+            warn!("[CMD] {}", e);
+        }
+    }
+
     // 检查文件监控器是否已初始化
     {
         let guard = state.file_monitor.lock().unwrap();
@@ -225,7 +360,7 @@ pub async fn queue_delete_folder(
 
     // 如果初始扫描已完成，立即处理队列
     if state.is_initial_scan_completed() {
-        println!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
+        info!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
         // 触发队列处理
         state.process_pending_config_changes();
 
@@ -234,7 +369,7 @@ pub async fn queue_delete_folder(
             "message": format!("文件夹 {} 删除操作已加入处理队列并即将执行", folder_path)
         }))
     } else {
-        println!("[CONFIG_QUEUE] 初始扫描未完成，将文件夹删除操作加入队列");
+        info!("[CONFIG_QUEUE] 初始扫描未完成，将文件夹删除操作加入队列");
         Ok(serde_json::json!({
             "status": "queued",
             "message": format!("文件夹 {} 删除操作已加入处理队列，将在初始扫描完成后处理", folder_path)
@@ -250,7 +385,7 @@ pub async fn queue_toggle_folder_status(
     is_blacklist: bool,
     state: tauri::State<'_, crate::AppState>,
 ) -> Result<serde_json::Value, String> {
-    println!(
+    info!(
         "[CMD] queue_toggle_folder_status 被调用，ID: {}, 路径: {}, 设为黑名单: {}",
         folder_id, folder_path, is_blacklist
     );
@@ -265,7 +400,7 @@ pub async fn queue_toggle_folder_status(
 
     // 检查初始扫描是否已完成
     if state.is_initial_scan_completed() {
-        println!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
+        info!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
         // 触发队列处理
         state.process_pending_config_changes();
 
@@ -274,7 +409,7 @@ pub async fn queue_toggle_folder_status(
             "message": format!("文件夹 {} 状态切换已加入处理队列并即将执行", folder_path)
         }))
     } else {
-        println!("[CONFIG_QUEUE] 初始扫描未完成，将文件夹状态切换操作加入队列");
+        info!("[CONFIG_QUEUE] 初始扫描未完成，将文件夹状态切换操作加入队列");
         Ok(serde_json::json!({
             "status": "queued",
             "message": format!("文件夹 {} 状态切换已加入处理队列，将在初始扫描完成后处理", folder_path)
@@ -288,12 +423,18 @@ pub async fn queue_add_whitelist_folder(
     folder_path: String,
     folder_alias: Option<String>,
     state: tauri::State<'_, crate::AppState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<serde_json::Value, String> {
-    println!(
+    info!(
         "[CMD] queue_add_whitelist_folder 被调用，路径: {}",
         folder_path
     );
 
+    // 保存安全范围书签，沙盒化的构建下次启动时据此恢复访问
+    if let Err(e) = crate::security_bookmarks::save_bookmark(&app_handle, &folder_path) {
+        warn!("[CMD] {}", e);
+    }
+
     // 添加到队列
     let change = crate::ConfigChangeRequest::AddWhitelist {
         folder_path: folder_path.clone(),
@@ -303,7 +444,7 @@ pub async fn queue_add_whitelist_folder(
 
     // 检查初始扫描是否已完成
     if state.is_initial_scan_completed() {
-        println!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
+        info!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
         // 触发队列处理
         state.process_pending_config_changes();
 
@@ -312,7 +453,7 @@ pub async fn queue_add_whitelist_folder(
             "message": format!("白名单文件夹 {} 已加入处理队列并即将执行", folder_path)
         }))
     } else {
-        println!("[CONFIG_QUEUE] 初始扫描未完成，将白名单添加操作加入队列");
+        info!("[CONFIG_QUEUE] 初始扫描未完成，将白名单添加操作加入队列");
         Ok(serde_json::json!({
             "status": "queued",
             "message": format!("白名单文件夹 {} 已加入处理队列，将在初始扫描完成后处理", folder_path)
@@ -353,7 +494,7 @@ pub async fn search_files_by_tags(
     operator: String,
     app_handle: tauri::AppHandle,
 ) -> Result<Vec<FileInfo>, String> {
-    println!(
+    info!(
         "[CMD] search_files_by_tags called with tags: {:?}, operator: {}",
         tag_names, operator
     );
@@ -366,7 +507,7 @@ pub async fn search_files_by_tags(
     };
 
     // Build the API request
-    let client = reqwest::Client::new();
+    let client = crate::http_client::shared();
     let url = format!("http://{}:{}/tagging/search-files", api_host, api_port);
 
     let request_data = serde_json::json!({
@@ -381,7 +522,7 @@ pub async fn search_files_by_tags(
             if response.status().is_success() {
                 match response.json::<Vec<FileInfo>>().await {
                     Ok(files) => {
-                        println!("[CMD] search_files_by_tags found {} files", files.len());
+                        info!("[CMD] search_files_by_tags found {} files", files.len());
                         Ok(files)
                     }
                     Err(e) => Err(format!("Failed to parse response: {}", e)),
@@ -408,14 +549,14 @@ pub async fn get_tag_cloud_data(
     limit: Option<u32>,
     app_handle: tauri::AppHandle,
 ) -> Result<serde_json::Value, String> {
-    println!("[CMD] get_tag_cloud_data 被调用，limit: {:?}", limit);
+    info!("[CMD] get_tag_cloud_data 被调用，limit: {:?}", limit);
 
     // 获取API信息
     let (api_host, api_port) = {
         let api_state = app_handle.state::<crate::ApiState>();
         let api_state_guard = api_state.0.lock().unwrap();
 
-        if api_state_guard.process_child.is_none() {
+        if !api_state_guard.is_running() {
             return Err("API服务未运行".to_string());
         }
 
@@ -423,7 +564,7 @@ pub async fn get_tag_cloud_data(
     };
 
     // 构建API请求
-    let client = reqwest::Client::new();
+    let client = crate::http_client::shared();
     let mut url = format!("http://{}:{}/tagging/tag-cloud", api_host, api_port);
 
     // 添加查询参数
@@ -454,3 +595,142 @@ pub async fn get_tag_cloud_data(
         Err(e) => Err(format!("发送请求失败: {}", e)),
     }
 }
+
+/// 获取当前API地址配置，供前端构造请求地址（主机和端口可能已被命令行、环境变量或设置覆盖）
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_api_endpoint(
+    api_state: tauri::State<'_, crate::ApiState>,
+) -> Result<serde_json::Value, String> {
+    let api_state_guard = api_state.0.lock().unwrap();
+    Ok(serde_json::json!({
+        "host": api_state_guard.host,
+        "port": api_state_guard.port,
+        "base_url": format!("http://{}:{}", api_state_guard.host, api_state_guard.port),
+        "db_path": api_state_guard.db_path,
+        "external": api_state_guard.external
+    }))
+}
+
+/// 运行时修改事件缓冲策略并持久化
+///
+/// strategy 取值：immediate / delayed_merge / throttle / default（恢复内置策略），后两种需要 duration_ms
+/// event 可以是通配模式，例如 `*-progress` 对所有进度事件生效（精确事件名优先）
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn set_event_buffer_strategy(
+    event: String,
+    strategy: String,
+    duration_ms: Option<u64>,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    info!(
+        "[CMD] set_event_buffer_strategy 被调用: {} -> {} ({:?}ms)",
+        event, strategy, duration_ms
+    );
+
+    let parsed = if strategy == "default" {
+        None
+    } else {
+        Some(EventBufferStrategy::parse(&strategy, duration_ms)?)
+    };
+    let event_buffer = EventBuffer::shared(&app_handle);
+    let effective = event_buffer.set_strategy(&event, parsed).await?;
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("事件 {} 的缓冲策略已更新", event),
+        "event": event,
+        "strategy": effective.to_json()
+    }))
+}
+
+/// 获取当前生效的事件缓冲策略
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn get_event_buffer_strategies(
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let event_buffer = EventBuffer::shared(&app_handle);
+    let strategies: serde_json::Map<String, serde_json::Value> = event_buffer
+        .strategies_snapshot()
+        .await
+        .into_iter()
+        .map(|(event, strategy)| (event, strategy.to_json()))
+        .collect();
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "strategies": strategies
+    }))
+}
+
+/// 获取事件缓冲区统计信息（当前排队的事件及其策略）
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn get_event_buffer_stats(
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let event_buffer = EventBuffer::shared(&app_handle);
+    let stats = event_buffer.get_stats().await;
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "stats": stats
+    }))
+}
+
+/// 向调用的窗口重放最近的状态类事件（模型状态、扫描状态等）
+///
+/// 窗口在注册事件监听后调用，避免晚打开的窗口不知道当前状态
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn replay_recent_events(
+    window: tauri::WebviewWindow,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let event_buffer = EventBuffer::shared(&app_handle);
+    let replayed = event_buffer.replay_recent_events(window.label());
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "replayed": replayed
+    }))
+}
+
+/// macOS 隐私设置面板的 URL：完全磁盘访问、文件和文件夹
+const PRIVACY_PANES: &[(&str, &str)] = &[
+    (
+        "full_disk_access",
+        "x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles",
+    ),
+    (
+        "files_and_folders",
+        "x-apple.systempreferences:com.apple.preference.security?Privacy_FilesAndFolders",
+    ),
+];
+
+/// 打开"系统设置 → 隐私与安全性"中的授权面板
+///
+/// `pane` 默认为 full_disk_access（完全磁盘访问），也可以是 files_and_folders（按文件夹授权）
+#[tauri::command(rename_all = "snake_case")]
+pub fn open_full_disk_access_settings(
+    pane: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let pane = pane.unwrap_or_else(|| "full_disk_access".to_string());
+    let (_, url) = PRIVACY_PANES
+        .iter()
+        .find(|(name, _)| *name == pane)
+        .ok_or_else(|| format!("未知的隐私设置面板: {}", pane))?;
+
+    if !cfg!(target_os = "macos") {
+        return Err("仅 macOS 需要在系统设置中授权磁盘访问".to_string());
+    }
+
+    info!("[CMD] 打开隐私设置面板: {}", url);
+    app_handle
+        .opener()
+        .open_url(*url, None::<&str>)
+        .map_err(|e| format!("打开系统设置失败: {}", e))?;
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("已打开系统设置: {}", pane)
+    }))
+}
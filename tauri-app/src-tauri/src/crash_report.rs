@@ -0,0 +1,209 @@
+//! # 崩溃报告 (Crash Reports)
+//!
+//! 安装 panic hook，任何线程（包括文件监控线程）panic 时，把以下内容写入
+//! 应用数据目录下的 `crash_reports/crash-<时间>.txt`：
+//! - panic 信息、线程名和 backtrace
+//! - 文件监控的配置摘要（`get_configuration_summary`）
+//! - 日志文件末尾的若干行
+//!
+//! 同时写入 `pending` 标记，下次启动时弹窗提示，用户可以在访达/资源管理器中显示该报告。
+//!
+//! 在 [`recoverable`] 中执行的任务（如哈希和文本提取，第三方解析库可能 panic）的 panic 会被调用方捕获，
+//! 应用继续运行，只记录警告日志，不写报告，也不执行之前安装的 hook（其中包括终止 API 进程的清理）。
+
+use log::{error, info, warn};
+use std::cell::Cell;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::AppState;
+
+/// 崩溃报告目录名，位于应用数据目录
+const CRASH_DIR: &str = "crash_reports";
+/// 记录未查看报告路径的标记文件
+const PENDING_MARKER: &str = "pending";
+/// 报告中包含的日志行数
+const LOG_TAIL_LINES: usize = 200;
+/// 读取日志末尾的最大字节数
+const LOG_TAIL_BYTES: u64 = 256 * 1024;
+/// 获取配置摘要的超时，panic 线程可能持有配置锁
+const SUMMARY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 正在生成报告，防止生成过程中再次 panic 时递归写报告
+static REPORTING: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// 当前线程正在执行 panic 会被捕获的任务
+    static RECOVERABLE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 执行 panic 会被调用方捕获的任务（例如在 `spawn_blocking` 中运行并检查 JoinError），
+/// 其中的 panic 不写崩溃报告
+pub fn recoverable<T>(task: impl FnOnce() -> T) -> T {
+    // 任务 panic 时也要恢复标记，阻塞线程会被复用
+    struct Reset(bool);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            RECOVERABLE.with(|flag| flag.set(self.0));
+        }
+    }
+    let _reset = Reset(RECOVERABLE.with(|flag| flag.replace(true)));
+    task()
+}
+
+/// 读取日志文件最后 `LOG_TAIL_LINES` 行
+fn read_log_tail(log_file: &Path) -> String {
+    let Ok(mut file) = fs::File::open(log_file) else {
+        return format!("(无法打开日志文件 {})", log_file.display());
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let _ = file.seek(SeekFrom::Start(len.saturating_sub(LOG_TAIL_BYTES)));
+    let mut buffer = Vec::new();
+    let _ = file.read_to_end(&mut buffer);
+    let text = String::from_utf8_lossy(&buffer);
+    let lines: Vec<&str> = text.lines().collect();
+    lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n")
+}
+
+/// 在单独的线程中获取配置摘要，超时则放弃，避免与 panic 线程持有的锁死锁
+fn configuration_summary(app_handle: &AppHandle) -> String {
+    let app_handle = app_handle.clone();
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let monitor = app_handle
+            .state::<AppState>()
+            .file_monitor
+            .lock()
+            .ok()
+            .and_then(|guard| guard.clone());
+        let summary = match monitor {
+            Some(monitor) => monitor.get_configuration_summary(),
+            None => serde_json::json!({ "file_monitor": "not initialized" }),
+        };
+        let _ = tx.send(summary);
+    });
+    match rx.recv_timeout(SUMMARY_TIMEOUT) {
+        Ok(summary) => serde_json::to_string_pretty(&summary).unwrap_or_default(),
+        Err(_) => "(获取配置摘要超时)".to_string(),
+    }
+}
+
+/// 写入崩溃报告和 pending 标记，返回报告路径
+fn write_report(
+    app_handle: &AppHandle,
+    crash_dir: &Path,
+    log_file: &Path,
+    panic_message: &str,
+) -> Result<PathBuf, String> {
+    let now = chrono::Local::now();
+    let thread = std::thread::current();
+    let report = format!(
+        "Knowledge Focus 崩溃报告\n\
+         时间: {}\n\
+         版本: {}\n\
+         系统: {} {}\n\
+         线程: {}\n\n\
+         == Panic ==\n{}\n\n\
+         == Backtrace ==\n{}\n\n\
+         == 配置摘要 ==\n{}\n\n\
+         == 最近日志 ==\n{}\n",
+        now.to_rfc3339(),
+        app_handle.package_info().version,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread.name().unwrap_or("<unnamed>"),
+        panic_message,
+        std::backtrace::Backtrace::force_capture(),
+        configuration_summary(app_handle),
+        read_log_tail(log_file),
+    );
+
+    fs::create_dir_all(crash_dir).map_err(|e| format!("创建崩溃报告目录失败: {}", e))?;
+    let report_path = crash_dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+    fs::write(&report_path, report).map_err(|e| format!("写入崩溃报告失败: {}", e))?;
+    fs::write(
+        crash_dir.join(PENDING_MARKER),
+        report_path.to_string_lossy().as_bytes(),
+    )
+    .map_err(|e| format!("写入崩溃标记失败: {}", e))?;
+    Ok(report_path)
+}
+
+/// 安装写崩溃报告的 panic hook，之前安装的 hook 在报告写完后继续执行
+pub fn install(app_handle: &AppHandle) {
+    let (Ok(data_dir), Ok(log_dir)) = (
+        app_handle.path().app_data_dir(),
+        app_handle.path().app_log_dir(),
+    ) else {
+        error!("[CRASH_REPORT] 无法解析应用目录，不安装崩溃报告");
+        return;
+    };
+    let crash_dir = data_dir.join(CRASH_DIR);
+    let log_file = log_dir.join(format!("{}.log", crate::log_level::LOG_FILE_NAME));
+    let app_handle = app_handle.clone();
+
+    let prev_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        if RECOVERABLE.with(Cell::get) {
+            warn!(
+                "[CRASH_REPORT] 线程 {} 中的任务 panic，已由调用方处理: {}",
+                std::thread::current().name().unwrap_or("<unnamed>"),
+                panic_info
+            );
+            return;
+        }
+        if !REPORTING.swap(true, Ordering::SeqCst) {
+            match write_report(&app_handle, &crash_dir, &log_file, &panic_info.to_string()) {
+                Ok(path) => error!("[CRASH_REPORT] 已写入崩溃报告: {}", path.display()),
+                Err(e) => warn!("[CRASH_REPORT] {}", e),
+            }
+            REPORTING.store(false, Ordering::SeqCst);
+        }
+        prev_hook(panic_info);
+    }));
+}
+
+/// 上次运行留下未查看的崩溃报告时，提示用户并提供显示报告的选项
+pub fn offer_pending_report(app_handle: &AppHandle) {
+    let Ok(data_dir) = app_handle.path().app_data_dir() else {
+        return;
+    };
+    let marker = data_dir.join(CRASH_DIR).join(PENDING_MARKER);
+    let Ok(report_path) = fs::read_to_string(&marker) else {
+        return;
+    };
+    // 只提示一次
+    let _ = fs::remove_file(&marker);
+    let report_path = PathBuf::from(report_path.trim());
+    if !report_path.exists() {
+        return;
+    }
+    info!(
+        "[CRASH_REPORT] 发现上次运行的崩溃报告: {}",
+        report_path.display()
+    );
+
+    let handle_for_reveal = app_handle.clone();
+    app_handle
+        .dialog()
+        .message("Knowledge Focus quit unexpectedly last time. A crash report was saved; you can attach it to a bug report.")
+        .title("Crash Report")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Show Report".to_string(),
+            "Dismiss".to_string(),
+        ))
+        .show(move |reveal| {
+            if reveal {
+                if let Err(e) = handle_for_reveal.opener().reveal_item_in_dir(&report_path) {
+                    error!("[CRASH_REPORT] 显示崩溃报告失败: {}", e);
+                }
+            }
+        });
+}
@@ -0,0 +1,94 @@
+//! # 深度链接 (Deep Links)
+//!
+//! 注册 `knowledgefocus://` URL 协议，供其他应用和脚本调用：
+//! - `knowledgefocus://open?path=<文件路径>`：显示主窗口，并通过 `deep-link-open` 事件让前端定位该文件
+//! - `knowledgefocus://search?q=<关键字>`：打开快速搜索窗口并填入关键字
+//!
+//! macOS 上链接通过系统事件送达；Windows 和 Linux 上链接作为启动参数传给新实例，
+//! 由单实例插件（deep-link 特性）转发给已运行的实例，冷启动时通过 `get_current` 读取。
+
+use log::{error, info};
+use std::path::Path;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+/// 注册的URL协议，与 tauri.conf.json 中 plugins.deep-link 的配置一致
+const SCHEME: &str = "knowledgefocus";
+
+fn query_param(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+        .filter(|value| !value.is_empty())
+}
+
+/// 显示主窗口并激活应用
+fn show_main_window(app_handle: &AppHandle) -> Result<tauri::WebviewWindow, String> {
+    #[cfg(target_os = "macos")]
+    let _ = app_handle.set_activation_policy(tauri::ActivationPolicy::Regular);
+    let window = app_handle
+        .get_webview_window("main")
+        .ok_or_else(|| "主窗口不存在".to_string())?;
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
+    Ok(window)
+}
+
+/// 处理一个深度链接
+pub fn handle_url(app_handle: &AppHandle, url: &Url) -> Result<(), String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("不支持的链接协议: {}", url));
+    }
+    // knowledgefocus://open?path=... 中 open 被解析为主机名
+    let action = url
+        .host_str()
+        .unwrap_or_else(|| url.path().trim_matches('/'));
+    info!("[DEEP_LINK] 收到深度链接: {}", url);
+
+    match action {
+        "open" => {
+            let path = query_param(url, "path").ok_or("缺少 path 参数")?;
+            if !Path::new(&path).exists() {
+                return Err(format!("文件不存在: {}", path));
+            }
+            let window = show_main_window(app_handle)?;
+            window
+                .emit("deep-link-open", serde_json::json!({ "path": path }))
+                .map_err(|e| e.to_string())
+        }
+        "search" => {
+            let query = query_param(url, "q").unwrap_or_default();
+            crate::quick_search::show_window(app_handle, Some(&query))
+        }
+        _ => Err(format!("未知的深度链接操作: {}", action)),
+    }
+}
+
+fn handle_urls(app_handle: &AppHandle, urls: &[Url]) {
+    for url in urls {
+        if let Err(e) = handle_url(app_handle, url) {
+            error!("[DEEP_LINK] 处理深度链接失败 {}: {}", url, e);
+        }
+    }
+}
+
+/// 注册URL协议和链接处理函数，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let deep_link = app_handle.deep_link();
+
+    // Windows 和 Linux 上开发模式下没有安装包注册协议，需要在运行时注册
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = deep_link.register_all() {
+        error!("[DEEP_LINK] 注册URL协议失败: {}", e);
+    }
+
+    let handle_for_event = app_handle.clone();
+    deep_link.on_open_url(move |event| handle_urls(&handle_for_event, &event.urls()));
+
+    // 应用由链接冷启动时
+    match deep_link.get_current() {
+        Ok(Some(urls)) => handle_urls(app_handle, &urls),
+        Ok(None) => {}
+        Err(e) => error!("[DEEP_LINK] 读取启动链接失败: {}", e),
+    }
+}
@@ -0,0 +1,204 @@
+//! # 延迟哈希 (Deferred Hashing)
+//!
+//! 默认情况下每个文件在发送到 API 前都要读取开头 4KB 计算哈希，初始扫描时哈希计算占去大部分读盘时间，
+//! 第一批粗筛结果要等很久才出现。
+//!
+//! 延迟哈希模式下粗筛批次不带哈希直接发送，发送成功的文件记入本地索引的待计算队列；
+//! 后台任务逐个计算哈希（一次只占用一个哈希线程），按块通过 `/file-screening/hashes` 补充到 API，
+//! 同时写回本地索引。队列保存在本地索引中，应用重启后继续处理。低功耗模式下暂停计算。
+//!
+//! 模式保存在 settings.json 的 `deferred_hashing` 中，默认关闭；本地索引不可用时该模式不生效。
+
+use log::{error, info};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Notify;
+
+/// settings.json 中保存延迟哈希模式的键
+const MODE_STORE_KEY: &str = "deferred_hashing";
+/// 每次从队列取出并补充到 API 的文件数
+const CHUNK_SIZE: usize = 100;
+/// 队列为空时的检查间隔
+const IDLE_INTERVAL: Duration = Duration::from_secs(30);
+/// 被唤醒后稍等片刻，等本地索引写入完成并攒够一块
+const WAKE_DELAY: Duration = Duration::from_secs(1);
+/// 补充到 API 失败后的重试间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// 每个文件之间让出的时间，避免与扫描争抢磁盘
+const HASH_PAUSE: Duration = Duration::from_millis(5);
+
+/// 是否启用延迟哈希模式
+static DEFERRED: AtomicBool = AtomicBool::new(false);
+
+static WAKE: OnceLock<Notify> = OnceLock::new();
+
+fn wake_signal() -> &'static Notify {
+    WAKE.get_or_init(Notify::new)
+}
+
+/// 当前是否延迟计算哈希（需要本地索引保存待计算队列）
+pub fn enabled() -> bool {
+    DEFERRED.load(Ordering::SeqCst) && crate::local_index::available()
+}
+
+/// 有新文件加入待计算队列，唤醒后台任务
+pub fn wake() {
+    wake_signal().notify_one();
+}
+
+/// 恢复保存的模式并启动后台任务，应在本地索引打开后调用
+pub fn init(app_handle: &AppHandle) {
+    let saved = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(MODE_STORE_KEY))
+        .and_then(|value| value.as_bool());
+    if let Some(deferred) = saved {
+        DEFERRED.store(deferred, Ordering::SeqCst);
+    }
+    // 关闭模式后仍会处理完队列中剩余的文件
+    tauri::async_runtime::spawn(run(app_handle.clone()));
+}
+
+// 后台任务：从队列取出文件，计算哈希后补充到 API 并写回本地索引
+async fn run(app_handle: AppHandle) {
+    loop {
+        crate::sleep_wake::wait_while_paused().await;
+        if !crate::power_profile::hashing_enabled() {
+            tokio::time::sleep(IDLE_INTERVAL).await;
+            continue;
+        }
+
+        let pending =
+            tauri::async_runtime::spawn_blocking(|| crate::local_index::pending_hashes(CHUNK_SIZE))
+                .await;
+        let pending = match pending {
+            Ok(Ok(pending)) => pending,
+            Ok(Err(e)) => {
+                error!("[DEFERRED_HASH] 读取待计算队列失败: {}", e);
+                tokio::time::sleep(IDLE_INTERVAL).await;
+                continue;
+            }
+            Err(_) => return,
+        };
+        if pending.is_empty() {
+            tokio::select! {
+                _ = wake_signal().notified() => tokio::time::sleep(WAKE_DELAY).await,
+                _ = tokio::time::sleep(IDLE_INTERVAL) => {}
+            }
+            continue;
+        }
+
+        // 发送后文件又有变动时计算的是最新内容的哈希；文件已删除或无法读取时只移出队列
+        let mut results = Vec::with_capacity(pending.len());
+        for file_path in pending {
+            let hash = crate::hash_pool::hash_prefix(PathBuf::from(&file_path), 4096).await;
+            results.push((file_path, hash));
+            tokio::time::sleep(HASH_PAUSE).await;
+        }
+
+        let updates: Vec<serde_json::Value> = results
+            .iter()
+            .filter_map(|(file_path, hash)| {
+                hash.as_ref()
+                    .map(|hash| serde_json::json!({ "file_path": file_path, "file_hash": hash }))
+            })
+            .collect();
+        if !updates.is_empty() {
+            if let Err(e) = post_hashes(&app_handle, updates).await {
+                error!(
+                    "[DEFERRED_HASH] 补充哈希失败，{} 秒后重试: {}",
+                    RETRY_INTERVAL.as_secs(),
+                    e
+                );
+                tokio::time::sleep(RETRY_INTERVAL).await;
+                continue;
+            }
+        }
+
+        let count = results.len();
+        let completed = tauri::async_runtime::spawn_blocking(move || {
+            crate::local_index::complete_hashes(&results)
+        })
+        .await;
+        match completed {
+            Ok(Ok(())) => info!("[DEFERRED_HASH] 已补充 {} 个文件的哈希", count),
+            Ok(Err(e)) => error!("[DEFERRED_HASH] 写回本地索引失败: {}", e),
+            Err(_) => return,
+        }
+    }
+}
+
+// 把计算好的哈希补充到 API 的粗筛结果
+async fn post_hashes(
+    app_handle: &AppHandle,
+    updates: Vec<serde_json::Value>,
+) -> Result<(), String> {
+    let (api_host, api_port) = {
+        let api_state = app_handle.state::<crate::ApiState>();
+        let api_state_guard = api_state.0.lock().unwrap();
+        (api_state_guard.host.clone(), api_state_guard.port)
+    };
+    let url = format!("http://{}:{}/file-screening/hashes", api_host, api_port);
+    let response = crate::http_client::shared()
+        .post(&url)
+        .timeout(Duration::from_secs(30))
+        .json(&serde_json::json!({ "updates": updates }))
+        .send()
+        .await
+        .map_err(|e| format!("请求失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("API返回错误状态: {}", response.status()));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("解析响应失败: {}", e))?;
+    if body["success"].as_bool() != Some(true) {
+        return Err(format!("API未能更新哈希: {}", body["message"]));
+    }
+    Ok(())
+}
+
+/// 开启或关闭延迟哈希模式
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_deferred_hashing(
+    enabled: bool,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    if enabled && !crate::local_index::available() {
+        return Err("本地索引不可用，无法延迟计算哈希".to_string());
+    }
+    DEFERRED.store(enabled, Ordering::SeqCst);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(MODE_STORE_KEY, serde_json::json!(enabled));
+    store
+        .save()
+        .map_err(|e| format!("保存延迟哈希模式失败: {}", e))?;
+
+    info!(
+        "[DEFERRED_HASH] 延迟哈希模式已{}",
+        if enabled { "开启" } else { "关闭" }
+    );
+    Ok(serde_json::json!({
+        "status": "success",
+        "enabled": enabled
+    }))
+}
+
+/// 获取延迟哈希模式和待计算哈希的文件数
+#[tauri::command(rename_all = "snake_case", async)]
+pub fn get_deferred_hashing() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "enabled": enabled(),
+        "pending": crate::local_index::pending_hash_count().unwrap_or(0)
+    }))
+}
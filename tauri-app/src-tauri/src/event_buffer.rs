@@ -1,11 +1,53 @@
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
 use tokio::sync::RwLock;
+
+use crate::bridge_events::{
+    BridgeEvent, BridgeEventKind, ModelDownloadCompletedPayload, ModelDownloadFailedPayload,
+    MultivectorCompletedPayload, MultivectorFailedPayload,
+};
+use crate::event_trace;
 use tokio::time::interval;
 
+/// settings.json 中保存运行时策略覆盖的键
+const STRATEGY_STORE_KEY: &str = "event_buffer_strategies";
+/// 未配置策略的事件使用的默认策略
+const DEFAULT_STRATEGY: EventBufferStrategy =
+    EventBufferStrategy::DelayedMerge(Duration::from_millis(500));
+/// 合并事件时最多保留的payload数量，超出部分只计数
+const MAX_MERGED_PAYLOADS: usize = 100;
+/// 退出时未发送的事件保存在应用数据目录下的文件名
+const PENDING_EVENTS_FILE: &str = "pending_bridge_events.json";
+/// 死信队列最多保留的事件数量，超出时丢弃最早的事件
+const MAX_DEAD_LETTERS: usize = 500;
+/// 死信事件最多重试的次数（每个flush周期重试一次）
+const MAX_DEAD_LETTER_ATTEMPTS: u32 = 60;
+/// 缓冲区最多保留的缓冲项数量，防止事件风暴占用过多内存
+const MAX_BUFFERED_EVENTS: usize = 1000;
+/// 缓冲区已满时，同名事件聚合到的溢出缓冲项的键后缀
+const OVERFLOW_KEY_SUFFIX: &str = "#overflow";
+/// 最近事件环形缓冲区的容量
+const RECENT_EVENTS_CAPACITY: usize = 200;
+/// 新窗口打开时需要重放的状态类事件，每个缓冲键只重放最新的一条
+const REPLAYABLE_EVENTS: &[&str] = &[
+    "system-status",
+    "model-status-changed",
+    "tagging-model-missing",
+    "model-download-progress",
+    "model-download-completed",
+    "model-download-failed",
+    "multivector-progress",
+    "scan_started",
+    "scan_completed",
+    "scan_error",
+];
+
 /// 桥接事件数据结构
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BridgeEventData {
@@ -24,18 +66,275 @@ pub enum EventBufferStrategy {
     Throttle(Duration),
 }
 
+impl EventBufferStrategy {
+    /// 从命令参数解析策略，`duration_ms` 对 Immediate 无效
+    pub fn parse(strategy: &str, duration_ms: Option<u64>) -> Result<Self, String> {
+        let duration = || {
+            duration_ms
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis)
+                .ok_or_else(|| format!("策略 {} 需要大于0的 duration_ms", strategy))
+        };
+        match strategy {
+            "immediate" => Ok(EventBufferStrategy::Immediate),
+            "delayed_merge" => Ok(EventBufferStrategy::DelayedMerge(duration()?)),
+            "throttle" => Ok(EventBufferStrategy::Throttle(duration()?)),
+            _ => Err(format!("未知的缓冲策略: {}", strategy)),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            EventBufferStrategy::Immediate => serde_json::json!({ "strategy": "immediate" }),
+            EventBufferStrategy::DelayedMerge(d) => serde_json::json!({
+                "strategy": "delayed_merge",
+                "duration_ms": d.as_millis() as u64
+            }),
+            EventBufferStrategy::Throttle(d) => serde_json::json!({
+                "strategy": "throttle",
+                "duration_ms": d.as_millis() as u64
+            }),
+        }
+    }
+
+    /// 缓冲事件在最后一次更新后多久由定期flush发出
+    fn flush_after(&self) -> Duration {
+        match self {
+            EventBufferStrategy::Immediate => Duration::ZERO,
+            EventBufferStrategy::DelayedMerge(d) | EventBufferStrategy::Throttle(d) => *d,
+        }
+    }
+}
+
 /// 缓冲的事件项
 #[derive(Debug, Clone)]
 struct BufferedEvent {
     data: BridgeEventData,
     last_time: Instant,
     count: u32,
+    /// 延迟合并期间收到的所有payload（最多保留 MAX_MERGED_PAYLOADS 个）
+    merged_payloads: Vec<serde_json::Value>,
+}
+
+impl BufferedEvent {
+    /// 生成发送给前端的事件数据
+    ///
+    /// 合并了多个事件时，在最后一个payload的基础上附加 `merged_count`、`merged_payloads`
+    /// 和 `merged_payloads_truncated`，前端既能按原字段读取最新状态，也能展示"已处理37个文件"这类汇总。
+    /// 非对象payload无法附加字段，原样发送最后一个payload，保证合并与否事件的结构都一致。
+    fn aggregated_data(&self) -> BridgeEventData {
+        if self.count <= 1 || self.merged_payloads.is_empty() {
+            return self.data.clone();
+        }
+
+        let mut payload = match &self.data.payload {
+            serde_json::Value::Object(map) => map.clone(),
+            _ => return self.data.clone(),
+        };
+        payload.insert("merged_count".to_string(), serde_json::json!(self.count));
+        payload.insert(
+            "merged_payloads_truncated".to_string(),
+            serde_json::json!(self.count as usize > self.merged_payloads.len()),
+        );
+        payload.insert(
+            "merged_payloads".to_string(),
+            serde_json::Value::Array(self.merged_payloads.clone()),
+        );
+
+        BridgeEventData {
+            event: self.data.event.clone(),
+            payload: serde_json::Value::Object(payload),
+        }
+    }
+}
+/// 事件名是否匹配通配模式，`*` 匹配任意长度的字符
+fn wildcard_matches(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// 查找事件的缓冲策略：精确匹配优先，其次是最具体（最长）的通配模式，最后使用默认策略
+fn resolve_strategy(
+    strategies: &HashMap<String, EventBufferStrategy>,
+    event: &str,
+) -> EventBufferStrategy {
+    if let Some(strategy) = strategies.get(event) {
+        return *strategy;
+    }
+    strategies
+        .iter()
+        .filter(|(pattern, _)| pattern.contains('*') && wildcard_matches(pattern, event))
+        .max_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| b.0.cmp(a.0)))
+        .map(|(_, strategy)| *strategy)
+        .unwrap_or(DEFAULT_STRATEGY)
+}
+
+/// 事件的窗口路由规则：匹配的事件只发送到列出的窗口，未匹配的事件发送到所有窗口
+///
+/// 进度、数据刷新等只有主窗口使用的事件不发送给其他窗口，避免多窗口时重复序列化；错误和状态类事件发送到所有窗口。
+/// 规则按顺序匹配，支持 `*` 通配。`REPLAYABLE_EVENTS` 中的事件（包括模型下载、多模态处理进度）不受这里的规则限制，
+/// 始终发送到所有窗口，这样后打开的窗口也能通过重放拿到最新进度。
+const EVENT_ROUTES: &[(&str, &[&str])] = &[
+    ("*-progress", &["main"]),
+    ("tags-updated", &["main"]),
+    ("database-updated", &["main"]),
+    ("task-completed", &["main"]),
+    ("file-processed", &["main"]),
+    ("screening-result-updated", &["main"]),
+    ("rag-retrieval-result", &["main"]),
+    ("tool-call-request", &["main"]),
+];
+
+/// 事件的目标窗口，None 表示发送到所有窗口
+fn route_targets(event: &str) -> Option<&'static [&'static str]> {
+    if REPLAYABLE_EVENTS.contains(&event) {
+        return None;
+    }
+    EVENT_ROUTES
+        .iter()
+        .find(|(pattern, _)| wildcard_matches(pattern, event))
+        .map(|(_, targets)| *targets)
+}
+
+/// 事件是否应发送到指定窗口
+fn routed_to(event: &str, window_label: &str) -> bool {
+    route_targets(event).is_none_or(|targets| targets.contains(&window_label))
+}
+
+/// 发送失败、等待重试的事件
+#[derive(Debug, Clone)]
+struct DeadLetter {
+    data: BridgeEventData,
+    attempts: u32,
+    last_error: String,
+}
+
+/// 按路由规则发送事件到前端，没有任何目标窗口时视为发送失败
+fn emit_to_frontend(app_handle: &AppHandle, event_data: &BridgeEventData) -> Result<(), String> {
+    let result = match route_targets(&event_data.event) {
+        None if app_handle.webview_windows().is_empty() => Err("没有可接收事件的窗口".to_string()),
+        None => app_handle
+            .emit(&event_data.event, &event_data.payload)
+            .map_err(|e| e.to_string()),
+        Some(targets) => {
+            let open_targets: Vec<&str> = targets
+                .iter()
+                .copied()
+                .filter(|label| app_handle.get_webview_window(label).is_some())
+                .collect();
+            if open_targets.is_empty() {
+                Err(format!("目标窗口未打开: {}", targets.join(", ")))
+            } else {
+                open_targets.iter().try_for_each(|label| {
+                    app_handle
+                        .emit_to(*label, &event_data.event, &event_data.payload)
+                        .map_err(|e| e.to_string())
+                })
+            }
+        }
+    };
+    let decision = if result.is_ok() {
+        "emitted"
+    } else {
+        "emit_failed"
+    };
+    event_trace::trace(
+        app_handle,
+        "bridge",
+        &event_data.event,
+        decision,
+        &event_data.payload,
+    );
+    result
 }
+
+/// 把发送失败的事件加入死信队列
+fn push_dead_letter(
+    dead_letters: &mut VecDeque<DeadLetter>,
+    event_data: BridgeEventData,
+    error: String,
+) {
+    if dead_letters.len() >= MAX_DEAD_LETTERS {
+        if let Some(dropped) = dead_letters.pop_front() {
+            warn!("❌ 死信队列已满，丢弃最早的事件: {}", dropped.data.event);
+        }
+    }
+    dead_letters.push_back(DeadLetter {
+        data: event_data,
+        attempts: 0,
+        last_error: error,
+    });
+}
+
+/// 重试死信队列中的事件，返回发送成功的数量；超过重试次数的事件被丢弃
+fn retry_dead_letters(app_handle: &AppHandle, dead_letters: &mut VecDeque<DeadLetter>) -> usize {
+    let mut delivered = 0;
+    let mut remaining = VecDeque::with_capacity(dead_letters.len());
+    for mut dead_letter in dead_letters.drain(..) {
+        match emit_to_frontend(app_handle, &dead_letter.data) {
+            Ok(()) => {
+                info!(
+                    "📮 死信事件重试发送成功: {} (第{}次重试)",
+                    dead_letter.data.event,
+                    dead_letter.attempts + 1
+                );
+                delivered += 1;
+            }
+            Err(e) => {
+                dead_letter.attempts += 1;
+                dead_letter.last_error = e;
+                if dead_letter.attempts >= MAX_DEAD_LETTER_ATTEMPTS {
+                    event_trace::trace(
+                        app_handle,
+                        "bridge",
+                        &dead_letter.data.event,
+                        "dead_letter_dropped",
+                        &dead_letter.data.payload,
+                    );
+                    error!(
+                        "❌ 死信事件重试{}次仍失败，已丢弃: {} - {}",
+                        dead_letter.attempts, dead_letter.data.event, dead_letter.last_error
+                    );
+                } else {
+                    remaining.push_back(dead_letter);
+                }
+            }
+        }
+    }
+    *dead_letters = remaining;
+    delivered
+}
+
 /// 智能事件缓冲器
 pub struct EventBuffer {
     app_handle: AppHandle,
     buffered_events: Arc<RwLock<HashMap<String, BufferedEvent>>>,
-    strategies: HashMap<String, EventBufferStrategy>,
+    /// 发送失败的事件，在每个flush周期重试
+    dead_letters: Arc<RwLock<VecDeque<DeadLetter>>>,
+    /// 最近收到的事件，用于向新打开的窗口重放状态
+    recent_events: Arc<Mutex<VecDeque<BridgeEventData>>>,
+    /// 缓冲区已满时并入溢出项的事件数
+    overflow_merged: AtomicU64,
+    /// 缓冲区已满时被提前发送的缓冲项数
+    evicted: AtomicU64,
+    strategies: Arc<RwLock<HashMap<String, EventBufferStrategy>>>,
+    /// 事件名 -> payload 中用于区分缓冲项的字段
+    merge_keys: HashMap<String, String>,
 }
 
 impl EventBuffer {
@@ -43,12 +342,20 @@ impl EventBuffer {
     pub fn new(app_handle: AppHandle) -> Self {
         let mut strategies = HashMap::new();
 
-        // 配置不同事件的缓冲策略
+        // 配置不同事件的缓冲策略，再应用 settings.json 中保存的运行时覆盖
         Self::configure_strategies(&mut strategies);
+        Self::apply_persisted_strategies(&app_handle, &mut strategies);
+        let mut merge_keys = HashMap::new();
+        Self::configure_merge_keys(&mut merge_keys);
         let buffer = Self {
             app_handle,
             buffered_events: Arc::new(RwLock::new(HashMap::new())),
-            strategies,
+            dead_letters: Arc::new(RwLock::new(VecDeque::new())),
+            recent_events: Arc::new(Mutex::new(VecDeque::with_capacity(RECENT_EVENTS_CAPACITY))),
+            overflow_merged: AtomicU64::new(0),
+            evicted: AtomicU64::new(0),
+            strategies: Arc::new(RwLock::new(strategies)),
+            merge_keys,
         };
 
         // 启动定期清理任务
@@ -57,6 +364,18 @@ impl EventBuffer {
         buffer
     }
 
+    /// 获取应用共享的事件缓冲器，首次调用时创建并注册到应用状态
+    ///
+    /// stdout 桥接和 SSE 事件通道共用同一个缓冲器，保证合并与节流窗口一致。必须在异步运行时中调用。
+    pub fn shared(app_handle: &AppHandle) -> Arc<EventBuffer> {
+        if let Some(buffer) = app_handle.try_state::<Arc<EventBuffer>>() {
+            return buffer.inner().clone();
+        }
+        let buffer = Arc::new(EventBuffer::new(app_handle.clone()));
+        app_handle.manage(buffer.clone());
+        buffer
+    }
+
     /// 配置各种事件的缓冲策略
     fn configure_strategies(strategies: &mut HashMap<String, EventBufferStrategy>) {
         use EventBufferStrategy::*;
@@ -76,6 +395,8 @@ impl EventBuffer {
         strategies.insert("multivector-failed".to_string(), Immediate);
         // OAuth 登录成功事件需要立即通知前端
         strategies.insert("oauth-login-success".to_string(), Immediate);
+        // 控制命令执行结果：每条结果对应一次请求，不能合并
+        strategies.insert("control-command-result".to_string(), Immediate);
 
         // === 延迟合并类（可缓冲，适合批量场景） ===
         // 标签更新：用户首次启动或大量文件处理时会频繁更新，5秒内合并
@@ -115,7 +436,7 @@ impl EventBuffer {
             "multivector-progress".to_string(),
             Throttle(Duration::from_secs(1)),
         );
-        
+
         // === 模型下载事件 ===
         // 模型下载进度：节流处理，避免UI更新过于频繁，最多每秒1次
         strategies.insert(
@@ -146,38 +467,448 @@ impl EventBuffer {
             Throttle(Duration::from_millis(500)),
         );
 
-        // 注意：未在此配置的事件类型将使用默认策略（500ms延迟合并）
+        // === 通配模式（精确配置优先，Python 端新增的同类事件自动适用） ===
+        // 各类进度事件：最多每秒1次
+        strategies.insert("*-progress".to_string(), Throttle(Duration::from_secs(1)));
+        // 各类错误和失败事件：立即通知
+        strategies.insert("*-error".to_string(), Immediate);
+        strategies.insert("*-failed".to_string(), Immediate);
+
+        // 注意：未在此配置且不匹配通配模式的事件类型将使用默认策略（500ms延迟合并）
+    }
+
+    /// 配置按 payload 字段合并的事件
+    ///
+    /// 同一事件中该字段取值不同的事件分别缓冲、分别发送，只有取值相同的事件才会互相合并或节流，
+    /// 避免不同任务的完成事件被合并成一个。未配置的事件仍只按事件名合并。
+    fn configure_merge_keys(merge_keys: &mut HashMap<String, String>) {
+        // 不同任务的完成事件都需要送达前端
+        merge_keys.insert("task-completed".to_string(), "task_id".to_string());
+        // 多个文件同时向量化时，各自的进度独立节流
+        merge_keys.insert("multivector-progress".to_string(), "file_path".to_string());
+        // 多个模型同时下载时，各自的进度独立节流
+        merge_keys.insert(
+            "model-download-progress".to_string(),
+            "model_name".to_string(),
+        );
+    }
+
+    /// 计算事件在缓冲区中的键：`事件名` 或 `事件名#字段值`
+    fn buffer_key(&self, event: &str, payload: &serde_json::Value) -> String {
+        let key_value = self
+            .merge_keys
+            .get(event)
+            .and_then(|field| payload.get(field))
+            .map(|value| match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            });
+        match key_value {
+            Some(key_value) => format!("{}#{}", event, key_value),
+            None => event.to_string(),
+        }
+    }
+
+    /// 记录最近的事件，超出容量时丢弃最早的事件
+    pub fn record_recent_event(&self, event_data: &BridgeEventData) {
+        let mut recent = self.recent_events.lock().unwrap();
+        if recent.len() >= RECENT_EVENTS_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(event_data.clone());
+    }
+
+    /// 向指定窗口重放最近的状态类事件，返回重放的事件数量
+    pub fn replay_recent_events(&self, window_label: &str) -> usize {
+        let recent = self.recent_events.lock().unwrap().clone();
+
+        // 同一缓冲键只保留最新的一条，并按原始顺序发送
+        let mut latest_index: HashMap<String, usize> = HashMap::new();
+        for (index, event_data) in recent.iter().enumerate() {
+            if REPLAYABLE_EVENTS.contains(&event_data.event.as_str())
+                && routed_to(&event_data.event, window_label)
+            {
+                latest_index.insert(
+                    self.buffer_key(&event_data.event, &event_data.payload),
+                    index,
+                );
+            }
+        }
+        let mut indexes: Vec<usize> = latest_index.into_values().collect();
+        indexes.sort_unstable();
+
+        let mut replayed = 0;
+        for index in indexes {
+            let event_data = &recent[index];
+            match self
+                .app_handle
+                .emit_to(window_label, &event_data.event, &event_data.payload)
+            {
+                Ok(()) => replayed += 1,
+                Err(e) => error!(
+                    "❌ 向窗口 {} 重放事件失败: {} - {}",
+                    window_label, event_data.event, e
+                ),
+            }
+        }
+        if replayed > 0 {
+            info!(
+                "♻️ 已向窗口 {} 重放 {} 个最近的状态事件",
+                window_label, replayed
+            );
+        }
+        replayed
+    }
+
+    fn pending_events_path(&self) -> Option<std::path::PathBuf> {
+        self.app_handle
+            .path()
+            .app_data_dir()
+            .ok()
+            .map(|dir| dir.join(PENDING_EVENTS_FILE))
+    }
+
+    /// 立即发送缓冲区中的所有事件（用于应用退出前），返回发送成功的数量
+    ///
+    /// 同步执行，发送失败的事件保留在缓冲区中，可由 `persist_pending_events` 保存
+    pub fn flush_all(&self) -> usize {
+        let pending: Vec<(String, BufferedEvent)> = match self.buffered_events.try_write() {
+            Ok(mut events) => events.drain().collect(),
+            Err(_) => {
+                error!("❌ 事件缓冲区被占用，无法立即发送缓冲的事件");
+                return 0;
+            }
+        };
+
+        let mut flushed = match self.dead_letters.try_write() {
+            Ok(mut dead_letters) => retry_dead_letters(&self.app_handle, &mut dead_letters),
+            Err(_) => 0,
+        };
+        let mut failed = Vec::new();
+        for (key, buffered) in pending {
+            let event_data = buffered.aggregated_data();
+            match emit_to_frontend(&self.app_handle, &event_data) {
+                Ok(()) => flushed += 1,
+                Err(e) => {
+                    error!("❌ 退出前发送桥接事件失败: {} - {}", event_data.event, e);
+                    failed.push((key, buffered));
+                }
+            }
+        }
+        if !failed.is_empty() {
+            if let Ok(mut events) = self.buffered_events.try_write() {
+                events.extend(failed);
+            }
+        }
+        if flushed > 0 {
+            info!("📤 退出前已发送 {} 个缓冲的桥接事件", flushed);
+        }
+        flushed
+    }
+
+    /// 应用退出时把缓冲区中尚未发送的事件保存到磁盘，下次启动时重新发送
+    ///
+    /// 退出流程是同步的，这里只尝试获取锁，拿不到锁时放弃保存。已保存的事件会从缓冲区移除，
+    /// 多次调用时追加到同一个文件。
+    pub fn persist_pending_events(&self) {
+        // 死信事件同样未送达，排在缓冲事件之前保存
+        let mut pending: Vec<BridgeEventData> = match self.dead_letters.try_write() {
+            Ok(mut dead_letters) => dead_letters.drain(..).map(|dead| dead.data).collect(),
+            Err(_) => {
+                error!("❌ 死信队列被占用，无法保存其中的事件");
+                Vec::new()
+            }
+        };
+        match self.buffered_events.try_write() {
+            Ok(mut events) => pending.extend(
+                events
+                    .drain()
+                    .map(|(_, buffered)| buffered.aggregated_data()),
+            ),
+            Err(_) => error!("❌ 事件缓冲区被占用，无法保存未发送的事件"),
+        }
+        if pending.is_empty() {
+            return;
+        }
+        let Some(path) = self.pending_events_path() else {
+            error!(
+                "❌ 无法获取应用数据目录，{} 个未发送的事件将丢失",
+                pending.len()
+            );
+            return;
+        };
+
+        let mut all_pending: Vec<BridgeEventData> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        all_pending.extend(pending);
+        match serde_json::to_string(&all_pending)
+            .map_err(|e| e.to_string())
+            .and_then(|content| std::fs::write(&path, content).map_err(|e| e.to_string()))
+        {
+            Ok(()) => info!(
+                "💾 已保存 {} 个未发送的桥接事件到 {}",
+                all_pending.len(),
+                path.display()
+            ),
+            Err(e) => error!("❌ 保存未发送的桥接事件失败: {}", e),
+        }
+    }
+
+    /// 重新发送上次退出时保存的事件，应在前端就绪后调用
+    pub async fn replay_persisted_events(&self) {
+        let Some(path) = self.pending_events_path() else {
+            return;
+        };
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return;
+        };
+        // 先删除文件，避免重放过程中再次退出导致重复发送
+        let _ = std::fs::remove_file(&path);
+
+        let pending: Vec<BridgeEventData> = match serde_json::from_str(&content) {
+            Ok(pending) => pending,
+            Err(e) => {
+                error!("❌ 解析上次保存的桥接事件失败: {}", e);
+                return;
+            }
+        };
+        info!("♻️ 重新发送上次退出时未发送的 {} 个桥接事件", pending.len());
+        for event_data in pending {
+            info!("♻️ 重放事件: {}", event_data.event);
+            self.handle_raw_event(event_data).await;
+        }
+    }
+
+    /// 读取 settings.json 中保存的策略覆盖
+    fn apply_persisted_strategies(
+        app_handle: &AppHandle,
+        strategies: &mut HashMap<String, EventBufferStrategy>,
+    ) {
+        let Ok(store) = app_handle.store("settings.json") else {
+            return;
+        };
+        let Some(serde_json::Value::Object(overrides)) = store.get(STRATEGY_STORE_KEY) else {
+            return;
+        };
+        for (event, value) in overrides {
+            let strategy = value.get("strategy").and_then(|v| v.as_str()).unwrap_or("");
+            let duration_ms = value.get("duration_ms").and_then(|v| v.as_u64());
+            match EventBufferStrategy::parse(strategy, duration_ms) {
+                Ok(parsed) => {
+                    info!("🔧 应用已保存的事件缓冲策略: {} -> {:?}", event, parsed);
+                    strategies.insert(event, parsed);
+                }
+                Err(e) => warn!("❌ 忽略无效的事件缓冲策略 {}: {}", event, e),
+            }
+        }
+    }
+
+    /// 运行时修改事件的缓冲策略并持久化到 settings.json
+    ///
+    /// `event` 可以是事件名或通配模式（如 `*-progress`）。
+    /// `strategy` 为 None 时恢复内置默认策略并删除保存的覆盖，没有内置策略的条目会被移除，
+    /// 之后按通配模式或默认策略处理
+    pub async fn set_strategy(
+        &self,
+        event: &str,
+        strategy: Option<EventBufferStrategy>,
+    ) -> Result<EventBufferStrategy, String> {
+        let effective = {
+            let mut strategies = self.strategies.write().await;
+            let restored = match strategy {
+                Some(strategy) => Some(strategy),
+                None => {
+                    let mut defaults = HashMap::new();
+                    Self::configure_strategies(&mut defaults);
+                    defaults.get(event).copied()
+                }
+            };
+            match restored {
+                Some(restored) => {
+                    strategies.insert(event.to_string(), restored);
+                }
+                None => {
+                    strategies.remove(event);
+                }
+            }
+            resolve_strategy(&strategies, event)
+        };
+
+        let store = self
+            .app_handle
+            .store("settings.json")
+            .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+        let mut overrides = match store.get(STRATEGY_STORE_KEY) {
+            Some(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        match strategy {
+            Some(strategy) => {
+                overrides.insert(event.to_string(), strategy.to_json());
+            }
+            None => {
+                overrides.remove(event);
+            }
+        }
+        store.set(STRATEGY_STORE_KEY, serde_json::Value::Object(overrides));
+        store
+            .save()
+            .map_err(|e| format!("保存事件缓冲策略失败: {}", e))?;
+
+        info!("🔧 事件缓冲策略已更新: {} -> {:?}", event, effective);
+        Ok(effective)
+    }
+
+    /// 当前生效的全部策略（按事件名排序）
+    pub async fn strategies_snapshot(&self) -> Vec<(String, EventBufferStrategy)> {
+        let mut strategies: Vec<(String, EventBufferStrategy)> = self
+            .strategies
+            .read()
+            .await
+            .iter()
+            .map(|(event, strategy)| (event.clone(), *strategy))
+            .collect();
+        strategies.sort_by(|a, b| a.0.cmp(&b.0));
+        strategies
+    }
+
+    /// 缓冲区统计信息：当前排队的事件及其生效策略，供调试面板展示
+    pub async fn get_stats(&self) -> serde_json::Value {
+        let strategies = self.strategies.read().await;
+        let events = self.buffered_events.read().await;
+        let now = Instant::now();
+
+        let mut queued: Vec<serde_json::Value> = events
+            .iter()
+            .map(|(key, buffered)| {
+                let strategy = resolve_strategy(&strategies, &buffered.data.event);
+                serde_json::json!({
+                    "key": key,
+                    "event": buffered.data.event,
+                    "strategy": strategy.to_json(),
+                    "count": buffered.count,
+                    "merged_payloads": buffered.merged_payloads.len(),
+                    "idle_ms": now.duration_since(buffered.last_time).as_millis() as u64,
+                    "flush_in_ms": strategy
+                        .flush_after()
+                        .saturating_sub(now.duration_since(buffered.last_time))
+                        .as_millis() as u64
+                })
+            })
+            .collect();
+        queued.sort_by(|a, b| a["key"].as_str().cmp(&b["key"].as_str()));
+        let merged_event_count: u64 = events.values().map(|buffered| buffered.count as u64).sum();
+        let dead_letters: Vec<serde_json::Value> = self
+            .dead_letters
+            .read()
+            .await
+            .iter()
+            .map(|dead_letter| {
+                serde_json::json!({
+                    "event": dead_letter.data.event,
+                    "attempts": dead_letter.attempts,
+                    "last_error": dead_letter.last_error
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "queued_count": queued.len(),
+            "merged_event_count": merged_event_count,
+            "queued": queued,
+            "dead_letter_count": dead_letters.len(),
+            "dead_letters": dead_letters,
+            "capacity": MAX_BUFFERED_EVENTS,
+            "overflow_merged_count": self.overflow_merged.load(Ordering::Relaxed),
+            "evicted_count": self.evicted.load(Ordering::Relaxed),
+            "routes": EVENT_ROUTES
+                .iter()
+                .map(|(pattern, targets)| serde_json::json!({ "event": pattern, "windows": targets }))
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// 校验并处理桥接通道收到的原始事件，已知事件的payload格式错误时拒绝该事件
+    pub async fn handle_raw_event(&self, event_data: BridgeEventData) {
+        let raw = event_trace::is_enabled().then(|| event_data.clone());
+        match BridgeEvent::parse(event_data) {
+            Ok(event) => self.handle_event(event).await,
+            Err(e) => {
+                error!("❌ 拒绝格式错误的桥接事件: {}", e);
+                if let Some(raw) = raw {
+                    event_trace::trace(
+                        &self.app_handle,
+                        "bridge",
+                        &raw.event,
+                        &format!("rejected: {}", e),
+                        &raw.payload,
+                    );
+                }
+            }
+        }
     }
 
     /// 处理incoming事件
-    pub async fn handle_event(&self, event_data: BridgeEventData) {
-        // ⚠️ 特殊处理：如果是模型下载失败/完成事件，清除缓冲区中的 progress 事件
-        if event_data.event == "model-download-failed" 
-            || event_data.event == "model-download-completed" {
-            self.clear_buffered_event("model-download-progress").await;
-            println!("🧹 已清除缓冲区中的 model-download-progress 事件");
-        }
-        
-        // ⚠️ 特殊处理：如果是多模态向量化完成/失败事件，清除缓冲区中的 progress 事件
-        if event_data.event == "multivector-completed" 
-            || event_data.event == "multivector-failed" {
-            self.clear_buffered_event("multivector-progress").await;
-            println!("🧹 已清除缓冲区中的 multivector-progress 事件");
-        }
-        
-        let strategy = self.strategies.get(&event_data.event).copied().unwrap_or(
-            EventBufferStrategy::DelayedMerge(Duration::from_millis(500)),
-        ); // 默认策略
+    pub async fn handle_event(&self, event: BridgeEvent) {
+        self.record_recent_event(&event.data);
+        let summary = event.summary();
+        if !summary.is_empty() {
+            info!("📨 桥接事件 {}: {}", event.data.event, summary);
+        }
+
+        match &event.kind {
+            // ⚠️ 特殊处理：模型下载失败/完成时，清除缓冲区中该模型的 progress 事件
+            BridgeEventKind::ModelDownloadCompleted(ModelDownloadCompletedPayload {
+                model_name,
+                ..
+            })
+            | BridgeEventKind::ModelDownloadFailed(ModelDownloadFailedPayload {
+                model_name, ..
+            }) => {
+                self.clear_buffered_event("model-download-progress", model_name)
+                    .await;
+                info!(
+                    "🧹 已清除缓冲区中模型 {} 的 model-download-progress 事件",
+                    model_name
+                );
+            }
+            // ⚠️ 特殊处理：多模态向量化完成/失败时，清除缓冲区中该文件的 progress 事件
+            BridgeEventKind::MultivectorCompleted(MultivectorCompletedPayload {
+                file_path,
+                ..
+            })
+            | BridgeEventKind::MultivectorFailed(MultivectorFailedPayload { file_path, .. }) => {
+                self.clear_buffered_event("multivector-progress", file_path)
+                    .await;
+                info!(
+                    "🧹 已清除缓冲区中文件 {} 的 multivector-progress 事件",
+                    file_path
+                );
+            }
+            _ => {}
+        }
+
+        let event_data = event.data;
+        let strategy = resolve_strategy(&*self.strategies.read().await, &event_data.event);
+        event_trace::trace(
+            &self.app_handle,
+            "bridge",
+            &event_data.event,
+            &format!("received ({:?})", strategy),
+            &event_data.payload,
+        );
 
         match strategy {
             EventBufferStrategy::Immediate => {
                 // 立即发送
-                println!("⚡ 立即转发事件: {}", event_data.event);
+                info!("⚡ 立即转发事件: {}", event_data.event);
                 self.emit_event(&event_data).await;
             }
             EventBufferStrategy::DelayedMerge(duration) => {
                 // 延迟合并处理
-                println!(
+                info!(
                     "🔄 延迟合并事件: {} ({}秒窗口)",
                     event_data.event,
                     duration.as_secs()
@@ -186,7 +917,7 @@ impl EventBuffer {
             }
             EventBufferStrategy::Throttle(duration) => {
                 // 节流处理
-                println!(
+                info!(
                     "⏱️  节流处理事件: {} ({}秒间隔)",
                     event_data.event,
                     duration.as_secs()
@@ -196,15 +927,62 @@ impl EventBuffer {
         }
     }
 
+    /// 缓冲区已满时为新缓冲项选择键
+    ///
+    /// 新的缓冲键不再单独占位，而是按事件名并入溢出项一起合并；溢出项也需要新位置时，
+    /// 淘汰最久未更新的缓冲项，返回其数据由调用方在释放锁后立即发送
+    fn bounded_key(
+        &self,
+        events: &mut HashMap<String, BufferedEvent>,
+        event_key: String,
+        event_name: &str,
+    ) -> (String, Option<BridgeEventData>) {
+        if events.len() < MAX_BUFFERED_EVENTS || events.contains_key(&event_key) {
+            return (event_key, None);
+        }
+
+        self.overflow_merged.fetch_add(1, Ordering::Relaxed);
+        let overflow_key = format!("{}{}", event_name, OVERFLOW_KEY_SUFFIX);
+        if events.contains_key(&overflow_key) {
+            return (overflow_key, None);
+        }
+
+        let oldest_key = events
+            .iter()
+            .min_by_key(|(_, buffered)| buffered.last_time)
+            .map(|(key, _)| key.clone());
+        let evicted = oldest_key
+            .and_then(|key| events.remove(&key))
+            .map(|buffered| buffered.aggregated_data());
+        if let Some(evicted) = &evicted {
+            self.evicted.fetch_add(1, Ordering::Relaxed);
+            event_trace::trace(
+                &self.app_handle,
+                "bridge",
+                &evicted.event,
+                "evicted",
+                &evicted.payload,
+            );
+            warn!(
+                "⚠️ 事件缓冲区已满 ({} 项)，提前发送最久未更新的事件: {}",
+                MAX_BUFFERED_EVENTS, evicted.event
+            );
+        }
+        (overflow_key, evicted)
+    }
+
     /// 处理延迟合并事件
     async fn handle_delayed_merge(&self, event_data: BridgeEventData, _duration: Duration) {
+        let event_key = self.buffer_key(&event_data.event, &event_data.payload);
         let mut events = self.buffered_events.write().await;
         let now = Instant::now();
-
-        let event_key = event_data.event.clone();
+        let (event_key, evicted) = self.bounded_key(&mut events, event_key, &event_data.event);
 
         if let Some(buffered) = events.get_mut(&event_key) {
             // 更新existing缓冲事件
+            if buffered.merged_payloads.len() < MAX_MERGED_PAYLOADS {
+                buffered.merged_payloads.push(event_data.payload.clone());
+            }
             buffered.data = event_data; // 保持最新的payload
             buffered.last_time = now;
             buffered.count += 1;
@@ -213,24 +991,43 @@ impl EventBuffer {
             events.insert(
                 event_key,
                 BufferedEvent {
+                    merged_payloads: vec![event_data.payload.clone()],
                     data: event_data,
                     last_time: now,
                     count: 1,
                 },
             );
         }
+
+        drop(events);
+        if let Some(evicted) = evicted {
+            self.emit_event(&evicted).await;
+        }
     }
 
     /// 处理节流事件
     async fn handle_throttle(&self, event_data: BridgeEventData, duration: Duration) {
+        let event_key = self.buffer_key(&event_data.event, &event_data.payload);
         let mut events = self.buffered_events.write().await;
         let now = Instant::now();
-        let event_key = event_data.event.clone();
+        let (event_key, evicted) = self.bounded_key(&mut events, event_key, &event_data.event);
+        if let Some(evicted) = evicted {
+            drop(events);
+            self.emit_event(&evicted).await;
+            events = self.buffered_events.write().await;
+        }
 
         if let Some(buffered) = events.get(&event_key) {
             // 检查是否超过了节流间隔
             if now.duration_since(buffered.last_time) < duration {
                 // 还在节流期内，更新数据但不发送
+                event_trace::trace(
+                    &self.app_handle,
+                    "bridge",
+                    &event_data.event,
+                    "throttled",
+                    &event_data.payload,
+                );
                 let mut updated = buffered.clone();
                 updated.data = event_data;
                 updated.last_time = now;
@@ -247,6 +1044,7 @@ impl EventBuffer {
                 data: event_data.clone(),
                 last_time: now,
                 count: 1,
+                merged_payloads: Vec::new(),
             },
         );
 
@@ -255,12 +1053,17 @@ impl EventBuffer {
         self.emit_event(&event_data).await;
     }
 
-    /// 发送事件到前端
+    /// 发送事件到前端，失败时放入死信队列等待重试
     async fn emit_event(&self, event_data: &BridgeEventData) {
-        if let Err(e) = self.app_handle.emit(&event_data.event, &event_data.payload) {
-            eprintln!("❌ 发送桥接事件到前端失败: {} - {}", event_data.event, e);
+        if let Err(e) = emit_to_frontend(&self.app_handle, event_data) {
+            error!(
+                "❌ 发送桥接事件到前端失败，加入死信队列: {} - {}",
+                event_data.event, e
+            );
+            let mut dead_letters = self.dead_letters.write().await;
+            push_dead_letter(&mut dead_letters, event_data.clone(), e);
         } else {
-            println!(
+            info!(
                 "📤 桥接事件已发送到前端: {} (payload: {}字节)",
                 event_data.event,
                 serde_json::to_string(&event_data.payload)
@@ -269,16 +1072,24 @@ impl EventBuffer {
             );
         }
     }
-    
+
     /// 清除缓冲区中特定类型的事件
-    async fn clear_buffered_event(&self, event_key: &str) {
+    ///
+    /// 该事件配置了合并字段时只清除合并字段等于 `key_value` 的那一项（如同一文件的进度），否则清除该事件的所有项
+    async fn clear_buffered_event(&self, event_name: &str, key_value: &str) {
         let mut events = self.buffered_events.write().await;
-        events.remove(event_key);
+        if self.merge_keys.contains_key(event_name) {
+            events.remove(&format!("{}#{}", event_name, key_value));
+        } else {
+            events.retain(|_, buffered| buffered.data.event != event_name);
+        }
     }
 
     /// 启动定期flush任务
     fn start_flush_task(&self) {
         let buffered_events = self.buffered_events.clone();
+        let dead_letters = self.dead_letters.clone();
+        let strategies = self.strategies.clone();
         let app_handle = self.app_handle.clone();
 
         tokio::spawn(async move {
@@ -292,22 +1103,21 @@ impl EventBuffer {
 
                 // 获取需要发送的事件
                 {
+                    let strategies = strategies.read().await;
                     let mut events = buffered_events.write().await;
                     let mut keys_to_remove = Vec::new();
 
                     for (key, buffered) in events.iter() {
                         let age = now.duration_since(buffered.last_time);
 
-                        // 如果事件超过一定时间未更新，就发送它
-                        let should_send = match key.as_str() {
-                            "tags-updated" | "database-updated" => age >= Duration::from_secs(5),
-                            "task-completed" => age >= Duration::from_secs(2),
-                            "file-processed" => age >= Duration::from_secs(2),
-                            _ => age >= Duration::from_secs(1), // 默认1秒
-                        };
+                        // 如果事件超过其策略的时间窗口未更新，就发送它（至少1秒，与检查周期一致）
+                        let flush_after = resolve_strategy(&strategies, &buffered.data.event)
+                            .flush_after()
+                            .max(Duration::from_secs(1));
+                        let should_send = age >= flush_after;
 
                         if should_send {
-                            events_to_send.push(buffered.data.clone());
+                            events_to_send.push(buffered.aggregated_data());
                             keys_to_remove.push(key.clone());
                         }
                     }
@@ -318,15 +1128,36 @@ impl EventBuffer {
                     }
                 }
 
-                // 发送事件（在锁外部进行）
+                // 先重试死信事件，保持事件的先后顺序
+                let mut dead_letters = dead_letters.write().await;
+                if !dead_letters.is_empty() {
+                    retry_dead_letters(&app_handle, &mut dead_letters);
+                }
+
+                // 发送事件（在缓冲区锁外部进行）
                 for event_data in events_to_send {
-                    if let Err(e) = app_handle.emit(&event_data.event, &event_data.payload) {
-                        eprintln!("❌ 定期flush时发送事件失败: {} - {}", event_data.event, e);
+                    if let Err(e) = emit_to_frontend(&app_handle, &event_data) {
+                        error!(
+                            "❌ 定期flush时发送事件失败，加入死信队列: {} - {}",
+                            event_data.event, e
+                        );
+                        push_dead_letter(&mut dead_letters, event_data, e);
                     } else {
-                        println!("⏰ 定期flush发送桥接事件: {} (延迟发送)", event_data.event);
+                        info!("⏰ 定期flush发送桥接事件: {} (延迟发送)", event_data.event);
                     }
                 }
             }
         });
     }
 }
+
+/// 记录不经过缓冲器直接发送的事件（如扫描状态），以便向新窗口重放
+pub fn record_app_event(app_handle: &AppHandle, event: &str, payload: serde_json::Value) {
+    event_trace::trace(app_handle, "monitor", event, "emitted", &payload);
+    if let Some(event_buffer) = app_handle.try_state::<Arc<EventBuffer>>() {
+        event_buffer.record_recent_event(&BridgeEventData {
+            event: event.to_string(),
+            payload,
+        });
+    }
+}
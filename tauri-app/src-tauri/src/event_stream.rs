@@ -0,0 +1,130 @@
+//! # 桥接事件通道 (Bridge Event Stream)
+//!
+//! 通过 SSE 订阅 Python API 的 `/events/stream` 端点接收桥接事件，并交给 `EventBuffer` 处理。
+//! 相比解析 stdout 中的 `EVENT_NOTIFY_JSON:` 前缀，不会因日志交错而截断事件，
+//! 外部API模式下也能收到事件。连接断开后按指数退避自动重连；
+//! 未连接期间 Python 会退回 stdout 发送事件，仍由 api_startup.rs 解析。
+
+use log::{error, info, warn};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use tauri::AppHandle;
+
+use crate::event_buffer::{BridgeEventData, EventBuffer};
+
+/// 首次重连等待时间
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// 重连等待时间上限
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// 解析一个 SSE 消息块，返回其中的桥接事件
+///
+/// 多行 `data:` 按规范用换行拼接，以 `:` 开头的注释行（心跳）会被忽略。
+fn parse_sse_message(block: &str) -> Option<BridgeEventData> {
+    let data: Vec<&str> = block
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|value| value.strip_prefix(' ').unwrap_or(value))
+        .collect();
+    if data.is_empty() {
+        return None;
+    }
+
+    let json = data.join("\n");
+    match serde_json::from_str::<BridgeEventData>(&json) {
+        Ok(event_data) => Some(event_data),
+        Err(e) => {
+            error!("[EVENT_STREAM] 解析事件失败: {} - 原始内容: {}", e, json);
+            None
+        }
+    }
+}
+
+/// 连接一次事件通道并持续读取，直到连接断开
+async fn consume_event_stream(
+    client: &reqwest::Client,
+    stream_url: &str,
+    event_buffer: &EventBuffer,
+) -> Result<(), String> {
+    let response = client
+        .get(stream_url)
+        .header("Accept", "text/event-stream")
+        .send()
+        .await
+        .map_err(|e| format!("连接失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("服务器返回状态码: {}", response.status()));
+    }
+    info!("[EVENT_STREAM] 已连接桥接事件通道: {}", stream_url);
+
+    let mut stream = response.bytes_stream();
+    // 按字节缓冲，避免多字节字符被拆在两个数据块之间时解码出错
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("读取事件流失败: {}", e))?;
+        buffer.extend(chunk.iter().filter(|&&byte| byte != b'\r'));
+
+        // SSE 消息以空行分隔
+        while let Some(pos) = buffer.windows(2).position(|window| window == b"\n\n") {
+            let block: Vec<u8> = buffer.drain(..pos + 2).collect();
+            if let Some(event_data) = parse_sse_message(&String::from_utf8_lossy(&block)) {
+                info!(
+                    "[EVENT_STREAM] 收到桥接事件: {} (通过缓冲器处理)",
+                    event_data.event
+                );
+                event_buffer.handle_raw_event(event_data).await;
+            }
+        }
+    }
+
+    Err("事件流已关闭".to_string())
+}
+
+/// 启动桥接事件通道，应在API首次就绪后调用
+pub fn start_event_stream(
+    app_handle: AppHandle,
+    api_state_mutex: Arc<Mutex<crate::ApiProcessState>>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let event_buffer = EventBuffer::shared(&app_handle);
+        // 事件流是长连接，只设置连接超时，不设置整体请求超时
+        let client = match crate::http_client::with_api_transport(
+            reqwest::Client::builder().connect_timeout(Duration::from_secs(5)),
+        )
+        .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                error!("[EVENT_STREAM] 创建HTTP客户端失败: {}", e);
+                return;
+            }
+        };
+        let mut reconnect_delay = INITIAL_RECONNECT_DELAY;
+
+        loop {
+            // 每次重连都重新读取地址，API重启后主机和端口可能变化
+            let stream_url = {
+                let api_state_guard = api_state_mutex.lock().unwrap();
+                format!(
+                    "http://{}:{}/events/stream",
+                    api_state_guard.host, api_state_guard.port
+                )
+            };
+
+            let started = tokio::time::Instant::now();
+            if let Err(e) = consume_event_stream(&client, &stream_url, &event_buffer).await {
+                warn!("[EVENT_STREAM] 桥接事件通道断开: {}", e);
+            }
+
+            // 连接保持过一段时间说明通道曾经可用，重置退避时间
+            if started.elapsed() > MAX_RECONNECT_DELAY {
+                reconnect_delay = INITIAL_RECONNECT_DELAY;
+            }
+            info!("[EVENT_STREAM] {:?} 后重新连接", reconnect_delay);
+            tokio::time::sleep(reconnect_delay).await;
+            reconnect_delay = (reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    });
+}
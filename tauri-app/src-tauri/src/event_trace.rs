@@ -0,0 +1,151 @@
+//! # 事件追踪 (Event Tracing)
+//!
+//! 排查"前端没收到通知"类问题时，需要知道事件是否到达 Rust、被如何缓冲以及最终是否发出。
+//! 开启追踪后，每个桥接事件和监控事件的处理决定（立即发送、合并、节流、死信、拒绝等）
+//! 都会带时间戳发送到 `debug-event-trace` 通道，并追加写入应用数据目录下的 event_trace.jsonl。
+//!
+//! 默认关闭，可通过 `set_event_tracing` 命令、settings.json 中的 `event_tracing_enabled`
+//! 或环境变量 `KNOWLEDGE_FOCUS_EVENT_TRACE=1` 开启。
+
+use log::{error, info};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+
+/// 追踪事件发送的前端通道
+const TRACE_CHANNEL: &str = "debug-event-trace";
+/// 追踪文件名
+const TRACE_FILE: &str = "event_trace.jsonl";
+/// settings.json 中保存追踪开关的键
+const TRACE_STORE_KEY: &str = "event_tracing_enabled";
+/// 开启追踪的环境变量
+const TRACE_ENV: &str = "KNOWLEDGE_FOCUS_EVENT_TRACE";
+
+static TRACE_ENABLED: AtomicBool = AtomicBool::new(false);
+static TRACE_WRITER: Mutex<Option<File>> = Mutex::new(None);
+
+fn trace_file_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join(TRACE_FILE))
+}
+
+/// 开启或关闭追踪，开启时打开追踪文件（追加写入）
+fn apply_enabled(app_handle: &AppHandle, enabled: bool) {
+    let mut writer = TRACE_WRITER.lock().unwrap();
+    if enabled {
+        if writer.is_none() {
+            *writer = trace_file_path(app_handle).and_then(|path| {
+                match OpenOptions::new().create(true).append(true).open(&path) {
+                    Ok(file) => {
+                        info!("[EVENT_TRACE] 事件追踪写入: {}", path.display());
+                        Some(file)
+                    }
+                    Err(e) => {
+                        error!("[EVENT_TRACE] 打开追踪文件失败 {}: {}", path.display(), e);
+                        None
+                    }
+                }
+            });
+        }
+    } else {
+        *writer = None;
+    }
+    TRACE_ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// 根据环境变量和 settings.json 初始化追踪开关，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let from_env = std::env::var(TRACE_ENV)
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    let from_store = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(TRACE_STORE_KEY))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    if from_env || from_store {
+        info!("[EVENT_TRACE] 事件追踪已开启");
+        apply_enabled(app_handle, true);
+    }
+}
+
+/// 追踪是否开启，调用方可据此避免无谓地复制payload
+pub fn is_enabled() -> bool {
+    TRACE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// 记录一条事件追踪
+///
+/// `source` 表示事件来源（bridge / monitor），`decision` 表示处理决定
+pub fn trace(
+    app_handle: &AppHandle,
+    source: &str,
+    event: &str,
+    decision: &str,
+    payload: &serde_json::Value,
+) {
+    if !is_enabled() {
+        return;
+    }
+    let record = serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "source": source,
+        "event": event,
+        "decision": decision,
+        "payload": payload
+    });
+
+    // 直接发送，不经过事件缓冲器，避免追踪本身被合并或节流
+    let _ = app_handle.emit(TRACE_CHANNEL, &record);
+    if let Some(file) = TRACE_WRITER.lock().unwrap().as_mut() {
+        if let Err(e) = writeln!(file, "{}", record) {
+            error!("[EVENT_TRACE] 写入追踪文件失败: {}", e);
+        }
+    }
+}
+
+/// 开启或关闭事件追踪，并保存到 settings.json
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_event_tracing(
+    enabled: bool,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    apply_enabled(&app_handle, enabled);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(TRACE_STORE_KEY, serde_json::json!(enabled));
+    store
+        .save()
+        .map_err(|e| format!("保存事件追踪设置失败: {}", e))?;
+
+    info!(
+        "[EVENT_TRACE] 事件追踪已{}",
+        if enabled { "开启" } else { "关闭" }
+    );
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("事件追踪已{}", if enabled { "开启" } else { "关闭" }),
+        "enabled": enabled,
+        "trace_file": trace_file_path(&app_handle).map(|path| path.to_string_lossy().to_string())
+    }))
+}
+
+/// 获取事件追踪状态
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_event_tracing_status(app_handle: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "enabled": is_enabled(),
+        "trace_file": trace_file_path(&app_handle).map(|path| path.to_string_lossy().to_string())
+    }))
+}
@@ -0,0 +1,157 @@
+//! # 扩展属性 (Extended Attributes)
+//!
+//! 读取浏览器和下载工具写入的下载来源信息，放入 FileMetadata 的 `extra_metadata`，
+//! 知识库据此记录文件"从哪里下载"：
+//! - macOS：`com.apple.metadata:kMDItemWhereFroms`（二进制 plist 字符串数组：下载地址、来源页面）
+//!   和 `com.apple.quarantine`（`标志;十六进制时间戳;下载程序;UUID`）
+//! - Linux：`user.xdg.origin.url` / `user.xdg.referrer.url`（Chrome、wget 等写入）
+//! - Windows：`Zone.Identifier` 备用数据流中的 `ZoneId`、`HostUrl`、`ReferrerUrl`
+//!
+//! 各平台的下载地址和来源页面统一写入 `download_url` / `referrer_url`，下载地址的主机名
+//! （小写，去掉 `www.`）写入 `download_host`，供按来源网站查找文件；
+//! 平台特有的原始信息（`where_froms`、`quarantine`、`zone_id`）原样保留。
+
+use serde_json::{Map, Value};
+use std::path::Path;
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use serde_json::{json, Map, Value};
+    use std::path::Path;
+
+    const WHERE_FROMS_ATTR: &str = "com.apple.metadata:kMDItemWhereFroms";
+    const QUARANTINE_ATTR: &str = "com.apple.quarantine";
+
+    /// 下载地址和来源页面，依次为下载地址、来源页面
+    fn where_froms(path: &Path) -> Option<Vec<String>> {
+        let data = xattr::get(path, WHERE_FROMS_ATTR).ok()??;
+        plist::from_bytes::<Vec<String>>(&data).ok()
+    }
+
+    /// 解析隔离标记：`0083;65a1b2c3;Safari;UUID`
+    fn quarantine(path: &Path) -> Option<Value> {
+        let data = xattr::get(path, QUARANTINE_ATTR).ok()??;
+        let value = String::from_utf8_lossy(&data);
+        let mut fields = value.trim_end_matches('\0').split(';');
+        let flags = fields.next()?.to_string();
+        let timestamp = fields.next().and_then(|t| i64::from_str_radix(t, 16).ok());
+        let agent = fields.next().filter(|agent| !agent.is_empty());
+        Some(json!({
+            "flags": flags,
+            "timestamp": timestamp,
+            "agent": agent
+        }))
+    }
+
+    pub fn read(path: &Path, attributes: &mut Map<String, Value>) {
+        if let Some(urls) = where_froms(path).filter(|urls| !urls.is_empty()) {
+            if let Some(download_url) = urls.first() {
+                attributes.insert("download_url".to_string(), json!(download_url));
+            }
+            if let Some(referrer_url) = urls.get(1) {
+                attributes.insert("referrer_url".to_string(), json!(referrer_url));
+            }
+            attributes.insert("where_froms".to_string(), json!(urls));
+        }
+        if let Some(quarantine) = quarantine(path) {
+            attributes.insert("quarantine".to_string(), quarantine);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use serde_json::{Map, Value};
+    use std::path::Path;
+
+    const URL_ATTRS: &[(&str, &str)] = &[
+        ("user.xdg.origin.url", "download_url"),
+        ("user.xdg.referrer.url", "referrer_url"),
+    ];
+
+    pub fn read(path: &Path, attributes: &mut Map<String, Value>) {
+        for (attr, key) in URL_ATTRS {
+            if let Ok(Some(data)) = xattr::get(path, attr) {
+                let url = String::from_utf8_lossy(&data)
+                    .trim_end_matches('\0')
+                    .to_string();
+                if !url.is_empty() {
+                    attributes.insert(key.to_string(), Value::String(url));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use serde_json::{json, Map, Value};
+    use std::path::Path;
+
+    /// 解析 `Zone.Identifier` 备用数据流：
+    /// ```text
+    /// [ZoneTransfer]
+    /// ZoneId=3
+    /// ReferrerUrl=https://example.com/
+    /// HostUrl=https://example.com/file.pdf
+    /// ```
+    pub fn read(path: &Path, attributes: &mut Map<String, Value>) {
+        let stream = format!("{}:Zone.Identifier", path.display());
+        let Ok(content) = std::fs::read_to_string(stream) else {
+            return;
+        };
+        for line in content.lines() {
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim() {
+                "ZoneId" => {
+                    if let Ok(zone_id) = value.parse::<i64>() {
+                        attributes.insert("zone_id".to_string(), json!(zone_id));
+                    }
+                }
+                "HostUrl" if !value.is_empty() => {
+                    attributes.insert("download_url".to_string(), json!(value));
+                }
+                "ReferrerUrl" if !value.is_empty() => {
+                    attributes.insert("referrer_url".to_string(), json!(value));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+mod platform {
+    use serde_json::{Map, Value};
+    use std::path::Path;
+
+    pub fn read(_path: &Path, _attributes: &mut Map<String, Value>) {}
+}
+
+/// 下载地址的主机名，`blob:`、`data:` 等没有主机名的地址返回 None
+fn download_host(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    Some(
+        host.strip_prefix("www.")
+            .map(str::to_string)
+            .unwrap_or(host),
+    )
+}
+
+/// 读取文件的下载来源扩展属性，没有时返回空集合
+pub fn read_provenance(path: &Path) -> Map<String, Value> {
+    let mut attributes = Map::new();
+    platform::read(path, &mut attributes);
+    let host = attributes
+        .get("download_url")
+        .and_then(Value::as_str)
+        .and_then(download_host);
+    if let Some(host) = host {
+        attributes.insert("download_host".to_string(), Value::String(host));
+    }
+    attributes
+}
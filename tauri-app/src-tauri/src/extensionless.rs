@@ -0,0 +1,118 @@
+//! # 无扩展名文件 (Extensionless Files)
+//!
+//! 很多笔记（`README`、`TODO`）和脚本（带 shebang 的可执行文件）没有扩展名，以前一律丢弃。
+//! 现在读取文件开头的内容判断：像文本的文件（没有 NUL 字节、是合法 UTF-8、不是已知的二进制格式）
+//! 按 `txt` 分类并索引，二进制文件仍然跳过。
+//!
+//! 策略保存在 settings.json 的 `extensionless_policy` 中：`index_text`（默认）/ `skip`（全部跳过）。
+
+use log::info;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+/// settings.json 中保存无扩展名文件策略的键
+const POLICY_STORE_KEY: &str = "extensionless_policy";
+/// 判断是否为文本时读取的字节数
+const SNIFF_BYTES: usize = 8192;
+/// 像文本的无扩展名文件按此扩展名分类
+pub const TEXT_EXTENSION: &str = "txt";
+
+/// 是否索引像文本的无扩展名文件，默认索引
+static INDEX_TEXT: AtomicBool = AtomicBool::new(true);
+
+fn policy_name(index_text: bool) -> &'static str {
+    if index_text {
+        "index_text"
+    } else {
+        "skip"
+    }
+}
+
+fn parse_policy(name: &str) -> Option<bool> {
+    match name {
+        "index_text" => Some(true),
+        "skip" => Some(false),
+        _ => None,
+    }
+}
+
+/// 文件开头的内容是否像文本
+fn is_text_like(bytes: &[u8]) -> bool {
+    if bytes.is_empty() || bytes.contains(&0) || infer::get(bytes).is_some() {
+        return false;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => true,
+        // 只是在读取边界截断了一个多字节字符
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// 无扩展名文件是否应当作文本索引（策略允许且内容像文本）
+pub fn is_indexable_text(path: &Path) -> bool {
+    if !INDEX_TEXT.load(Ordering::SeqCst) {
+        return false;
+    }
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut buffer = Vec::with_capacity(SNIFF_BYTES);
+    match file.take(SNIFF_BYTES as u64).read_to_end(&mut buffer) {
+        Ok(_) => is_text_like(&buffer),
+        Err(_) => false,
+    }
+}
+
+/// 当前是否索引像文本的无扩展名文件
+pub fn index_text_enabled() -> bool {
+    INDEX_TEXT.load(Ordering::SeqCst)
+}
+
+/// 恢复保存的无扩展名文件策略，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let saved = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(POLICY_STORE_KEY))
+        .and_then(|value| value.as_str().and_then(parse_policy));
+    if let Some(index_text) = saved {
+        INDEX_TEXT.store(index_text, Ordering::SeqCst);
+    }
+}
+
+/// 设置无扩展名文件策略：index_text（索引像文本的文件）或 skip（全部跳过）
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_extensionless_policy(
+    policy: String,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let index_text =
+        parse_policy(&policy).ok_or_else(|| format!("未知的无扩展名文件策略: {}", policy))?;
+    INDEX_TEXT.store(index_text, Ordering::SeqCst);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(POLICY_STORE_KEY, serde_json::json!(policy));
+    store
+        .save()
+        .map_err(|e| format!("保存无扩展名文件策略失败: {}", e))?;
+
+    info!("[EXTENSIONLESS] 无扩展名文件策略已设置为: {}", policy);
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("无扩展名文件策略已设置为 {}", policy)
+    }))
+}
+
+/// 获取当前无扩展名文件策略
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_extensionless_policy() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "policy": policy_name(INDEX_TEXT.load(Ordering::SeqCst))
+    }))
+}
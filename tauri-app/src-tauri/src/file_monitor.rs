@@ -10,17 +10,26 @@
 //! 注意：尽管模块名为"monitor"，但它实际上是整个文件处理系统的协调中心，
 //! 负责调用file_scanner模块来执行具体的文件操作，同时管理整个系统的配置和状态。
 
+use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue; // For extra_data in FileFilterRuleRust
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::Emitter;
 use tokio::fs;
 use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::watch;
 use tokio::time::sleep;
 use walkdir::WalkDir;
 
+// 批量请求体超过该大小（字节）时gzip压缩后发送
+const BATCH_COMPRESSION_THRESHOLD: usize = 64 * 1024;
+// 元数据通道容量：扫描和文件事件产生元数据的速度超过批处理器时，发送方在通道已满后等待
+pub const METADATA_CHANNEL_CAPACITY: usize = 1000;
+
 // --- Blacklist Trie for Hierarchical Blacklisting ---
 #[derive(Debug, Default, Clone)]
 struct BlacklistTrieNode {
@@ -32,6 +41,7 @@ impl BlacklistTrieNode {
     // Inserts a path into the Trie.
     // Paths are expected to be absolute and components UTF-8.
     fn insert(&mut self, path: &Path) {
+        let case_insensitive = crate::path_utils::is_case_insensitive(path);
         let mut current_node = self;
         // Handle the case where the root "/" itself is blacklisted.
         if path.components().count() == 1 && path.has_root() {
@@ -45,9 +55,11 @@ impl BlacklistTrieNode {
             match component {
                 std::path::Component::Normal(os_str) => {
                     if let Some(name) = os_str.to_str() {
-                        current_node = current_node.children.entry(name.to_string()).or_default();
+                        // 按 NFC 保存，不区分大小写的卷上再转为小写
+                        let key = crate::path_utils::comparable_component(name, case_insensitive);
+                        current_node = current_node.children.entry(key).or_default();
                     } else {
-                        eprintln!(
+                        warn!(
                             "[BLACKLIST_TRIE] Non-UTF8 path component in blacklist path: {:?}",
                             path
                         );
@@ -68,6 +80,7 @@ impl BlacklistTrieNode {
     // Checks if the given path or any of its ancestors are in the Trie and marked as blacklisted.
     // Path is assumed to be absolute.
     fn is_path_or_ancestor_blacklisted(&self, path: &Path) -> bool {
+        let case_insensitive = crate::path_utils::is_case_insensitive(path);
         let mut current_node = self;
 
         // Check if the root of the trie itself is blacklisted (e.g., if "/" was inserted).
@@ -79,7 +92,8 @@ impl BlacklistTrieNode {
             match component {
                 std::path::Component::Normal(os_str) => {
                     if let Some(name) = os_str.to_str() {
-                        if let Some(next_node) = current_node.children.get(name) {
+                        let key = crate::path_utils::comparable_component(name, case_insensitive);
+                        if let Some(next_node) = current_node.children.get(&key) {
                             if next_node.is_blacklisted_here {
                                 return true; // This path component or an ancestor forms a blacklisted path.
                             }
@@ -89,7 +103,7 @@ impl BlacklistTrieNode {
                             return false;
                         }
                     } else {
-                        eprintln!(
+                        warn!(
                             "[BLACKLIST_TRIE] Non-UTF8 path component in path to check: {:?}",
                             path
                         );
@@ -115,10 +129,67 @@ impl BlacklistTrieNode {
 // 文件监控统计信息
 #[derive(Debug, Default, Clone, Serialize)]
 pub struct MonitorStats {
-    pub processed_files: u64,  // 处理的文件数量
-    pub filtered_files: u64,   // 被过滤的文件数量
-    pub filtered_bundles: u64, // 处理的macOS包数量（改为只计数，不过滤）
-    pub error_count: u64,      // 处理错误次数
+    pub processed_files: u64,           // 处理的文件数量
+    pub filtered_files: u64,            // 被过滤的文件数量
+    pub filtered_bundles: u64,          // 处理的macOS包数量（改为只计数，不过滤）
+    pub error_count: u64,               // 处理错误次数
+    pub channel_full_waits: u64,        // 通道已满、发送方等待的次数
+    pub dropped_events: u64,            // 原始事件通道已满时丢弃的文件事件数（随后补扫所在目录）
+    pub debounce_overflow_flushes: u64, // 防抖缓冲区达到上限、提前发送的次数
+    pub spilled_records: u64,           // 扫描时通道已满、溢出到磁盘的元数据条数
+}
+
+// 批处理器中待发送的数据：批量模式下缓存元数据，流式模式下直接写入进行中的NDJSON请求
+#[derive(Default)]
+struct PendingBatch {
+    items: Vec<FileMetadata>,
+    // 文件路径 -> 在 items 中的位置，编辑器反复保存同一文件时只保留最新的元数据
+    positions: std::collections::HashMap<String, usize>,
+    stream: Option<crate::screening_upload::NdjsonUpload>,
+    // 已写入流式请求的文件，请求成功后记入已发送缓存和本地索引
+    streamed: Vec<crate::local_index::IndexedFile>,
+}
+
+impl PendingBatch {
+    // 缓存元数据，同一路径已在批次中时替换为新的元数据并返回 true
+    fn push_item(&mut self, metadata: FileMetadata) -> bool {
+        if let Some(&index) = self.positions.get(&metadata.file_path) {
+            self.items[index] = metadata;
+            return true;
+        }
+        self.positions
+            .insert(metadata.file_path.clone(), self.items.len());
+        self.items.push(metadata);
+        false
+    }
+
+    // 取出缓存的元数据
+    fn take_items(&mut self) -> Vec<FileMetadata> {
+        self.positions.clear();
+        std::mem::take(&mut self.items)
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+            + self
+                .stream
+                .as_ref()
+                .map_or(0, |stream| stream.record_count())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 是否应立即发送：批量模式按批量大小，流式请求按单个请求的记录数上限
+    fn is_full(&self, batch_size: usize) -> bool {
+        self.items.len() >= batch_size
+            || self
+                .stream
+                .as_ref()
+                .map_or(0, |stream| stream.record_count())
+                >= crate::screening_upload::MAX_RECORDS_PER_REQUEST
+    }
 }
 
 // 批处理器统计信息
@@ -132,6 +203,8 @@ struct BatchProcessorStats {
     directory_skipped: u64,           // 跳过的目录
     bundle_skipped: u64,              // 跳过的macOS bundle文件
     processed_files: u64,             // 实际处理的文件数
+    duplicates_merged: u64,           // 同一批次内重复路径合并的次数
+    unchanged_skipped: u64,           // 自上次成功发送后没有变化的文件
 }
 
 // --- New Configuration Structs ---
@@ -158,7 +231,8 @@ pub enum RuleTypeRust {
     OSBundle,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+// 变体按优先级从低到高声明，派生的 Ord 即为 Low < Medium < High
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RulePriorityRust {
     #[serde(alias = "low")]
     Low,
@@ -178,7 +252,7 @@ pub enum RuleActionRust {
     Label,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FileFilterRuleRust {
     pub id: i32,
     pub name: String,
@@ -194,7 +268,7 @@ pub struct FileFilterRuleRust {
     pub extra_data: Option<JsonValue>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FileExtensionMapRust {
     pub id: i32,
     pub extension: String, // Should be without dot, lowercase
@@ -268,17 +342,236 @@ pub struct MonitoredDirectory {
     pub is_blacklist: bool,
     pub created_at: Option<String>, // Added field
     pub updated_at: Option<String>, // Added field
+    #[serde(default)]
+    pub include_hidden: bool, // 是否索引该目录中的隐藏文件和隐藏目录（如 Obsidian 的 .obsidian）
+}
+
+impl MonitoredDirectory {
+    /// 展开 `~/` 后的目录路径
+    pub fn expanded_path(&self) -> PathBuf {
+        crate::path_utils::expand_home(&self.path)
+    }
+}
+
+// 预编译的过滤规则：构建配置快照时编译一次正则、通配符和组合条件，评估每个文件时直接使用
+#[derive(Debug)]
+pub struct CompiledRule {
+    pub rule: FileFilterRuleRust,
+    // pattern_type 为 regex 时的正则，模式无效时为 None（规则不匹配）
+    regex: Option<regex::Regex>,
+    // pattern_type 为 glob 时的匹配器，模式无效时为 None（规则不匹配）
+    glob: Option<globset::GlobMatcher>,
+    // extra_data.conditions 中的组合条件
+    conditions: Option<Result<crate::rule_conditions::Condition, String>>,
+}
+
+impl CompiledRule {
+    fn compile(rule: &FileFilterRuleRust) -> CompiledRule {
+        let regex = if rule.pattern_type == "regex" {
+            match regex::Regex::new(&rule.pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    error!(
+                        "[APPLY_RULES] Invalid regex pattern in rule '{}': {}",
+                        rule.name, e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let glob = if rule.pattern_type == "glob" {
+            FileMonitor::compile_glob_rule(rule)
+        } else {
+            None
+        };
+        let conditions = rule
+            .extra_data
+            .as_ref()
+            .and_then(|extra| extra.get("conditions"))
+            .map(crate::rule_conditions::Condition::parse);
+        if let Some(Err(e)) = &conditions {
+            warn!(
+                "[APPLY_RULES] Rule '{}' has invalid conditions: {}",
+                rule.name, e
+            );
+        }
+        CompiledRule {
+            rule: rule.clone(),
+            regex,
+            glob,
+            conditions,
+        }
+    }
+
+    // 启用的规则按优先级从高到低排序（同级按 ID 升序）并编译
+    fn compile_all(rules: &[FileFilterRuleRust]) -> Vec<CompiledRule> {
+        let mut enabled: Vec<&FileFilterRuleRust> =
+            rules.iter().filter(|rule| rule.enabled).collect();
+        enabled.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.id.cmp(&b.id)));
+        enabled.into_iter().map(CompiledRule::compile).collect()
+    }
+}
+
+// 配置快照：某一时刻的完整配置以及由它派生的数据，创建后不再修改。
+// 刷新配置时构建新快照并整体替换，扫描过程中持有同一个快照，不会看到更新到一半的配置
+#[derive(Debug)]
+pub struct ConfigSnapshot {
+    // 从 /config/all 获取并合并本地规则后的配置，尚未获取时为 None
+    pub config: Option<AllConfigurations>,
+    // 监控目录列表（用于监控）
+    pub monitored_dirs: Vec<MonitoredDirectory>,
+    // 黑名单目录列表（仅用于检查路径是否在黑名单中）
+    pub blacklist_dirs: Vec<MonitoredDirectory>,
+    // 当前生效的Bundle扩展名
+    pub bundle_extensions: Vec<String>,
+    // 扩展名白名单（小写），为空表示不按扩展名过滤
+    pub valid_extensions: std::collections::HashSet<String>,
+    // 由黑名单目录构建的层级黑名单
+    blacklist_trie: BlacklistTrieNode,
+    // 启用的过滤规则，按评估顺序排列并已预编译
+    pub filter_rules: Vec<CompiledRule>,
+}
+
+impl ConfigSnapshot {
+    // 尚未获取配置时的空快照
+    fn empty() -> ConfigSnapshot {
+        ConfigSnapshot {
+            config: None,
+            monitored_dirs: Vec::new(),
+            blacklist_dirs: Vec::new(),
+            bundle_extensions: Self::fallback_bundle_extensions(),
+            valid_extensions: std::collections::HashSet::new(),
+            blacklist_trie: BlacklistTrieNode::default(),
+            filter_rules: Vec::new(),
+        }
+    }
+
+    // 由配置构建快照：拆分监控目录和黑名单、构建黑名单Trie、提取Bundle扩展名和扩展名白名单
+    fn from_config(config: AllConfigurations) -> ConfigSnapshot {
+        let mut monitored_dirs = Vec::new();
+        let mut blacklist_dirs = Vec::new();
+        let mut blacklist_trie = BlacklistTrieNode::default();
+
+        for dir in &config.monitored_folders {
+            // 如果是黑名单文件夹，则添加到黑名单列表中
+            if dir.is_blacklist {
+                blacklist_dirs.push(dir.clone());
+                // 配置中的路径和解析链接后的真实路径各插入一次：链接只在加载配置时解析，
+                // 检查文件时无论经链接路径还是真实路径访问都能直接在Trie中匹配
+                let configured = crate::path_utils::normalize_path(&dir.expanded_path());
+                let real = crate::link_policy::real_path(&configured);
+                if real != configured {
+                    blacklist_trie.insert(&real);
+                }
+                blacklist_trie.insert(&configured);
+                debug!(
+                    "[CONFIG_FETCH] Added to blacklist (Vec & Trie): {} (真实路径: {:?})",
+                    dir.path, real
+                );
+                continue; // 黑名单文件夹不添加到监控列表
+            }
+            // 所有非黑名单文件夹都监控，不再检查授权状态
+            monitored_dirs.push(dir.clone());
+        }
+
+        let valid_extensions = config
+            .file_extension_maps
+            .iter()
+            .map(|map| map.extension.to_lowercase())
+            .collect();
+        let bundle_extensions = Self::bundle_extensions_of(&config);
+        let filter_rules = CompiledRule::compile_all(&config.file_filter_rules);
+
+        ConfigSnapshot {
+            config: Some(config),
+            monitored_dirs,
+            blacklist_dirs,
+            bundle_extensions,
+            valid_extensions,
+            blacklist_trie,
+            filter_rules,
+        }
+    }
+
+    // 默认Bundle扩展名列表
+    fn fallback_bundle_extensions() -> Vec<String> {
+        [
+            ".app",
+            ".bundle",
+            ".framework",
+            ".fcpbundle",
+            ".photoslibrary",
+            ".imovielibrary",
+            ".tvlibrary",
+            ".theater",
+            ".plugin",
+            ".component",
+            ".colorSync",
+            ".mdimporter",
+            ".qlgenerator",
+            ".saver",
+            ".service",
+            ".wdgt",
+            ".xpc",
+        ]
+        .iter()
+        .map(|ext| ext.to_string())
+        .collect()
+    }
+
+    // 从配置中提取Bundle扩展名列表
+    pub(crate) fn bundle_extensions_of(config: &AllConfigurations) -> Vec<String> {
+        // 1. 优先使用直接提供的 bundle_extensions 列表
+        if !config.bundle_extensions.is_empty() {
+            return config.bundle_extensions.clone();
+        }
+
+        // 2. 如果直接列表为空，从规则中提取（兼容旧版API）
+        let bundle_extensions: Vec<String> = config
+            .file_filter_rules
+            .iter()
+            .filter(|rule| rule.rule_type == RuleTypeRust::OSBundle && rule.enabled)
+            .filter_map(|rule| {
+                // 确保pattern是以点开头的扩展名格式
+                let pattern = &rule.pattern;
+                if pattern.starts_with('.') {
+                    Some(pattern.to_string())
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        if !bundle_extensions.is_empty() {
+            info!(
+                "[BUNDLE_EXT] 从规则配置中提取了 {} 个Bundle扩展名",
+                bundle_extensions.len()
+            );
+            return bundle_extensions;
+        }
+
+        // 如果没有从配置中获取到，使用默认列表
+        let fallback_extensions = Self::fallback_bundle_extensions();
+        info!(
+            "[BUNDLE_EXT] 使用默认Bundle扩展名列表，共 {} 项",
+            fallback_extensions.len()
+        );
+        fallback_extensions
+    }
+
+    // 检查路径或其上级目录是否在黑名单中
+    fn is_blacklisted(&self, path: &Path) -> bool {
+        self.blacklist_trie.is_path_or_ancestor_blacklisted(path)
+    }
 }
 
 // 初始化文件监控器
 #[derive(Clone)]
 pub struct FileMonitor {
-    // 监控目录列表（用于监控）
-    monitored_dirs: Arc<Mutex<Vec<MonitoredDirectory>>>,
-    // 黑名单目录列表（仅用于检查路径是否在黑名单中）
-    blacklist_dirs: Arc<Mutex<Vec<MonitoredDirectory>>>,
-    // 配置缓存（包含所有配置信息，如Bundle扩展名等）
-    config_cache: Arc<Mutex<Option<AllConfigurations>>>,
+    // 当前配置快照，刷新配置时整体替换
+    config_snapshot: Arc<RwLock<Arc<ConfigSnapshot>>>,
     // API主机和端口
     api_host: String,
     api_port: u16,
@@ -292,41 +585,122 @@ pub struct FileMonitor {
     batch_interval: Duration,
     // 监控统计数据
     stats: Arc<Mutex<MonitorStats>>,
-    // New field for hierarchical blacklist
-    blacklist_trie: Arc<Mutex<BlacklistTrieNode>>,
     // 添加状态标志位，防止重复处理
     is_batch_processor_running: Arc<Mutex<bool>>,
     is_initial_scan_running: Arc<Mutex<bool>>,
+    // 立即发送批处理的请求序号（退出前排空用）
+    batch_flush_request: Arc<watch::Sender<u64>>,
+    // 批处理器已完成的最新排空请求序号
+    batch_flush_ack: Arc<AtomicU64>,
+    // API是否接受gzip压缩的批量请求体（不支持的旧版API会在首次失败后关闭）
+    batch_compression: Arc<AtomicBool>,
 }
 
 impl FileMonitor {
     // 创建新的文件监控器实例
     pub fn new(api_host: String, api_port: u16) -> FileMonitor {
         FileMonitor {
-            monitored_dirs: Arc::new(Mutex::new(Vec::new())),
-            blacklist_dirs: Arc::new(Mutex::new(Vec::new())), // Still keep this for other potential uses or direct listing
-            config_cache: Arc::new(Mutex::new(None)),
+            config_snapshot: Arc::new(RwLock::new(Arc::new(ConfigSnapshot::empty()))),
             api_host,
             api_port,
-            client: reqwest::Client::builder()
-                .timeout(Duration::from_secs(30))
-                .build()
-                .expect("Failed to create HTTP client"),
+            client: crate::http_client::shared(),
             stats: Arc::new(Mutex::new(MonitorStats::default())),
             metadata_tx: None,
             batch_size: 50,
             batch_interval: Duration::from_secs(10),
-            blacklist_trie: Arc::new(Mutex::new(BlacklistTrieNode::default())), // Initialize Trie
             // 初始化状态标志位
             is_batch_processor_running: Arc::new(Mutex::new(false)),
             is_initial_scan_running: Arc::new(Mutex::new(false)),
+            batch_flush_request: Arc::new(watch::channel(0).0),
+            batch_flush_ack: Arc::new(AtomicU64::new(0)),
+            batch_compression: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// 请求批处理器接收完通道中已排队的元数据并立即发送，等待完成（用于应用退出前）
+    ///
+    /// 同步等待，不能在异步运行时的工作线程中调用。返回 true 表示已排空或批处理器未运行
+    pub fn drain_pending_batches(&self, timeout: Duration) -> bool {
+        if !*self.is_batch_processor_running.lock().unwrap() {
+            return true;
+        }
+
+        let request_id = *self.batch_flush_request.borrow() + 1;
+        self.batch_flush_request.send_replace(request_id);
+        info!("[BATCH_PROC] 请求排空批处理 (#{})", request_id);
+
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if self.batch_flush_ack.load(Ordering::SeqCst) >= request_id
+                || !*self.is_batch_processor_running.lock().unwrap()
+            {
+                info!("[BATCH_PROC] 批处理已排空");
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(50));
         }
+        warn!("[BATCH_PROC] 等待批处理排空超时 ({:?})", timeout);
+        false
+    }
+
+    /// 当前配置快照，持有期间不受配置刷新影响
+    pub fn snapshot(&self) -> Arc<ConfigSnapshot> {
+        self.config_snapshot.read().unwrap().clone()
+    }
+
+    // 整体替换配置快照，并刷新依赖配置的全局状态
+    fn replace_snapshot(&self, snapshot: ConfigSnapshot) {
+        let snapshot = Arc::new(snapshot);
+        let previous = std::mem::replace(
+            &mut *self.config_snapshot.write().unwrap(),
+            snapshot.clone(),
+        );
+        Self::on_snapshot_replaced(&previous, &snapshot);
+    }
+
+    // 基于当前配置修改出新的快照并替换（修改期间持有写锁，避免并发修改相互覆盖），
+    // 配置尚未加载时返回 None
+    fn update_config<R>(
+        &self,
+        update: impl FnOnce(&mut AllConfigurations) -> Result<R, String>,
+    ) -> Option<Result<R, String>> {
+        let mut guard = self.config_snapshot.write().unwrap();
+        let mut config = guard.config.clone()?;
+        let result = update(&mut config);
+        if result.is_ok() {
+            let previous =
+                std::mem::replace(&mut *guard, Arc::new(ConfigSnapshot::from_config(config)));
+            Self::on_snapshot_replaced(&previous, &guard);
+        }
+        Some(result)
+    }
+
+    fn on_snapshot_replaced(previous: &ConfigSnapshot, snapshot: &ConfigSnapshot) {
+        // 粗筛结果只取决于过滤规则和扩展名映射；只有它们变化时同一文件的结果才可能不同，
+        // 需要清空已发送签名重新发送，监控目录等其他配置变化不影响
+        let rules_changed = match (&previous.config, &snapshot.config) {
+            (Some(previous), Some(current)) => {
+                previous.file_filter_rules != current.file_filter_rules
+                    || previous.file_extension_maps != current.file_extension_maps
+            }
+            (None, None) => false,
+            _ => true,
+        };
+        if rules_changed {
+            crate::seen_cache::invalidate();
+            // Structure 规则作为项目标记
+            if let Some(config) = &snapshot.config {
+                crate::project_roots::update_rules(&config.file_filter_rules);
+            }
+            info!("[CONFIG_FETCH] 过滤规则或扩展名映射已变化，已发送文件的签名缓存已清空");
+        }
+        info!("[CONFIG_FETCH] Blacklist Trie rebuilt.");
     }
 
     // --- fetch all configurations ---
     async fn fetch_and_store_all_config(&self) -> Result<(), String> {
         let url = format!("http://{}:{}/config/all", self.api_host, self.api_port);
-        println!(
+        info!(
             "[CONFIG_FETCH] Fetching all configurations from URL: {}",
             url
         );
@@ -338,7 +712,7 @@ impl FileMonitor {
 
         while retry_count < max_retries {
             if retry_count > 0 {
-                println!(
+                info!(
                     "[CONFIG_FETCH] 重试获取配置 ({}/{})",
                     retry_count, max_retries
                 );
@@ -351,66 +725,21 @@ impl FileMonitor {
                 Ok(response) => {
                     if response.status().is_success() {
                         match response.json::<AllConfigurations>().await {
-                            Ok(config_data) => {
-                                println!("[CONFIG_FETCH] Successfully parsed AllConfigurations. Categories: {}, FilterRules: {}, ExtMaps: {}, MonitoredFolders: {}",
+                            Ok(mut config_data) => {
+                                // 合并用户的本地规则文件
+                                if let Err(e) = crate::local_rules::merge_into(&mut config_data) {
+                                    warn!("[CONFIG_FETCH] {}", e);
+                                }
+                                info!("[CONFIG_FETCH] Successfully parsed AllConfigurations. Categories: {}, FilterRules: {}, ExtMaps: {}, MonitoredFolders: {}",
                                     config_data.file_categories.len(),
                                     config_data.file_filter_rules.len(),
                                     config_data.file_extension_maps.len(),
                                     config_data.monitored_folders.len()
                                 );
-                                let mut cache = self.config_cache.lock().unwrap();
-                                *cache = Some(config_data.clone()); // Store all fetched config
-
-                                // 更新监控目录和黑名单目录列表
-                                let mut monitored_dirs_lock = self.monitored_dirs.lock().unwrap();
-                                let mut blacklist_dirs_lock = self.blacklist_dirs.lock().unwrap(); // 同时获取黑名单锁
-
-                                // 清空黑名单目录列表，准备重新填充
-                                blacklist_dirs_lock.clear();
-
-                                // --- Build Blacklist Trie ---
-                                let mut new_blacklist_trie = BlacklistTrieNode::default();
-                                // --- End of Build Blacklist Trie ---
-
-                                // 根据完全磁盘访问权限状态分类文件夹
-                                let mut authorized_folders = Vec::new();
-
-                                for dir in &config_data.monitored_folders {
-                                    // 如果是黑名单文件夹，则添加到黑名单列表中
-                                    if dir.is_blacklist {
-                                        blacklist_dirs_lock.push(dir.clone());
-                                        // Add to Trie
-                                        let blacklist_path = PathBuf::from(&dir.path);
-                                        // TODO: Ensure blacklist_path is absolute and normalized before inserting.
-                                        // Assuming paths from API are suitable for now.
-                                        new_blacklist_trie.insert(&blacklist_path);
-                                        println!(
-                                            "[CONFIG_FETCH] Added to blacklist (Vec & Trie): {}",
-                                            dir.path
-                                        );
-                                        continue; // 黑名单文件夹不添加到监控列表
-                                    }
-
-                                    // 对于非黑名单文件夹，直接添加到监控列表
-                                    let should_monitor = if config_data.full_disk_access {
-                                        true // 有完全访问权限时监控所有非黑名单文件夹
-                                    } else {
-                                        true // 现在不再检查授权状态，所有非黑名单文件夹都监控
-                                    };
-
-                                    if should_monitor {
-                                        authorized_folders.push(dir.clone());
-                                    }
-                                }
-
-                                *monitored_dirs_lock = authorized_folders;
-
-                                // Update the shared blacklist_trie
-                                *self.blacklist_trie.lock().unwrap() = new_blacklist_trie;
-                                println!("[CONFIG_FETCH] Blacklist Trie rebuilt.");
-
-                                println!("[CONFIG_FETCH] Updated monitored_dirs with {} entries and blacklist_dirs with {} entries from /config/all. (Full disk access: {})",
-                                    monitored_dirs_lock.len(), blacklist_dirs_lock.len(), config_data.full_disk_access);
+                                let snapshot = ConfigSnapshot::from_config(config_data);
+                                info!("[CONFIG_FETCH] Updated monitored_dirs with {} entries and blacklist_dirs with {} entries from /config/all. (Full disk access: {})",
+                                    snapshot.monitored_dirs.len(), snapshot.blacklist_dirs.len(), snapshot.config.as_ref().is_some_and(|config| config.full_disk_access));
+                                self.replace_snapshot(snapshot);
                                 return Ok(());
                             }
                             Err(e) => {
@@ -418,7 +747,7 @@ impl FileMonitor {
                                     "[CONFIG_FETCH] Failed to parse AllConfigurations JSON: {}",
                                     e
                                 );
-                                eprintln!("{}", last_error);
+                                error!("{}", last_error);
                             }
                         }
                     } else {
@@ -428,12 +757,12 @@ impl FileMonitor {
                             .await
                             .unwrap_or_else(|_| "Failed to read error response text".to_string());
                         last_error = format!("[CONFIG_FETCH] API request for /config/all failed with status: {}. Body: {}", status, err_text);
-                        eprintln!("{}", last_error);
+                        error!("{}", last_error);
                     }
                 }
                 Err(e) => {
                     last_error = format!("[CONFIG_FETCH] Failed to send request to {}: {}", url, e);
-                    eprintln!("{}", last_error);
+                    error!("{}", last_error);
                 }
             }
 
@@ -450,7 +779,7 @@ impl FileMonitor {
             "http://{}:{}/file-scanning-config",
             self.api_host, self.api_port
         );
-        println!(
+        info!(
             "[CONFIG_FETCH] Fetching simplified file scanning config from URL: {}",
             url
         );
@@ -461,10 +790,10 @@ impl FileMonitor {
                     match response.json::<FileScanningConfig>().await {
                         Ok(config) => {
                             if let Some(error) = &config.error_message {
-                                println!("[CONFIG_FETCH] API returned error: {}", error);
+                                error!("[CONFIG_FETCH] API returned error: {}", error);
                                 Err(format!("API error: {}", error))
                             } else {
-                                println!("[CONFIG_FETCH] Successfully parsed FileScanningConfig. Extensions: {}, Bundles: {}, Ignore patterns: {}, Categories: {}",
+                                info!("[CONFIG_FETCH] Successfully parsed FileScanningConfig. Extensions: {}, Bundles: {}, Ignore patterns: {}, Categories: {}",
                                     config.extension_mappings.len(),
                                     config.bundle_extensions.len(),
                                     config.ignore_patterns.len(),
@@ -476,20 +805,20 @@ impl FileMonitor {
                         Err(e) => {
                             let error_msg =
                                 format!("Failed to parse file scanning config JSON: {}", e);
-                            println!("[CONFIG_FETCH] {}", error_msg);
+                            error!("[CONFIG_FETCH] {}", error_msg);
                             Err(error_msg)
                         }
                     }
                 } else {
                     let error_msg =
                         format!("API request failed with status: {}", response.status());
-                    println!("[CONFIG_FETCH] {}", error_msg);
+                    error!("[CONFIG_FETCH] {}", error_msg);
                     Err(error_msg)
                 }
             }
             Err(e) => {
                 let error_msg = format!("Failed to send request: {}", e);
-                println!("[CONFIG_FETCH] {}", error_msg);
+                error!("[CONFIG_FETCH] {}", error_msg);
                 Err(error_msg)
             }
         }
@@ -498,8 +827,7 @@ impl FileMonitor {
 
     // 获取当前配置
     pub fn get_configurations(&self) -> Option<AllConfigurations> {
-        let config_guard = self.config_cache.lock().unwrap();
-        config_guard.clone()
+        self.snapshot().config.clone()
     }
 
     // 添加监控目录
@@ -510,21 +838,48 @@ impl FileMonitor {
 
     // 获取监控目录列表
     pub fn get_monitored_directories(&self) -> Vec<MonitoredDirectory> {
-        let dirs = self.monitored_dirs.lock().unwrap();
-        dirs.clone()
+        self.snapshot().monitored_dirs.clone()
     }
 
     /// 获取当前监控的目录列表
     pub fn get_monitored_dirs(&self) -> Vec<String> {
-        // 获取监控目录锁
-        let monitored_dirs_guard = self.monitored_dirs.lock().unwrap();
         // 转换MonitoredDirectory为String路径
-        monitored_dirs_guard
+        self.snapshot()
+            .monitored_dirs
             .iter()
             .map(|dir| dir.path.clone())
             .collect()
     }
 
+    /// 获取监控统计数据快照
+    pub fn get_stats(&self) -> MonitorStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// 更新监控统计数据（供防抖动监控器记录通道溢出）
+    pub fn update_stats(&self, update: impl FnOnce(&mut MonitorStats)) {
+        if let Ok(mut stats) = self.stats.lock() {
+            update(&mut stats);
+        }
+    }
+
+    /// 发送到有界通道：通道已满时记录一次等待，再等待接收方腾出空间；通道已关闭时返回 false
+    pub async fn send_with_backpressure<T>(&self, tx: &Sender<T>, value: T) -> bool {
+        match tx.try_send(value) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(value)) => {
+                self.update_stats(|stats| stats.channel_full_waits += 1);
+                tx.send(value).await.is_ok()
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// 初始扫描是否已经开始（扫描完成后仍为 true）
+    pub fn has_initial_scan_started(&self) -> bool {
+        *self.is_initial_scan_running.lock().unwrap()
+    }
+
     // 获取元数据发送通道
     pub fn get_metadata_sender(&self) -> Option<Sender<FileMetadata>> {
         // 克隆当前的metadata_tx通道（如果存在）
@@ -543,68 +898,144 @@ impl FileMonitor {
 
     // --- Bundle扩展名处理机制 ---
 
-    /// 从当前配置中提取Bundle扩展名列表
+    /// 当前配置中的Bundle扩展名列表
     pub fn extract_bundle_extensions(&self) -> Vec<String> {
-        let fallback_extensions = vec![
-            ".app".to_string(),
-            ".bundle".to_string(),
-            ".framework".to_string(),
-            ".fcpbundle".to_string(),
-            ".photoslibrary".to_string(),
-            ".imovielibrary".to_string(),
-            ".tvlibrary".to_string(),
-            ".theater".to_string(),
-            ".plugin".to_string(),
-            ".component".to_string(),
-            ".colorSync".to_string(),
-            ".mdimporter".to_string(),
-            ".qlgenerator".to_string(),
-            ".saver".to_string(),
-            ".service".to_string(),
-            ".wdgt".to_string(),
-            ".xpc".to_string(),
-        ];
+        self.snapshot().bundle_extensions.clone()
+    }
 
-        // 尝试从配置缓存中获取bundle扩展名
-        let config_guard = self.config_cache.lock().unwrap();
-        if let Some(config) = config_guard.as_ref() {
-            // 1. 优先使用直接提供的 bundle_extensions 列表
-            if !config.bundle_extensions.is_empty() {
-                // println!("[BUNDLE_EXT] 使用/config/all中直接提供的 {} 个Bundle扩展名", config.bundle_extensions.len());
-                return config.bundle_extensions.clone();
+    /// 规范化Bundle扩展名：去掉空白、转为小写并补上前导的点，如 `LogicX` -> `.logicx`
+    fn normalize_bundle_extension(extension: &str) -> Result<String, String> {
+        let name = extension.trim().trim_start_matches('.').to_lowercase();
+        if name.is_empty() || name.contains(['.', '/', '\\']) {
+            return Err(format!("无效的Bundle扩展名: {}", extension));
+        }
+        Ok(format!(".{}", name))
+    }
+
+    /// 修改缓存配置中的Bundle扩展名列表，列表为空（旧版API）时先填入当前生效的扩展名
+    fn update_cached_bundle_extensions(&self, update: impl FnOnce(&mut Vec<String>)) {
+        let current = self.extract_bundle_extensions();
+        self.update_config(|config| {
+            if config.bundle_extensions.is_empty() {
+                config.bundle_extensions = current;
             }
+            update(&mut config.bundle_extensions);
+            Ok(())
+        });
+    }
 
-            // 2. 如果直接列表为空，从规则中提取（兼容旧版API）
-            let bundle_extensions: Vec<String> = config
-                .file_filter_rules
-                .iter()
-                .filter(|rule| rule.rule_type == RuleTypeRust::OSBundle && rule.enabled)
-                .filter_map(|rule| {
-                    // 确保pattern是以点开头的扩展名格式
-                    let pattern = &rule.pattern;
-                    if pattern.starts_with('.') {
-                        Some(pattern.to_string())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+    /// 添加自定义Bundle扩展名：立即加入缓存的扩展名列表，保存到本地规则文件并同步到API，
+    /// 返回是否已同步到API（API离线时本地规则仍会在下次获取配置时合并）
+    pub async fn add_custom_bundle_extension(&self, extension: &str) -> Result<bool, String> {
+        let extension = Self::normalize_bundle_extension(extension)?;
+        crate::local_rules::add_bundle_extension(&extension)?;
+        self.update_cached_bundle_extensions(|extensions| {
+            if !extensions.contains(&extension) {
+                extensions.push(extension.clone());
+            }
+        });
+        info!("[BUNDLE_EXT] 已添加自定义Bundle扩展名: {}", extension);
 
-            if !bundle_extensions.is_empty() {
-                println!(
-                    "[BUNDLE_EXT] 从规则配置中提取了 {} 个Bundle扩展名",
-                    bundle_extensions.len()
+        let url = format!(
+            "http://{}:{}/bundle-extensions",
+            self.api_host, self.api_port
+        );
+        let body = serde_json::json!({
+            "extension": extension,
+            "description": "用户自定义的Bundle扩展名"
+        });
+        match self
+            .send_bundle_extension_request(self.client.post(&url).json(&body))
+            .await
+        {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                warn!(
+                    "[BUNDLE_EXT] 同步Bundle扩展名 {} 到API失败: {}",
+                    extension, e
                 );
-                return bundle_extensions;
+                Ok(false)
             }
         }
+    }
 
-        // 如果没有从配置中获取到，使用默认列表
-        println!(
-            "[BUNDLE_EXT] 使用默认Bundle扩展名列表，共 {} 项",
-            fallback_extensions.len()
+    /// 移除自定义Bundle扩展名：从缓存的扩展名列表和本地规则文件中移除，并在API中停用，
+    /// 返回是否已同步到API
+    pub async fn remove_custom_bundle_extension(&self, extension: &str) -> Result<bool, String> {
+        let extension = Self::normalize_bundle_extension(extension)?;
+        crate::local_rules::remove_bundle_extension(&extension)?;
+        self.update_cached_bundle_extensions(|extensions| {
+            extensions.retain(|existing| !existing.eq_ignore_ascii_case(&extension))
+        });
+        info!("[BUNDLE_EXT] 已移除自定义Bundle扩展名: {}", extension);
+
+        match self.remove_bundle_extension_from_api(&extension).await {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                warn!(
+                    "[BUNDLE_EXT] 从API移除Bundle扩展名 {} 失败: {}",
+                    extension, e
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    /// 在API中查找扩展名对应的记录并停用，API中没有该扩展名时视为成功
+    async fn remove_bundle_extension_from_api(&self, extension: &str) -> Result<(), String> {
+        let url = format!(
+            "http://{}:{}/bundle-extensions",
+            self.api_host, self.api_port
         );
-        fallback_extensions
+        let response: JsonValue = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        let ext_id = response
+            .get("data")
+            .and_then(|data| data.as_array())
+            .into_iter()
+            .flatten()
+            .find(|item| {
+                item.get("extension")
+                    .and_then(|value| value.as_str())
+                    .is_some_and(|value| value.eq_ignore_ascii_case(extension))
+            })
+            .and_then(|item| item.get("id"))
+            .and_then(|id| id.as_i64());
+        let Some(ext_id) = ext_id else {
+            return Ok(());
+        };
+        self.send_bundle_extension_request(self.client.delete(format!("{}/{}", url, ext_id)))
+            .await
+    }
+
+    /// 发送Bundle扩展名管理请求，API返回 `status: error` 时转为错误
+    async fn send_bundle_extension_request(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<(), String> {
+        let response: JsonValue = request
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.get("status").and_then(|status| status.as_str()) == Some("success") {
+            Ok(())
+        } else {
+            Err(response
+                .get("message")
+                .and_then(|message| message.as_str())
+                .unwrap_or("未知错误")
+                .to_string())
+        }
     }
 
     // --- End of Bundle扩展名处理机制 ---
@@ -613,14 +1044,12 @@ impl FileMonitor {
 
     /// 刷新文件夹配置（重新获取监控目录和黑名单）
     pub async fn refresh_folder_configuration(&self) -> Result<bool, String> {
-        println!("[FILE_MONITOR] 开始刷新文件夹配置...");
+        info!("[FILE_MONITOR] 开始刷新文件夹配置...");
 
         // 保存当前配置的快照
-        let current_monitored_dirs = self.get_monitored_dirs();
-        let current_blacklist_dirs = {
-            let blacklist_guard = self.blacklist_dirs.lock().unwrap();
-            blacklist_guard.clone()
-        };
+        let current = self.snapshot();
+        let current_monitored_dirs = &current.monitored_dirs;
+        let current_blacklist_dirs = &current.blacklist_dirs;
 
         // 从API重新获取配置
         if let Err(e) = self.fetch_and_store_all_config().await {
@@ -628,11 +1057,9 @@ impl FileMonitor {
         }
 
         // 检查配置是否变化
-        let new_monitored_dirs = self.get_monitored_dirs();
-        let new_blacklist_dirs = {
-            let blacklist_guard = self.blacklist_dirs.lock().unwrap();
-            blacklist_guard.clone()
-        };
+        let new = self.snapshot();
+        let new_monitored_dirs = &new.monitored_dirs;
+        let new_blacklist_dirs = &new.blacklist_dirs;
 
         // 对比变化
         let monitored_changed = current_monitored_dirs.len() != new_monitored_dirs.len()
@@ -648,16 +1075,16 @@ impl FileMonitor {
         let config_changed = monitored_changed || blacklist_changed;
 
         if config_changed {
-            println!("[FILE_MONITOR] 文件夹配置已更新:");
+            info!("[FILE_MONITOR] 文件夹配置已更新:");
             if monitored_changed {
-                println!(
+                info!(
                     "[FILE_MONITOR]   - 监控目录: {} -> {}",
                     current_monitored_dirs.len(),
                     new_monitored_dirs.len()
                 );
             }
             if blacklist_changed {
-                println!(
+                info!(
                     "[FILE_MONITOR]   - 黑名单目录: {} -> {}",
                     current_blacklist_dirs.len(),
                     new_blacklist_dirs.len()
@@ -665,22 +1092,22 @@ impl FileMonitor {
             }
             Ok(true)
         } else {
-            println!("[FILE_MONITOR] 文件夹配置未变化");
+            info!("[FILE_MONITOR] 文件夹配置未变化");
             Ok(false)
         }
     }
 
     /// 刷新所有配置（通过单一API调用获取所有配置）
     pub async fn refresh_all_configurations(&self) -> Result<(), String> {
-        println!("[CONFIG_REFRESH_ALL] 开始刷新所有配置...");
+        info!("[CONFIG_REFRESH_ALL] 开始刷新所有配置...");
 
         // 刷新文件夹配置（包含所有配置数据，包括Bundle扩展名）
         if let Err(e) = self.refresh_folder_configuration().await {
-            eprintln!("[CONFIG_REFRESH_ALL] 配置刷新失败: {}", e);
+            error!("[CONFIG_REFRESH_ALL] 配置刷新失败: {}", e);
             return Err(e);
         }
 
-        println!("[CONFIG_REFRESH_ALL] 所有配置刷新成功");
+        info!("[CONFIG_REFRESH_ALL] 所有配置刷新成功");
 
         // 配置刷新完成后，触发配置更新事件通知所有监听器
         self.notify_config_updated();
@@ -691,25 +1118,260 @@ impl FileMonitor {
     fn notify_config_updated(&self) {
         // 这里可以实现实际的配置更新通知机制
         // 目前只是记录日志，将来可以添加实际的通知逻辑
-        println!("[CONFIG_NOTIFY] 配置已成功更新，后续扫描将使用新配置");
+        info!("[CONFIG_NOTIFY] 配置已成功更新，后续扫描将使用新配置");
     }
 
-    /// 获取当前配置状态摘要
-    pub fn get_configuration_summary(&self) -> serde_json::Value {
-        let config_guard = self.config_cache.lock().unwrap();
-        let monitored_dirs = self.monitored_dirs.lock().unwrap();
-        let blacklist_dirs = self.blacklist_dirs.lock().unwrap();
+    /// 重新读取本地规则文件并合并到当前配置（不访问 API），返回合并的过滤规则数和扩展名映射数
+    pub fn reload_local_rules(&self) -> Result<(usize, usize), String> {
+        let merged = self
+            .update_config(crate::local_rules::merge_into)
+            .ok_or_else(|| "配置尚未加载，无法合并本地规则".to_string())??;
+        info!(
+            "[LOCAL_RULES] 已重新加载本地规则: {} 条过滤规则, {} 条扩展名映射",
+            merged.0, merged.1
+        );
+        Ok(merged)
+    }
 
-        // 从配置中提取Bundle扩展名数量
-        let bundle_extensions_count = config_guard
+    /// 规则试运行：抽样遍历目录中最多 `sample_limit` 个文件，用当前配置评估所有规则，
+    /// 返回每条规则匹配和排除的文件数，不向 API 发送任何数据，也不计入监控统计
+    pub async fn audit_rules(
+        &self,
+        directory: &str,
+        sample_limit: usize,
+    ) -> Result<serde_json::Value, String> {
+        // 报告中附带的文件示例数量
+        const EXAMPLE_LIMIT: usize = 50;
+
+        let snapshot = self.snapshot();
+        let config = snapshot
+            .config
             .as_ref()
-            .map(|c| {
-                c.file_filter_rules
-                    .iter()
-                    .filter(|rule| rule.rule_type == RuleTypeRust::OSBundle && rule.enabled)
-                    .count()
+            .ok_or_else(|| "配置尚未加载，无法评估规则".to_string())?;
+        let root = crate::path_utils::normalize_path(Path::new(directory));
+        if !root.is_dir() {
+            return Err(format!("目录不存在: {}", directory));
+        }
+
+        // 规则名 -> (匹配的文件数, 被该规则排除的文件数)
+        let mut rule_counts: std::collections::HashMap<String, (u64, u64)> =
+            std::collections::HashMap::new();
+        let mut sampled = 0usize;
+        let mut excluded = 0u64;
+        let mut examples = Vec::new();
+        let mut truncated = false;
+
+        let mut visited_dirs = crate::link_policy::VisitedDirs::new();
+        let walk_dir = WalkDir::new(&root).follow_links(crate::link_policy::follow_links());
+        let walker = walk_dir.into_iter().filter_entry(|e| {
+            visited_dirs.should_walk(e)
+                && !self.is_in_blacklist(e.path())
+                && !Self::is_macos_bundle_folder(e.path())
+        });
+        for entry in walker.filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            if sampled >= sample_limit {
+                truncated = true;
+                break;
+            }
+            let Some(mut metadata) = Self::get_file_metadata(entry.path()).await else {
+                continue;
+            };
+            sampled += 1;
+            Self::evaluate_rules(config, &snapshot.filter_rules, &mut metadata);
+
+            let excluded_by = metadata
+                .extra_metadata
+                .as_ref()
+                .and_then(|extra| extra.get("excluded_by_rule_name"))
+                .and_then(|name| name.as_str())
+                .map(str::to_string);
+            for rule_name in metadata.initial_rule_matches.iter().flatten() {
+                rule_counts.entry(rule_name.clone()).or_default().0 += 1;
+            }
+            if let Some(rule_name) = &excluded_by {
+                excluded += 1;
+                rule_counts.entry(rule_name.clone()).or_default().1 += 1;
+            }
+            if examples.len() < EXAMPLE_LIMIT {
+                examples.push(serde_json::json!({
+                    "file_path": metadata.file_path,
+                    "excluded_by": excluded_by,
+                    "matched_rules": metadata.initial_rule_matches,
+                    "category_id": metadata.category_id,
+                    "labels": metadata.labels,
+                }));
+            }
+        }
+
+        let mut rules: Vec<serde_json::Value> = rule_counts
+            .into_iter()
+            .map(|(rule_name, (matched, excluded))| {
+                serde_json::json!({
+                    "rule_name": rule_name,
+                    "matched": matched,
+                    "excluded": excluded,
+                })
             })
-            .unwrap_or(0);
+            .collect();
+        rules.sort_by(|a, b| b["matched"].as_u64().cmp(&a["matched"].as_u64()));
+
+        info!(
+            "[AUDIT_RULES] {:?}: 抽样 {} 个文件，保留 {}，排除 {}",
+            root,
+            sampled,
+            sampled as u64 - excluded,
+            excluded
+        );
+        Ok(serde_json::json!({
+            "directory": crate::path_utils::path_to_nfc_string(&root),
+            "sampled_files": sampled,
+            "truncated": truncated,
+            "kept": sampled as u64 - excluded,
+            "excluded": excluded,
+            "rules": rules,
+            "examples": examples,
+        }))
+    }
+
+    /// 扫描基准测试：对目录中最多 `sample_limit` 个文件依次执行遍历、读取元数据、计算哈希、
+    /// 评估规则和序列化，分别计时，用于在用户机器上量化不同版本间的性能变化。
+    /// 不向 API 发送任何数据，也不计入监控统计
+    pub async fn run_scan_benchmark(
+        &self,
+        directory: &str,
+        sample_limit: usize,
+    ) -> Result<serde_json::Value, String> {
+        let snapshot = self.snapshot();
+        let config = snapshot
+            .config
+            .as_ref()
+            .ok_or_else(|| "配置尚未加载，无法评估规则".to_string())?;
+        let root = crate::path_utils::normalize_path(Path::new(directory));
+        if !root.is_dir() {
+            return Err(format!("目录不存在: {}", directory));
+        }
+
+        // 单个阶段的耗时和吞吐量
+        let phase = |elapsed: Duration, items: usize, bytes: Option<u64>| {
+            let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+            let mut result = serde_json::json!({
+                "elapsed_ms": elapsed.as_secs_f64() * 1000.0,
+                "items": items,
+                "items_per_sec": items as f64 / secs,
+            });
+            if let Some(bytes) = bytes {
+                result["bytes"] = serde_json::json!(bytes);
+                result["bytes_per_sec"] = serde_json::json!(bytes as f64 / secs);
+            }
+            result
+        };
+
+        // 1. 遍历目录
+        let started = Instant::now();
+        let mut visited_dirs = crate::link_policy::VisitedDirs::new();
+        let mut paths = Vec::new();
+        let mut truncated = false;
+        let walk_dir = WalkDir::new(&root).follow_links(crate::link_policy::follow_links());
+        let walker = walk_dir.into_iter().filter_entry(|e| {
+            visited_dirs.should_walk(e)
+                && !self.is_in_blacklist(e.path())
+                && !Self::is_macos_bundle_folder(e.path())
+        });
+        for entry in walker.filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            if paths.len() >= sample_limit {
+                truncated = true;
+                break;
+            }
+            paths.push(entry.into_path());
+        }
+        let walk = phase(started.elapsed(), paths.len(), None);
+
+        // 2. 读取元数据（含扩展属性、内容类型识别和编程语言检测）
+        let started = Instant::now();
+        let mut files = Vec::with_capacity(paths.len());
+        for path in &paths {
+            if let Some(metadata) = Self::get_file_metadata(path).await {
+                files.push(metadata);
+            }
+        }
+        let metadata = phase(started.elapsed(), files.len(), None);
+
+        // 3. 计算哈希（与扫描相同，只读取文件开头 4KB）
+        let started = Instant::now();
+        let mut hashed_bytes = 0u64;
+        for file in &mut files {
+            file.hash_value = Self::calculate_simple_hash(Path::new(&file.file_path), 4096).await;
+            if file.hash_value.is_some() {
+                hashed_bytes += file.file_size.min(4096);
+            }
+        }
+        let hashing = phase(started.elapsed(), files.len(), Some(hashed_bytes));
+
+        // 4. 评估规则
+        let started = Instant::now();
+        let mut excluded = 0usize;
+        for file in &mut files {
+            if Self::evaluate_rules(config, &snapshot.filter_rules, file) {
+                excluded += 1;
+            }
+        }
+        let rules = phase(started.elapsed(), files.len(), None);
+
+        // 5. 按批次序列化为发送到 API 的请求体
+        let started = Instant::now();
+        let mut serialized_bytes = 0u64;
+        for chunk in files.chunks(self.batch_size.max(1)) {
+            let body = serde_json::to_vec(&serde_json::json!({
+                "data_list": chunk,
+                "auto_create_tasks": true,
+            }))
+            .map_err(|e| format!("Failed to serialize metadata batch: {}", e))?;
+            serialized_bytes += body.len() as u64;
+        }
+        let serialization = phase(started.elapsed(), files.len(), Some(serialized_bytes));
+
+        info!(
+            "[BENCHMARK] {:?}: {} 个文件，遍历 {:.1}ms，元数据 {:.1}ms，哈希 {:.1}ms，规则 {:.1}ms，序列化 {:.1}ms",
+            root,
+            files.len(),
+            walk["elapsed_ms"].as_f64().unwrap_or_default(),
+            metadata["elapsed_ms"].as_f64().unwrap_or_default(),
+            hashing["elapsed_ms"].as_f64().unwrap_or_default(),
+            rules["elapsed_ms"].as_f64().unwrap_or_default(),
+            serialization["elapsed_ms"].as_f64().unwrap_or_default()
+        );
+        Ok(serde_json::json!({
+            "directory": crate::path_utils::path_to_nfc_string(&root),
+            "version": env!("CARGO_PKG_VERSION"),
+            "watcher_backend": crate::file_monitor_debounced::watcher_backend(),
+            "sampled_files": files.len(),
+            "truncated": truncated,
+            "excluded": excluded,
+            "phases": {
+                "walk": walk,
+                "metadata": metadata,
+                "hashing": hashing,
+                "rules": rules,
+                "serialization": serialization,
+            },
+        }))
+    }
+
+    /// 获取当前配置状态摘要
+    pub fn get_configuration_summary(&self) -> serde_json::Value {
+        let snapshot = self.snapshot();
+        let config_guard = &snapshot.config;
+        let monitored_dirs = &snapshot.monitored_dirs;
+        let blacklist_dirs = &snapshot.blacklist_dirs;
+
+        // 当前生效的Bundle扩展名数量
+        let bundle_extensions_count = snapshot.bundle_extensions.len();
 
         // 获取当前时间，用于显示配置时间戳
         let current_timestamp = SystemTime::now()
@@ -726,38 +1388,59 @@ impl FileMonitor {
             "monitored_dirs_count": monitored_dirs.len(),
             "blacklist_dirs_count": blacklist_dirs.len(),
             "bundle_extensions_count": bundle_extensions_count,
+            "watcher_backend": crate::file_monitor_debounced::watcher_backend(),
             "timestamp": current_timestamp
         })
     }
 
     // --- End of 配置刷新机制 ---
 
-    // 计算简单文件哈希（使用文件前4KB内容）
+    // 计算简单文件哈希（使用文件前4KB内容），在哈希线程池中执行，不占用tokio工作线程
     async fn calculate_simple_hash(path: &Path, max_bytes: usize) -> Option<String> {
-        match fs::File::open(path).await {
-            Ok(mut file) => {
-                use tokio::io::AsyncReadExt;
-                let mut buffer = vec![0u8; max_bytes.min(4096)]; // 最多读4KB
-                match file.read(&mut buffer).await {
-                    Ok(n) => {
-                        buffer.truncate(n);
-                        if n > 0 {
-                            use sha2::{Digest, Sha256};
-                            let mut hasher = Sha256::new();
-                            hasher.update(&buffer);
-                            let result = hasher.finalize();
-                            Some(format!("{:x}", result))
-                        } else {
-                            None
-                        }
-                    }
-                    Err(_) => None,
-                }
-            }
-            Err(_) => None,
+        // 最多读4KB
+        crate::hash_pool::hash_prefix(path.to_path_buf(), max_bytes.min(4096)).await
+    }
+
+    // 读取文件开头的字节，按魔数识别文件类型（扩展名缺失或与内容不符时用于分类）
+    async fn sniff_content_type(path: &Path) -> Option<infer::Type> {
+        use tokio::io::AsyncReadExt;
+        let mut file = fs::File::open(path).await.ok()?;
+        let mut buffer = vec![0u8; 8192];
+        let n = file.read(&mut buffer).await.ok()?;
+        infer::get(&buffer[..n])
+    }
+
+    // 分类置信度：基础分由优先级决定（High 0.9 / Medium 0.75 / Low 0.6），再按匹配方式扣分：
+    // 扩展名、项目结构和 Bundle 是精确匹配不扣分，regex / glob 扣 0.1，keyword 子串匹配容易误中扣 0.2
+    fn category_confidence(priority: &RulePriorityRust, match_deduction: f64) -> f64 {
+        let base = match priority {
+            RulePriorityRust::High => 0.9,
+            RulePriorityRust::Medium => 0.75,
+            RulePriorityRust::Low => 0.6,
+        };
+        ((base - match_deduction) * 100.0).round() / 100.0
+    }
+
+    fn rule_match_deduction(filter_rule: &FileFilterRuleRust) -> f64 {
+        match filter_rule.rule_type {
+            RuleTypeRust::Extension | RuleTypeRust::Structure | RuleTypeRust::OSBundle => 0.0,
+            _ if filter_rule.pattern_type == "keyword" => 0.2,
+            _ => 0.1,
         }
     }
 
+    // 最适用于某扩展名的映射：同一扩展名有多条映射时取优先级最高的（同级取 ID 最小的）
+    fn best_extension_map<'a>(
+        config: &'a AllConfigurations,
+        ext: &str,
+    ) -> Option<&'a FileExtensionMapRust> {
+        config
+            .file_extension_maps
+            .iter()
+            .filter(|ext_map_rule| ext_map_rule.extension == ext)
+            .min_by(|a, b| b.priority.cmp(&a.priority).then(a.id.cmp(&b.id)))
+    }
+
     // 提取文件扩展名
     fn extract_extension(path: &Path) -> Option<String> {
         path.extension()
@@ -765,40 +1448,51 @@ impl FileMonitor {
             .map(|s| s.to_lowercase())
     }
 
-    // 检查文件是否隐藏
+    // 检查文件是否隐藏（以.开头，或Windows上带有隐藏/系统属性）
     fn is_hidden_file(path: &Path) -> bool {
-        // 先检查文件/文件夹名本身是否以.开头
-        let is_name_hidden = path
-            .file_name()
-            .and_then(|name| name.to_str())
-            .map(|name| name.starts_with("."))
-            .unwrap_or(false);
-
-        if is_name_hidden {
-            return true;
-        }
+        crate::path_utils::is_hidden(path)
+    }
 
-        // 检查路径中是否有任何部分是隐藏文件夹（以.开头）
-        if let Some(path_str) = path.to_str() {
-            // 分割路径并检查每个部分
-            for part in path_str.split('/') {
-                if !part.is_empty() && part.starts_with(".") && part != "." && part != ".." {
-                    return true;
-                }
+    // 初始扫描的遍历顺序：同一目录下先处理文件，再按修改时间从新到旧进入子目录，
+    // 最近有改动的目录先被扫描，"最近文件"在首次启动后很快就有内容。
+    // 只比较子目录之间的修改时间，文件之间不额外读取元数据
+    fn recency_first(a: &walkdir::DirEntry, b: &walkdir::DirEntry) -> std::cmp::Ordering {
+        match (a.file_type().is_dir(), b.file_type().is_dir()) {
+            (false, true) => std::cmp::Ordering::Less,
+            (true, false) => std::cmp::Ordering::Greater,
+            (false, false) => std::cmp::Ordering::Equal,
+            (true, true) => {
+                let modified = |entry: &walkdir::DirEntry| {
+                    entry.metadata().ok().and_then(|m| m.modified().ok())
+                };
+                // 读取不到修改时间的目录排在最后
+                modified(b).cmp(&modified(a))
             }
         }
+    }
 
-        false
+    // 路径所在的监控目录是否开启了隐藏文件索引（include_hidden）
+    fn hidden_files_allowed(dirs: &[MonitoredDirectory], path: &Path) -> bool {
+        dirs.iter().any(|dir| {
+            dir.include_hidden
+                && !dir.is_blacklist
+                && crate::path_utils::path_starts_with(path, &dir.expanded_path())
+        })
     }
 
     // 检查是否为macOS bundle文件夹
-    /// 静态方法：检查是否为macOS bundle文件夹（使用默认扩展名列表）
+    /// 静态方法：检查是否为macOS bundle文件夹（macOS 上由 Launch Services 判断，否则使用默认扩展名列表）
     pub fn is_macos_bundle_folder(path: &Path) -> bool {
         // 首先处理可能为null的情况
         if path.as_os_str().is_empty() {
             return false;
         }
 
+        // macOS 上按 Launch Services 的包标志判断，与访达的显示一致
+        if let Some(is_package) = crate::bundle_detection::is_package(path) {
+            return is_package;
+        }
+
         // 默认bundle扩展名列表（用于静态调用）
         let default_bundle_extensions = [
             ".app",
@@ -811,20 +1505,6 @@ impl FileMonitor {
             ".theater",
         ];
 
-        // 1. 检查文件/目录名是否以已知的bundle扩展名结尾
-        if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-            let lowercase_name = file_name.to_lowercase();
-
-            // 检查文件名是否匹配bundle扩展名
-            if default_bundle_extensions
-                .iter()
-                .any(|ext| lowercase_name.ends_with(ext))
-            {
-                return true;
-            }
-        }
-
-        // 添加实例方法，使用配置中的扩展名列表
         Self::is_macos_bundle_folder_with_extensions(path, &default_bundle_extensions)
     }
 
@@ -842,8 +1522,26 @@ impl FileMonitor {
         let bundle_extension_refs: Vec<&str> =
             bundle_extensions.iter().map(AsRef::as_ref).collect();
 
-        // 使用共享的检查逻辑
-        Self::is_macos_bundle_folder_with_extensions(path, &bundle_extension_refs)
+        match crate::bundle_detection::is_package(path) {
+            Some(true) => true,
+            // Launch Services 不认识的包格式：目录扩展名在配置的列表中时也按 bundle 处理
+            Some(false) => {
+                path.is_dir() && Self::has_bundle_extension(path, &bundle_extension_refs)
+            }
+            // 使用共享的检查逻辑
+            None => Self::is_macos_bundle_folder_with_extensions(path, &bundle_extension_refs),
+        }
+    }
+
+    /// 辅助方法：目录本身的扩展名是否在指定的bundle扩展名列表中（扩展名带点，不区分大小写）
+    fn has_bundle_extension(path: &Path, bundle_extensions: &[&str]) -> bool {
+        path.extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase()))
+            .is_some_and(|ext| {
+                bundle_extensions
+                    .iter()
+                    .any(|bundle_ext| bundle_ext.to_lowercase() == ext)
+            })
     }
 
     /// 辅助方法：使用指定扩展名列表检查是否为macOS bundle
@@ -898,6 +1596,11 @@ impl FileMonitor {
 
     // 检查文件是否在macOS bundle内部，如果是则返回bundle路径
     pub fn is_inside_macos_bundle(path: &Path) -> Option<PathBuf> {
+        // macOS 上向上查找 Launch Services 认定的最外层包
+        if crate::bundle_detection::is_available() {
+            return crate::bundle_detection::containing_package(path);
+        }
+
         if let Some(path_str) = path.to_str() {
             // 检查常见bundle扩展
             let bundle_extensions = [
@@ -927,6 +1630,11 @@ impl FileMonitor {
 
     // 检查路径是否在黑名单内 (New implementation using Trie)
     fn is_in_blacklist(&self, path: &Path) -> bool {
+        // Time Machine 等备份位置默认与黑名单一样处理
+        if crate::backup_exclusion::is_backup_path(path) {
+            return true;
+        }
+
         // Ensure path is absolute for consistent Trie checking.
         // Paths from notify events are typically absolute.
         // If path might be relative, it needs normalization first.
@@ -945,8 +1653,7 @@ impl FileMonitor {
             path.to_path_buf()
         };
 
-        let trie_guard = self.blacklist_trie.lock().unwrap();
-        let result = trie_guard.is_path_or_ancestor_blacklisted(&path_to_check);
+        let result = self.snapshot().is_blacklisted(&path_to_check);
 
         // if result {
         //     println!("[BLACKLIST_TRIE_CHECK] Path {:?} IS IN BLACKLIST", path_to_check);
@@ -956,25 +1663,119 @@ impl FileMonitor {
         result
     }
 
+    // 规则作用范围：extra_data.scope 列出的目录（支持 `~/`），文件位于其中之一时规则才生效；
+    // 没有 scope 的规则对所有监控目录生效，如 `{"scope": ["~/Projects"]}` 只在 Projects 下排除 *.log
+    fn rule_in_scope(filter_rule: &FileFilterRuleRust, file_path: &Path) -> bool {
+        let Some(scope) = filter_rule
+            .extra_data
+            .as_ref()
+            .and_then(|extra| extra.get("scope"))
+            .and_then(|scope| scope.as_array())
+        else {
+            return true;
+        };
+        let home = std::env::var("HOME").ok();
+        scope.iter().filter_map(|dir| dir.as_str()).any(|dir| {
+            let expanded = match (dir.strip_prefix("~/"), &home) {
+                (Some(rest), Some(home)) => Path::new(home).join(rest),
+                _ => PathBuf::from(dir),
+            };
+            crate::path_utils::path_starts_with(file_path, &expanded)
+        })
+    }
+
+    // 文件夹规则：按 pattern_type 匹配上级路径（不区分大小写）
+    // - keyword：任意一级文件夹名包含关键字
+    // - regex：匹配整个上级路径
+    // - glob：匹配整个上级路径（如 `**/node_modules/**`），或任意一级文件夹名（如 `node_*`）
+    fn folder_rule_matches(compiled: &CompiledRule, parent: &Path) -> bool {
+        let parent_str = parent.to_string_lossy().to_lowercase();
+        let pattern = compiled.rule.pattern.to_lowercase();
+        let folder_names = || {
+            parent.components().filter_map(|component| match component {
+                std::path::Component::Normal(name) => Some(name.to_string_lossy().to_lowercase()),
+                _ => None,
+            })
+        };
+        match compiled.rule.pattern_type.as_str() {
+            "keyword" => folder_names().any(|name| name.contains(&pattern)),
+            "regex" => compiled
+                .regex
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(&parent_str)),
+            "glob" => compiled.glob.as_ref().is_some_and(|matcher| {
+                // 统一为 `/` 分隔，末尾补上分隔符，让 `**/name/**` 也能匹配 name 本身
+                let glob_path = parent_str.replace('\\', "/");
+                matcher.is_match(&glob_path)
+                    || matcher.is_match(format!("{}/", glob_path))
+                    || folder_names().any(|name| matcher.is_match(&name))
+            }),
+            _ => false,
+        }
+    }
+
+    // 编译 glob 类型规则的模式（不区分大小写，`*` 可以跨越路径分隔符，`**` 匹配任意层级目录）
+    fn compile_glob_rule(filter_rule: &FileFilterRuleRust) -> Option<globset::GlobMatcher> {
+        match globset::GlobBuilder::new(&filter_rule.pattern)
+            .case_insensitive(true)
+            .build()
+        {
+            Ok(glob) => Some(glob.compile_matcher()),
+            Err(e) => {
+                error!(
+                    "[APPLY_RULES] Invalid glob pattern in rule '{}': {}",
+                    filter_rule.name, e
+                );
+                None
+            }
+        }
+    }
+
     // 初步应用规则进行分类
     async fn apply_initial_rules(&self, metadata: &mut FileMetadata) {
-        let config_guard = self.config_cache.lock().unwrap();
-        if config_guard.is_none() {
-            eprintln!("[APPLY_RULES] Configuration cache is empty. Cannot apply rules.");
+        let snapshot = self.snapshot();
+        let Some(config) = snapshot.config.as_ref() else {
+            warn!("[APPLY_RULES] Configuration cache is empty. Cannot apply rules.");
             return;
-        }
-        let config = config_guard.as_ref().unwrap();
+        };
 
         // 更新处理文件计数器
         if let Ok(mut stats) = self.stats.lock() {
             stats.processed_files += 1;
         }
 
-        // 创建额外元数据对象
-        let mut extra_data = serde_json::Map::new();
+        let excluded = Self::evaluate_rules(config, &snapshot.filter_rules, metadata);
+        crate::rule_telemetry::record(config, metadata);
+        if excluded {
+            // 更新被过滤的文件统计
+            if let Ok(mut stats) = self.stats.lock() {
+                stats.filtered_files += 1;
+            }
+        }
+    }
+
+    // 对文件元数据应用所有初步规则（分类、标牌、排除标记），不修改任何统计，
+    // 返回文件是否被过滤规则排除（不含隐藏文件和构建产物的自动排除）
+    fn evaluate_rules(
+        config: &AllConfigurations,
+        filter_rules: &[CompiledRule],
+        metadata: &mut FileMetadata,
+    ) -> bool {
+        let mut excluded_by_rule = false;
+
+        // 创建额外元数据对象，保留读取元数据时得到的扩展属性
+        let mut extra_data = match metadata.extra_metadata.take() {
+            Some(serde_json::Value::Object(existing)) => existing,
+            _ => serde_json::Map::new(),
+        };
 
-        // 强制标记隐藏文件为排除
-        if metadata.is_hidden {
+        // 强制标记隐藏文件为排除，所在监控目录开启了隐藏文件索引时除外
+        if metadata.is_hidden
+            && !Self::hidden_files_allowed(
+                &config.monitored_folders,
+                Path::new(&metadata.file_path),
+            )
+        {
             extra_data.insert(
                 "excluded_by_rule_id".to_string(),
                 serde_json::Value::Number(serde_json::Number::from(9999)),
@@ -986,34 +1787,58 @@ impl FileMonitor {
             // println!("[APPLY_RULES] 隐藏文件将被自动排除: {}", metadata.file_name);
         }
 
+        let file_path = Path::new(&metadata.file_path);
+
+        // 系统截图文件夹中的新截图
+        if crate::screenshots::is_screenshot(file_path) {
+            crate::tags::add(&mut metadata.labels, crate::screenshots::SCREENSHOT_TAG);
+        }
+
+        // 记录文件所属的项目
+        let project = crate::project_roots::project_root_of(file_path);
+        if let Some(project) = &project {
+            extra_data.insert(
+                "project_root".to_string(),
+                serde_json::Value::String(crate::path_utils::path_to_nfc_string(&project.root)),
+            );
+            extra_data.insert(
+                "project_id".to_string(),
+                serde_json::Value::String(project.project_id.clone()),
+            );
+            extra_data.insert(
+                "project_marker".to_string(),
+                serde_json::Value::String(project.marker.clone()),
+            );
+        }
+
         // 根据扩展名进行初步分类
+        let mut category_from_ext = false;
+        // 分类的置信度和来源，供 Python 端优先精炼低置信度的文件
+        let mut category_confidence: Option<(f64, &str)> = None;
         if let Some(ext) = &metadata.extension {
             // 从API获取规则
-            for ext_map_rule in &config.file_extension_maps {
-                if ext_map_rule.extension == *ext {
-                    metadata.category_id = Some(ext_map_rule.category_id);
-                    // Find category name for extra_data (optional, but nice for debugging)
-                    let category_name = config
-                        .file_categories
-                        .iter()
-                        .find(|cat| cat.id == ext_map_rule.category_id)
-                        .map_or("unknown_category_id".to_string(), |cat| cat.name.clone());
-                    extra_data.insert(
-                        "file_type_from_ext_map".to_string(),
-                        serde_json::Value::String(category_name),
-                    );
-                    // println!("[APPLY_RULES] Applied category {} from extension map for ext: {}", ext_map_rule.category_id, ext);
-                    break; // Assuming first match is enough, or consider priority
-                }
+            if let Some(ext_map_rule) = Self::best_extension_map(config, ext) {
+                category_from_ext = true;
+                category_confidence = Some((
+                    Self::category_confidence(&ext_map_rule.priority, 0.0),
+                    "extension_map",
+                ));
+                metadata.category_id = Some(ext_map_rule.category_id);
+                // Find category name for extra_data (optional, but nice for debugging)
+                let category_name = config
+                    .file_categories
+                    .iter()
+                    .find(|cat| cat.id == ext_map_rule.category_id)
+                    .map_or("unknown_category_id".to_string(), |cat| cat.name.clone());
+                extra_data.insert(
+                    "file_type_from_ext_map".to_string(),
+                    serde_json::Value::String(category_name),
+                );
+                // info!("[APPLY_RULES] Applied category {} from extension map for ext: {}", ext_map_rule.category_id, ext);
             }
 
             // 添加基于扩展名的标牌
-            if metadata.labels.is_none() {
-                metadata.labels = Some(Vec::new());
-            }
-            if let Some(labels) = &mut metadata.labels {
-                labels.push(format!("ext:{}", ext));
-            }
+            crate::tags::add(&mut metadata.labels, &format!("ext:{}", ext));
 
             // 记录扩展名到额外元数据
             extra_data.insert(
@@ -1022,6 +1847,40 @@ impl FileMonitor {
             );
         }
 
+        // 按内容识别出的类型分类：没有扩展名、扩展名没有映射，或扩展名与内容不符时采用。
+        // 内容是压缩包格式时不覆盖已有扩展名（docx、pages 等文档本身就是 zip 容器）
+        let detected_ext = extra_data
+            .get("detected_extension")
+            .and_then(|value| value.as_str())
+            .map(str::to_string);
+        if let Some(detected_ext) = detected_ext {
+            let detected_kind = extra_data
+                .get("detected_kind")
+                .and_then(|value| value.as_str());
+            let detected_archive = detected_kind == Some("archive");
+            let extension_mismatch = !detected_archive
+                && metadata
+                    .extension
+                    .as_deref()
+                    .is_some_and(|ext| ext != detected_ext);
+            if !category_from_ext || extension_mismatch {
+                if let Some(ext_map_rule) = Self::best_extension_map(config, &detected_ext) {
+                    metadata.category_id = Some(ext_map_rule.category_id);
+                    // 魔数识别比较可靠，无扩展名文本的内容嗅探只能说明是文本
+                    let content_confidence = if detected_kind == Some("text") {
+                        0.4
+                    } else {
+                        0.6
+                    };
+                    category_confidence = Some((content_confidence, "content"));
+                    extra_data.insert(
+                        "category_from_content".to_string(),
+                        serde_json::Value::Bool(true),
+                    );
+                }
+            }
+        }
+
         // 根据文件名应用初步规则
         let filename = metadata.file_name.to_lowercase();
         let mut rule_matches = metadata.initial_rule_matches.clone().unwrap_or_default(); // Preserve existing if any
@@ -1030,12 +1889,33 @@ impl FileMonitor {
         let mut is_bundle_file = metadata.is_os_bundle.unwrap_or(false);
 
         // Apply FileFilterRuleRust
-        for filter_rule in &config.file_filter_rules {
-            if !filter_rule.enabled {
+        // 快照中的规则按优先级从高到低排列（同级按 ID 升序），结果与 API 返回规则的顺序无关：
+        // - 分类：由匹配到的最高优先级规则决定，低优先级规则不再覆盖
+        // - 排除：匹配的 Exclude 规则会排除文件（同级时 Exclude 优先于 Include / Label），
+        //   记录的是优先级最高的那条
+        // - 覆盖：优先级严格高于 Exclude 规则的 Include 规则可以把文件从排除中救回，
+        //   如 Medium 排除 `**/node_modules/**`，High 包含 `**/node_modules/my-notes/**`
+        // - 构建产物目录（target、node_modules 等）的自动排除是优先级最低的内置 Exclude 规则，
+        //   在所有规则之后应用，任何匹配的 Include 规则都可以覆盖
+        let mut category_from_rule = false;
+        let mut include_rule: Option<&FileFilterRuleRust> = None;
+        // 循环中会修改 metadata 的分类和标签，组合条件和作用范围使用一份拷贝
+        let rule_file_path = metadata.file_path.clone();
+        let rule_extension = metadata.extension.clone();
+        let facts = crate::rule_conditions::FileFacts {
+            extension: rule_extension.as_deref(),
+            file_name: &filename,
+            path: &rule_file_path,
+            size: metadata.file_size,
+        };
+
+        for compiled in filter_rules {
+            let filter_rule = &compiled.rule;
+            if !Self::rule_in_scope(filter_rule, Path::new(&rule_file_path)) {
                 continue;
             }
 
-            // 实现正则表达式、关键字和通配符匹配逻辑
+            // 实现正则表达式、关键字和通配符匹配逻辑（正则和通配符已在构建快照时编译）
             let mut matched_this_rule = false;
 
             match filter_rule.rule_type {
@@ -1046,75 +1926,51 @@ impl FileMonitor {
                             matched_this_rule = true;
                             // println!("[APPLY_RULES] Matched filename keyword rule '{}' for: {}", filter_rule.name, filename);
                         }
-                    } else if filter_rule.pattern_type == "regex" {
+                    } else if let Some(regex) = &compiled.regex {
                         // 正则表达式匹配
-                        match regex::Regex::new(&filter_rule.pattern) {
-                            Ok(regex) => {
-                                if regex.is_match(&filename) {
-                                    matched_this_rule = true;
-                                    // println!("[APPLY_RULES] Matched filename regex rule '{}' for: {}", filter_rule.name, filename);
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!(
-                                    "[APPLY_RULES] Invalid regex pattern in rule '{}': {}",
-                                    filter_rule.name, e
-                                );
-                            }
+                        if regex.is_match(&filename) {
+                            matched_this_rule = true;
+                            // println!("[APPLY_RULES] Matched filename regex rule '{}' for: {}", filter_rule.name, filename);
+                        }
+                    } else if let Some(matcher) = &compiled.glob {
+                        // 通配符匹配 - 如 `*.bak`；带路径分隔符的模式（如 `**/drafts/*.md`）匹配完整路径
+                        if matcher.is_match(&filename)
+                            || matcher.is_match(metadata.file_path.replace('\\', "/"))
+                        {
+                            matched_this_rule = true;
                         }
                     }
                 }
                 RuleTypeRust::OSBundle => {
                     // 检查文件名是否匹配macOS Bundle模式
-                    if filter_rule.pattern_type == "regex" {
-                        match regex::Regex::new(&filter_rule.pattern) {
-                            Ok(regex) => {
-                                if regex.is_match(&filename) {
-                                    matched_this_rule = true;
-                                    println!(
-                                        "[APPLY_RULES] Matched OS_BUNDLE regex rule '{}' for: {}",
-                                        filter_rule.name, filename
-                                    );
+                    if let Some(regex) = &compiled.regex {
+                        if regex.is_match(&filename) {
+                            matched_this_rule = true;
+                            debug!(
+                                "[APPLY_RULES] Matched OS_BUNDLE regex rule '{}' for: {}",
+                                filter_rule.name, filename
+                            );
 
-                                    // 对于OSBundle类型，标记为bundle而不是排除
-                                    is_bundle_file = true;
+                            // 对于OSBundle类型，标记为bundle而不是排除
+                            is_bundle_file = true;
 
-                                    // 记录bundle规则信息
-                                    extra_data.insert(
-                                        "macos_bundle_rule_id".to_string(),
-                                        serde_json::Value::Number(serde_json::Number::from(
-                                            filter_rule.id,
-                                        )),
-                                    );
-                                    extra_data.insert(
-                                        "macos_bundle_rule_name".to_string(),
-                                        serde_json::Value::String(filter_rule.name.clone()),
-                                    );
-                                    extra_data.insert(
-                                        "is_macos_bundle".to_string(),
-                                        serde_json::Value::Bool(true),
-                                    );
+                            // 记录bundle规则信息
+                            extra_data.insert(
+                                "macos_bundle_rule_id".to_string(),
+                                serde_json::Value::Number(serde_json::Number::from(filter_rule.id)),
+                            );
+                            extra_data.insert(
+                                "macos_bundle_rule_name".to_string(),
+                                serde_json::Value::String(filter_rule.name.clone()),
+                            );
+                            extra_data.insert(
+                                "is_macos_bundle".to_string(),
+                                serde_json::Value::Bool(true),
+                            );
 
-                                    // 将bundle文件添加到标牌中
-                                    if metadata.labels.is_none() {
-                                        metadata.labels = Some(Vec::new());
-                                    }
-                                    if let Some(labels) = &mut metadata.labels {
-                                        if !labels.contains(&filter_rule.name) {
-                                            labels.push(filter_rule.name.clone());
-                                        }
-                                        if !labels.contains(&"macos_bundle".to_string()) {
-                                            labels.push("macos_bundle".to_string());
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!(
-                                    "[APPLY_RULES] Invalid regex pattern in rule '{}': {}",
-                                    filter_rule.name, e
-                                );
-                            }
+                            // 将bundle文件添加到标牌中
+                            crate::tags::add(&mut metadata.labels, &filter_rule.name);
+                            crate::tags::add(&mut metadata.labels, "macos_bundle");
                         }
                     }
                 }
@@ -1125,27 +1981,53 @@ impl FileMonitor {
                         {
                             matched_this_rule = true;
                             // println!("[APPLY_RULES] Matched extension rule '{}' for: {}", filter_rule.name, ext_val);
-                        } else if filter_rule.pattern_type == "regex" {
+                        } else if let Some(regex) = &compiled.regex {
                             // 扩展名的正则表达式匹配
-                            match regex::Regex::new(&filter_rule.pattern) {
-                                Ok(regex) => {
-                                    if regex.is_match(ext_val) {
-                                        matched_this_rule = true;
-                                        // println!("[APPLY_RULES] Matched extension regex rule '{}' for: {}", filter_rule.name, ext_val);
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!(
-                                        "[APPLY_RULES] Invalid regex pattern in rule '{}': {}",
-                                        filter_rule.name, e
-                                    );
-                                }
+                            if regex.is_match(ext_val) {
+                                matched_this_rule = true;
+                                // println!("[APPLY_RULES] Matched extension regex rule '{}' for: {}", filter_rule.name, ext_val);
                             }
+                        } else if let Some(matcher) = &compiled.glob {
+                            // 扩展名的通配符匹配 - 既支持 `ba?` 这样只写扩展名的模式，也支持 `*.bak`
+                            if matcher.is_match(ext_val)
+                                || matcher.is_match(format!(".{}", ext_val))
+                            {
+                                matched_this_rule = true;
+                            }
+                        }
+                    }
+                }
+                RuleTypeRust::Folder => {
+                    // 文件夹规则匹配文件所在的上级路径
+                    if let Some(parent) = Path::new(&metadata.file_path).parent() {
+                        if Self::folder_rule_matches(compiled, parent) {
+                            matched_this_rule = true;
+                            debug!(
+                                "[APPLY_RULES] Matched folder rule '{}' for: {:?}",
+                                filter_rule.name, parent
+                            );
                         }
                     }
                 }
-                // Folder and Structure rules might need more context than a single FileMetadata
-                _ => {}
+                RuleTypeRust::Structure => {
+                    // Structure 规则的 pattern 是项目标记，文件所属项目由该规则识别时匹配
+                    if project
+                        .as_ref()
+                        .is_some_and(|project| project.rule_id == Some(filter_rule.id))
+                    {
+                        matched_this_rule = true;
+                    }
+                }
+            }
+
+            // 组合条件：extra_data.conditions 也成立时规则才算匹配，pattern 为空的规则只看组合条件；
+            // 格式错误的组合条件在构建快照时已记录警告，规则不匹配
+            if let Some(conditions) = &compiled.conditions {
+                if matched_this_rule || filter_rule.pattern.is_empty() {
+                    matched_this_rule = conditions
+                        .as_ref()
+                        .is_ok_and(|condition| condition.evaluate(&facts));
+                }
             }
 
             if matched_this_rule {
@@ -1155,30 +2037,38 @@ impl FileMonitor {
                 if filter_rule.rule_type != RuleTypeRust::OSBundle {
                     match filter_rule.action {
                         RuleActionRust::Label => {
-                            if metadata.labels.is_none() {
-                                metadata.labels = Some(Vec::new());
-                            }
-                            if let Some(labels) = &mut metadata.labels {
-                                // Avoid duplicate labels from the same rule, or use a Set
-                                if !labels.contains(&filter_rule.name) {
-                                    // Simple check
-                                    labels.push(filter_rule.name.clone());
-                                }
-                                // If rule has a specific label in extra_data, use that
-                                if let Some(JsonValue::String(label_value)) = filter_rule
-                                    .extra_data
-                                    .as_ref()
-                                    .and_then(|ed| ed.get("label_value"))
-                                {
-                                    if !labels.contains(label_value) {
-                                        labels.push(label_value.clone());
-                                    }
-                                }
+                            crate::tags::add(&mut metadata.labels, &filter_rule.name);
+                            // If rule has a specific label in extra_data, use that
+                            // label_value 可以是层级标牌，如 `work/clients/acme`
+                            if let Some(JsonValue::String(label_value)) = filter_rule
+                                .extra_data
+                                .as_ref()
+                                .and_then(|ed| ed.get("label_value"))
+                            {
+                                crate::tags::add(&mut metadata.labels, label_value);
                             }
                         }
                         RuleActionRust::Exclude => {
-                            // 只有非bundle文件才能被排除
-                            if !is_bundle_file {
+                            let overriding_include = include_rule
+                                .filter(|include| include.priority > filter_rule.priority);
+                            if let Some(include) = overriding_include {
+                                // 被更高优先级的 Include 规则覆盖，不排除
+                                extra_data.insert(
+                                    "exclude_overridden_by_rule_id".to_string(),
+                                    JsonValue::Number(serde_json::Number::from(include.id)),
+                                );
+                                extra_data.insert(
+                                    "exclude_overridden_by_rule_name".to_string(),
+                                    JsonValue::String(include.name.clone()),
+                                );
+                                debug!(
+                                    "[APPLY_RULES] Exclude rule '{}' overridden by include rule '{}' for: {}",
+                                    filter_rule.name, include.name, filename
+                                );
+                            } else if !is_bundle_file
+                                && !extra_data.contains_key("excluded_by_rule_id")
+                            {
+                                // 只有非bundle文件才能被排除，已被更高优先级规则排除时不再覆盖
                                 extra_data.insert(
                                     "excluded_by_rule_id".to_string(),
                                     JsonValue::Number(serde_json::Number::from(filter_rule.id)),
@@ -1187,26 +2077,78 @@ impl FileMonitor {
                                     "excluded_by_rule_name".to_string(),
                                     JsonValue::String(filter_rule.name.clone()),
                                 );
-
-                                // 更新被过滤的文件统计
-                                if let Ok(mut stats) = self.stats.lock() {
-                                    stats.filtered_files += 1;
-                                }
+                                excluded_by_rule = true;
                             }
                         }
                         RuleActionRust::Include => {
-                            // Default behavior, no specific action needed
+                            // 记录优先级最高的 Include 规则，用于覆盖更低优先级的 Exclude 规则
+                            if include_rule.is_none() {
+                                include_rule = Some(filter_rule);
+                            }
                         }
                     }
                 }
 
-                // 设置分类ID（如果规则有定义）
+                // 设置分类ID（如果规则有定义），只采用最高优先级规则的分类
                 if let Some(cat_id) = filter_rule.category_id {
-                    metadata.category_id = Some(cat_id);
+                    if !category_from_rule {
+                        metadata.category_id = Some(cat_id);
+                        category_from_rule = true;
+                        category_confidence = Some((
+                            Self::category_confidence(
+                                &filter_rule.priority,
+                                Self::rule_match_deduction(filter_rule),
+                            ),
+                            "rule",
+                        ));
+                    }
                 }
             }
         }
 
+        // 项目根目录下的构建产物目录自动排除
+        if let Some(derived_dir) = crate::project_roots::derived_dir_of(Path::new(&rule_file_path))
+        {
+            if let Some(include) = include_rule {
+                extra_data.insert(
+                    "exclude_overridden_by_rule_id".to_string(),
+                    JsonValue::Number(serde_json::Number::from(include.id)),
+                );
+                extra_data.insert(
+                    "exclude_overridden_by_rule_name".to_string(),
+                    JsonValue::String(include.name.clone()),
+                );
+                debug!(
+                    "[APPLY_RULES] Derived directory {:?} overridden by include rule '{}' for: {}",
+                    derived_dir, include.name, filename
+                );
+            } else if !excluded_by_rule {
+                extra_data.insert(
+                    "excluded_by_rule_id".to_string(),
+                    JsonValue::Number(serde_json::Number::from(9998)),
+                );
+                extra_data.insert(
+                    "excluded_by_rule_name".to_string(),
+                    JsonValue::String("项目构建产物自动排除".to_string()),
+                );
+                debug!(
+                    "[APPLY_RULES] File {:?} is inside derived directory {:?}",
+                    rule_file_path, derived_dir
+                );
+            }
+        }
+
+        if let Some((confidence, source)) = category_confidence {
+            extra_data.insert(
+                "category_confidence".to_string(),
+                serde_json::json!(confidence),
+            );
+            extra_data.insert(
+                "category_source".to_string(),
+                serde_json::Value::String(source.to_string()),
+            );
+        }
+
         // 更新元数据中的bundle标记
         metadata.is_os_bundle = Some(is_bundle_file);
 
@@ -1219,14 +2161,23 @@ impl FileMonitor {
         if !extra_data.is_empty() {
             metadata.extra_metadata = Some(serde_json::Value::Object(extra_data));
         }
+
+        excluded_by_rule
     }
 
-    // 获取文件元数据
+    // 获取文件元数据；扫描中优先使用按目录批量读取的元数据
     async fn get_file_metadata(path: &Path) -> Option<FileMetadata> {
-        match fs::metadata(path).await {
-            Ok(metadata) => {
-                let file_name = path.file_name()?.to_str()?.to_string();
-                let is_dir = metadata.is_dir();
+        let metadata = match crate::bulk_metadata::lookup(path) {
+            Some(stat) => Some(stat),
+            None => fs::metadata(path)
+                .await
+                .ok()
+                .map(|metadata| crate::bulk_metadata::EntryStat::from(&metadata)),
+        };
+        match metadata {
+            Some(metadata) => {
+                let file_name = crate::path_utils::to_nfc(path.file_name()?.to_str()?);
+                let is_dir = metadata.is_dir;
                 let extension = if !is_dir {
                     Self::extract_extension(path)
                 } else {
@@ -1234,65 +2185,93 @@ impl FileMonitor {
                 };
 
                 // 获取时间戳，如果出错则使用当前时间
-                let created = metadata
-                    .created()
-                    .map(|time| {
-                        time.duration_since(UNIX_EPOCH)
-                            .map(|d| d.as_secs())
-                            .unwrap_or_else(|_| {
-                                SystemTime::now()
-                                    .duration_since(UNIX_EPOCH)
-                                    .unwrap()
-                                    .as_secs()
-                            })
-                    })
-                    .unwrap_or_else(|_| {
-                        SystemTime::now()
-                            .duration_since(UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs()
-                    });
-
-                let modified = metadata
-                    .modified()
-                    .map(|time| {
-                        time.duration_since(UNIX_EPOCH)
-                            .map(|d| d.as_secs())
-                            .unwrap_or_else(|_| {
-                                SystemTime::now()
-                                    .duration_since(UNIX_EPOCH)
-                                    .unwrap()
-                                    .as_secs()
-                            })
-                    })
-                    .unwrap_or_else(|_| {
-                        SystemTime::now()
-                            .duration_since(UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs()
-                    });
+                let to_secs = |time: Option<SystemTime>| {
+                    time.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs())
+                        .unwrap_or_else(|| {
+                            SystemTime::now()
+                                .duration_since(UNIX_EPOCH)
+                                .unwrap()
+                                .as_secs()
+                        })
+                };
+                let created = to_secs(metadata.created);
+                let modified = to_secs(metadata.modified);
 
                 // 检查是否为macOS bundle
                 let is_bundle = Self::is_macos_bundle_folder(path);
 
+                // 下载来源等扩展属性，以及按内容识别的文件类型
+                let mut extra = serde_json::Map::new();
+                if !is_dir {
+                    extra = crate::extended_attributes::read_provenance(path);
+                    if let Some(content_type) = Self::sniff_content_type(path).await {
+                        extra.insert(
+                            "detected_mime".to_string(),
+                            JsonValue::String(content_type.mime_type().to_string()),
+                        );
+                        extra.insert(
+                            "detected_extension".to_string(),
+                            JsonValue::String(content_type.extension().to_string()),
+                        );
+                        extra.insert(
+                            "detected_kind".to_string(),
+                            JsonValue::String(
+                                format!("{:?}", content_type.matcher_type()).to_lowercase(),
+                            ),
+                        );
+                    } else if extension.is_none() && crate::extensionless::is_indexable_text(path) {
+                        // 像文本的无扩展名文件（笔记、脚本等）按 txt 分类
+                        extra.insert(
+                            "detected_mime".to_string(),
+                            JsonValue::String("text/plain".to_string()),
+                        );
+                        extra.insert(
+                            "detected_extension".to_string(),
+                            JsonValue::String(crate::extensionless::TEXT_EXTENSION.to_string()),
+                        );
+                        extra.insert(
+                            "detected_kind".to_string(),
+                            JsonValue::String("text".to_string()),
+                        );
+                    }
+                }
+
+                // 代码文件的编程语言，作为 lang: 标牌供前端按语言筛选
+                let language = if is_dir {
+                    None
+                } else {
+                    crate::code_language::detect(path, extension.as_deref())
+                };
+                if let Some(language) = language {
+                    extra.insert(
+                        "language".to_string(),
+                        JsonValue::String(language.to_string()),
+                    );
+                }
+
                 Some(FileMetadata {
-                    file_path: path.to_str()?.to_string(),
+                    file_path: crate::path_utils::to_nfc(path.to_str()?),
                     file_name,
                     extension,
-                    file_size: if is_dir { 0 } else { metadata.len() },
+                    file_size: if is_dir { 0 } else { metadata.len },
                     created_time: created,
                     modified_time: modified,
                     is_dir,
                     is_hidden: Self::is_hidden_file(path),
                     hash_value: None, // 哈希值稍后计算
                     category_id: None,
-                    labels: None,
+                    labels: language.map(|language| vec![format!("lang:{}", language)]),
                     initial_rule_matches: None,
-                    extra_metadata: None,
+                    extra_metadata: if extra.is_empty() {
+                        None
+                    } else {
+                        Some(serde_json::Value::Object(extra))
+                    },
                     is_os_bundle: Some(is_bundle), // 标记是否为macOS bundle
                 })
             }
-            Err(_) => None,
+            None => None,
         }
     }
 
@@ -1302,7 +2281,7 @@ impl FileMonitor {
         metadata_batch: Vec<FileMetadata>,
     ) -> Result<ApiResponse, String> {
         if metadata_batch.is_empty() {
-            println!("[TEST_DEBUG] send_batch_metadata_to_api: Batch is empty, nothing to send.");
+            debug!("[TEST_DEBUG] send_batch_metadata_to_api: Batch is empty, nothing to send.");
             // 根据你的逻辑，这里可能需要返回一个表示成功的默认 ApiResponse
             return Ok(ApiResponse {
                 success: true,
@@ -1333,7 +2312,41 @@ impl FileMonitor {
         // let keys: Vec<String> = request_body.keys().cloned().collect();
         // println!("[TEST_DEBUG] send_batch_metadata_to_api: Request body for batch keys: {:?}", keys);
 
-        match self.client.post(&url).json(&request_body).send().await {
+        let body = serde_json::to_vec(&request_body)
+            .map_err(|e| format!("Failed to serialize metadata batch: {}", e))?;
+
+        // 初始扫描的批次可达数MB，超过阈值时gzip压缩后发送
+        let compressed = if body.len() >= BATCH_COMPRESSION_THRESHOLD
+            && self.batch_compression.load(Ordering::Relaxed)
+        {
+            Self::gzip_body(&body)
+        } else {
+            None
+        };
+        let result = match compressed {
+            Some(compressed) => {
+                debug!(
+                    "[BATCH_SEND] 请求体已压缩: {} -> {} 字节",
+                    body.len(),
+                    compressed.len()
+                );
+                match self.post_batch_body(&url, compressed, true).await {
+                    // 旧版API不认识Content-Encoding，会把压缩数据当作JSON解析而返回4xx
+                    Ok(response) if Self::may_reject_compression(response.status()) => {
+                        let retry = self.post_batch_body(&url, body, false).await;
+                        if matches!(&retry, Ok(response) if response.status().is_success()) {
+                            warn!("[BATCH_SEND] API不支持压缩的请求体，后续批次改为不压缩发送");
+                            self.batch_compression.store(false, Ordering::Relaxed);
+                        }
+                        retry
+                    }
+                    other => other,
+                }
+            }
+            None => self.post_batch_body(&url, body, false).await,
+        };
+
+        match result {
             Ok(response) => {
                 let status = response.status();
                 // println!("[TEST_DEBUG] send_batch_metadata_to_api: Received response with status: {}", status);
@@ -1349,7 +2362,7 @@ impl FileMonitor {
                             Ok(api_resp)
                         }
                         Err(e) => {
-                            eprintln!("[TEST_DEBUG] send_batch_metadata_to_api: Failed to parse successful response body: {}. Raw body snippet: {}", e, &response_text[..std::cmp::min(response_text.len(), 200)]);
+                            error!("[TEST_DEBUG] send_batch_metadata_to_api: Failed to parse successful response body: {}. Raw body snippet: {}", e, &response_text[..std::cmp::min(response_text.len(), 200)]);
                             Err(format!("Failed to parse API response from successful request: {}. Body snippet: {}", e, &response_text[..std::cmp::min(response_text.len(), 200)]))
                         }
                     }
@@ -1358,7 +2371,7 @@ impl FileMonitor {
                         .text()
                         .await
                         .unwrap_or_else(|_| "Failed to read error response text".to_string());
-                    eprintln!("[TEST_DEBUG] send_batch_metadata_to_api: API request failed with status: {}. Body snippet: {}", status, &err_text[..std::cmp::min(err_text.len(), 200)]);
+                    warn!("[TEST_DEBUG] send_batch_metadata_to_api: API request failed with status: {}. Body snippet: {}", status, &err_text[..std::cmp::min(err_text.len(), 200)]);
                     Err(format!(
                         "API request failed with status {}: {}",
                         status,
@@ -1367,7 +2380,7 @@ impl FileMonitor {
                 }
             }
             Err(e) => {
-                eprintln!(
+                error!(
                     "[TEST_DEBUG] send_batch_metadata_to_api: Failed to send batch data to API: {}",
                     e
                 );
@@ -1376,6 +2389,190 @@ impl FileMonitor {
         }
     }
 
+    // 把通过过滤的元数据加入待发送批次；流式模式下写入进行中的NDJSON请求（已写出的记录无法合并）。
+    // 返回是否与批次中同一路径的元数据合并
+    async fn push_pending_batch(&self, batch: &mut PendingBatch, metadata: FileMetadata) -> bool {
+        // 本批已按批量模式开始缓存时保持批量模式，模式切换从下一批生效
+        if crate::screening_upload::streaming_enabled() && batch.items.is_empty() {
+            let stream = batch.stream.get_or_insert_with(|| {
+                crate::screening_upload::NdjsonUpload::open(
+                    &self.client,
+                    format!(
+                        "http://{}:{}/file-screening/stream",
+                        self.api_host, self.api_port
+                    ),
+                )
+            });
+            match stream.push(&metadata).await {
+                Ok(()) => {
+                    batch
+                        .streamed
+                        .push(crate::local_index::IndexedFile::of(&metadata));
+                    return false;
+                }
+                Err(e) => warn!("[BATCH_PROC] 写入流式请求失败，本批改为批量发送: {}", e),
+            }
+        }
+        batch.push_item(metadata)
+    }
+
+    // 发送待发送批次：结束进行中的流式请求，并在后台批量发送缓存的元数据；
+    // 进行中的批量请求达到并发上限时先等待其中一个完成。返回流式请求的结果，批量请求的错误在后台任务中记录
+    async fn flush_pending_batch(
+        &self,
+        batch: &mut PendingBatch,
+        in_flight: &mut tokio::task::JoinSet<()>,
+    ) -> Result<(), String> {
+        let mut result = Ok(());
+        if let Some(stream) = batch.stream.take() {
+            let streamed = std::mem::take(&mut batch.streamed);
+            let count = stream.record_count();
+            result = match stream.finish().await {
+                Ok(response) => Self::check_stream_response(response).await,
+                Err(e) => Err(e),
+            };
+            if result.is_ok() {
+                Self::record_sent(streamed);
+            }
+            debug!("[BATCH_PROC] 流式请求已结束，共 {} 条记录", count);
+        }
+        let items = batch.take_items();
+        if !items.is_empty() {
+            while in_flight.len() >= crate::screening_upload::batch_concurrency() {
+                in_flight.join_next().await;
+            }
+            let sent = items
+                .iter()
+                .map(crate::local_index::IndexedFile::of)
+                .collect();
+            let monitor = self.clone();
+            in_flight.spawn(async move {
+                match monitor.send_batch_metadata_to_api(items).await {
+                    Ok(response) if response.success => Self::record_sent(sent),
+                    Ok(_) => {}
+                    Err(e) => error!("[BATCH_PROC] 批量发送错误: {}", e),
+                }
+            });
+        }
+        result
+    }
+
+    // 记录已成功发送到API的文件：写入已发送缓存和本地索引，延迟哈希模式下同时排队计算哈希
+    fn record_sent(files: Vec<crate::local_index::IndexedFile>) {
+        crate::seen_cache::record(
+            files
+                .iter()
+                .map(|file| (file.file_path.clone(), file.signature()))
+                .collect(),
+        );
+        crate::text_extraction::enqueue(&files);
+        let defer_hashes = crate::deferred_hash::enabled();
+        crate::local_index::record(files, defer_hashes);
+        if defer_hashes {
+            crate::deferred_hash::wake();
+        }
+    }
+
+    // 检查流式提交的响应
+    async fn check_stream_response(response: reqwest::Response) -> Result<(), String> {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(format!(
+                "Streamed upload failed with status {}: {}",
+                status,
+                &body[..std::cmp::min(body.len(), 200)]
+            ));
+        }
+        match serde_json::from_str::<ApiResponse>(&body) {
+            Ok(api_resp) if api_resp.success => Ok(()),
+            Ok(api_resp) => Err(format!(
+                "Streamed upload rejected: {}",
+                api_resp.message.unwrap_or_default()
+            )),
+            Err(e) => Err(format!("Failed to parse streamed upload response: {}", e)),
+        }
+    }
+
+    // 发送已序列化的批量请求体，compressed 为 true 时标记为gzip编码
+    async fn post_batch_body(
+        &self,
+        url: &str,
+        body: Vec<u8>,
+        compressed: bool,
+    ) -> reqwest::Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if compressed {
+            request = request.header(reqwest::header::CONTENT_ENCODING, "gzip");
+        }
+        request.body(body).send().await
+    }
+
+    // gzip压缩请求体，压缩失败时返回None（按原样发送）
+    fn gzip_body(body: &[u8]) -> Option<Vec<u8>> {
+        let mut encoder = flate2::write::GzEncoder::new(
+            Vec::with_capacity(body.len() / 4),
+            flate2::Compression::fast(),
+        );
+        match encoder.write_all(body).and_then(|_| encoder.finish()) {
+            Ok(compressed) => Some(compressed),
+            Err(e) => {
+                warn!("[BATCH_SEND] 压缩请求体失败，改为不压缩发送: {}", e);
+                None
+            }
+        }
+    }
+
+    // 不支持压缩的API对压缩请求体可能返回的状态码
+    fn may_reject_compression(status: reqwest::StatusCode) -> bool {
+        matches!(status.as_u16(), 400 | 415 | 422)
+    }
+
+    /// 将外部来源（如拖放到主窗口）的路径送入粗筛管道
+    ///
+    /// 目录执行一次单目录扫描，文件和macOS包按文件变化事件处理后交给批处理器。
+    /// 返回处理方式（"directory" / "file" / "bundle"），被拒绝时返回原因
+    pub async fn ingest_path(
+        &self,
+        path: &Path,
+        app_handle: &tauri::AppHandle,
+    ) -> Result<&'static str, String> {
+        if !path.exists() {
+            return Err("路径不存在".to_string());
+        }
+        // bundle内部的文件归属于bundle本身
+        let path = Self::is_inside_macos_bundle(path).unwrap_or_else(|| path.to_path_buf());
+        if self.is_in_blacklist(&path) {
+            return Err("位于黑名单文件夹中".to_string());
+        }
+
+        let is_bundle = self.check_if_macos_bundle(&path);
+        if path.is_dir() && !is_bundle {
+            self.scan_single_directory(&path.to_string_lossy(), Some(app_handle))
+                .await?;
+            return Ok("directory");
+        }
+
+        let sender = self
+            .get_metadata_sender()
+            .ok_or_else(|| "批处理器未运行，文件监控尚未启动".to_string())?;
+        let metadata = self
+            .process_file_event(
+                path.clone(),
+                notify::EventKind::Create(notify::event::CreateKind::File),
+                app_handle,
+            )
+            .await
+            .ok_or_else(|| "不在监控文件夹中，或被隐藏文件、扩展名、过滤规则排除".to_string())?;
+        if !self.send_with_backpressure(&sender, metadata).await {
+            return Err("发送到批处理器失败，通道已关闭".to_string());
+        }
+        Ok(if is_bundle { "bundle" } else { "file" })
+    }
+
     // 处理文件变化事件 - 公开给防抖动监控器使用
     pub async fn process_file_event(
         &self,
@@ -1385,15 +2582,20 @@ impl FileMonitor {
     ) -> Option<FileMetadata> {
         // println!("[PROCESS_EVENT] Processing event {:?} for path {:?}", event_kind, path);
 
+        // 去掉Windows的\\?\长路径前缀，保证与监控目录和黑名单的前缀比较一致
+        let path = crate::path_utils::normalize_path(&path);
+
         // 对于删除事件进行特殊处理 - 调用API删除相应的记录
         if let notify::EventKind::Remove(_) = event_kind {
-            println!(
+            info!(
                 "[PROCESS_EVENT] 检测到文件删除: {:?}. 正在从粗筛结果表中删除记录...",
                 path
             );
 
             // 构建API请求URL
-            let path_str = path.to_string_lossy().to_string();
+            let path_str = crate::path_utils::path_to_nfc_string(&path);
+            crate::seen_cache::forget(&path_str);
+            crate::local_index::remove(&path_str);
             let url = format!(
                 "http://{}:{}/screening/delete-by-path",
                 self.api_host, self.api_port
@@ -1409,7 +2611,7 @@ impl FileMonitor {
                 Ok(response) => {
                     let status = response.status();
                     if status.is_success() {
-                        println!("[PROCESS_EVENT] 成功删除文件 {:?} 的粗筛记录", path);
+                        info!("[PROCESS_EVENT] 成功删除文件 {:?} 的粗筛记录", path);
                         // 发射 screening-result-updated 事件
                         let payload = serde_json::json!({
                             "message": "文件筛选成功",
@@ -1417,16 +2619,16 @@ impl FileMonitor {
                         });
 
                         if let Err(e) = app_handle.emit("screening-result-updated", &payload) {
-                            eprintln!("[防抖监控] 发射screening-result-updated事件失败: {}", e);
+                            error!("[防抖监控] 发射screening-result-updated事件失败: {}", e);
                         } else {
-                            println!("[防抖监控] 发射screening-result-updated事件: 文件筛选成功 - 删除文件");
+                            debug!("[防抖监控] 发射screening-result-updated事件: 文件筛选成功 - 删除文件");
                         }
                     } else {
                         let err_text = response
                             .text()
                             .await
                             .unwrap_or_else(|_| "Failed to read error response text".to_string());
-                        eprintln!(
+                        error!(
                             "[PROCESS_EVENT] 删除粗筛记录失败，状态码: {}. 错误信息: {}",
                             status,
                             &err_text[..std::cmp::min(err_text.len(), 200)]
@@ -1434,7 +2636,7 @@ impl FileMonitor {
                     }
                 }
                 Err(e) => {
-                    eprintln!("[PROCESS_EVENT] 发送删除请求失败: {}", e);
+                    error!("[PROCESS_EVENT] 发送删除请求失败: {}", e);
                 }
             }
 
@@ -1442,10 +2644,10 @@ impl FileMonitor {
         }
 
         // 检查路径是否属于当前监控目录，忽略已删除目录的事件
-        let path_str = path.to_string_lossy().to_string();
         let belongs_to_monitored_dir = {
-            let dirs = self.monitored_dirs.lock().unwrap();
-            // println!("[DEBUG] 检查路径 {:?} 是否属于监控目录", path_str);
+            let snapshot = self.snapshot();
+            let dirs = &snapshot.monitored_dirs;
+            // info!("[DEBUG] 检查路径 {:?} 是否属于监控目录", path);
             // println!("[DEBUG] 当前监控目录列表:");
             // for (i, dir) in dirs.iter().enumerate() {
             //     // 展开波浪号路径
@@ -1474,7 +2676,7 @@ impl FileMonitor {
                 } else {
                     dir.path.clone()
                 };
-                path_str.starts_with(&expanded_path)
+                crate::path_utils::path_starts_with(&path, Path::new(&expanded_path))
             });
             // println!("[DEBUG] 匹配结果: {}", belongs);
             belongs
@@ -1486,15 +2688,15 @@ impl FileMonitor {
         }
 
         // 强制检查配置缓存是否存在 - 确保API已就绪
-        if self.config_cache.lock().unwrap().is_none() {
-            eprintln!("[PROCESS_EVENT] Config cache is not populated. Cannot process file event for {:?}. Attempting to fetch.", path);
+        if self.snapshot().config.is_none() {
+            warn!("[PROCESS_EVENT] Config cache is not populated. Cannot process file event for {:?}. Attempting to fetch.", path);
             match self.fetch_and_store_all_config().await {
-                Ok(_) => println!(
+                Ok(_) => info!(
                     "[PROCESS_EVENT] Config fetched successfully. Processing for {:?}",
                     path
                 ),
                 Err(e) => {
-                    eprintln!(
+                    error!(
                         "[PROCESS_EVENT] Failed to fetch config: {}. Aborting processing for {:?}",
                         e, path
                     );
@@ -1509,9 +2711,15 @@ impl FileMonitor {
             return None;
         }
 
-        // 忽略系统隐藏文件，如 .DS_Store - 次优先检查
-        if Self::is_hidden_file(&path) {
-            println!(
+        // 本次事件的后续检查都使用同一份配置
+        let snapshot = self.snapshot();
+
+        // 忽略系统隐藏文件，如 .DS_Store、desktop.ini，以及Windows隐藏目录中的文件 - 次优先检查；
+        // 所在监控目录开启了隐藏文件索引时不忽略
+        if crate::path_utils::is_hidden_or_in_hidden_dir(&path)
+            && !Self::hidden_files_allowed(&snapshot.monitored_dirs, &path)
+        {
+            debug!(
                 "[PROCESS_EVENT] Path {:?} is a hidden file. Ignoring.",
                 path
             );
@@ -1525,25 +2733,14 @@ impl FileMonitor {
         if path.is_file() && !is_bundle {
             // 添加 !is_bundle 条件，让bundle文件跳过白名单检查
             // 获取配置中的有效扩展名集合
-            let valid_extensions: std::collections::HashSet<String> = {
-                let config_guard = self.config_cache.lock().unwrap();
-                if let Some(config) = config_guard.as_ref() {
-                    config
-                        .file_extension_maps
-                        .iter()
-                        .map(|map| map.extension.to_lowercase())
-                        .collect()
-                } else {
-                    std::collections::HashSet::new()
-                }
-            };
+            let valid_extensions = &snapshot.valid_extensions;
 
             // 如果有效扩展名集合不为空，进行扩展名检查（不检查bundle文件）
             if !valid_extensions.is_empty() {
                 if let Some(ext) = Self::extract_extension(&path) {
                     let ext_lower = ext.to_lowercase();
                     if !valid_extensions.contains(&ext_lower) {
-                        println!("[PROCESS_EVENT] File {:?} has extension '{}' which is not in our whitelist. Ignoring.", path, ext_lower);
+                        debug!("[PROCESS_EVENT] File {:?} has extension '{}' which is not in our whitelist. Ignoring.", path, ext_lower);
                         if let Ok(mut stats) = self.stats.lock() {
                             stats.filtered_files += 1;
                         }
@@ -1552,7 +2749,7 @@ impl FileMonitor {
                 } else if path.is_file() {
                     // 没有扩展名的文件
                     // 如果是文件且没有扩展名，也进行过滤（可选，取决于是否要处理无扩展名文件）
-                    println!(
+                    debug!(
                         "[PROCESS_EVENT] File {:?} has no extension. Ignoring.",
                         path
                     );
@@ -1568,7 +2765,7 @@ impl FileMonitor {
         if let Some(bundle_path) = Self::is_inside_macos_bundle(&path) {
             if !is_bundle {
                 // 如果是bundle内部文件，但自身不是bundle
-                println!("[PROCESS_EVENT] Path {:?} is inside bundle {:?}. Redirecting event to the bundle.", path, bundle_path);
+                debug!("[PROCESS_EVENT] Path {:?} is inside bundle {:?}. Redirecting event to the bundle.", path, bundle_path);
                 // 使用 Box::pin 处理递归调用，避免无限大的 Future
                 return Box::pin(self.process_file_event(bundle_path, event_kind, app_handle))
                     .await;
@@ -1580,7 +2777,7 @@ impl FileMonitor {
         if path.is_dir() && cfg!(target_os = "macos") {
             let info_plist = path.join("Contents/Info.plist");
             if info_plist.exists() {
-                println!(
+                debug!(
                     "[PROCESS_EVENT] Path {:?} is a macOS bundle folder (by Info.plist).",
                     path
                 );
@@ -1603,7 +2800,7 @@ impl FileMonitor {
 
                 if dot_files_count > 5 {
                     // 如果有超过5个隐藏文件，可能是一个macOS包
-                    println!("[PROCESS_EVENT] Path {:?} contains many hidden files ({}). Likely a macOS bundle.", path, dot_files_count);
+                    debug!("[PROCESS_EVENT] Path {:?} contains many hidden files ({}). Likely a macOS bundle.", path, dot_files_count);
                     is_bundle = true; // 标记为bundle，但继续处理
                 }
             }
@@ -1612,7 +2809,7 @@ impl FileMonitor {
         // 忽略黑名单中的路径 - 需要在bundle检查之后执行，但在获取元数据前执行
         // 这样可以避免对黑名单中的路径进行不必要的文件元数据操作
         if self.is_in_blacklist(&path) {
-            println!("[PROCESS_EVENT] Path {:?} is in blacklist. Ignoring.", path);
+            debug!("[PROCESS_EVENT] Path {:?} is in blacklist. Ignoring.", path);
             if let Ok(mut stats) = self.stats.lock() {
                 stats.filtered_files += 1;
             }
@@ -1635,7 +2832,7 @@ impl FileMonitor {
 
         // 如果是macOS bundle文件，在元数据中标记
         if is_bundle || is_bundle_by_plist {
-            println!("[PROCESS_EVENT] Marking path {:?} as macOS bundle.", path);
+            debug!("[PROCESS_EVENT] Marking path {:?} as macOS bundle.", path);
             metadata.is_os_bundle = Some(true);
 
             // 在统计中记录bundle数量
@@ -1644,8 +2841,11 @@ impl FileMonitor {
             }
         }
 
-        // 仅为文件计算哈希，不为目录计算
-        if !metadata.is_dir {
+        // 仅为文件计算哈希，不为目录计算；低功耗模式下跳过，延迟哈希模式下发送后由后台队列计算
+        if !metadata.is_dir
+            && crate::power_profile::hashing_enabled()
+            && !crate::deferred_hash::enabled()
+        {
             metadata.hash_value = Self::calculate_simple_hash(&path, 4096).await;
         }
 
@@ -1660,7 +2860,7 @@ impl FileMonitor {
             // 只有非bundle文件才检查排除标记
             if let Some(extra_meta) = &metadata.extra_metadata {
                 if extra_meta.get("excluded_by_rule_id").is_some() {
-                    println!("[PROCESS_EVENT] File {:?} was excluded by rule: {:?}. Not processing further.", metadata.file_path, extra_meta.get("excluded_by_rule_name"));
+                    debug!("[PROCESS_EVENT] File {:?} was excluded by rule: {:?}. Not processing further.", metadata.file_path, extra_meta.get("excluded_by_rule_name"));
                     // 如果文件被标记为排除，直接返回None，不进行进一步处理
                     return None;
                 }
@@ -1683,7 +2883,7 @@ impl FileMonitor {
         {
             let mut is_running = self.is_batch_processor_running.lock().unwrap();
             if *is_running {
-                println!("[BATCH_PROC] 批处理器已在运行，跳过重复启动");
+                debug!("[BATCH_PROC] 批处理器已在运行，跳过重复启动");
                 return;
             }
             *is_running = true;
@@ -1706,32 +2906,66 @@ impl FileMonitor {
             directory_skipped: 0,
             bundle_skipped: 0,
             processed_files: 0,
+            duplicates_merged: 0,
+            unchanged_skipped: 0,
         };
 
-        println!(
+        info!(
             "[BATCH_PROC] 启动批处理器，批量大小={}, 间隔={:?}",
             batch_size, batch_interval
         );
-        let mut batch = Vec::with_capacity(batch_size);
+        let mut batch = PendingBatch::default();
+        // 进行中的批量请求
+        let mut in_flight = tokio::task::JoinSet::new();
         let mut last_send = tokio::time::Instant::now();
+        let mut flush_rx = self.batch_flush_request.subscribe();
+        // 尚未完成的排空请求，等通道中已排队的元数据都进入批处理后再发送
+        let mut pending_drain: Option<u64> = None;
 
         loop {
+            if let Some(request_id) = pending_drain {
+                if rx.is_empty() {
+                    if !batch.is_empty() {
+                        info!(
+                            "[BATCH_PROC] 收到排空请求，正在发送剩余批处理 ({} 项)",
+                            batch.len()
+                        );
+                        if let Err(e) = self.flush_pending_batch(&mut batch, &mut in_flight).await {
+                            error!("[BATCH_PROC] 排空批处理发送错误: {}", e);
+                        }
+                        last_send = tokio::time::Instant::now();
+                    }
+                    // 等待进行中的批量请求全部完成后再确认排空
+                    while in_flight.join_next().await.is_some() {}
+                    self.batch_flush_ack.store(request_id, Ordering::SeqCst);
+                    pending_drain = None;
+                }
+            }
+
             tokio::select! {
+                Ok(()) = flush_rx.changed() => {
+                    // 应用退出前的排空请求：先接收通道中剩余的元数据，再发送最后一批
+                    pending_drain = Some(*flush_rx.borrow_and_update());
+                },
                 maybe_metadata = rx.recv() => {
                     if let Some(metadata) = maybe_metadata {
                         stats.received_files += 1;
 
-                        // 跳过隐藏文件 - 高优先级过滤条件
-                        if metadata.is_hidden {
+                        // 跳过隐藏文件 - 高优先级过滤条件，所在监控目录开启了隐藏文件索引时除外
+                        let hidden_allowed = metadata.is_hidden && Self::hidden_files_allowed(
+                            &self.snapshot().monitored_dirs,
+                            Path::new(&metadata.file_path),
+                        );
+                        if metadata.is_hidden && !hidden_allowed {
                             stats.hidden_files_skipped += 1;
-                            println!("[BATCH_PROC] 跳过隐藏文件: {:?}", metadata.file_path);
+                            debug!("[BATCH_PROC] 跳过隐藏文件: {:?}", metadata.file_path);
                             continue;
                         }
 
                         // 检查是否为macOS bundle文件
                         // 现在我们不再跳过bundle文件，而是将其作为单个文件处理
                         if metadata.is_os_bundle.unwrap_or(false) {
-                            println!("[BATCH_PROC] 处理macOS bundle文件: {:?}", metadata.file_path);
+                            debug!("[BATCH_PROC] 处理macOS bundle文件: {:?}", metadata.file_path);
                             // 仍然计数，但是不跳过
                             //stats.bundle_skipped += 1;
                             //continue;
@@ -1741,7 +2975,7 @@ impl FileMonitor {
                         if let Some(extra) = &metadata.extra_metadata {
                             if extra.get("excluded_by_rule_id").is_some() {
                                 stats.rule_excluded_files_skipped += 1;
-                                println!("[BATCH_PROC] 跳过已排除的文件: {:?} (规则: {:?})", metadata.file_path, extra.get("excluded_by_rule_name"));
+                                debug!("[BATCH_PROC] 跳过已排除的文件: {:?} (规则: {:?})", metadata.file_path, extra.get("excluded_by_rule_name"));
                                 continue;
                             }
                         }
@@ -1749,29 +2983,27 @@ impl FileMonitor {
                         // 白名单扩展名检查（双重保险）- 但是bundle文件例外
                         if !metadata.is_dir && !metadata.is_os_bundle.unwrap_or(false) {  // 添加对bundle文件的例外
                             // 获取配置中的有效扩展名集合
-                            let valid_extensions: std::collections::HashSet<String> = {
-                                let config_guard = self.config_cache.lock().unwrap();
-                                if let Some(config) = config_guard.as_ref() {
-                                    config.file_extension_maps.iter()
-                                        .map(|map| map.extension.to_lowercase())
-                                        .collect()
-                                } else {
-                                    std::collections::HashSet::new()
-                                }
-                            };
+                            let snapshot = self.snapshot();
+                            let valid_extensions = &snapshot.valid_extensions;
 
                             if !valid_extensions.is_empty() {
                                 if let Some(ext) = &metadata.extension {
                                     let ext_lower = ext.to_lowercase();
                                     if !valid_extensions.contains(&ext_lower) {
                                         stats.invalid_extension_skipped += 1;
-                                        println!("[BATCH_PROC] 跳过非白名单扩展名的文件: {:?} (扩展名: {})", metadata.file_path, ext_lower);
+                                        debug!("[BATCH_PROC] 跳过非白名单扩展名的文件: {:?} (扩展名: {})", metadata.file_path, ext_lower);
                                         continue;
                                     }
                                 } else {
-                                    stats.invalid_extension_skipped += 1;
-                                    println!("[BATCH_PROC] 跳过无扩展名文件: {:?}", metadata.file_path);
-                                    continue;
+                                    // 无扩展名文件：按内容识别为文本时保留，二进制文件跳过
+                                    let is_text = metadata.extra_metadata.as_ref()
+                                        .and_then(|extra| extra.get("detected_kind"))
+                                        .and_then(|kind| kind.as_str()) == Some("text");
+                                    if !(is_text && crate::extensionless::index_text_enabled()) {
+                                        stats.invalid_extension_skipped += 1;
+                                        debug!("[BATCH_PROC] 跳过无扩展名文件: {:?}", metadata.file_path);
+                                        continue;
+                                    }
                                 }
                             }
                         }
@@ -1779,7 +3011,7 @@ impl FileMonitor {
                         // 检查文件名是否包含 .DS_Store (额外检查)
                         if metadata.file_name.contains(".DS_Store") {
                             stats.ds_store_skipped += 1;
-                            println!("[BATCH_PROC] 跳过 .DS_Store 文件: {:?}", metadata.file_path);
+                            debug!("[BATCH_PROC] 跳过 .DS_Store 文件: {:?}", metadata.file_path);
                             continue;
                         }
 
@@ -1790,22 +3022,31 @@ impl FileMonitor {
                             continue;
                         }
 
+                        // 重新扫描和重复事件中，自上次成功发送后没有变化的文件不再发送
+                        if crate::seen_cache::is_unchanged(&metadata) {
+                            stats.unchanged_skipped += 1;
+                            debug!("[BATCH_PROC] 跳过未变化的文件: {:?}", metadata.file_path);
+                            continue;
+                        }
+
                         stats.processed_files += 1;
 
-                        batch.push(metadata);
-                        if batch.len() >= batch_size {
+                        if self.push_pending_batch(&mut batch, metadata).await {
+                            stats.duplicates_merged += 1;
+                            debug!("[BATCH_PROC] 合并批次内的重复路径");
+                        }
+                        if batch.is_full(batch_size) {
                             // println!("[BATCH_PROC] 批处理达到大小限制 ({} 项)，正在发送到API", batch.len());
 
                             // 发送数据到API
-                            if let Err(e) = self.send_batch_metadata_to_api(batch.clone()).await {
-                                eprintln!("[BATCH_PROC] 批量发送错误: {}", e);
+                            if let Err(e) = self.flush_pending_batch(&mut batch, &mut in_flight).await {
+                                error!("[BATCH_PROC] 批量发送错误: {}", e);
                             }
 
-                            batch.clear();
                             last_send = tokio::time::Instant::now();
 
                             // 每次发送后输出统计信息
-                            println!("[BATCH_STATS] 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {})",
+                            info!("[BATCH_STATS] 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {}, 未变化: {}), 合并重复: {}",
                                 stats.received_files,
                                 stats.processed_files,
                                 stats.received_files - stats.processed_files,
@@ -1814,23 +3055,25 @@ impl FileMonitor {
                                 stats.invalid_extension_skipped,
                                 stats.ds_store_skipped,
                                 stats.directory_skipped,
-                                stats.bundle_skipped
+                                stats.bundle_skipped,
+                                stats.unchanged_skipped,
+                                stats.duplicates_merged
                             );
                         }
                     } else {
                         // 通道关闭
                         if !batch.is_empty() {
-                            println!("[BATCH_PROC] 通道关闭，正在发送剩余批处理 ({} 项)", batch.len());
+                            info!("[BATCH_PROC] 通道关闭，正在发送剩余批处理 ({} 项)", batch.len());
 
                             // 发送剩余数据到API
-                            if let Err(e) = self.send_batch_metadata_to_api(batch.clone()).await {
-                                eprintln!("[BATCH_PROC] 最终批量发送错误: {}", e);
+                            if let Err(e) = self.flush_pending_batch(&mut batch, &mut in_flight).await {
+                                error!("[BATCH_PROC] 最终批量发送错误: {}", e);
                             }
-                            batch.clear();
                         }
+                        while in_flight.join_next().await.is_some() {}
 
                         // 输出最终统计信息
-                        println!("[BATCH_PROC] 最终统计: 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {})",
+                        info!("[BATCH_PROC] 最终统计: 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {}, 未变化: {}), 合并重复: {}",
                             stats.received_files,
                             stats.processed_files,
                             stats.received_files - stats.processed_files,
@@ -1839,26 +3082,28 @@ impl FileMonitor {
                             stats.invalid_extension_skipped,
                             stats.ds_store_skipped,
                             stats.directory_skipped,
-                            stats.bundle_skipped
+                            stats.bundle_skipped,
+                            stats.unchanged_skipped,
+                            stats.duplicates_merged
                         );
 
-                        println!("[BATCH_PROC] 元数据通道关闭。退出批处理器。");
+                        info!("[BATCH_PROC] 元数据通道关闭。退出批处理器。");
                         return;
                     }
                 },
                 _ = sleep(batch_interval) => {
-                    if !batch.is_empty() && tokio::time::Instant::now().duration_since(last_send) >= batch_interval {
-                                        println!("[BATCH_PROC] 达到批处理间隔，正在发送批处理 ({} 项)", batch.len());
+                    // 刚从睡眠中恢复时暂停定时发送，积压的元数据留在批次中
+                    if !batch.is_empty() && !crate::sleep_wake::is_paused() && tokio::time::Instant::now().duration_since(last_send) >= batch_interval {
+                                        info!("[BATCH_PROC] 达到批处理间隔，正在发送批处理 ({} 项)", batch.len());
 
                         // 发送数据到API
-                        if let Err(e) = self.send_batch_metadata_to_api(batch.clone()).await {
-                            eprintln!("[BATCH_PROC] 批量发送错误: {}", e);
+                        if let Err(e) = self.flush_pending_batch(&mut batch, &mut in_flight).await {
+                            error!("[BATCH_PROC] 批量发送错误: {}", e);
                         }
-                        batch.clear();
                         last_send = tokio::time::Instant::now();
 
                         // 每次发送后输出统计信息
-                        println!("[BATCH_STATS] 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {})",
+                        info!("[BATCH_STATS] 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {}, 未变化: {}), 合并重复: {}",
                             stats.received_files,
                             stats.processed_files,
                             stats.received_files - stats.processed_files,
@@ -1867,7 +3112,9 @@ impl FileMonitor {
                             stats.invalid_extension_skipped,
                             stats.ds_store_skipped,
                             stats.directory_skipped,
-                            stats.bundle_skipped
+                            stats.bundle_skipped,
+                            stats.unchanged_skipped,
+                            stats.duplicates_merged
                         );
                     }
                 }
@@ -1886,41 +3133,54 @@ impl FileMonitor {
         {
             let mut is_running_guard = self.is_initial_scan_running.lock().unwrap();
             if *is_running_guard {
-                println!("[INITIAL_SCAN] Initial scan has already been initiated or completed for this monitor instance. Skipping.");
+                debug!("[INITIAL_SCAN] Initial scan has already been initiated or completed for this monitor instance. Skipping.");
                 return Ok(());
             }
             *is_running_guard = true; // Mark as initiated
         }
 
-        let directories = self.monitored_dirs.lock().unwrap().clone();
+        // 整个初始扫描使用同一份配置快照，扫描期间的配置刷新不影响本次扫描
+        let snapshot = self.snapshot();
+        let mut directories = snapshot.monitored_dirs.clone();
+        // 最近修改过的监控目录先扫描
+        directories.sort_by_cached_key(|dir| {
+            std::cmp::Reverse(
+                std::fs::metadata(crate::path_utils::normalize_path(Path::new(&dir.path)))
+                    .and_then(|m| m.modified())
+                    .ok(),
+            )
+        });
 
         // 获取完全磁盘访问权限状态
-        let full_disk_access = {
-            let cache_guard = self.config_cache.lock().unwrap();
-            cache_guard
-                .as_ref()
-                .map_or(false, |config| config.full_disk_access)
-        };
+        let full_disk_access = snapshot
+            .config
+            .as_ref()
+            .is_some_and(|config| config.full_disk_access);
 
-        println!(
+        info!(
             "[INITIAL_SCAN] Full disk access status: {}",
             full_disk_access
         );
 
+        // 按真实路径记录已扫描的目录，跨监控目录去重并防止链接循环
+        let mut visited_dirs = crate::link_policy::VisitedDirs::new();
+        // 通道已满时溢出到磁盘，扫描不等待批处理器
+        let mut sender = crate::spill_queue::SpillingSender::new(tx_metadata.clone());
+
         for dir in directories {
             // 使用与 start_monitoring 相同的逻辑来决定是否扫描目录
             // 所有非黑名单目录都扫描
             let should_scan = !dir.is_blacklist;
 
             if !should_scan {
-                println!("[INITIAL_SCAN] 跳过目录: {}", dir.path);
+                debug!("[INITIAL_SCAN] 跳过目录: {}", dir.path);
                 continue;
             }
 
-            println!("[INITIAL_SCAN] 扫描目录: {}", dir.path);
-            let path = PathBuf::from(&dir.path);
+            info!("[INITIAL_SCAN] 扫描目录: {}", dir.path);
+            let path = crate::path_utils::normalize_path(Path::new(&dir.path));
             if !path.exists() {
-                println!("[INITIAL_SCAN] 目录不存在: {}", dir.path);
+                warn!("[INITIAL_SCAN] 目录不存在: {}", dir.path);
                 continue;
             }
 
@@ -1932,12 +3192,20 @@ impl FileMonitor {
             let mut processed_files = 0;
             let mut skipped_bundles = 0;
 
-            println!("[INITIAL_SCAN] 开始递归扫描目录: {}", dir.path);
+            info!("[INITIAL_SCAN] 开始递归扫描目录: {}", dir.path);
 
             // 修改扫描方法，使用过滤器来排除不需要处理的路径
-            let walker = WalkDir::new(&path).into_iter().filter_entry(|e| {
-                // 不扫描隐藏文件
-                if Self::is_hidden_file(e.path()) {
+            let walk_dir = WalkDir::new(&path)
+                .follow_links(crate::link_policy::follow_links())
+                .sort_by(Self::recency_first);
+            let walker = walk_dir.into_iter().filter_entry(|e| {
+                // 不扫描隐藏文件，目录开启了隐藏文件索引时除外
+                if !dir.include_hidden && Self::is_hidden_file(e.path()) {
+                    return false;
+                }
+
+                // 按链接策略处理符号链接目录和目录联接
+                if !visited_dirs.should_walk(e) {
                     return false;
                 }
 
@@ -1947,6 +3215,12 @@ impl FileMonitor {
                     return false;
                 }
 
+                // 不扫描项目的构建产物目录，Include 规则指向其中的路径时除外
+                if e.file_type().is_dir() && crate::project_roots::should_skip_dir(e.path()) {
+                    debug!("[INITIAL_SCAN] 跳过构建产物目录: {:?}", e.path());
+                    return false;
+                }
+
                 // 不扫描macOS bundle以及其内部的所有文件
                 if Self::is_macos_bundle_folder(e.path()) {
                     // 只增加bundle计数如果是顶层的bundle（不是bundle内部的文件）
@@ -1956,14 +3230,14 @@ impl FileMonitor {
                         skipped_bundles += 1; // 注意：这是线程安全的，因为在同一线程中
                                               // 不能在这里更新stats，因为这是在过滤器闭包中
                     }
-                    println!("[INITIAL_SCAN] 跳过Bundle: {:?}", e.path());
+                    debug!("[INITIAL_SCAN] 跳过Bundle: {:?}", e.path());
                     return false;
                 }
 
                 // 检查路径中的任何部分是否包含macOS bundle扩展名
                 // 这样可以确保bundle内部的所有文件也被跳过
                 if let Some(bundle_path) = Self::is_inside_macos_bundle(e.path()) {
-                    println!(
+                    debug!(
                         "[INITIAL_SCAN] 跳过Bundle内部文件: {:?}，属于Bundle: {:?}",
                         e.path(),
                         bundle_path
@@ -1983,18 +3257,7 @@ impl FileMonitor {
                 // 如果是文件，检查扩展名是否在白名单中
                 if e.path().is_file() {
                     // 获取配置中的有效扩展名集合
-                    let valid_extensions: std::collections::HashSet<String> = {
-                        let config_guard = self.config_cache.lock().unwrap();
-                        if let Some(config) = config_guard.as_ref() {
-                            config
-                                .file_extension_maps
-                                .iter()
-                                .map(|map| map.extension.to_lowercase())
-                                .collect()
-                        } else {
-                            std::collections::HashSet::new()
-                        }
-                    };
+                    let valid_extensions = &snapshot.valid_extensions;
 
                     if !valid_extensions.is_empty() {
                         if let Some(ext) = Self::extract_extension(e.path()) {
@@ -2024,6 +3287,7 @@ impl FileMonitor {
                 };
 
                 total_files += 1;
+                crate::power_profile::throttle_scan(total_files).await;
                 let entry_path = entry.path().to_path_buf();
 
                 // 每处理1000个文件时重新检查黑名单配置（防止配置更新后继续扫描已加入黑名单的路径）
@@ -2031,7 +3295,7 @@ impl FileMonitor {
                 if files_processed_count % 1000 == 0 {
                     // 动态检查路径是否现在在黑名单中（配置可能已更新）
                     if self.is_in_blacklist(&entry_path) {
-                        println!(
+                        debug!(
                             "[INITIAL_SCAN] 检测到配置更新，跳过新加入黑名单的路径: {:?}",
                             entry_path
                         );
@@ -2049,14 +3313,14 @@ impl FileMonitor {
                     )
                     .await
                 {
-                    let _ = tx_metadata.send(metadata).await;
+                    sender.send(self, metadata).await;
                     processed_files += 1;
                 } else {
                     skipped_files += 1;
                 }
             }
 
-            println!("[INITIAL_SCAN] 目录 {} 扫描完成: 总文件数 {}, 处理文件数 {}, 跳过文件数 {} (其中macOS包数量: {})", 
+            info!("[INITIAL_SCAN] 目录 {} 扫描完成: 总文件数 {}, 处理文件数 {}, 跳过文件数 {} (其中macOS包数量: {})", 
                      dir.path, total_files, processed_files, skipped_files, skipped_bundles);
 
             // 更新全局统计信息
@@ -2067,6 +3331,7 @@ impl FileMonitor {
             }
         }
 
+        sender.finish().await;
         Ok(())
     }
 
@@ -2076,7 +3341,7 @@ impl FileMonitor {
         app_handle: tauri::AppHandle,
     ) -> Result<(), String> {
         // 确保API就绪 - 重试机制
-        println!("[START_MONITORING] 正在等待API服务就绪...");
+        info!("[START_MONITORING] 正在等待API服务就绪...");
 
         // 最多尝试30次，每次等待1秒，共计最多等待30秒
         let max_retries = 30;
@@ -2086,13 +3351,13 @@ impl FileMonitor {
         while !config_fetched && retries < max_retries {
             match self.fetch_and_store_all_config().await {
                 Ok(_) => {
-                    println!("[START_MONITORING] 成功连接到API服务并获取配置！");
+                    info!("[START_MONITORING] 成功连接到API服务并获取配置！");
                     config_fetched = true;
                 }
                 Err(e) => {
                     if retries % 5 == 0 {
                         // 每5次尝试输出一次日志，避免日志过多
-                        println!(
+                        info!(
                             "[START_MONITORING] API服务未就绪，正在重试 ({}/{}): {}",
                             retries, max_retries, e
                         );
@@ -2107,7 +3372,7 @@ impl FileMonitor {
             return Err("无法连接到API服务或获取配置，已达到最大重试次数".to_string());
         }
 
-        let (metadata_tx, metadata_rx) = mpsc::channel::<FileMetadata>(100);
+        let (metadata_tx, metadata_rx) = mpsc::channel::<FileMetadata>(METADATA_CHANNEL_CAPACITY);
         self.metadata_tx = Some(metadata_tx.clone());
 
         // 启动批处理器
@@ -2125,15 +3390,18 @@ impl FileMonitor {
         let metadata_tx_for_scan = metadata_tx; // Pass ownership of this clone
         let app_handle_for_scan = app_handle.clone();
         tokio::spawn(async move {
-            if let Err(e) = self_clone_for_scan
-                .perform_initial_scan(&metadata_tx_for_scan, &app_handle_for_scan)
-                .await
+            // 扫描中按目录批量读取文件元数据
+            if let Err(e) = crate::bulk_metadata::scope(
+                self_clone_for_scan
+                    .perform_initial_scan(&metadata_tx_for_scan, &app_handle_for_scan),
+            )
+            .await
             {
-                eprintln!("[INITIAL_SCAN] Error: {}", e);
+                error!("[INITIAL_SCAN] Error: {}", e);
             }
 
             // 初始扫描后批处理器会自动发送数据到API
-            println!("[INITIAL_SCAN] Initial scan process completed.");
+            info!("[INITIAL_SCAN] Initial scan process completed.");
         });
 
         Ok(())
@@ -2145,30 +3413,44 @@ impl FileMonitor {
         path: &str,
         app_handle: Option<&tauri::AppHandle>,
     ) -> Result<(), String> {
-        println!("[SINGLE_SCAN] 开始扫描单个目录: {}", path);
+        self.scan_directory_modified_since(path, None, app_handle)
+            .await
+    }
+
+    // 扫描单个目录，指定 `modified_since` 时只处理该时间之后修改过的文件（增量扫描）
+    pub async fn scan_directory_modified_since(
+        &self,
+        path: &str,
+        modified_since: Option<SystemTime>,
+        app_handle: Option<&tauri::AppHandle>,
+    ) -> Result<(), String> {
+        info!(
+            "[SINGLE_SCAN] 开始扫描单个目录: {} (修改时间晚于: {:?})",
+            path, modified_since
+        );
 
         // 检查配置缓存是否存在
-        if self.config_cache.lock().unwrap().is_none() {
-            eprintln!("[SINGLE_SCAN] 配置缓存为空，尝试获取配置");
+        if self.snapshot().config.is_none() {
+            warn!("[SINGLE_SCAN] 配置缓存为空，尝试获取配置");
             self.fetch_and_store_all_config().await?;
         }
+        let snapshot = self.snapshot();
 
         // 获取完全磁盘访问权限状态
-        let _full_disk_access = {
-            let cache_guard = self.config_cache.lock().unwrap();
-            cache_guard
-                .as_ref()
-                .map_or(false, |config| config.full_disk_access)
-        };
+        let _full_disk_access = snapshot
+            .config
+            .as_ref()
+            .is_some_and(|config| config.full_disk_access);
 
         // 检查目录是否在黑名单中
-        if self.is_in_blacklist(Path::new(path)) {
-            println!("[SINGLE_SCAN] 目录在黑名单中，跳过扫描: {}", path);
+        if self.is_in_blacklist(&crate::path_utils::normalize_path(Path::new(path))) {
+            debug!("[SINGLE_SCAN] 目录在黑名单中，跳过扫描: {}", path);
             return Ok(());
         }
 
-        // 创建metadata发送通道
-        let (metadata_tx, metadata_rx) = mpsc::channel::<FileMetadata>(100);
+        // 创建metadata发送通道，通道已满时溢出到磁盘
+        let (metadata_tx, metadata_rx) = mpsc::channel::<FileMetadata>(METADATA_CHANNEL_CAPACITY);
+        let mut sender = crate::spill_queue::SpillingSender::new(metadata_tx);
 
         // 启动批处理器
         let batch_size = self.batch_size;
@@ -2181,8 +3463,8 @@ impl FileMonitor {
         });
 
         // 扫描目录
-        println!("[SINGLE_SCAN] 开始扫描目录: {}", path);
-        let path_buf = PathBuf::from(path);
+        info!("[SINGLE_SCAN] 开始扫描目录: {}", path);
+        let path_buf = crate::path_utils::normalize_path(Path::new(path));
         if !path_buf.exists() {
             return Err(format!("目录不存在: {}", path));
         }
@@ -2193,22 +3475,36 @@ impl FileMonitor {
         let mut skipped_bundles = 0;
 
         // 使用 WalkDir 执行递归扫描
-        let walker = WalkDir::new(&path_buf).into_iter().filter_entry(|e| {
-            // 不扫描隐藏文件
-            if Self::is_hidden_file(e.path()) {
+        let mut visited_dirs = crate::link_policy::VisitedDirs::new();
+        let walk_dir = WalkDir::new(&path_buf).follow_links(crate::link_policy::follow_links());
+        let include_hidden = Self::hidden_files_allowed(&snapshot.monitored_dirs, &path_buf);
+        let walker = walk_dir.into_iter().filter_entry(|e| {
+            // 不扫描隐藏文件，所在监控目录开启了隐藏文件索引时除外
+            if !include_hidden && Self::is_hidden_file(e.path()) {
+                return false;
+            }
+
+            // 按链接策略处理符号链接目录和目录联接
+            if !visited_dirs.should_walk(e) {
+                return false;
+            }
+
+            // 不扫描项目的构建产物目录，Include 规则指向其中的路径时除外
+            if e.file_type().is_dir() && crate::project_roots::should_skip_dir(e.path()) {
+                debug!("[SINGLE_SCAN] 跳过构建产物目录: {:?}", e.path());
                 return false;
             }
 
             // 不扫描macOS bundle以及其内部的所有文件
             if Self::is_macos_bundle_folder(e.path()) {
                 skipped_bundles += 1;
-                println!("[SINGLE_SCAN] 跳过Bundle: {:?}", e.path());
+                debug!("[SINGLE_SCAN] 跳过Bundle: {:?}", e.path());
                 return false;
             }
 
             // 检查路径中的任何部分是否包含macOS bundle扩展名
             if let Some(bundle_path) = Self::is_inside_macos_bundle(e.path()) {
-                println!(
+                debug!(
                     "[SINGLE_SCAN] 跳过Bundle内部文件: {:?}，属于Bundle: {:?}",
                     e.path(),
                     bundle_path
@@ -2219,53 +3515,71 @@ impl FileMonitor {
             true
         });
 
-        for entry in walker {
-            match entry {
-                Ok(entry) => {
-                    total_files += 1;
+        // 扫描中按目录批量读取文件元数据
+        crate::bulk_metadata::scope(async {
+            for entry in walker {
+                match entry {
+                    Ok(entry) => {
+                        total_files += 1;
+                        crate::power_profile::throttle_scan(total_files).await;
 
-                    if total_files % 100 == 0 {
-                        println!("[SINGLE_SCAN] 扫描进度: {} 个文件", total_files);
-                    }
+                        if total_files % 100 == 0 {
+                            debug!("[SINGLE_SCAN] 扫描进度: {} 个文件", total_files);
+                        }
 
-                    if !entry.file_type().is_file() {
-                        continue; // 仅处理文件，跳过目录
-                    }
+                        if !entry.file_type().is_file() {
+                            continue; // 仅处理文件，跳过目录
+                        }
 
-                    // 处理单个文件 - 复用现有的 process_file_event 方法
-                    if let Some(app_handle) = app_handle {
-                        if let Some(metadata) = self
-                            .process_file_event(
-                                entry.path().to_path_buf(),
-                                notify::EventKind::Create(notify::event::CreateKind::Any),
-                                app_handle,
-                            )
-                            .await
-                        {
-                            if metadata_tx.send(metadata).await.is_err() {
-                                eprintln!("[SINGLE_SCAN] 无法发送元数据到批处理器，通道可能已关闭");
+                        // 增量扫描：跳过指定时间之前修改的文件
+                        if let Some(since) = modified_since {
+                            let modified = match crate::bulk_metadata::lookup(entry.path()) {
+                                Some(stat) => stat.modified,
+                                None => entry.metadata().ok().and_then(|m| m.modified().ok()),
+                            };
+                            if modified.is_some_and(|modified| modified < since) {
+                                continue;
+                            }
+                        }
+
+                        // 处理单个文件 - 复用现有的 process_file_event 方法
+                        if let Some(app_handle) = app_handle {
+                            if let Some(metadata) = self
+                                .process_file_event(
+                                    entry.path().to_path_buf(),
+                                    notify::EventKind::Create(notify::event::CreateKind::Any),
+                                    app_handle,
+                                )
+                                .await
+                            {
+                                if !sender.send(self, metadata).await {
+                                    error!(
+                                        "[SINGLE_SCAN] 无法发送元数据到批处理器，通道可能已关闭"
+                                    );
+                                }
+                                processed_files += 1;
+                            } else {
+                                skipped_files += 1;
                             }
-                            processed_files += 1;
                         } else {
+                            // 如果没有 app_handle，跳过此文件或使用备用处理逻辑
+                            warn!(
+                                "[SINGLE_SCAN] 跳过文件，因为没有提供 app_handle: {:?}",
+                                entry.path()
+                            );
                             skipped_files += 1;
                         }
-                    } else {
-                        // 如果没有 app_handle，跳过此文件或使用备用处理逻辑
-                        eprintln!(
-                            "[SINGLE_SCAN] 跳过文件，因为没有提供 app_handle: {:?}",
-                            entry.path()
-                        );
+                    }
+                    Err(e) => {
+                        warn!("[SINGLE_SCAN] 无法访问项目: {}", e);
                         skipped_files += 1;
                     }
                 }
-                Err(e) => {
-                    eprintln!("[SINGLE_SCAN] 无法访问项目: {}", e);
-                    skipped_files += 1;
-                }
             }
-        }
+        })
+        .await;
 
-        println!("[SINGLE_SCAN] 目录 {} 扫描完成: 总文件数 {}, 处理文件数 {}, 跳过文件数 {} (其中macOS包数量: {})", 
+        info!("[SINGLE_SCAN] 目录 {} 扫描完成: 总文件数 {}, 处理文件数 {}, 跳过文件数 {} (其中macOS包数量: {})", 
             path, total_files, processed_files, skipped_files, skipped_bundles);
 
         // 更新统计信息
@@ -2275,6 +3589,7 @@ impl FileMonitor {
             stats.filtered_bundles += skipped_bundles as u64;
         }
 
+        sender.finish().await;
         Ok(())
     }
 }
@@ -15,6 +15,7 @@ use chrono::{
     Local,
     TimeZone,
 };
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 // use std::collections::HashSet;
 use std::path::{Path, PathBuf};
@@ -22,7 +23,9 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{command, AppHandle, Emitter, Manager, State}; // 添加Emitter trait
 use walkdir::WalkDir;
 
+use crate::event_buffer::record_app_event;
 use crate::file_monitor::{AllConfigurations, FileExtensionMapRust};
+use crate::startup_stages::{report_stage, StageStatus, StartupStage};
 use crate::AppState; // Import AppState from lib.rs
 
 // 定义文件信息结构
@@ -71,30 +74,9 @@ fn get_file_extension(file_path: &Path) -> Option<String> {
         .map(|ext| ext.to_lowercase())
 }
 
-// 检查文件是否隐藏
+// 检查文件是否隐藏（以.开头，或Windows上带有隐藏/系统属性）
 fn is_hidden_file(path: &Path) -> bool {
-    // 先检查文件/目录名本身是否以.开头
-    let is_name_hidden = path
-        .file_name()
-        .and_then(|name| name.to_str())
-        .map(|name| name.starts_with("."))
-        .unwrap_or(false);
-
-    if is_name_hidden {
-        return true;
-    }
-
-    // 检查路径中是否有任何部分是隐藏目录（以.开头）
-    if let Some(path_str) = path.to_str() {
-        // 分割路径并检查每个部分
-        for part in path_str.split('/') {
-            if !part.is_empty() && part.starts_with(".") && part != "." && part != ".." {
-                return true;
-            }
-        }
-    }
-
-    false
+    crate::path_utils::is_hidden(path)
 }
 
 // 检查是否为macOS bundle文件夹
@@ -104,6 +86,11 @@ fn is_macos_bundle_folder(path: &Path) -> bool {
         return false;
     }
 
+    // macOS 上按 Launch Services 的包标志判断
+    if let Some(is_package) = crate::bundle_detection::is_package(path) {
+        return is_package;
+    }
+
     // 设置常用的bundle扩展名
     let fallback_bundle_extensions = [
         ".app",
@@ -157,6 +144,11 @@ fn is_macos_bundle_folder(path: &Path) -> bool {
 
 // 检查文件是否在macOS bundle内部，如果是则返回bundle路径
 fn is_inside_macos_bundle(path: &Path) -> Option<PathBuf> {
+    // macOS 上向上查找 Launch Services 认定的最外层包
+    if crate::bundle_detection::is_available() {
+        return crate::bundle_detection::containing_package(path);
+    }
+
     if let Some(path_str) = path.to_str() {
         // 检查常见bundle扩展
         let bundle_extensions = [
@@ -346,13 +338,13 @@ pub async fn scan_files_by_time_range(
     time_range: TimeRange,
     app_state: State<'_, AppState>, // Access AppState
 ) -> Result<Vec<FileInfo>, String> {
-    println!("调用 scan_files_by_time_range: {:?}", time_range);
+    info!("调用 scan_files_by_time_range: {:?}", time_range);
 
     let config = app_state.get_config().await?; // Use the AppState to get config
 
-    println!("开始扫描文件...");
+    info!("开始扫描文件...");
     let result = scan_files_with_filter(&config, Some(time_range), None).await;
-    println!(
+    info!(
         "扫描完成, 文件数量: {}",
         result.as_ref().map_or(0, |files| files.len())
     );
@@ -366,13 +358,13 @@ pub async fn scan_files_by_type(
     file_type: FileType,
     app_state: State<'_, AppState>, // Access AppState
 ) -> Result<Vec<FileInfo>, String> {
-    println!("调用 scan_files_by_type: {:?}", file_type);
+    info!("调用 scan_files_by_type: {:?}", file_type);
 
     let config = app_state.get_config().await?; // Use the AppState to get config
 
-    println!("开始扫描文件...");
+    info!("开始扫描文件...");
     let result = scan_files_with_filter(&config, None, Some(file_type)).await;
-    println!(
+    info!(
         "扫描完成, 文件数量: {}",
         result.as_ref().map_or(0, |files| files.len())
     );
@@ -387,7 +379,7 @@ pub async fn scan_files_simplified_command(
     file_type: Option<FileType>,
     app_state: State<'_, AppState>,
 ) -> Result<Vec<FileInfo>, String> {
-    println!(
+    info!(
         "[SIMPLIFIED_SCAN] 调用简化扫描: 时间范围={:?}, 文件类型={:?}",
         time_range, file_type
     );
@@ -399,7 +391,7 @@ pub async fn scan_files_simplified_command(
     let config = app_state.get_config().await?;
     let monitored_folders = &config.monitored_folders;
 
-    println!(
+    info!(
         "[SIMPLIFIED_SCAN] 开始简化扫描，监控文件夹数: {}",
         monitored_folders.len()
     );
@@ -407,8 +399,8 @@ pub async fn scan_files_simplified_command(
         scan_files_simplified(&simplified_config, monitored_folders, time_range, file_type).await;
 
     match &result {
-        Ok(files) => println!("[SIMPLIFIED_SCAN] 扫描完成，文件数量: {}", files.len()),
-        Err(e) => println!("[SIMPLIFIED_SCAN] 扫描失败: {}", e),
+        Ok(files) => info!("[SIMPLIFIED_SCAN] 扫描完成，文件数量: {}", files.len()),
+        Err(e) => warn!("[SIMPLIFIED_SCAN] 扫描失败: {}", e),
     }
 
     result
@@ -420,7 +412,7 @@ pub async fn start_backend_scanning(
     app_handle: tauri::AppHandle,
     app_state: tauri::State<'_, AppState>,
 ) -> Result<bool, String> {
-    println!("[扫描] 启动后端全量扫描工作");
+    info!("[扫描] 启动后端全量扫描工作");
     // println!("[扫描] 【重要提示】此函数只能在前端确认用户已授予完全磁盘访问权限后调用");
     // println!("[扫描] 正确流程：Splash检查权限通过 -> 调用start_backend_scanning -> 进入应用");
 
@@ -433,16 +425,16 @@ pub async fn start_backend_scanning(
         };
         match monitor_option {
             Some(monitor) => {
-                println!("[扫描] Found FileMonitor instance in AppState.");
+                info!("[扫描] Found FileMonitor instance in AppState.");
                 monitor
             }
             None => {
                 // This case should ideally not happen if setup_file_monitoring_infrastructure ran correctly.
-                eprintln!("[扫描] FileMonitor not found in AppState. This is unexpected. Creating a new one.");
+                warn!("[扫描] FileMonitor not found in AppState. This is unexpected. Creating a new one.");
                 let (api_host, api_port) = {
                     let api_state = app_handle.state::<crate::ApiState>();
                     let api_state_guard = api_state.0.lock().unwrap();
-                    if api_state_guard.process_child.is_none() {
+                    if !api_state_guard.is_running() {
                         return Err("API服务未运行，无法启动文件监控".to_string());
                     }
                     (api_state_guard.host.clone(), api_state_guard.port)
@@ -462,30 +454,37 @@ pub async fn start_backend_scanning(
     // which already fetches configuration.
     /*
     if let Err(e) = file_monitor_instance.refresh_all_configurations().await {
-        eprintln!("[扫描] 刷新配置失败: {}", e);
+        error!("[扫描] 刷新配置失败: {}", e);
         return Err(format!("无法刷新配置: {}", e));
     }
 
     // 检查是否有监控目录
     let monitored_dirs = file_monitor_instance.get_monitored_directories();
     if monitored_dirs.is_empty() {
-        println!("[扫描] 没有监控目录，无需启动扫描");
+        info!("[扫描] 没有监控目录，无需启动扫描");
         return Ok(false);
     }
 
-    println!("[扫描] 找到 {} 个监控目录，准备启动扫描", monitored_dirs.len());
+    info!("[扫描] 找到 {} 个监控目录，准备启动扫描", monitored_dirs.len());
     */
 
     // 发送事件通知前端扫描开始
     if let Err(e) = app_handle.emit("scan_started", ()) {
-        eprintln!("[扫描] 发送扫描开始事件失败: {:?}", e);
+        error!("[扫描] 发送扫描开始事件失败: {:?}", e);
     }
+    record_app_event(&app_handle, "scan_started", serde_json::Value::Null);
+    report_stage(
+        &app_handle,
+        StartupStage::InitialScan,
+        StageStatus::Started,
+        None,
+    );
 
     // 启动后台扫描任务
     let app_handle_clone = app_handle.clone();
     tokio::spawn(async move {
         // file_monitor_instance is moved into this task.
-        println!("[扫描] 开始执行全量扫描");
+        info!("[扫描] 开始执行全量扫描");
 
         // 设置扫描完成标志为false
         let app_state_handle = app_handle_clone.state::<AppState>();
@@ -500,7 +499,7 @@ pub async fn start_backend_scanning(
             .await
         {
             Ok(_) => {
-                println!("[扫描] 初始扫描和监控设置完成");
+                info!("[扫描] 初始扫描和监控设置完成");
 
                 // 更新扫描完成标志
                 {
@@ -517,13 +516,27 @@ pub async fn start_backend_scanning(
                     app_state_handle.update_config(config);
                     let mut app_state_monitor_guard = app_state_handle.file_monitor.lock().unwrap();
                     *app_state_monitor_guard = Some(file_monitor_instance.clone());
-                    println!("[扫描] 已更新AppState配置");
+                    info!("[扫描] 已更新AppState配置");
                 }
 
                 // 发送事件通知前端扫描完成
                 if let Err(e) = app_handle_clone.emit("scan_completed", true) {
-                    eprintln!("[扫描] 发送扫描完成事件失败: {:?}", e);
+                    error!("[扫描] 发送扫描完成事件失败: {:?}", e);
                 }
+                record_app_event(&app_handle_clone, "scan_completed", serde_json::json!(true));
+                crate::tray_status::set_monitor_error(&app_handle_clone, false);
+                report_stage(
+                    &app_handle_clone,
+                    StartupStage::InitialScan,
+                    StageStatus::Completed,
+                    None,
+                );
+                report_stage(
+                    &app_handle_clone,
+                    StartupStage::Ready,
+                    StageStatus::Completed,
+                    None,
+                );
 
                 // 初始化或重新初始化防抖动监控器
                 let debounced_monitor_state = app_state_handle.debounced_file_monitor.clone();
@@ -536,11 +549,11 @@ pub async fn start_backend_scanning(
 
                 let mut debounced_monitor = match debounced_monitor_opt {
                     Some(monitor) => {
-                        println!("[扫描] 使用已存在的防抖动监控器");
+                        info!("[扫描] 使用已存在的防抖动监控器");
                         monitor
                     }
                     None => {
-                        println!("[扫描] 创建新的防抖动监控器");
+                        info!("[扫描] 创建新的防抖动监控器");
                         // 创建新的防抖动监控器
                         let monitor_arc = std::sync::Arc::new(file_monitor_instance.clone());
                         let new_monitor = crate::file_monitor_debounced::DebouncedFileMonitor::new(
@@ -567,20 +580,17 @@ pub async fn start_backend_scanning(
                     .collect();
 
                 if directories.is_empty() {
-                    println!("[扫描] 没有需要监控的白名单目录，跳过防抖动监控器启动");
+                    info!("[扫描] 没有需要监控的白名单目录，跳过防抖动监控器启动");
                 } else {
-                    println!(
+                    info!(
                         "[扫描] 正在启动防抖动监控，监控 {} 个目录",
                         directories.len()
                     );
 
-                    if let Err(e) = debounced_monitor
-                        .start_monitoring(directories, std::time::Duration::from_millis(2_000))
-                        .await
-                    {
-                        eprintln!("[扫描] 启动防抖动监控失败: {}", e);
+                    if let Err(e) = debounced_monitor.start_monitoring(directories).await {
+                        error!("[扫描] 启动防抖动监控失败: {}", e);
                     } else {
-                        println!("[扫描] 防抖动监控已启动");
+                        info!("[扫描] 防抖动监控已启动");
 
                         // 更新 AppState 中的防抖动监控器
                         {
@@ -591,12 +601,24 @@ pub async fn start_backend_scanning(
                 }
             }
             Err(e) => {
-                eprintln!("[扫描] 初始扫描失败: {}", e);
+                error!("[扫描] 初始扫描失败: {}", e);
+                report_stage(
+                    &app_handle_clone,
+                    StartupStage::InitialScan,
+                    StageStatus::Failed,
+                    Some(e.to_string()),
+                );
 
                 // 发送事件通知前端扫描失败
                 if let Err(emit_err) = app_handle_clone.emit("scan_error", e.to_string()) {
-                    eprintln!("[扫描] 发送扫描错误事件失败: {:?}", emit_err);
+                    error!("[扫描] 发送扫描错误事件失败: {:?}", emit_err);
                 }
+                record_app_event(
+                    &app_handle_clone,
+                    "scan_error",
+                    serde_json::json!(e.to_string()),
+                );
+                crate::tray_status::set_monitor_error(&app_handle_clone, true);
             }
         }
     });
@@ -608,7 +630,7 @@ pub async fn start_backend_scanning(
 fn log_permission_check(action: &str, path: &Path) {
     #[cfg(target_os = "macos")]
     {
-        println!(
+        info!(
             "[权限] {} 访问路径: {} - 提示：此访问应当在前端权限验证通过后进行",
             action,
             path.display()
@@ -617,7 +639,7 @@ fn log_permission_check(action: &str, path: &Path) {
 
     #[cfg(not(target_os = "macos"))]
     {
-        println!("[权限] {} 访问路径: {}", action, path.display());
+        info!("[权限] {} 访问路径: {}", action, path.display());
     }
 }
 
@@ -650,24 +672,38 @@ async fn scan_files_with_filter(
         total_included: 0,
     };
 
+    // 按真实路径记录已扫描的目录，跨监控目录去重并防止链接循环
+    let mut visited_dirs = crate::link_policy::VisitedDirs::new();
+
+    // 系统索引查询用的扩展名：按文件类型过滤时只查询该类型的扩展名
+    let indexed_extensions: Vec<String> = match file_type {
+        Some(ref ft) if *ft != FileType::All => extension_maps
+            .iter()
+            .filter(|map| get_category_ids_for_file_type(ft).contains(&map.category_id))
+            .map(|map| map.extension.to_lowercase())
+            .collect(),
+        _ => Vec::new(),
+    };
+
     for monitored_dir in &config.monitored_folders {
         // Only scan authorized and non-blacklisted directories
         // 只扫描非黑名单目录
         let should_scan = !monitored_dir.is_blacklist;
 
         if !should_scan {
-            println!("[SCAN] 跳过黑名单目录 {:?}", monitored_dir.path);
+            info!("[SCAN] 跳过黑名单目录 {:?}", monitored_dir.path);
             continue;
         }
 
-        let path = Path::new(&monitored_dir.path);
+        let path_buf = crate::path_utils::normalize_path(Path::new(&monitored_dir.path));
+        let path = path_buf.as_path();
 
         // 记录权限敏感目录的访问
         log_permission_check("开始扫描", path);
 
         // 确保前端已经验证权限
         if path.to_string_lossy().contains("/Users") {
-            println!(
+            info!(
                 "[SCAN] 访问用户敏感目录: {:?} - 应该已经通过前端权限检查",
                 path
             );
@@ -677,39 +713,68 @@ async fn scan_files_with_filter(
             continue;
         }
 
-        for entry in WalkDir::new(path)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
+        // 候选文件：优先查询系统索引，不可用时回退到 WalkDir 遍历
+        let candidates: Box<dyn Iterator<Item = (PathBuf, bool)> + Send + '_> =
+            match crate::indexed_search::query_files(path, time_range.as_ref(), &indexed_extensions)
+            {
+                Some(paths) => Box::new(
+                    paths
+                        .into_iter()
+                        .filter(|path| !crate::backup_exclusion::is_backup_path(path))
+                        .map(|path| {
+                            let is_file = path.is_file();
+                            (path, is_file)
+                        }),
+                ),
+                None => Box::new(
+                    WalkDir::new(path)
+                        .follow_links(crate::link_policy::follow_links())
+                        .into_iter()
+                        .filter_entry(|e| {
+                            !crate::backup_exclusion::is_backup_path(e.path())
+                                && visited_dirs.should_walk(e)
+                                && !(e.file_type().is_dir()
+                                    && crate::project_roots::should_skip_dir(e.path()))
+                        })
+                        .filter_map(|e| e.ok())
+                        .map(|entry| (entry.path().to_path_buf(), entry.file_type().is_file())),
+                ),
+            };
+
+        for (entry_path, is_file) in candidates {
             stats.total_discovered += 1;
+            crate::power_profile::throttle_scan(stats.total_discovered).await;
 
-            // 首先，最高优先级过滤 - 隐藏文件
-            if is_hidden_file(entry.path()) {
+            // 首先，最高优先级过滤 - 隐藏文件（目录开启了隐藏文件索引时不过滤）
+            if !monitored_dir.include_hidden && is_hidden_file(&entry_path) {
                 stats.hidden_filtered += 1;
                 continue;
             }
 
             // 检查是否为macOS bundle或位于bundle内部（高优先级过滤）
-            if is_macos_bundle_folder(entry.path()) {
+            if is_macos_bundle_folder(&entry_path) {
                 stats.bundle_filtered += 1;
                 continue;
             }
 
-            if let Some(_) = is_inside_macos_bundle(entry.path()) {
+            if let Some(_) = is_inside_macos_bundle(&entry_path) {
                 stats.bundle_filtered += 1;
                 continue;
             }
 
             // 路径级别过滤 - 检查路径中是否包含需要过滤的目录
-            let path = entry.path();
+            let path = entry_path.as_path();
             let mut should_skip = false;
 
             for component in path.components() {
                 if let std::path::Component::Normal(name) = component {
                     if let Some(name_str) = name.to_str() {
                         // 过滤掉路径中包含以点开头的目录（隐藏目录）
-                        if name_str.starts_with(".") && name_str != "." && name_str != ".." {
+                        if !monitored_dir.include_hidden
+                            && name_str.starts_with(".")
+                            && name_str != "."
+                            && name_str != ".."
+                        {
                             stats.hidden_filtered += 1;
                             should_skip = true;
                             break;
@@ -728,20 +793,25 @@ async fn scan_files_with_filter(
             }
 
             // 只处理文件（不处理目录）
-            if !entry.file_type().is_file() {
+            if !is_file {
                 continue;
             }
 
-            let file_path = entry.path();
+            let file_path = entry_path.as_path();
             let extension = get_file_extension(file_path);
+            // 像文本的无扩展名文件（笔记、脚本等）按 txt 过滤和分类
+            let classify_extension = extension.clone().or_else(|| {
+                crate::extensionless::is_indexable_text(file_path)
+                    .then(|| crate::extensionless::TEXT_EXTENSION.to_string())
+            });
 
             // 白名单扩展名过滤：只处理有扩展名且扩展名在配置白名单中的文件
-            if let Some(ref ext) = extension {
+            if let Some(ref ext) = classify_extension {
                 let ext_lower = ext.to_lowercase();
                 if !valid_extensions.contains(&ext_lower) {
                     // 扩展名不在白名单中，跳过并记录
                     stats.extension_filtered += 1;
-                    println!(
+                    info!(
                         "[SCAN] 跳过非白名单扩展名文件: {} (扩展名: {})",
                         file_path.display(),
                         ext_lower
@@ -751,14 +821,14 @@ async fn scan_files_with_filter(
             } else if file_type != Some(FileType::All) {
                 // 没有扩展名且不是查找所有文件类型，跳过
                 stats.extension_filtered += 1;
-                println!("[SCAN] 跳过无扩展名文件: {}", file_path.display());
+                info!("[SCAN] 跳过无扩展名文件: {}", file_path.display());
                 continue;
             }
 
             // 应用文件类型过滤器
             if let Some(ref ft) = file_type {
-                if !is_file_of_type(&extension, ft, extension_maps) {
-                    println!(
+                if !is_file_of_type(&classify_extension, ft, extension_maps) {
+                    info!(
                         "[SCAN] 跳过不匹配类型过滤器的文件: {} (期望类型: {:?})",
                         file_path.display(),
                         ft
@@ -771,7 +841,7 @@ async fn scan_files_with_filter(
             let metadata = match std::fs::metadata(file_path) {
                 Ok(meta) => meta,
                 Err(e) => {
-                    println!(
+                    info!(
                         "[SCAN] 无法获取文件元数据: {} (错误: {})",
                         file_path.display(),
                         e
@@ -794,7 +864,7 @@ async fn scan_files_with_filter(
             // 应用时间范围过滤器
             if let Some(ref tr) = time_range {
                 if !is_file_in_time_range(modified_time_secs, tr) {
-                    println!(
+                    info!(
                         "[SCAN] 跳过不在时间范围内的文件: {} (范围: {:?})",
                         file_path.display(),
                         tr
@@ -816,11 +886,11 @@ async fn scan_files_with_filter(
             let file_name = file_path
                 .file_name()
                 .and_then(|name| name.to_str())
-                .unwrap_or("")
-                .to_string();
+                .map(crate::path_utils::to_nfc)
+                .unwrap_or_default();
 
             // 根据扩展名匹配分类ID
-            let category_id = extension.as_ref().and_then(|ext| {
+            let category_id = classify_extension.as_ref().and_then(|ext| {
                 extension_maps
                     .iter()
                     .find(|map| map.extension.to_lowercase() == ext.to_lowercase())
@@ -829,7 +899,7 @@ async fn scan_files_with_filter(
 
             // 文件通过了所有过滤器，添加到结果列表
             files.push(FileInfo {
-                file_path: file_path.to_string_lossy().into_owned(),
+                file_path: crate::path_utils::path_to_nfc_string(&file_path),
                 file_name,
                 file_size,
                 extension,
@@ -842,15 +912,15 @@ async fn scan_files_with_filter(
 
             // 返回前500个文件
             if files.len() >= 500 {
-                println!("[SCAN] 已达到500个文件的限制，停止扫描");
+                info!("[SCAN] 已达到500个文件的限制，停止扫描");
                 break;
             }
         }
     }
 
     // 打印扫描统计信息
-    println!("[SCAN] 扫描统计: 发现文件总数: {}, 包含文件数: {}, 被过滤文件数: {} (隐藏: {}, 扩展名: {}, Bundle: {})", 
-        stats.total_discovered, 
+    info!("[SCAN] 扫描统计: 发现文件总数: {}, 包含文件数: {}, 被过滤文件数: {} (隐藏: {}, 扩展名: {}, Bundle: {})", 
+        stats.total_discovered,
         stats.total_included,
         stats.hidden_filtered + stats.extension_filtered + stats.bundle_filtered,
         stats.hidden_filtered,
@@ -871,56 +941,66 @@ async fn scan_files_simplified(
     let mut files = Vec::new();
     let mut stats = ScanStats::default();
 
-    println!(
+    info!(
         "[SCAN_SIMPLIFIED] 开始简化扫描，监控文件夹数: {}",
         monitored_folders.len()
     );
-    println!(
+    info!(
         "[SCAN_SIMPLIFIED] 配置：扩展名映射: {}, Bundle扩展名: {}, 忽略规则: {}",
         config.extension_mappings.len(),
         config.bundle_extensions.len(),
         config.ignore_patterns.len()
     );
 
+    // 按真实路径记录已扫描的目录，跨监控文件夹去重并防止链接循环
+    let mut visited_dirs = crate::link_policy::VisitedDirs::new();
+
     // 遍历所有监控的文件夹
     for folder in monitored_folders {
         if folder.is_blacklist {
-            println!("[SCAN_SIMPLIFIED] 跳过黑名单文件夹: {}", folder.path);
+            info!("[SCAN_SIMPLIFIED] 跳过黑名单文件夹: {}", folder.path);
             continue;
         }
 
-        let folder_path = PathBuf::from(&folder.path);
+        let folder_path = crate::path_utils::normalize_path(Path::new(&folder.path));
         if !folder_path.exists() {
-            println!("[SCAN_SIMPLIFIED] 文件夹不存在: {}", folder.path);
+            warn!("[SCAN_SIMPLIFIED] 文件夹不存在: {}", folder.path);
             continue;
         }
 
-        println!("[SCAN_SIMPLIFIED] 扫描文件夹: {}", folder.path);
+        info!("[SCAN_SIMPLIFIED] 扫描文件夹: {}", folder.path);
 
         // 使用walkdir遍历文件夹
-        let walker = WalkDir::new(&folder_path).follow_links(false).max_depth(10); // 限制最大深度避免无限递归
-
-        for entry in walker.into_iter() {
+        let walker = WalkDir::new(&folder_path)
+            .follow_links(crate::link_policy::follow_links())
+            .max_depth(10); // 限制最大深度避免无限递归
+
+        for entry in walker.into_iter().filter_entry(|e| {
+            !crate::backup_exclusion::is_backup_path(e.path())
+                && visited_dirs.should_walk(e)
+                && !(e.file_type().is_dir() && crate::project_roots::should_skip_dir(e.path()))
+        }) {
             let entry = match entry {
                 Ok(e) => e,
                 Err(e) => {
-                    println!("[SCAN_SIMPLIFIED] 读取文件时出错: {}", e);
+                    warn!("[SCAN_SIMPLIFIED] 读取文件时出错: {}", e);
                     continue;
                 }
             };
 
             let file_path = entry.path();
             stats.total_discovered += 1;
+            crate::power_profile::throttle_scan(stats.total_discovered).await;
 
-            // 检查是否为隐藏文件
-            if is_hidden_file(file_path) {
+            // 检查是否为隐藏文件（文件夹开启了隐藏文件索引时不过滤）
+            if !folder.include_hidden && is_hidden_file(file_path) {
                 stats.hidden_filtered += 1;
                 continue;
             }
 
             // 检查是否为Bundle
             if file_path.is_dir() && is_macos_bundle(file_path, &config.bundle_extensions) {
-                println!("[SCAN_SIMPLIFIED] 发现Bundle: {}", file_path.display());
+                info!("[SCAN_SIMPLIFIED] 发现Bundle: {}", file_path.display());
 
                 // 将Bundle作为整体文件处理
                 let bundle_extension = get_file_extension(file_path);
@@ -982,11 +1062,11 @@ async fn scan_files_simplified(
                         let file_name = file_path
                             .file_name()
                             .and_then(|name| name.to_str())
-                            .unwrap_or("")
-                            .to_string();
+                            .map(crate::path_utils::to_nfc)
+                            .unwrap_or_default();
 
                         files.push(FileInfo {
-                            file_path: file_path.to_string_lossy().into_owned(),
+                            file_path: crate::path_utils::path_to_nfc_string(&file_path),
                             file_name,
                             file_size: metadata.len(),
                             extension: bundle_extension,
@@ -996,18 +1076,18 @@ async fn scan_files_simplified(
                         });
 
                         stats.total_included += 1;
-                        println!(
+                        info!(
                             "[SCAN_SIMPLIFIED] 包含Bundle: {} (分类: {})",
                             file_path.display(),
                             category_id
                         );
                     } else {
                         stats.bundle_filtered += 1;
-                        println!("[SCAN_SIMPLIFIED] Bundle扩展名不在关注范围: {}", ext);
+                        info!("[SCAN_SIMPLIFIED] Bundle扩展名不在关注范围: {}", ext);
                     }
                 } else {
                     stats.bundle_filtered += 1;
-                    println!(
+                    info!(
                         "[SCAN_SIMPLIFIED] Bundle无法获取扩展名: {}",
                         file_path.display()
                     );
@@ -1020,7 +1100,7 @@ async fn scan_files_simplified(
             // 检查是否在Bundle内部
             if let Some(bundle_path) = find_containing_bundle(file_path, &config.bundle_extensions)
             {
-                println!(
+                info!(
                     "[SCAN_SIMPLIFIED] 跳过Bundle内部文件: {} (Bundle: {})",
                     file_path.display(),
                     bundle_path.display()
@@ -1036,9 +1116,14 @@ async fn scan_files_simplified(
 
             // 获取文件扩展名
             let extension = get_file_extension(file_path);
+            // 像文本的无扩展名文件（笔记、脚本等）按 txt 分类
+            let classify_extension = extension.clone().or_else(|| {
+                crate::extensionless::is_indexable_text(file_path)
+                    .then(|| crate::extensionless::TEXT_EXTENSION.to_string())
+            });
 
             // 只包含在扩展名映射中的文件
-            let category_id = if let Some(ref ext) = extension {
+            let category_id = if let Some(ref ext) = classify_extension {
                 if let Some(&cat_id) = config.extension_mappings.get(ext) {
                     cat_id
                 } else {
@@ -1103,11 +1188,11 @@ async fn scan_files_simplified(
             let file_name = file_path
                 .file_name()
                 .and_then(|name| name.to_str())
-                .unwrap_or("")
-                .to_string();
+                .map(crate::path_utils::to_nfc)
+                .unwrap_or_default();
 
             files.push(FileInfo {
-                file_path: file_path.to_string_lossy().into_owned(),
+                file_path: crate::path_utils::path_to_nfc_string(&file_path),
                 file_name,
                 file_size: metadata.len(),
                 extension,
@@ -1120,7 +1205,7 @@ async fn scan_files_simplified(
 
             // 限制返回文件数量
             if files.len() >= 500 {
-                println!("[SCAN_SIMPLIFIED] 已达到500个文件的限制，停止扫描");
+                info!("[SCAN_SIMPLIFIED] 已达到500个文件的限制，停止扫描");
                 break;
             }
         }
@@ -1132,8 +1217,8 @@ async fn scan_files_simplified(
     }
 
     // 打印扫描统计信息
-    println!("[SCAN_SIMPLIFIED] 扫描统计: 发现总数: {}, 包含: {}, 过滤: {} (隐藏: {}, 扩展名: {}, Bundle: {})", 
-        stats.total_discovered, 
+    info!("[SCAN_SIMPLIFIED] 扫描统计: 发现总数: {}, 包含: {}, 过滤: {} (隐藏: {}, 扩展名: {}, Bundle: {})", 
+        stats.total_discovered,
         stats.total_included,
         stats.hidden_filtered + stats.extension_filtered + stats.bundle_filtered,
         stats.hidden_filtered,
@@ -0,0 +1,55 @@
+//! # 哈希计算线程池 (Hashing Pool)
+//!
+//! 文件哈希需要读盘和计算 SHA-256，以前直接在 tokio 工作线程上执行，
+//! 初始扫描时成千上万个文件同时计算哈希，会占满运行时，API 请求和文件监控事件都被拖慢。
+//!
+//! 现在哈希计算在 `spawn_blocking` 的阻塞线程上执行，并用信号量限制同时进行的数量
+//! （CPU 核数的一半，1~4 个），其余任务排队等待。以后的整文件哈希也应通过 `run` 执行。
+
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tokio::sync::Semaphore;
+
+/// 同时进行的哈希计算数上限
+const MAX_CONCURRENCY: usize = 4;
+
+static PERMITS: OnceLock<Semaphore> = OnceLock::new();
+
+fn permits() -> &'static Semaphore {
+    PERMITS.get_or_init(|| {
+        let cores = std::thread::available_parallelism().map_or(2, |n| n.get());
+        Semaphore::new((cores / 2).clamp(1, MAX_CONCURRENCY))
+    })
+}
+
+/// 在哈希线程池中执行阻塞任务，任务 panic 时返回 None（不写崩溃报告）
+pub async fn run<T, F>(task: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let _permit = permits().acquire().await.ok()?;
+    tokio::task::spawn_blocking(move || crate::crash_report::recoverable(task))
+        .await
+        .ok()
+}
+
+/// 计算文件开头最多 max_bytes 字节的 SHA-256（阻塞）
+fn hash_prefix_blocking(path: &Path, max_bytes: usize) -> Option<String> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut buffer = Vec::with_capacity(max_bytes);
+    file.take(max_bytes as u64).read_to_end(&mut buffer).ok()?;
+    if buffer.is_empty() {
+        return None;
+    }
+    Some(format!("{:x}", Sha256::digest(&buffer)))
+}
+
+/// 在哈希线程池中计算文件开头最多 max_bytes 字节的 SHA-256，空文件或无法读取时返回 None
+pub async fn hash_prefix(path: PathBuf, max_bytes: usize) -> Option<String> {
+    run(move || hash_prefix_blocking(&path, max_bytes))
+        .await
+        .flatten()
+}
@@ -0,0 +1,151 @@
+//! # 共享 HTTP 客户端 (Shared HTTP Client)
+//!
+//! 以前每个命令都新建一个 `reqwest::Client`，FileMonitor 也有自己的一份，每次请求都要重新建立连接。
+//! 现在整个应用共用一个带连接池的客户端：空闲连接保持复用（keep-alive），
+//! 对端支持 HTTP/2 时启用自适应窗口和 PING 保活。`reqwest::Client` 内部是引用计数的，克隆开销很小。
+//!
+//! 超时可在 settings.json 中配置（单位：秒）：
+//! - `http_request_timeout_secs`：整个请求的默认超时，默认 30（单个请求仍可用 `.timeout()` 覆盖）
+//! - `http_connect_timeout_secs`：建立连接的超时，默认 5
+//! - `http2_prior_knowledge`：直接以 HTTP/2 连接 API，默认 false（uvicorn 只支持 HTTP/1.1，换用 hypercorn 等服务器时才开启）
+//!
+//! 事件流（SSE 长连接）不能设置整体超时，仍使用自己的客户端。
+//!
+//! 配置了API本地套接字（见 api_config.rs）时，共享客户端和事件流客户端的所有请求都经 Unix 套接字发送，
+//! URL 中的主机和端口只用于构造请求。访问其他地址（如 sidecar 的健康检查）使用 [`shared_tcp`]。
+
+use log::{error, info, warn};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const REQUEST_TIMEOUT_STORE_KEY: &str = "http_request_timeout_secs";
+const CONNECT_TIMEOUT_STORE_KEY: &str = "http_connect_timeout_secs";
+const HTTP2_PRIOR_KNOWLEDGE_STORE_KEY: &str = "http2_prior_knowledge";
+
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 5;
+/// 空闲连接在池中保留的时间
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// 每个主机最多保留的空闲连接数
+const POOL_MAX_IDLE_PER_HOST: usize = 16;
+/// TCP keep-alive 探测间隔
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static TCP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+/// API 本地套接字路径，未配置时请求走 TCP
+static API_SOCKET: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// 共享客户端的配置
+#[derive(Debug, Clone)]
+struct HttpClientSettings {
+    request_timeout: Duration,
+    connect_timeout: Duration,
+    http2_prior_knowledge: bool,
+}
+
+impl Default for HttpClientSettings {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS),
+            http2_prior_knowledge: false,
+        }
+    }
+}
+
+/// 配置了API本地套接字时，让客户端的所有请求经套接字发送
+pub fn with_api_transport(builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+    match API_SOCKET.get_or_init(|| None) {
+        #[cfg(unix)]
+        Some(path) => builder.unix_socket(path.clone()),
+        _ => builder,
+    }
+}
+
+/// 按配置构建客户端
+fn build(settings: &HttpClientSettings) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder()
+        .timeout(settings.request_timeout)
+        .connect_timeout(settings.connect_timeout)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+        .tcp_keepalive(TCP_KEEPALIVE)
+        .tcp_nodelay(true)
+        .http2_adaptive_window(true)
+        .http2_keep_alive_interval(TCP_KEEPALIVE)
+        .http2_keep_alive_while_idle(true);
+    if settings.http2_prior_knowledge {
+        builder = builder.http2_prior_knowledge();
+    }
+    with_api_transport(builder).build().unwrap_or_else(|e| {
+        error!("[HTTP_CLIENT] 创建共享HTTP客户端失败，使用默认配置: {}", e);
+        reqwest::Client::new()
+    })
+}
+
+/// 读取保存的超时配置并创建共享客户端，应在启动时、首次发送请求之前调用
+///
+/// `api_socket` 为 API 本地套接字路径，未配置时为 None
+pub fn init(app_handle: &AppHandle, api_socket: Option<&str>) {
+    if API_SOCKET.set(api_socket.map(PathBuf::from)).is_err() {
+        warn!("[HTTP_CLIENT] API传输方式已在初始化前确定，本次套接字配置不生效");
+    }
+
+    let mut settings = HttpClientSettings::default();
+    if let Ok(store) = app_handle.store("settings.json") {
+        // 只接受正整数秒数
+        let store_secs = |key: &str| {
+            store
+                .get(key)
+                .and_then(|value| value.as_u64())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+        };
+        if let Some(timeout) = store_secs(REQUEST_TIMEOUT_STORE_KEY) {
+            settings.request_timeout = timeout;
+        }
+        if let Some(timeout) = store_secs(CONNECT_TIMEOUT_STORE_KEY) {
+            settings.connect_timeout = timeout;
+        }
+        if let Some(enabled) = store
+            .get(HTTP2_PRIOR_KNOWLEDGE_STORE_KEY)
+            .and_then(|value| value.as_bool())
+        {
+            settings.http2_prior_knowledge = enabled;
+        }
+    }
+
+    if CLIENT.set(build(&settings)).is_err() {
+        warn!("[HTTP_CLIENT] 共享HTTP客户端已在初始化前被使用，本次配置不生效");
+        return;
+    }
+    info!(
+        "[HTTP_CLIENT] 共享HTTP客户端已创建，请求超时: {:?}，连接超时: {:?}，HTTP/2 直连: {}，本地套接字: {:?}",
+        settings.request_timeout, settings.connect_timeout, settings.http2_prior_knowledge, api_socket
+    );
+}
+
+/// 获取共享客户端；未调用 init 时使用默认配置
+pub fn shared() -> reqwest::Client {
+    CLIENT
+        .get_or_init(|| build(&HttpClientSettings::default()))
+        .clone()
+}
+
+/// 获取走 TCP 的共享客户端，用于访问API以外的地址（不受API本地套接字配置影响）
+pub fn shared_tcp() -> reqwest::Client {
+    TCP_CLIENT
+        .get_or_init(|| {
+            reqwest::Client::builder()
+                .timeout(Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS))
+                .connect_timeout(Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS))
+                .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new())
+        })
+        .clone()
+}
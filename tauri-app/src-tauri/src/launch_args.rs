@@ -0,0 +1,236 @@
+//! # 启动参数 (Launch Arguments)
+//!
+//! 支持通过命令行驱动应用，便于脚本调用和系统"打开方式"菜单：
+//! - `--add-folder <路径>`：添加白名单文件夹并扫描
+//! - `--scan <路径>`：扫描指定目录
+//! - 单独的目录路径（"打开方式"菜单传入）等同于 `--add-folder`
+//! - 单独的文件路径（双击关联文件）发出 `external-open` 事件，文件所在目录已被监控时同时重新扫描该目录
+//!
+//! 两种选项都支持 `--scan=<路径>` 写法，相对路径相对于启动时的工作目录解析。
+//! 应用已在运行时，单实例插件把新实例的参数转发过来，同样由 `dispatch` 处理。
+//! API 和文件监控尚未就绪时先等待，扫描本身通过配置变更队列执行，初始扫描完成前会排队。
+
+use log::{error, info, warn};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::file_monitor::FileMonitor;
+use crate::{AppState, ConfigChangeRequest};
+
+/// 等待文件监控就绪的最长时间
+const READY_TIMEOUT: Duration = Duration::from_secs(180);
+/// 添加文件夹请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 启动参数中请求的操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LaunchAction {
+    AddFolder(String),
+    Scan(String),
+    Open(String),
+}
+
+/// 把路径解析为所在目录：普通目录返回自身，文件和macOS包返回所在目录，包内文件归属于包本身
+pub fn resolve_directory_from_path(path: &Path) -> Option<PathBuf> {
+    let path = FileMonitor::is_inside_macos_bundle(path).unwrap_or_else(|| path.to_path_buf());
+    if path.is_dir() && !FileMonitor::is_macos_bundle_folder(&path) {
+        Some(path)
+    } else {
+        path.parent().map(Path::to_path_buf)
+    }
+}
+
+/// 相对路径按工作目录解析为绝对路径
+fn resolve_path(value: &str, cwd: &Path) -> String {
+    let path = PathBuf::from(value);
+    let path = if path.is_absolute() {
+        path
+    } else {
+        cwd.join(path)
+    };
+    path.to_string_lossy().to_string()
+}
+
+/// 解析启动参数，第一个参数是可执行文件路径，跳过
+fn parse_actions(args: &[String], cwd: &Path) -> Vec<LaunchAction> {
+    let mut actions = Vec::new();
+    let mut iter = args.iter().skip(1).peekable();
+    while let Some(arg) = iter.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if arg.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        match name {
+            "--add-folder" | "--scan" => {
+                let Some(value) = inline_value.or_else(|| iter.next().cloned()) else {
+                    warn!("[LAUNCH_ARGS] 参数 {} 缺少路径", name);
+                    continue;
+                };
+                let path = resolve_path(&value, cwd);
+                actions.push(if name == "--scan" {
+                    LaunchAction::Scan(path)
+                } else {
+                    LaunchAction::AddFolder(path)
+                });
+            }
+            // 其他带值的选项（如 --api-port 60315）跳过其值
+            _ if name.starts_with("--") => {
+                if inline_value.is_none() && iter.peek().is_some_and(|next| !next.starts_with("--"))
+                {
+                    iter.next();
+                }
+            }
+            // "打开方式"菜单传入的目录，或双击打开的关联文件
+            _ => {
+                let path = resolve_path(arg, cwd);
+                let candidate = Path::new(&path);
+                if !candidate.exists() {
+                    continue;
+                }
+                if candidate.is_dir() && !FileMonitor::is_macos_bundle_folder(candidate) {
+                    actions.push(LaunchAction::AddFolder(path));
+                } else {
+                    actions.push(LaunchAction::Open(path));
+                }
+            }
+        }
+    }
+    actions
+}
+
+/// 等待API启动且文件监控基础设施就绪
+async fn wait_until_ready(app_handle: &AppHandle) -> Option<(String, u16)> {
+    let deadline = tokio::time::Instant::now() + READY_TIMEOUT;
+    while tokio::time::Instant::now() < deadline {
+        let monitor_ready = app_handle
+            .state::<AppState>()
+            .file_monitor
+            .lock()
+            .unwrap()
+            .is_some();
+        if monitor_ready {
+            let api_state = app_handle.state::<crate::ApiState>();
+            let api_state_guard = api_state.0.lock().unwrap();
+            if api_state_guard.is_running() {
+                return Some((api_state_guard.host.clone(), api_state_guard.port));
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    None
+}
+
+/// 通过API添加白名单文件夹
+pub async fn add_folder(host: &str, port: u16, path: &str) -> Result<(), String> {
+    let response = crate::http_client::shared()
+        .post(format!("http://{}:{}/directories", host, port))
+        .timeout(REQUEST_TIMEOUT)
+        .json(&serde_json::json!({ "path": path, "alias": "", "is_blacklist": false }))
+        .send()
+        .await
+        .map_err(|e| format!("请求添加文件夹失败: {}", e))?;
+    let result: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("解析添加文件夹结果失败: {}", e))?;
+    if result["status"] == "success" {
+        Ok(())
+    } else {
+        Err(result["message"].as_str().unwrap_or("未知错误").to_string())
+    }
+}
+
+/// 把扫描加入配置变更队列，初始扫描已完成时立即处理
+pub fn queue_scan(app_handle: &AppHandle, path: &str) {
+    let app_state = app_handle.state::<AppState>();
+    app_state.add_pending_config_change(ConfigChangeRequest::AddWhitelist {
+        folder_path: path.to_string(),
+        folder_alias: None,
+    });
+    if app_state.is_initial_scan_completed() {
+        app_state.process_pending_config_changes();
+    }
+}
+
+/// 目录是否位于某个白名单监控目录之内
+fn is_monitored(app_handle: &AppHandle, directory: &Path) -> bool {
+    let file_monitor = app_handle
+        .state::<AppState>()
+        .file_monitor
+        .lock()
+        .unwrap()
+        .clone();
+    file_monitor.is_some_and(|monitor| {
+        monitor.get_monitored_directories().iter().any(|dir| {
+            !dir.is_blacklist
+                && crate::path_utils::path_starts_with(directory, Path::new(&dir.path))
+        })
+    })
+}
+
+/// 处理外部打开的文件：通知前端，文件所在目录已被监控时重新扫描该目录
+fn open_external(app_handle: &AppHandle, path: &str) {
+    let directory = resolve_directory_from_path(Path::new(path));
+    let monitored = match &directory {
+        Some(directory) if is_monitored(app_handle, directory) => {
+            queue_scan(app_handle, &directory.to_string_lossy());
+            true
+        }
+        _ => false,
+    };
+    info!(
+        "[LAUNCH_ARGS] 外部打开: {} (目录: {:?}，已监控: {})",
+        path, directory, monitored
+    );
+    let _ = app_handle.emit(
+        "external-open",
+        serde_json::json!({
+            "path": path,
+            "directory": directory.map(|dir| dir.to_string_lossy().to_string()),
+            "monitored": monitored
+        }),
+    );
+}
+
+/// 处理启动参数（首次启动或单实例插件转发的参数）
+pub fn dispatch(app_handle: &AppHandle, args: Vec<String>, cwd: &str) {
+    let actions = parse_actions(&args, Path::new(cwd));
+    if actions.is_empty() {
+        return;
+    }
+    info!("[LAUNCH_ARGS] 启动参数请求的操作: {:?}", actions);
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some((host, port)) = wait_until_ready(&app_handle).await else {
+            warn!(
+                "[LAUNCH_ARGS] {:?} 内API或文件监控未就绪，放弃执行启动参数",
+                READY_TIMEOUT
+            );
+            return;
+        };
+
+        for action in actions {
+            match action {
+                LaunchAction::AddFolder(path) => match add_folder(&host, port, &path).await {
+                    Ok(()) => {
+                        info!("[LAUNCH_ARGS] 已添加文件夹: {}", path);
+                        queue_scan(&app_handle, &path);
+                        let _ = app_handle.emit("launch-folder-added", &path);
+                    }
+                    Err(e) => error!("[LAUNCH_ARGS] 添加文件夹 {} 失败: {}", path, e),
+                },
+                LaunchAction::Scan(path) => {
+                    if Path::new(&path).is_dir() {
+                        info!("[LAUNCH_ARGS] 扫描目录: {}", path);
+                        queue_scan(&app_handle, &path);
+                    } else {
+                        warn!("[LAUNCH_ARGS] 扫描路径不是目录: {}", path);
+                    }
+                }
+                LaunchAction::Open(path) => open_external(&app_handle, &path),
+            }
+        }
+    });
+}
@@ -1,139 +1,279 @@
+mod api_config; // API地址配置模块
+mod api_control; // API控制命令通道
 mod api_startup; // API启动模块
+mod api_watchdog; // API看门狗模块
+mod backup_exclusion; // 扫描时排除备份位置
+mod bridge_events; // 桥接事件类型定义
+mod bulk_metadata; // 按目录批量读取文件元数据（macOS getattrlistbulk / Windows FileFullDirectoryInfo）
+mod bundle_detection; // 通过 Launch Services 识别 macOS 包
+mod clipboard; // 剪贴板捕获，记录复制的文本和文件（需用户开启）
+mod code_language; // 源代码语言识别
 mod commands;
+mod crash_report; // 崩溃报告
+mod deep_link; // knowledgefocus:// 深度链接处理
+mod deferred_hash; // 延迟哈希模式，粗筛结果发送后在后台补充哈希
 mod event_buffer;
+mod event_stream; // 桥接事件SSE通道
+mod event_trace; // 事件追踪调试模块
+mod extended_attributes; // 下载来源等扩展属性
+mod extensionless; // 无扩展名文件的内容识别和策略
 mod file_monitor;
 mod file_monitor_debounced; // 防抖动文件监控模块
 mod file_scanner; // 文件扫描模块
+mod hash_pool; // 限制并发的文件哈希线程池
+mod http_client; // 共享的连接池HTTP客户端
+mod indexed_search; // 系统索引扫描后端（Spotlight / Windows Search）
+mod launch_args; // 启动参数处理（--add-folder / --scan）
+mod link_policy; // 符号链接和目录联接的遍历策略
+mod local_embedding; // 本地向量化，在 Rust 端计算提取文本的向量
+mod local_index; // 已发送文件的本地 SQLite 索引
+mod local_rules; // 用户本地规则文件
+mod log_level; // 运行时日志级别
+mod ocr; // 图片文字识别，截图等图片的文字随文本提取发送
+mod path_utils; // 隐藏文件判断和Windows长路径处理
+mod power_profile; // 电池供电时的低功耗模式
+mod process_tree; // 按 PID 终止进程树、等待进程退出和端口释放
+mod project_roots; // 项目根目录识别和构建产物目录跳过
+mod quick_search; // 快速搜索窗口和全局快捷键
+mod rule_conditions; // 过滤规则的组合条件（AND / OR）
+mod rule_telemetry; // 规则命中统计上报
+mod screening_upload; // 粗筛数据的NDJSON流式提交
+mod screenshots; // 截图文件夹识别，新截图优先处理并通知前端
+mod security_bookmarks; // 沙盒化构建的安全范围书签
+mod seen_cache; // 已发送文件的签名缓存，跳过未变化的文件
+mod settings_window; // 独立设置窗口
 mod setup_file_monitor; // 事件缓冲模块
+mod shutdown; // 托盘退出的有序退出流程
+mod sidecar_supervisor; // sidecar进程监管模块（主API和额外工作进程）
+mod sleep_wake; // 系统睡眠/唤醒检测
+mod spill_queue; // 扫描元数据溢出到磁盘的队列，限制扫描时的内存占用
+mod startup_stages; // 启动阶段报告模块
+mod tags; // 层级标牌的规范化和去重
+mod text_extraction; // 文本提取，把文本、Markdown、代码和 PDF 的文本发送到 API
+mod tray_status; // 托盘菜单状态模块
+mod update_channel; // 更新通道和下载策略
+mod volumes; // 卷挂载/卸载检测
+mod watch_limits; // inotify 监控数量上限检测
+mod watch_registry; // 所有监控目录共用的 watcher 和根目录注册表
 
 use file_monitor::FileMonitor;
 use file_monitor_debounced::DebouncedFileMonitor;
-use reqwest;
-use std::sync::{Arc, Mutex};
-use tauri::Emitter;
+use log::{error, info, warn};
+use startup_stages::{report_stage, StageStatus, StartupStage};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use tauri::Manager;
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     WindowEvent,
 };
+use tauri::{Emitter, Listener};
 use tokio::time::{sleep, Duration};
 
+/// 托盘图标ID，与前端 main.tsx 中 `TrayIcon.getById("1")` 保持一致
+const TRAY_ICON_ID: &str = "1";
+
+/// 托盘菜单和应用菜单中"暂停监控"勾选项的ID
+const PAUSE_MONITORING_ID: &str = "pause_monitoring";
+
 // 存储API进程的状态
 struct ApiProcessState {
-    process_child: Option<tauri_plugin_shell::process::CommandChild>,
+    // 持有主API（名称 sidecar_supervisor::API_NAME）和额外 sidecar 的进程句柄
+    supervisor: sidecar_supervisor::SidecarSupervisor,
     port: u16,
     host: String,
     db_path: String,
+    // API 额外监听的 Unix 套接字路径（可选），Rust 端请求经此发送
+    socket_path: Option<String>,
+    // 是否连接到外部启动的API（进程不由应用管理，退出时不终止）
+    external: bool,
+}
+
+impl ApiProcessState {
+    /// API是否处于可用状态：由应用启动的sidecar或已连接的外部API
+    fn is_running(&self) -> bool {
+        self.supervisor.is_running(sidecar_supervisor::API_NAME) || self.external
+    }
+}
+
+// 是否已连接外部API，供无法访问 ApiState 的静态清理（如 panic hook）判断
+static EXTERNAL_API_ATTACHED: AtomicBool = AtomicBool::new(false);
+
+// 本应用启动的API进程（uv）的PID，供无法访问 ApiState 的静态清理只终止自己的进程树，0 表示没有
+static API_ROOT_PID: AtomicU32 = AtomicU32::new(0);
+
+/// 记录本应用启动的API进程PID，每次启动或重启API后调用
+pub(crate) fn record_api_pid(pid: u32) {
+    API_ROOT_PID.store(pid, Ordering::SeqCst);
+}
+
+// 本次启动生成的API关闭令牌，通过环境变量传给API进程，`/shutdown` 请求需携带
+static SHUTDOWN_TOKEN: OnceLock<String> = OnceLock::new();
+
+/// API关闭令牌，应用每次启动时随机生成
+pub(crate) fn shutdown_token() -> &'static str {
+    SHUTDOWN_TOKEN.get_or_init(|| {
+        use sha2::{Digest, Sha256};
+        use std::hash::{BuildHasher, Hasher};
+
+        // RandomState 的种子来自系统随机数，再混入时间和进程号
+        let mut hasher = Sha256::new();
+        for _ in 0..4 {
+            let mut random = std::collections::hash_map::RandomState::new().build_hasher();
+            random.write_u32(std::process::id());
+            hasher.update(random.finish().to_le_bytes());
+        }
+        hasher.update(
+            chrono::Utc::now()
+                .timestamp_nanos_opt()
+                .unwrap_or_default()
+                .to_le_bytes(),
+        );
+        format!("{:x}", hasher.finalize())
+    })
 }
 
+// 退出时等待API优雅关闭的最长时间
+const GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 退出时等待文件元数据批处理排空的最长时间
+const BATCH_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
 // API进程管理器，用于应用退出时自动清理资源
 struct ApiProcessManager {
+    app_handle: tauri::AppHandle,
     api_state: Arc<Mutex<ApiProcessState>>,
+    sidecars: sidecar_supervisor::SidecarSupervisor,
+    /// 清理已执行，窗口销毁、退出事件和 Drop 都会调用 cleanup，只执行第一次
+    cleaned_up: AtomicBool,
 }
 
 impl ApiProcessManager {
-    /// 实例清理方法，执行完整的清理逻辑
+    /// 实例清理方法，执行完整的清理逻辑；重复调用时直接返回
     pub fn cleanup(&self) {
-        println!("执行ApiProcessManager完整清理");
-        eprintln!("执行ApiProcessManager完整清理"); // 同时输出到 stderr
-
-        // 尝试获取并终止 API 进程
-        if let Ok(mut api_state) = self.api_state.lock() {
-            if let Some(child) = api_state.process_child.take() {
-                println!("通过实例方法终止 uv 和 Python API 进程树");
-
-                // 由于使用 uv 启动，需要终止整个进程树
-                // 先尝试获取进程ID用于进程树清理
-                let child_pid = child.pid();
-                println!("uv 进程 PID: {}", child_pid);
-
-                // 尝试终止 uv 进程（这会终止直接子进程，但不一定终止孙进程）
-                match child.kill() {
-                    Ok(_) => {
-                        println!("发送终止信号到 uv 进程成功");
-
-                        // 等待短暂时间让进程响应信号
-                        std::thread::sleep(std::time::Duration::from_millis(1000));
-                    }
-                    Err(e) => {
-                        eprintln!("终止 uv 进程失败: {}", e);
-                    }
+        if self.cleaned_up.swap(true, Ordering::SeqCst) {
+            info!("ApiProcessManager 已完成清理，跳过");
+            return;
+        }
+        info!("执行ApiProcessManager完整清理");
+        warn!("执行ApiProcessManager完整清理"); // 同时输出到 stderr
+
+        // 在API关闭前送出尚未发送的文件元数据和桥接事件
+        self.flush_pending_work();
+
+        // 先终止额外的 sidecar 工作进程，它们可能依赖主API
+        self.sidecars.stop_all();
+
+        // 先请求API优雅关闭，给进行中的数据库写入留出收尾时间；只有优雅关闭失败时才强制终止
+        let graceful_target = match self.api_state.lock() {
+            Ok(api_state) => api_state
+                .supervisor
+                .pid(sidecar_supervisor::API_NAME)
+                .map(|pid| {
+                    (
+                        api_state.host.clone(),
+                        api_state.port,
+                        process_tree::tree_pids(pid),
+                    )
+                }),
+            Err(_) => {
+                error!("无法获取 API 状态互斥锁");
+                None
+            }
+        };
+        let Some((host, port, pids)) = graceful_target else {
+            info!("没有需要终止的 API 进程");
+            return;
+        };
+
+        let graceful = Self::request_graceful_shutdown(&host, port, &pids);
+        // 进程已经退出或即将被强制终止，监管器不再持有句柄
+        let child = self.api_state.lock().ok().and_then(|mut api_state| {
+            api_state
+                .supervisor
+                .take_child(sidecar_supervisor::API_NAME)
+        });
+        if graceful {
+            info!("API 已优雅关闭");
+        } else {
+            // 只终止本应用启动的 uv 和 Python API 进程树
+            warn!("API 未能在超时时间内优雅关闭，强制终止进程树: {:?}", pids);
+            process_tree::terminate(&pids, true);
+            if let Some(child) = child {
+                if let Err(e) = child.kill() {
+                    error!("终止 uv 进程失败: {}", e);
                 }
+            }
+        }
+        record_api_pid(0);
+    }
 
-                // // 在Unix系统上，强制清理整个进程树和相关进程
-                // #[cfg(unix)]
-                // {
-                //     println!("开始清理 uv 和 Python 进程树");
-
-                //     // 1. 首先尝试通过进程组终止（如果 uv 创建了进程组）
-                //     println!("尝试终止进程组...");
-                //     let _ = std::process::Command::new("pkill")
-                //         .args(["-g", &child_pid.to_string()])
-                //         .status();
-
-                //     // 2. 使用 pgrep 找到所有 uv 相关的子进程并终止
-                //     println!("查找并终止 uv 的所有子进程...");
-                //     if let Ok(output) = std::process::Command::new("pgrep")
-                //         .args(["-P", &child_pid.to_string()])
-                //         .output() {
-                //         let children_pids = String::from_utf8_lossy(&output.stdout);
-                //         for pid_str in children_pids.lines() {
-                //             if let Ok(pid) = pid_str.parse::<u32>() {
-                //                 println!("终止子进程 PID: {}", pid);
-                //                 let _ = std::process::Command::new("kill")
-                //                     .args(["-TERM", &pid.to_string()])
-                //                     .status();
-                //             }
-                //         }
-                //     }
-
-                //     // 3. 等待一下后强制终止
-                //     std::thread::sleep(std::time::Duration::from_millis(500));
-
-                //     // 4. 使用精确的进程命令行匹配来清理 Python 进程
-                //     println!("使用命令行匹配清理 Python 进程...");
-                //     let cleanup_patterns = [
-                //         "main.py --host 127.0.0.1 --port 60315",
-                //         "/api/main.py",
-                //         "knowledge-focus.db",
-                //     ];
-
-                //     for pattern in &cleanup_patterns {
-                //         println!("清理匹配模式: {}", pattern);
-                //         // 先发送 SIGTERM
-                //         let _ = std::process::Command::new("pkill")
-                //             .args(["-f", pattern])
-                //             .status();
-                //     }
-
-                //     // 5. 等待后强制终止
-                //     std::thread::sleep(std::time::Duration::from_millis(1000));
-                //     for pattern in &cleanup_patterns {
-                //         let _ = std::process::Command::new("pkill")
-                //             .args(["-9", "-f", pattern])
-                //             .status();
-                //     }
-
-                //     // 6. 最后清理 uv 进程本身（以防还在运行）
-                //     println!("最终清理 uv 进程: {}", child_pid);
-                //     let _ = std::process::Command::new("kill")
-                //         .args(["-9", &child_pid.to_string()])
-                //         .status();
-
-                //     println!("进程树清理完成");
-                // }
-
-                println!("API 进程树终止完成");
-            } else {
-                println!("没有需要终止的 API 进程");
+    /// 排空文件元数据批处理，并发送或保存缓冲的桥接事件
+    fn flush_pending_work(&self) {
+        let file_monitor = match self.app_handle.try_state::<AppState>() {
+            Some(app_state) => app_state
+                .file_monitor
+                .lock()
+                .ok()
+                .and_then(|guard| guard.clone()),
+            None => None,
+        };
+        if let Some(monitor) = file_monitor {
+            monitor.drain_pending_batches(BATCH_DRAIN_TIMEOUT);
+        }
+
+        if let Some(event_buffer) = self
+            .app_handle
+            .try_state::<Arc<event_buffer::EventBuffer>>()
+        {
+            // 主窗口已销毁时发送的事件无人接收，直接保存到磁盘，下次启动时重新发送
+            if self.app_handle.get_webview_window("main").is_some() {
+                event_buffer.flush_all();
             }
-        } else {
-            eprintln!("无法获取 API 状态互斥锁");
+            event_buffer.persist_pending_events();
         }
+    }
 
-        // 执行静态清理作为后备
-        Self::cleanup_processes_static();
+    /// 调用 `/shutdown` 端点请求API优雅关闭，并等待API进程树退出
+    ///
+    /// uvicorn 在关闭监听端口后才执行 lifespan 关闭和数据库清理，`/health` 无响应不代表进程已退出，
+    /// 因此按 PID 等待进程真正结束。返回 true 表示API已在超时时间内退出
+    fn request_graceful_shutdown(host: &str, port: u16, pids: &[u32]) -> bool {
+        let shutdown_url = format!("http://{}:{}/shutdown", host, port);
+        info!("请求API优雅关闭: {}", shutdown_url);
+
+        // cleanup 可能在异步运行时的线程中被调用（例如 Drop），不能直接 block_on，
+        // 因此把请求交给应用的异步运行时，在这里同步等待结果
+        let (tx, rx) = std::sync::mpsc::channel();
+        tauri::async_runtime::spawn(async move {
+            let accepted = match http_client::shared()
+                .post(&shutdown_url)
+                .header("X-Shutdown-Token", shutdown_token())
+                .timeout(Duration::from_secs(2))
+                .send()
+                .await
+            {
+                Ok(response) if response.status().is_success() => {
+                    info!("API 已接受关闭请求");
+                    true
+                }
+                Ok(response) => {
+                    warn!("API 关闭请求被拒绝，状态码: {}", response.status());
+                    false
+                }
+                Err(e) => {
+                    error!("发送 API 关闭请求失败: {}", e);
+                    false
+                }
+            };
+            let _ = tx.send(accepted);
+        });
+
+        if !rx.recv_timeout(Duration::from_secs(3)).unwrap_or(false) {
+            return false;
+        }
+        process_tree::wait_for_exit_blocking(pids, GRACEFUL_SHUTDOWN_TIMEOUT)
     }
 
     /// 静态清理方法，可以在任何地方调用（后备清理）
@@ -143,67 +283,38 @@ impl ApiProcessManager {
 
     /// 静态清理的实际实现
     fn cleanup_processes_static() {
-        println!("执行静态进程清理");
-        eprintln!("执行静态进程清理"); // 同时输出到 stderr
+        info!("执行静态进程清理");
+        warn!("执行静态进程清理"); // 同时输出到 stderr
 
-        // 在Unix系统上，强制清理所有相关的进程
-        #[cfg(unix)]
-        {
-            println!("开始强制清理所有相关的 uv 和 Python 进程");
-
-            // 使用多种模式确保清理干净，包括 uv 进程
-            let cleanup_patterns = [
-                "uv run --directory",
-                "main.py --host 127.0.0.1 --port 60315",
-                // "/api/main.py",
-                // "knowledge-focus.db",
-                "mlx_service.py --port 60316",
-            ];
-
-            for pattern in &cleanup_patterns {
-                println!("清理模式: {}", pattern);
-
-                // 先发送SIGTERM
-                match std::process::Command::new("pkill")
-                    .args(["-f", pattern])
-                    .status()
-                {
-                    Ok(status) => {
-                        println!("SIGTERM 发送结果: {:?}", status);
-                    }
-                    Err(e) => {
-                        println!("SIGTERM 发送失败: {}", e);
-                    }
-                }
-
-                // 等待一秒后发送SIGKILL
-                std::thread::sleep(std::time::Duration::from_millis(1000));
-                match std::process::Command::new("pkill")
-                    .args(["-9", "-f", pattern])
-                    .status()
-                {
-                    Ok(status) => {
-                        println!("SIGKILL 发送结果: {:?}", status);
-                    }
-                    Err(e) => {
-                        println!("SIGKILL 发送失败: {}", e);
-                    }
-                }
-            }
+        // 外部API由开发者自行管理，不能终止
+        if EXTERNAL_API_ATTACHED.load(Ordering::SeqCst) {
+            info!("已连接外部API，跳过静态进程清理");
+            return;
+        }
 
-            println!("静态进程清理完成");
-            eprintln!("静态进程清理完成");
+        // 只终止本应用启动的API进程树，不按命令行模式匹配，避免误杀其他 uv 或 Python 进程
+        let root_pid = API_ROOT_PID.swap(0, Ordering::SeqCst);
+        if root_pid == 0 || !process_tree::is_alive(root_pid) {
+            info!("没有需要清理的 API 进程");
+            return;
         }
+        let pids = process_tree::tree_pids(root_pid);
+        warn!("强制终止本应用启动的 API 进程树: {:?}", pids);
+        process_tree::terminate(&pids, true);
+        info!("静态进程清理完成");
     }
 }
 
 // 实现 Drop trait，在应用退出时自动终止 API 进程
 impl Drop for ApiProcessManager {
     fn drop(&mut self) {
-        println!("应用程序退出，ApiProcessManager.drop() 被调用");
-        eprintln!("应用程序退出，ApiProcessManager.drop() 被调用"); // 同时输出到 stderr
+        info!("应用程序退出，ApiProcessManager.drop() 被调用");
+        warn!("应用程序退出，ApiProcessManager.drop() 被调用"); // 同时输出到 stderr
 
-        // 调用实例清理方法
+        // 有序退出流程已完成清理时不再重复
+        if shutdown::has_completed() {
+            return;
+        }
         self.cleanup();
     }
 }
@@ -220,6 +331,8 @@ pub struct AppState {
     // 配置变更队列管理
     pending_config_changes: Arc<Mutex<Vec<ConfigChangeRequest>>>,
     initial_scan_completed: Arc<Mutex<bool>>,
+    // 暂停监控时丢弃文件变化事件（托盘菜单切换）
+    monitoring_paused: Arc<AtomicBool>,
 }
 
 impl AppState {
@@ -231,6 +344,7 @@ impl AppState {
             debounced_file_monitor: Arc::new(Mutex::new(None)), // 初始化新字段
             pending_config_changes: Arc::new(Mutex::new(Vec::new())), // 初始化配置变更队列
             initial_scan_completed: Arc::new(Mutex::new(false)), // 初始化扫描完成标志
+            monitoring_paused: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -262,15 +376,19 @@ impl AppState {
     }
 
     // 刷新简化配置（从API获取最新配置）
-    pub async fn refresh_simplified_config(&self) -> Result<(), String> {
-        println!("[CONFIG] 开始刷新简化配置");
+    pub async fn refresh_simplified_config(
+        &self,
+        api_host: String,
+        api_port: u16,
+    ) -> Result<(), String> {
+        info!("[CONFIG] 开始刷新简化配置");
 
         // 创建临时的FileMonitor实例来获取配置
-        let temp_monitor = file_monitor::FileMonitor::new("127.0.0.1".to_string(), 60315);
+        let temp_monitor = file_monitor::FileMonitor::new(api_host, api_port);
 
         match temp_monitor.fetch_file_scanning_config().await {
             Ok(config) => {
-                println!(
+                info!(
                     "[CONFIG] 成功获取简化配置: 扩展名映射={}, Bundle扩展名={}",
                     config.extension_mappings.len(),
                     config.bundle_extensions.len()
@@ -279,12 +397,26 @@ impl AppState {
                 Ok(())
             }
             Err(e) => {
-                println!("[CONFIG] 获取简化配置失败: {}", e);
+                warn!("[CONFIG] 获取简化配置失败: {}", e);
                 Err(format!("获取简化配置失败: {}", e))
             }
         }
     }
 
+    /// 文件监控是否已暂停
+    pub fn is_monitoring_paused(&self) -> bool {
+        self.monitoring_paused.load(Ordering::SeqCst)
+    }
+
+    /// 暂停或恢复文件监控，暂停期间文件变化事件被丢弃，不读取文件也不发送到API
+    pub fn set_monitoring_paused(&self, paused: bool) {
+        self.monitoring_paused.store(paused, Ordering::SeqCst);
+        info!(
+            "[MONITOR] 文件监控已{}",
+            if paused { "暂停" } else { "恢复" }
+        );
+    }
+
     // 配置变更队列管理方法
 
     /// 检查首次扫描是否已完成
@@ -300,7 +432,7 @@ impl AppState {
 
         // 如果扫描完成，处理待处理的配置变更
         if completed {
-            println!("[CONFIG_QUEUE] 首次扫描完成，开始处理待处理的配置变更");
+            info!("[CONFIG_QUEUE] 首次扫描完成，开始处理待处理的配置变更");
             self.process_pending_config_changes();
         }
     }
@@ -309,7 +441,7 @@ impl AppState {
     pub fn add_pending_config_change(&self, change: ConfigChangeRequest) {
         let mut pending_changes = self.pending_config_changes.lock().unwrap();
         pending_changes.push(change.clone());
-        println!("[CONFIG_QUEUE] 添加配置变更到队列: {:?}", change);
+        info!("[CONFIG_QUEUE] 添加配置变更到队列: {:?}", change);
     }
 
     /// 检查是否有待处理的配置变更
@@ -337,7 +469,7 @@ impl AppState {
             return;
         }
 
-        println!(
+        info!(
             "[CONFIG_QUEUE] 开始处理 {} 个待处理的配置变更",
             changes.len()
         );
@@ -345,9 +477,10 @@ impl AppState {
         // 在独立的异步任务中处理配置变更
         let changes_clone = changes.clone();
         let file_monitor = self.file_monitor.clone();
+        let debounced_file_monitor = self.debounced_file_monitor.clone();
 
         tauri::async_runtime::spawn(async move {
-            Self::execute_config_changes(changes_clone, file_monitor).await;
+            Self::execute_config_changes(changes_clone, file_monitor, debounced_file_monitor).await;
         });
     }
 
@@ -355,8 +488,9 @@ impl AppState {
     async fn execute_config_changes(
         changes: Vec<ConfigChangeRequest>,
         file_monitor: Arc<Mutex<Option<FileMonitor>>>,
+        debounced_file_monitor: Arc<Mutex<Option<DebouncedFileMonitor>>>,
     ) {
-        println!("[CONFIG_QUEUE] 开始执行 {} 个配置变更", changes.len());
+        info!("[CONFIG_QUEUE] 开始执行 {} 个配置变更", changes.len());
 
         // 获取文件监控器
         let monitor = {
@@ -364,7 +498,7 @@ impl AppState {
             match &*guard {
                 Some(monitor) => monitor.clone(),
                 None => {
-                    eprintln!("[CONFIG_QUEUE] 文件监控器未初始化，无法执行配置变更");
+                    error!("[CONFIG_QUEUE] 文件监控器未初始化，无法执行配置变更");
                     return;
                 }
             }
@@ -377,10 +511,12 @@ impl AppState {
         for change in changes {
             match Self::execute_single_config_change(&change, &monitor).await {
                 Ok(_) => {
-                    println!("[CONFIG_QUEUE] 成功执行配置变更: {:?}", change);
+                    info!("[CONFIG_QUEUE] 成功执行配置变更: {:?}", change);
+                    // 变更执行成功后再同步OS监控，失败时监控状态与API中的配置保持一致
+                    Self::sync_watched_directories(&change, &debounced_file_monitor);
                 }
                 Err(e) => {
-                    eprintln!("[CONFIG_QUEUE] 执行配置变更失败: {:?}, 错误: {}", change, e);
+                    error!("[CONFIG_QUEUE] 执行配置变更失败: {:?}, 错误: {}", change, e);
                     failed_changes.push((change, e));
                 }
             }
@@ -397,20 +533,20 @@ impl AppState {
             // 保证在刷新配置前有足够的暂停时间让API服务器恢复
             sleep(Duration::from_secs(1)).await;
 
-            println!("[CONFIG_QUEUE] 尝试刷新配置 ({}/{})", retry, max_retries);
+            info!("[CONFIG_QUEUE] 尝试刷新配置 ({}/{})", retry, max_retries);
             match monitor.refresh_all_configurations().await {
                 Ok(_) => {
-                    println!("[CONFIG_QUEUE] 所有配置变更执行完成，监控配置已刷新");
+                    info!("[CONFIG_QUEUE] 所有配置变更执行完成，监控配置已刷新");
                     refresh_success = true;
                     break;
                 }
                 Err(e) => {
-                    eprintln!(
+                    error!(
                         "[CONFIG_QUEUE] 刷新监控配置失败 ({}/{}): {}",
                         retry, max_retries, e
                     );
                     if retry < max_retries {
-                        println!("[CONFIG_QUEUE] 将在 {} 秒后重试刷新配置", retry);
+                        info!("[CONFIG_QUEUE] 将在 {} 秒后重试刷新配置", retry);
                         sleep(Duration::from_secs(retry)).await;
                     }
                 }
@@ -418,13 +554,13 @@ impl AppState {
         }
 
         if !refresh_success {
-            eprintln!("[CONFIG_QUEUE] 严重警告: 配置刷新失败，系统可能处于不一致状态！");
+            error!("[CONFIG_QUEUE] 严重警告: 配置刷新失败，系统可能处于不一致状态！");
             // 这里可以添加额外的恢复步骤或通知用户
         }
 
         // 报告失败的变更
         if !failed_changes.is_empty() {
-            eprintln!(
+            error!(
                 "[CONFIG_QUEUE] 注意: {} 个配置变更执行失败，可能需要用户手动操作",
                 failed_changes.len()
             );
@@ -432,6 +568,46 @@ impl AppState {
         }
     }
 
+    /// 根据配置变更添加或释放目录的OS监控
+    fn sync_watched_directories(
+        change: &ConfigChangeRequest,
+        debounced_file_monitor: &Arc<Mutex<Option<DebouncedFileMonitor>>>,
+    ) {
+        let guard = debounced_file_monitor.lock().unwrap();
+        let Some(debounced_monitor) = guard.as_ref() else {
+            return;
+        };
+        match change {
+            // 删除白名单文件夹或将其转为黑名单：释放该目录的监控
+            ConfigChangeRequest::DeleteFolder {
+                folder_path,
+                is_blacklist: false,
+                ..
+            }
+            | ConfigChangeRequest::ToggleFolder {
+                folder_path,
+                is_blacklist: true,
+                ..
+            } => {
+                if debounced_monitor.stop_monitoring_directory(folder_path) {
+                    info!("[CONFIG_QUEUE] 已释放目录监控: {}", folder_path);
+                }
+            }
+            // 新增白名单文件夹或将其转为白名单：开始监控该目录
+            ConfigChangeRequest::AddWhitelist { folder_path, .. }
+            | ConfigChangeRequest::ToggleFolder {
+                folder_path,
+                is_blacklist: false,
+                ..
+            } => {
+                if let Err(e) = debounced_monitor.add_directory_to_watch(folder_path.clone()) {
+                    error!("[CONFIG_QUEUE] 添加目录监控失败 {}: {}", folder_path, e);
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// 执行单个配置变更
     async fn execute_single_config_change(
         change: &ConfigChangeRequest,
@@ -453,14 +629,14 @@ impl AppState {
                     while retry_count < max_retries {
                         match Self::cleanup_screening_data_for_path(folder_path, monitor).await {
                             Ok(_) => {
-                                println!("[CONFIG_QUEUE] 成功清理路径 {} 的粗筛数据", folder_path);
+                                info!("[CONFIG_QUEUE] 成功清理路径 {} 的粗筛数据", folder_path);
                                 break;
                             }
                             Err(e) => {
                                 last_error = e.to_string();
                                 retry_count += 1;
                                 if retry_count < max_retries {
-                                    println!(
+                                    info!(
                                         "[CONFIG_QUEUE] 清理粗筛数据失败，将重试 ({}/{}): {}",
                                         retry_count, max_retries, last_error
                                     );
@@ -476,7 +652,7 @@ impl AppState {
                 }
 
                 // 对于文件夹删除，主要工作已在前端完成，这里主要是确保监控状态同步
-                println!("[CONFIG_QUEUE] 文件夹删除变更处理完成: {}", folder_path);
+                info!("[CONFIG_QUEUE] 文件夹删除变更处理完成: {}", folder_path);
                 Ok(())
             }
 
@@ -489,7 +665,7 @@ impl AppState {
                 while retry_count < max_retries {
                     match Self::cleanup_screening_data_for_path(folder_path, monitor).await {
                         Ok(_) => {
-                            println!(
+                            info!(
                                 "[CONFIG_QUEUE] 成功清理黑名单路径 {} 的粗筛数据",
                                 folder_path
                             );
@@ -499,7 +675,7 @@ impl AppState {
                             last_error = e.to_string();
                             retry_count += 1;
                             if retry_count < max_retries {
-                                println!(
+                                info!(
                                     "[CONFIG_QUEUE] 清理黑名单粗筛数据失败，将重试 ({}/{}): {}",
                                     retry_count, max_retries, last_error
                                 );
@@ -513,7 +689,7 @@ impl AppState {
                     return Err(format!("清理黑名单粗筛数据失败: {}", last_error));
                 }
 
-                println!(
+                info!(
                     "[CONFIG_QUEUE] 黑名单文件夹添加变更处理完成: {}",
                     folder_path
                 );
@@ -532,14 +708,14 @@ impl AppState {
                     // 转为白名单时执行增量扫描
                     monitor.scan_single_directory(folder_path, None).await?;
                 }
-                println!("[CONFIG_QUEUE] 文件夹状态切换变更处理完成: {}", folder_path);
+                info!("[CONFIG_QUEUE] 文件夹状态切换变更处理完成: {}", folder_path);
                 Ok(())
             }
 
             ConfigChangeRequest::AddWhitelist { folder_path, .. } => {
                 // 新增白名单文件夹时执行增量扫描
                 monitor.scan_single_directory(folder_path, None).await?;
-                println!(
+                info!(
                     "[CONFIG_QUEUE] 白名单文件夹添加变更处理完成: {}",
                     folder_path
                 );
@@ -548,7 +724,7 @@ impl AppState {
 
             ConfigChangeRequest::BundleExtensionChange => {
                 // Bundle扩展名变更通常需要重启生效，这里只记录
-                println!("[CONFIG_QUEUE] Bundle扩展名变更处理完成，重启应用后生效");
+                info!("[CONFIG_QUEUE] Bundle扩展名变更处理完成，重启应用后生效");
                 Ok(())
             }
         }
@@ -565,16 +741,15 @@ impl AppState {
             monitor.get_api_port()
         );
 
-        println!("[CLEANUP] 开始清理路径 {} 的粗筛数据", folder_path);
+        info!("[CLEANUP] 开始清理路径 {} 的粗筛数据", folder_path);
 
-        // 创建一个更长超时设置的客户端
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(30)) // 设置30秒超时
-            .build()
-            .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+        // 同时移除本地的已发送记录，否则文件夹恢复监控后重新扫描会把这些文件当作未变化而跳过
+        crate::seen_cache::forget(folder_path);
+        crate::local_index::remove(folder_path);
 
-        let response = client
+        let response = crate::http_client::shared()
             .post(&api_url)
+            .timeout(Duration::from_secs(30)) // 设置30秒超时
             .json(&serde_json::json!({
                 "path": folder_path,
                 // 添加额外的请求元数据，帮助调试
@@ -596,14 +771,14 @@ impl AppState {
             // 从响应中提取删除的记录数
             let deleted_count = result.get("deleted").and_then(|v| v.as_i64()).unwrap_or(0);
 
-            println!(
+            info!(
                 "[CLEANUP] 成功清理路径 {} 的粗筛数据，删除 {} 条记录",
                 folder_path, deleted_count
             );
 
             // 额外的验证: 如果应该有记录被删除但返回0，可能要警告
             if folder_path.contains("Pictures") && deleted_count == 0 {
-                println!("[CLEANUP] 警告: 清理图片目录相关的粗筛数据，但未删除任何记录");
+                warn!("[CLEANUP] 警告: 清理图片目录相关的粗筛数据，但未删除任何记录");
             }
 
             Ok(())
@@ -615,12 +790,115 @@ impl AppState {
                 .unwrap_or_else(|_| "无法读取错误响应".to_string());
 
             let error_msg = format!("清理粗筛数据失败 (状态码: {}): {}", status, error_text);
-            eprintln!("[CLEANUP] {}", error_msg);
+            warn!("[CLEANUP] {}", error_msg);
             Err(error_msg)
         }
     }
 }
 
+/// 处理拖放到主窗口的文件和文件夹：逐项检查黑名单和bundle规则后送入粗筛管道，
+/// 每项结果通过 `file-drop-accepted` / `file-drop-rejected` 事件通知前端
+fn handle_dropped_paths(app_handle: &tauri::AppHandle, paths: Vec<std::path::PathBuf>) {
+    let file_monitor = app_handle
+        .state::<AppState>()
+        .file_monitor
+        .lock()
+        .unwrap()
+        .clone();
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        for path in paths {
+            let path_str = path.to_string_lossy().to_string();
+            let result = match &file_monitor {
+                Some(monitor) => monitor.ingest_path(&path, &app_handle).await,
+                None => Err("文件监控尚未初始化".to_string()),
+            };
+            match result {
+                Ok(kind) => {
+                    info!("[DRAG_DROP] 已接收拖放的{}: {}", kind, path_str);
+                    let _ = app_handle.emit(
+                        "file-drop-accepted",
+                        serde_json::json!({ "path": path_str, "kind": kind }),
+                    );
+                }
+                Err(reason) => {
+                    info!("[DRAG_DROP] 拒绝拖放的路径 {}: {}", path_str, reason);
+                    let _ = app_handle.emit(
+                        "file-drop-rejected",
+                        serde_json::json!({ "path": path_str, "reason": reason }),
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// 切换文件监控暂停状态，菜单勾选项通过 `monitoring-paused-changed` 事件同步
+fn toggle_monitoring_paused(app_handle: &tauri::AppHandle) {
+    let app_state = app_handle.state::<AppState>();
+    let paused = !app_state.is_monitoring_paused();
+    app_state.set_monitoring_paused(paused);
+    let _ = app_handle.emit("monitoring-paused-changed", paused);
+}
+
+/// 选择文件夹，添加为白名单文件夹并扫描（应用菜单 File → Add Folder…）
+#[cfg(target_os = "macos")]
+fn add_folder_from_menu(app_handle: &tauri::AppHandle) {
+    use tauri_plugin_dialog::DialogExt;
+
+    let app_handle_for_pick = app_handle.clone();
+    app_handle.dialog().file().pick_folder(move |folder| {
+        let Some(path) = folder.and_then(|folder| folder.into_path().ok()) else {
+            return;
+        };
+        let path = path.to_string_lossy().to_string();
+        let app_handle = app_handle_for_pick;
+        tauri::async_runtime::spawn(async move {
+            let (host, port) = {
+                let api_state = app_handle.state::<ApiState>();
+                let api_state_guard = api_state.0.lock().unwrap();
+                (api_state_guard.host.clone(), api_state_guard.port)
+            };
+            match launch_args::add_folder(&host, port, &path).await {
+                Ok(()) => {
+                    info!("[APP_MENU] 已添加文件夹: {}", path);
+                    if let Err(e) = security_bookmarks::save_bookmark(&app_handle, &path) {
+                        warn!("[APP_MENU] {}", e);
+                    }
+                    launch_args::queue_scan(&app_handle, &path);
+                    let _ = app_handle.emit("menu-folder-added", &path);
+                }
+                Err(e) => error!("[APP_MENU] 添加文件夹 {} 失败: {}", path, e),
+            }
+        });
+    });
+}
+
+/// 重新扫描所有白名单文件夹（应用菜单 Monitor → Rescan All）
+#[cfg(target_os = "macos")]
+fn rescan_all_folders(app_handle: &tauri::AppHandle) {
+    let file_monitor = app_handle
+        .state::<AppState>()
+        .file_monitor
+        .lock()
+        .unwrap()
+        .clone();
+    let Some(monitor) = file_monitor else {
+        error!("[APP_MENU] 文件监控尚未初始化，无法重新扫描");
+        return;
+    };
+    let folders: Vec<String> = monitor
+        .get_monitored_directories()
+        .into_iter()
+        .filter(|dir| !dir.is_blacklist)
+        .map(|dir| dir.path)
+        .collect();
+    info!("[APP_MENU] 重新扫描 {} 个文件夹", folders.len());
+    for folder in folders {
+        launch_args::queue_scan(app_handle, &folder);
+    }
+}
+
 // 配置变更请求类型
 #[derive(Debug, Clone)]
 pub enum ConfigChangeRequest {
@@ -653,20 +931,14 @@ pub enum ConfigChangeRequest {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let sidecar_supervisor = sidecar_supervisor::SidecarSupervisor::new();
     tauri::Builder::default()
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_window_state::Builder::new().build())
         .plugin(tauri_plugin_process::init())
-        .plugin(
-            tauri_plugin_log::Builder::new()
-                .level(log::LevelFilter::Info) // 你可以设置一个全局的默认级别，例如 Info
-                .level_for("tao", log::LevelFilter::Warn) // 将 tao crate 的日志级别设为 Warn
-                .level_for("notify", log::LevelFilter::Info) // Revert to INFO or desired level
-                // .level_for("notify_debouncer_full", log::LevelFilter::Info) // Revert to INFO or desired level
-                .build(),
-        )
+        .plugin(log_level::plugin())
         .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
-            println!(
+            info!(
                 "另一个实例已尝试启动，参数: {:?}，工作文件夹: {}",
                 args, cwd
             );
@@ -675,6 +947,8 @@ pub fn run() {
                 window.show().unwrap();
                 window.set_focus().unwrap();
             }
+            // 执行新实例参数中的 --add-folder / --scan
+            launch_args::dispatch(app, args, &cwd);
         }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
@@ -685,41 +959,81 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_macos_permissions::init())
         .plugin(tauri_plugin_screenshots::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_deep_link::init())
         // 创建和管理AppState
         .manage(AppState::new())
         .setup(|app| {
             let app_handle = app.handle();
             let api_state_instance = app.state::<ApiState>();
 
+            // sidecar 进程监管器（主API和额外工作进程共用）
+            let sidecar_supervisor = app
+                .state::<sidecar_supervisor::SidecarSupervisor>()
+                .inner()
+                .clone();
+
             // 创建 ApiProcessManager 并注册到应用，用于应用退出时自动清理 API 进程
             let api_manager = ApiProcessManager {
+                app_handle: app_handle.clone(),
                 api_state: api_state_instance.0.clone(),
+                sidecars: sidecar_supervisor,
+                cleaned_up: AtomicBool::new(false),
             };
             app_handle.manage(api_manager);
-            println!("已注册 ApiProcessManager，将在应用退出时自动清理 API 进程");
+            info!("已注册 ApiProcessManager，将在应用退出时自动清理 API 进程");
 
             // 注册全局 panic hook 用于清理
             let prev_hook = std::panic::take_hook();
             std::panic::set_hook(Box::new(move |panic_info| {
-                println!("Panic detected, executing cleanup: {:?}", panic_info);
+                info!("Panic detected, executing cleanup: {:?}", panic_info);
                 ApiProcessManager::cleanup_processes();
                 prev_hook(panic_info);
             }));
+            // panic 时先写崩溃报告，再执行上面的清理；上次运行留下的报告提示用户查看
+            crash_report::install(app_handle);
+            crash_report::offer_pending_report(app_handle);
 
             // Start the Python API service automatically
-            let db_path_str = app_handle
-                .path()
-                .app_data_dir()
-                .map_err(|e| e.to_string())?
-                .join("knowledge-focus.db")
-                .to_string_lossy()
-                .to_string();
+            // 主机、端口和数据库路径可通过命令行参数、环境变量或 settings.json 覆盖
+            let api_endpoint = api_config::resolve_api_endpoint(app_handle)?;
+            info!(
+                "API地址配置: {}:{}，数据库: {}",
+                api_endpoint.host, api_endpoint.port, api_endpoint.db_path
+            );
+            log_level::init(app_handle);
+            http_client::init(app_handle, api_endpoint.socket_path.as_deref());
+            power_profile::init(app_handle);
+            link_policy::init(app_handle);
+            local_rules::init(app_handle);
+            volumes::init(app_handle);
+            backup_exclusion::init(app_handle);
+            indexed_search::init(app_handle);
+            extensionless::init(app_handle);
+            screening_upload::init(app_handle);
+            local_index::init(app_handle);
+            deferred_hash::init(app_handle);
+            ocr::init(app_handle);
+            text_extraction::init(app_handle);
+            local_embedding::init(app_handle);
+            clipboard::init(app_handle);
+            sleep_wake::init(app_handle);
+            // 文件监控开始前恢复用户授权文件夹的访问权限（沙盒化构建）
+            security_bookmarks::init(app_handle);
+            event_trace::init(app_handle);
+            quick_search::init(app_handle);
+            deep_link::init(app_handle);
+            let cwd = std::env::current_dir()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .unwrap_or_default();
+            launch_args::dispatch(app_handle, std::env::args().collect(), &cwd);
             {
                 // Scope for MutexGuard
                 let mut api_state_guard = api_state_instance.0.lock().unwrap();
-                api_state_guard.port = 60315;
-                api_state_guard.host = "127.0.0.1".to_string();
-                api_state_guard.db_path = db_path_str;
+                api_state_guard.port = api_endpoint.port;
+                api_state_guard.host = api_endpoint.host;
+                api_state_guard.db_path = api_endpoint.db_path;
+                api_state_guard.socket_path = api_endpoint.socket_path;
             }
 
             // 启动Python API
@@ -734,12 +1048,30 @@ pub fn run() {
             tauri::async_runtime::spawn(async move {
                 let tx_for_api = Arc::clone(&tx);
 
+                // 外部API模式下，若配置的地址上已有可用的API则直接连接，不再启动sidecar
+                let attached_external = crate::api_startup::external_api_requested()
+                    && crate::api_startup::try_attach_external_api(
+                        &app_handle_for_api,
+                        &api_state_for_api,
+                    )
+                    .await;
+
                 // 调用api_startup模块中的start_python_api函数
-                // 但我们不使用它返回的接收端，因为我们已经创建了自己的通信通道
-                let _ = crate::api_startup::start_python_api(
-                    app_handle_for_api.clone(),
-                    api_state_for_api.clone(),
-                );
+                // 它返回的接收端只用于感知启动流程失败（如 uv sync 失败），就绪信号仍由下面的健康检查发出
+                let mut startup_rx = if attached_external {
+                    report_stage(
+                        &app_handle_for_api,
+                        StartupStage::UvSync,
+                        StageStatus::Skipped,
+                        Some("已连接外部API".to_string()),
+                    );
+                    None
+                } else {
+                    Some(crate::api_startup::start_python_api(
+                        app_handle_for_api.clone(),
+                        api_state_for_api.clone(),
+                    ))
+                };
 
                 // 获取API主机和端口
                 let (api_host, api_port) = {
@@ -749,10 +1081,10 @@ pub fn run() {
 
                 // 构建API健康检查URL
                 let api_url = format!("http://{}:{}/health", api_host, api_port);
-                println!("开始检查API是否就绪，API健康检查地址: {}", api_url);
+                info!("开始检查API是否就绪，API健康检查地址: {}", api_url);
 
-                // 使用reqwest客户端检查API健康状态
-                let client = reqwest::Client::new();
+                // 使用共享客户端检查API健康状态
+                let client = crate::http_client::shared();
                 let max_retries = 10000; // 最多尝试次数，足够长让用户看到详细日志
                 let retry_interval = std::time::Duration::from_millis(1000); // 毫秒
                 let mut api_ready = false;
@@ -761,9 +1093,22 @@ pub fn run() {
                     // 首先检查API进程是否运行
                     let api_running = {
                         let api_state_guard = api_state_for_api.lock().unwrap();
-                        api_state_guard.process_child.is_some()
+                        api_state_guard.is_running()
                     };
 
+                    // 启动流程已失败（明确失败，或启动任务已结束但进程不存在）时不再等待
+                    if let Some(rx) = startup_rx.as_mut() {
+                        let startup_failed = match rx.try_recv() {
+                            Ok(success) => !success,
+                            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => !api_running,
+                            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => false,
+                        };
+                        if startup_failed {
+                            error!("API启动流程失败，停止等待API就绪");
+                            break;
+                        }
+                    }
+
                     if !api_running {
                         // 如果进程不存在，等待短暂时间后再次检查
                         tokio::time::sleep(retry_interval).await;
@@ -778,7 +1123,7 @@ pub fn run() {
                         .await
                     {
                         Ok(response) if response.status().is_success() => {
-                            println!("第{}次尝试: API健康检查成功，API已就绪", i + 1);
+                            info!("第{}次尝试: API健康检查成功，API已就绪", i + 1);
                             api_ready = true;
                             break;
                         }
@@ -786,20 +1131,49 @@ pub fn run() {
                             // API尚未准备好，等待后重试
                             if (i + 1) % 5 == 0 {
                                 // 每5次打印一次，避免日志过多
-                                println!("第{}次尝试: API尚未就绪，继续等待...", i + 1);
+                                info!("第{}次尝试: API尚未就绪，继续等待...", i + 1);
                             }
                             tokio::time::sleep(retry_interval).await;
                         }
                     }
                 }
 
+                // 检查API接口版本，不兼容时不发送就绪信号，避免后续请求出现难以理解的解析错误
+                if api_ready {
+                    if let Err(e) = crate::api_startup::verify_api_version(
+                        &app_handle_for_api,
+                        &api_state_for_api,
+                    )
+                    .await
+                    {
+                        warn!("API版本检查未通过: {}", e);
+                        api_ready = false;
+                    }
+                }
+
+                if api_ready {
+                    report_stage(
+                        &app_handle_for_api,
+                        StartupStage::ApiBoot,
+                        StageStatus::Completed,
+                        None,
+                    );
+                } else {
+                    report_stage(
+                        &app_handle_for_api,
+                        StartupStage::ApiBoot,
+                        StageStatus::Failed,
+                        Some("API未能就绪".to_string()),
+                    );
+                }
+
                 // 简化的 API 就绪信号发送逻辑
                 // 发送信号到内部通道 (用于文件监控启动等)
                 let _api_ready_sent = {
                     let mut lock = tx_for_api.lock().unwrap();
                     if let Some(sender) = lock.take() {
                         let send_result = sender.send(api_ready);
-                        println!("已发送内部API就绪信号: {}", api_ready);
+                        info!("已发送内部API就绪信号: {}", api_ready);
                         send_result.is_ok() && api_ready
                     } else {
                         false
@@ -808,16 +1182,39 @@ pub fn run() {
 
                 // API 就绪时发送给主窗口，简化了条件检查
                 if api_ready {
-                    println!("Python API 已完全就绪，向主窗口发送 API 就绪信号");
+                    info!("Python API 已完全就绪，向主窗口发送 API 就绪信号");
 
                     // 获取主窗口句柄并发送就绪事件
                     if let Some(main) = app_handle_for_api.get_webview_window("main") {
                         // 向主窗口发送 API 就绪事件，这里是唯一发送位置
                         let _ = main.emit("api-ready", true);
-                        println!("已向主窗口发送 API 就绪信号");
+                        info!("已向主窗口发送 API 就绪信号");
                     } else {
-                        eprintln!("找不到主窗口，无法发送 API 就绪信号");
+                        error!("找不到主窗口，无法发送 API 就绪信号");
                     }
+
+                    // API首次就绪后启动看门狗，持续监测API健康状态
+                    crate::api_watchdog::start_api_watchdog(
+                        app_handle_for_api.clone(),
+                        api_state_for_api.clone(),
+                    );
+
+                    // 定期上报规则命中统计
+                    crate::rule_telemetry::start_reporting(api_state_for_api.clone());
+
+                    // 订阅桥接事件通道，替代解析stdout
+                    crate::event_stream::start_event_stream(
+                        app_handle_for_api.clone(),
+                        api_state_for_api.clone(),
+                    );
+
+                    // 启动 settings.json 中配置的额外 sidecar 工作进程
+                    crate::sidecar_supervisor::start_configured_sidecars(&app_handle_for_api);
+
+                    // 重新发送上次退出时未能发送的桥接事件
+                    crate::event_buffer::EventBuffer::shared(&app_handle_for_api)
+                        .replay_persisted_events()
+                        .await;
                 }
             });
 
@@ -833,7 +1230,12 @@ pub fn run() {
                 // 等待API就绪信号
                 match rx.await {
                     Ok(true) => {
-                        println!("收到API就绪信号，准备文件监控基础设施（不开始扫描）...");
+                        info!("收到API就绪信号，准备文件监控基础设施（不开始扫描）...");
+                        let (api_host, api_port) = {
+                            let api_state_guard = api_state_for_monitor.lock().unwrap();
+                            (api_state_guard.host.clone(), api_state_guard.port)
+                        };
+
                         // 初始化文件监控基础设施，但不开始自动扫描
                         crate::setup_file_monitor::setup_file_monitoring_infrastructure(
                             app_handle_for_monitor.clone(),
@@ -843,11 +1245,26 @@ pub fn run() {
                         .await;
 
                         // 初始化简化配置
-                        println!("开始初始化简化配置...");
+                        info!("开始初始化简化配置...");
+                        report_stage(
+                            &app_handle_for_monitor,
+                            StartupStage::ConfigFetch,
+                            StageStatus::Started,
+                            None,
+                        );
                         let app_state = app_handle_for_monitor.state::<AppState>();
-                        match app_state.refresh_simplified_config().await {
+                        match app_state
+                            .refresh_simplified_config(api_host, api_port)
+                            .await
+                        {
                             Ok(()) => {
-                                println!("简化配置初始化成功");
+                                info!("简化配置初始化成功");
+                                report_stage(
+                                    &app_handle_for_monitor,
+                                    StartupStage::ConfigFetch,
+                                    StageStatus::Completed,
+                                    None,
+                                );
                                 if let Some(window) =
                                     app_handle_for_monitor.get_webview_window("main")
                                 {
@@ -855,7 +1272,13 @@ pub fn run() {
                                 }
                             }
                             Err(e) => {
-                                eprintln!("简化配置初始化失败: {}", e);
+                                error!("简化配置初始化失败: {}", e);
+                                report_stage(
+                                    &app_handle_for_monitor,
+                                    StartupStage::ConfigFetch,
+                                    StageStatus::Failed,
+                                    Some(e.clone()),
+                                );
                                 if let Some(window) =
                                     app_handle_for_monitor.get_webview_window("main")
                                 {
@@ -868,7 +1291,8 @@ pub fn run() {
                         }
                     }
                     _ => {
-                        eprintln!("API未能成功启动，无法初始化文件监控基础设施");
+                        error!("API未能成功启动，无法初始化文件监控基础设施");
+                        tray_status::set_api_down(&app_handle_for_monitor, true);
                         if let Some(window) = app_handle_for_monitor.get_webview_window("main") {
                             let _ =
                                 window.emit("file-monitor-error", "API未就绪，无法初始化文件监控");
@@ -899,6 +1323,29 @@ pub fn run() {
                     Some("cmd+shift+right"),
                 )?;
 
+                // 创建文件、监控和帮助菜单项
+                let add_folder_item =
+                    MenuItem::with_id(app, "add_folder", "Add Folder…", true, Some("cmd+o"))?;
+                let rescan_all_item =
+                    MenuItem::with_id(app, "rescan_all", "Rescan All", true, Some("cmd+r"))?;
+                let menu_pause_item = CheckMenuItem::with_id(
+                    app,
+                    PAUSE_MONITORING_ID,
+                    "Pause Monitoring",
+                    true,
+                    app.state::<AppState>().is_monitoring_paused(),
+                    Some("cmd+shift+p"),
+                )?;
+                let website_item = MenuItem::with_id(
+                    app,
+                    "help_website",
+                    "Knowledge Focus Website",
+                    true,
+                    None::<&str>,
+                )?;
+                let show_logs_item =
+                    MenuItem::with_id(app, "help_show_logs", "Show Logs", true, None::<&str>)?;
+
                 // 创建应用菜单
                 let app_menu = Submenu::with_id_and_items(
                     app,
@@ -914,6 +1361,10 @@ pub fn run() {
                     ],
                 )?;
 
+                // 创建文件菜单
+                let file_menu =
+                    Submenu::with_id_and_items(app, "file", "File", true, &[&add_folder_item])?;
+
                 // 创建编辑菜单（标准的剪切、复制、粘贴功能）
                 let edit_menu = Submenu::with_id_and_items(
                     app,
@@ -947,29 +1398,91 @@ pub fn run() {
                     ],
                 )?;
 
+                // 创建监控菜单
+                let monitor_menu = Submenu::with_id_and_items(
+                    app,
+                    "monitor",
+                    "Monitor",
+                    true,
+                    &[&rescan_all_item, &menu_pause_item],
+                )?;
+
+                // 创建帮助菜单
+                let help_menu = Submenu::with_id_and_items(
+                    app,
+                    "help",
+                    "Help",
+                    true,
+                    &[&website_item, &show_logs_item],
+                )?;
+
                 // 创建主菜单栏
-                let menu = Menu::with_items(app, &[&app_menu, &edit_menu, &window_menu])?;
+                let menu = Menu::with_items(
+                    app,
+                    &[
+                        &app_menu,
+                        &file_menu,
+                        &edit_menu,
+                        &monitor_menu,
+                        &window_menu,
+                        &help_menu,
+                    ],
+                )?;
 
                 // 设置应用菜单
                 app.set_menu(menu)?;
 
+                // 暂停状态可能由托盘菜单切换，勾选状态以事件为准
+                app.listen_any("monitoring-paused-changed", move |event| {
+                    if let Ok(paused) = serde_json::from_str::<bool>(event.payload()) {
+                        let _ = menu_pause_item.set_checked(paused);
+                    }
+                });
+
                 // 处理菜单事件
                 app.on_menu_event(move |app, event| {
                     match event.id().as_ref() {
                         "settings" => {
-                            println!("Settings 菜单项被点击");
+                            info!("Settings 菜单项被点击");
                             if let Some(window) = app.get_webview_window("main") {
                                 let _ = window.emit("menu-settings", "general");
                             }
                         }
                         "about" => {
-                            println!("About 菜单项被点击");
+                            info!("About 菜单项被点击");
                             if let Some(window) = app.get_webview_window("main") {
                                 let _ = window.emit("menu-settings", "about");
                             }
                         }
+                        "add_folder" => {
+                            info!("Add Folder 菜单项被点击");
+                            add_folder_from_menu(app);
+                        }
+                        "rescan_all" => {
+                            info!("Rescan All 菜单项被点击");
+                            rescan_all_folders(app);
+                        }
+                        PAUSE_MONITORING_ID => {
+                            info!("Pause Monitoring 菜单项被点击");
+                            toggle_monitoring_paused(app);
+                        }
+                        "help_website" => {
+                            use tauri_plugin_opener::OpenerExt;
+                            let _ = app.opener().open_url(
+                                "https://github.com/huozhong-in/knowledge-focus",
+                                None::<&str>,
+                            );
+                        }
+                        "help_show_logs" => {
+                            use tauri_plugin_opener::OpenerExt;
+                            if let Ok(log_dir) = app.path().app_log_dir() {
+                                let _ = app
+                                    .opener()
+                                    .open_path(log_dir.to_string_lossy(), None::<&str>);
+                            }
+                        }
                         "move_left" => {
-                            println!("Move Left 菜单项被点击");
+                            info!("Move Left 菜单项被点击");
                             if let Some(window) = app.get_webview_window("main") {
                                 // 获取屏幕尺寸并将窗口移动到左半屏
                                 if let Ok(monitor) = window.current_monitor() {
@@ -1003,7 +1516,7 @@ pub fn run() {
                             }
                         }
                         "move_right" => {
-                            println!("Move Right 菜单项被点击");
+                            info!("Move Right 菜单项被点击");
                             if let Some(window) = app.get_webview_window("main") {
                                 // 获取屏幕尺寸并将窗口移动到右半屏
                                 if let Ok(monitor) = window.current_monitor() {
@@ -1043,30 +1556,56 @@ pub fn run() {
             }
 
             // 设置托盘图标和菜单
+            let pause_i = CheckMenuItem::with_id(
+                app,
+                PAUSE_MONITORING_ID,
+                "Pause Monitoring",
+                true,
+                false,
+                None::<&str>,
+            )?;
             let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&quit_i])?;
-            // 在托盘菜单事件中处理退出操作
-            let tray_icon = TrayIconBuilder::new()
+            let status_items = tray_status::TrayStatusItems::new(app.handle())?;
+            let menu = Menu::with_items(
+                app,
+                &[
+                    &status_items.scan_state,
+                    &status_items.processed,
+                    &status_items.filtered,
+                    &PredefinedMenuItem::separator(app)?,
+                    &pause_i,
+                    &PredefinedMenuItem::separator(app)?,
+                    &quit_i,
+                ],
+            )?;
+            tray_status::start_refresh(app.handle().clone(), status_items.clone());
+            // 托盘菜单和应用菜单都可以切换暂停状态，托盘勾选项和状态项以事件为准
+            let app_handle_for_sync = app.handle().clone();
+            app.listen_any("monitoring-paused-changed", move |event| {
+                let Ok(paused) = serde_json::from_str::<bool>(event.payload()) else {
+                    return;
+                };
+                // 部分平台点击时会自动切换勾选状态，这里以监控状态为准
+                let _ = pause_i.set_checked(paused);
+                let _ = pause_i.set_text(if paused {
+                    "Monitoring Paused"
+                } else {
+                    "Pause Monitoring"
+                });
+                // 刷新状态项，同时更新托盘角标和提示文字
+                status_items.refresh(&app_handle_for_sync);
+            });
+            // 在托盘菜单事件中处理暂停监控和退出操作
+            let tray_icon = TrayIconBuilder::with_id(TRAY_ICON_ID)
                 .menu(&menu)
                 .show_menu_on_left_click(false) // Changed to false for right-click menu
-                .on_menu_event(|app, event| match event.id.as_ref() {
+                .on_menu_event(move |app, event| match event.id.as_ref() {
+                    PAUSE_MONITORING_ID => toggle_monitoring_paused(app),
                     "quit" => {
-                        println!("退出菜单项被点击");
-
-                        // 在退出前执行完整清理
-                        println!("执行完整进程清理");
-
-                        // 尝试获取ApiProcessManager并执行完整清理
-                        if let Some(api_manager) = app.try_state::<ApiProcessManager>() {
-                            api_manager.cleanup();
-                            println!("通过ApiProcessManager实例执行了完整清理");
-                        } else {
-                            println!("无法获取ApiProcessManager，使用静态清理");
-                            ApiProcessManager::cleanup_processes();
-                        }
+                        info!("退出菜单项被点击");
 
-                        // 终止所有资源并退出应用
-                        app.exit(0);
+                        // 依次停止监控、排空批处理和桥接事件、关闭API，完成后退出应用
+                        shutdown::request_shutdown(app);
                     }
                     _ => {
                         // println!("menu item {:?} not handled", event.id);
@@ -1109,52 +1648,130 @@ pub fn run() {
                     }
                 })
                 .build(app)?;
-            println!("Tray Icon ID: {:?}", tray_icon.id());
+            info!("Tray Icon ID: {:?}", tray_icon.id());
             Ok(())
         })
-        // 管理API进程状态
+        // 管理启动阶段记录
+        .manage(startup_stages::StartupStageState::default())
+        // 管理sidecar进程监管器和API进程状态
+        .manage(sidecar_supervisor.clone())
         .manage(ApiState(Arc::new(Mutex::new(ApiProcessState {
-            process_child: None,
-            port: 60315,
-            host: "127.0.0.1".to_string(),
+            supervisor: sidecar_supervisor,
+            port: api_config::DEFAULT_API_PORT,
+            host: api_config::DEFAULT_API_HOST.to_string(),
             db_path: String::new(),
+            socket_path: None,
+            external: false,
         }))))
         // 管理文件监控状态
         .manage(Arc::new(Mutex::new(Option::<FileMonitor>::None)))
         .invoke_handler(tauri::generate_handler![
-            commands::refresh_monitoring_config,         // 刷新监控配置
-            commands::refresh_simplified_config,         // 刷新简化配置
-            commands::read_directory,                    // 读取目录内容
-            commands::get_tag_cloud_data,                // 获取标签云数据
-            commands::search_files_by_tags,              // 按标签搜索文件
-            commands::queue_add_blacklist_folder,        // 添加黑名单文件夹
-            commands::queue_delete_folder,               // 删除文件夹
-            commands::queue_toggle_folder_status,        // 切换文件夹状态（黑名单/白名单）
-            commands::queue_add_whitelist_folder,        // 添加白名单文件夹
-            commands::queue_get_status,                  // 获取队列状态
-            file_scanner::start_backend_scanning,        // 后端扫描启动命令
-            file_scanner::scan_files_by_time_range,      // 按时间范围扫描文件
-            file_scanner::scan_files_by_type,            // 按类型扫描文件
-            file_scanner::scan_files_simplified_command, // 简化扫描命令（支持Bundle和新配置）
+            commands::refresh_monitoring_config,          // 刷新监控配置
+            commands::refresh_simplified_config,          // 刷新简化配置
+            commands::read_directory,                     // 读取目录内容
+            commands::get_tag_cloud_data,                 // 获取标签云数据
+            commands::search_files_by_tags,               // 按标签搜索文件
+            commands::queue_add_blacklist_folder,         // 添加黑名单文件夹
+            commands::queue_delete_folder,                // 删除文件夹
+            commands::queue_toggle_folder_status,         // 切换文件夹状态（黑名单/白名单）
+            commands::queue_add_whitelist_folder,         // 添加白名单文件夹
+            commands::queue_get_status,                   // 获取队列状态
+            commands::get_api_endpoint,                   // 获取API地址配置
+            commands::set_event_buffer_strategy,          // 运行时修改事件缓冲策略
+            commands::get_event_buffer_strategies,        // 获取事件缓冲策略
+            commands::get_event_buffer_stats,             // 获取事件缓冲区统计信息
+            commands::replay_recent_events,               // 向窗口重放最近的状态事件
+            commands::open_full_disk_access_settings,     // 打开系统设置中的磁盘访问授权面板
+            commands::audit_rules,                        // 规则试运行，预览规则匹配结果
+            commands::run_scan_benchmark,                 // 扫描基准测试，分阶段测量吞吐量
+            commands::add_custom_bundle_extension,        // 添加自定义Bundle扩展名
+            commands::remove_custom_bundle_extension,     // 移除自定义Bundle扩展名
+            api_control::send_api_command,                // 向Python API发送控制命令
+            backup_exclusion::set_backup_exclusion,       // 设置是否排除备份位置
+            backup_exclusion::get_backup_exclusion,       // 获取备份排除设置和检测到的备份位置
+            event_trace::set_event_tracing,               // 开启或关闭事件追踪
+            event_trace::get_event_tracing_status,        // 获取事件追踪状态
+            extensionless::set_extensionless_policy,      // 设置无扩展名文件策略
+            extensionless::get_extensionless_policy,      // 获取无扩展名文件策略
+            screening_upload::set_screening_upload_mode,  // 设置粗筛数据提交模式
+            screening_upload::get_screening_upload_mode,  // 获取粗筛数据提交模式
+            screening_upload::set_batch_send_concurrency, // 设置批量请求并发数
+            screening_upload::get_batch_send_concurrency, // 获取批量请求并发数
+            local_index::local_index_search,              // 按文件名搜索本地索引
+            local_index::local_index_recent_files,        // 本地索引中最近修改的文件
+            local_index::local_index_duplicates,          // 本地索引中的候选重复文件
+            local_index::local_index_diff_with_api,       // 比对本地索引与API粗筛结果
+            deferred_hash::set_deferred_hashing,          // 开启或关闭延迟哈希模式
+            deferred_hash::get_deferred_hashing,          // 获取延迟哈希模式和待计算数量
+            text_extraction::set_text_extraction,         // 开启或关闭文本提取
+            text_extraction::get_text_extraction,         // 获取文本提取开关和统计
+            local_embedding::set_local_embedding_model,   // 设置本地向量模型目录
+            local_embedding::get_local_embedding_status,  // 获取本地向量化状态和统计
+            ocr::set_ocr_mode,                            // 设置图片文字识别模式
+            ocr::get_ocr_mode,                            // 获取图片文字识别模式
+            clipboard::set_clipboard_capture,             // 开启或关闭剪贴板捕获
+            clipboard::pause_clipboard_capture,           // 暂停剪贴板捕获
+            clipboard::resume_clipboard_capture,          // 恢复剪贴板捕获
+            clipboard::get_clipboard_capture,             // 获取剪贴板捕获状态和统计
+            screenshots::get_screenshot_directory,        // 获取检测到的截图文件夹
+            indexed_search::set_scan_backend,             // 设置扫描后端（系统索引或遍历目录）
+            indexed_search::get_scan_backend,             // 获取扫描后端
+            link_policy::set_link_policy,                 // 设置符号链接和目录联接的遍历策略
+            link_policy::get_link_policy,                 // 获取链接遍历策略
+            local_rules::reload_local_rules,              // 重新加载本地规则文件
+            log_level::set_log_level,                     // 运行时修改日志级别
+            log_level::get_log_levels,                    // 获取日志级别
+            power_profile::set_power_profile,             // 设置电源模式
+            power_profile::get_power_profile,             // 获取电源模式和电源状态
+            quick_search::set_quick_search_shortcut,      // 修改快速搜索快捷键
+            quick_search::get_quick_search_shortcut,      // 获取快速搜索快捷键
+            security_bookmarks::list_security_bookmarks,  // 列出已保存安全范围书签的文件夹
+            settings_window::open_settings_window,        // 打开独立设置窗口
+            settings_window::close_settings_window,       // 关闭独立设置窗口
+            startup_stages::get_startup_stages,           // 获取启动阶段记录
+            update_channel::check_for_update,             // 按更新通道检查更新
+            update_channel::download_and_install_update,  // 下载并安装更新
+            update_channel::set_update_channel,           // 设置更新通道
+            update_channel::set_update_policy,            // 设置更新下载策略
+            update_channel::get_update_settings,          // 获取更新通道和下载策略
+            sidecar_supervisor::get_sidecar_status,       // 获取sidecar工作进程状态
+            sidecar_supervisor::restart_sidecar,          // 手动重启sidecar工作进程
+            file_scanner::start_backend_scanning,         // 后端扫描启动命令
+            file_scanner::scan_files_by_time_range,       // 按时间范围扫描文件
+            file_scanner::scan_files_by_type,             // 按类型扫描文件
+            file_scanner::scan_files_simplified_command,  // 简化扫描命令（支持Bundle和新配置）
         ])
+        .on_page_load(|webview, payload| {
+            // 新打开的窗口（如设置窗口）加载完成后补发最近的状态事件
+            if matches!(payload.event(), tauri::webview::PageLoadEvent::Finished) {
+                if let Some(event_buffer) = webview
+                    .app_handle()
+                    .try_state::<Arc<event_buffer::EventBuffer>>()
+                {
+                    event_buffer.replay_recent_events(webview.label());
+                }
+            }
+        })
         .on_window_event(|window, event| match event {
             WindowEvent::Destroyed => {
                 // 获取窗口的标牌，区分是哪个窗口被销毁
                 let window_label = window.label();
 
-                println!("窗口被销毁: {}", window_label);
+                info!("窗口被销毁: {}", window_label);
 
-                // 如果是主窗口被销毁，执行清理
-                if window_label == "main" {
-                    println!("主窗口被销毁，执行完整进程清理");
+                // 如果是主窗口被销毁，执行清理（有序退出流程已完成清理时跳过）
+                if window_label == "main" && shutdown::has_completed() {
+                    info!("主窗口被销毁，有序退出流程已完成清理");
+                } else if window_label == "main" {
+                    info!("主窗口被销毁，执行完整进程清理");
 
                     // 尝试获取ApiProcessManager并执行完整清理
                     if let Some(api_manager) = window.app_handle().try_state::<ApiProcessManager>()
                     {
                         api_manager.cleanup();
-                        println!("通过ApiProcessManager实例执行了完整清理");
+                        info!("通过ApiProcessManager实例执行了完整清理");
                     } else {
-                        println!("无法获取ApiProcessManager，使用静态清理");
+                        warn!("无法获取ApiProcessManager，使用静态清理");
                         ApiProcessManager::cleanup_processes();
                     }
                 }
@@ -1172,7 +1789,7 @@ pub fn run() {
                             // Prevent the default window close behavior
                             api.prevent_close();
                             // Hide the window
-                            println!("隐藏主窗口而不是关闭");
+                            info!("隐藏主窗口而不是关闭");
                             window.hide().unwrap();
                             let _ = window
                                 .app_handle()
@@ -1185,12 +1802,27 @@ pub fn run() {
                             window.app_handle().exit(0);
                         }
                     }
+                    // 设置窗口真正关闭，下次打开时重新创建（位置和大小由window-state插件保存）
+                    settings_window::SETTINGS_LABEL => {
+                        info!("关闭设置窗口");
+                    }
                     // 对于其他窗口，采用默认行为
                     _ => {
-                        println!("关闭其他窗口: {}", window_label);
+                        info!("关闭其他窗口: {}", window_label);
                     }
                 }
             }
+            // 拖放到主窗口的文件送入粗筛管道
+            WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. })
+                if window.label() == "main" =>
+            {
+                info!("[DRAG_DROP] 主窗口收到 {} 个拖放路径", paths.len());
+                handle_dropped_paths(window.app_handle(), paths.clone());
+            }
+            // 快速搜索窗口失去焦点时隐藏
+            WindowEvent::Focused(false) if window.label() == quick_search::QUICK_SEARCH_LABEL => {
+                let _ = window.hide();
+            }
             _ => {}
         })
         .build(tauri::generate_context!())
@@ -1213,30 +1845,36 @@ pub fn run() {
                 event,
                 tauri::RunEvent::ExitRequested { .. } | tauri::RunEvent::Exit
             ) {
-                println!("=== 收到重要运行事件: {} ===", event_name);
+                info!("=== 收到重要运行事件: {} ===", event_name);
             }
 
             match event {
+                tauri::RunEvent::ExitRequested { .. } if shutdown::has_completed() => {
+                    info!("ExitRequested 事件：有序退出流程已完成清理");
+                }
                 tauri::RunEvent::ExitRequested { .. } => {
-                    // 应用退出请求时终止API进程
-                    println!("ExitRequested 事件：开始清理API进程");
+                    // 应用退出请求时终止API进程（清理前会先送出未发送的元数据和桥接事件）
+                    info!("ExitRequested 事件：开始清理API进程");
 
                     // 尝试获取ApiProcessManager并执行完整清理
                     if let Some(api_manager) = app_handle.try_state::<ApiProcessManager>() {
                         api_manager.cleanup();
-                        println!("通过ApiProcessManager实例执行了完整清理");
+                        info!("通过ApiProcessManager实例执行了完整清理");
                     } else {
-                        println!("无法获取ApiProcessManager，使用静态清理");
+                        warn!("无法获取ApiProcessManager，使用静态清理");
                         ApiProcessManager::cleanup_processes();
                     }
 
-                    println!("ExitRequested 事件：资源清理完毕");
+                    info!("ExitRequested 事件：资源清理完毕");
+                }
+                tauri::RunEvent::Exit if shutdown::has_completed() => {
+                    info!("Exit 事件：有序退出流程已完成清理");
                 }
                 tauri::RunEvent::Exit => {
                     // 应用最终退出时的备用清理
-                    println!("Exit 事件：进行备用API进程清理");
+                    info!("Exit 事件：进行备用API进程清理");
                     ApiProcessManager::cleanup_processes();
-                    println!("Exit 事件：备用清理完毕");
+                    info!("Exit 事件：备用清理完毕");
                 }
                 _ => {
                     // 其他事件不做处理
@@ -0,0 +1,148 @@
+//! # 符号链接与目录联接策略 (Symlink & Junction Policy)
+//!
+//! Windows 上 OneDrive、`AppData` 等位置常见 NTFS 目录联接（junction），跟随它们会把
+//! 同一棵目录树扫描多次，互相指向的联接还会导致无限递归。遍历和监控目录时统一按策略处理
+//! 符号链接目录和目录联接（标准库把 junction 等名称代理类重解析点也视为符号链接）：
+//! - `skip`（默认）：不进入链接目录，只扫描监控目录本身的真实内容
+//! - `follow`：进入链接目录，但按真实路径记录已访问的目录，每个目录只扫描一次，防止循环
+//!
+//! 选择保存在 settings.json 的 `link_policy` 中，修改后对之后的扫描和新建的监控生效。
+
+use log::info;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+/// settings.json 中保存链接策略的键
+const POLICY_STORE_KEY: &str = "link_policy";
+
+/// 是否跟随链接目录，默认不跟随
+static FOLLOW_LINKS: AtomicBool = AtomicBool::new(false);
+
+fn policy_name(follow: bool) -> &'static str {
+    if follow {
+        "follow"
+    } else {
+        "skip"
+    }
+}
+
+fn parse_policy(name: &str) -> Option<bool> {
+    match name {
+        "skip" => Some(false),
+        "follow" => Some(true),
+        _ => None,
+    }
+}
+
+/// 是否跟随符号链接目录和目录联接
+pub fn follow_links() -> bool {
+    FOLLOW_LINKS.load(Ordering::SeqCst)
+}
+
+/// 目录的真实路径（解析所有链接），无法解析时返回原路径
+pub fn real_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path)
+        .map(|real| crate::path_utils::normalize_path(&real))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 按真实路径记录已访问的目录，用于循环保护和去重
+#[derive(Default)]
+pub struct VisitedDirs {
+    seen: HashSet<PathBuf>,
+}
+
+impl VisitedDirs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 第一次访问该目录（按真实路径）时返回 true
+    pub fn first_visit(&mut self, dir: &Path) -> bool {
+        self.seen.insert(real_path(dir))
+    }
+
+    /// WalkDir 的 `filter_entry` 中调用，决定是否处理该条目（以及是否进入该目录）
+    ///
+    /// 遍历时需用 `follow_links(follow_links())` 创建 WalkDir
+    pub fn should_walk(&mut self, entry: &walkdir::DirEntry) -> bool {
+        // 监控目录本身即使是链接也要扫描
+        if entry.depth() > 0 && entry.path_is_symlink() && !follow_links() {
+            return false;
+        }
+        if entry.file_type().is_dir() && follow_links() && !self.first_visit(entry.path()) {
+            info!(
+                "[LINK_POLICY] 目录已通过其他路径扫描过，跳过: {:?}",
+                entry.path()
+            );
+            return false;
+        }
+        true
+    }
+}
+
+/// 去掉真实路径重复的目录（例如一个监控目录是指向另一个监控目录的联接），保留第一次出现的
+pub fn dedup_directories(directories: Vec<String>) -> Vec<String> {
+    let mut visited = VisitedDirs::new();
+    directories
+        .into_iter()
+        .filter(|dir| {
+            let first = visited.first_visit(Path::new(dir));
+            if !first {
+                info!(
+                    "[LINK_POLICY] 目录与已监控的目录指向同一位置，跳过: {}",
+                    dir
+                );
+            }
+            first
+        })
+        .collect()
+}
+
+/// 恢复保存的链接策略，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let saved = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(POLICY_STORE_KEY))
+        .and_then(|value| value.as_str().and_then(parse_policy));
+    if let Some(follow) = saved {
+        FOLLOW_LINKS.store(follow, Ordering::SeqCst);
+    }
+}
+
+/// 设置链接策略：skip（不进入链接目录）或 follow（进入，每个真实目录只扫描一次）
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_link_policy(
+    policy: String,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let follow = parse_policy(&policy).ok_or_else(|| format!("未知的链接策略: {}", policy))?;
+    FOLLOW_LINKS.store(follow, Ordering::SeqCst);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(POLICY_STORE_KEY, serde_json::json!(policy));
+    store
+        .save()
+        .map_err(|e| format!("保存链接策略失败: {}", e))?;
+
+    info!("[LINK_POLICY] 链接策略已设置为: {}", policy);
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("链接策略已设置为 {}", policy)
+    }))
+}
+
+/// 获取当前链接策略
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_link_policy() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "policy": policy_name(follow_links())
+    }))
+}
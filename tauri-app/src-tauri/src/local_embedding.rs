@@ -0,0 +1,380 @@
+//! # 本地向量化 (Local Embeddings)
+//!
+//! 文本提取发送成功后，把文本切成段落块，在 Rust 端用本地 BERT 类模型（candle，CPU 推理）计算向量，
+//! 按块通过 `/file-content/embeddings` 发送到 API，语义检索不再依赖 Python 进程的 GPU/CPU。
+//!
+//! 模型目录需包含 `config.json`、`tokenizer.json` 和 `model.safetensors`（如 bge-small、all-MiniLM），
+//! 向量做均值池化和 L2 归一化。模型目录保存在 settings.json 的 `local_embedding_model_dir` 中，
+//! 未设置时不计算向量。推理代码需要编译时启用 `local-embeddings` 特性，未启用时无法设置模型目录。
+//!
+//! 待计算队列有界，队列已满时丢弃，文件下次变化时重新计算。计算在单个阻塞线程上依次进行，
+//! 系统睡眠时暂停。
+
+use log::{error, info};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::mpsc;
+
+/// settings.json 中保存模型目录的键
+const MODEL_DIR_STORE_KEY: &str = "local_embedding_model_dir";
+/// 待计算队列容量（文件数）
+const QUEUE_CAPACITY: usize = 1_000;
+/// 每块最多的字符数，超出的段落按字符切开
+const CHUNK_CHARS: usize = 1_000;
+/// 每个文件最多计算的块数
+const MAX_CHUNKS_PER_FILE: usize = 256;
+/// 每次推理的块数
+const INFERENCE_BATCH: usize = 16;
+
+static MODEL: Mutex<Option<Arc<model::Embedder>>> = Mutex::new(None);
+static QUEUE: OnceLock<mpsc::Sender<(String, String)>> = OnceLock::new();
+/// 已发送到 API 的向量数
+static EMBEDDED_CHUNKS: AtomicU64 = AtomicU64::new(0);
+/// 队列已满时丢弃的文件数
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "local-embeddings")]
+mod model {
+    use candle_core::{Device, Tensor};
+    use candle_nn::VarBuilder;
+    use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+    use std::path::Path;
+    use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+
+    /// 模型支持的最大 token 数
+    const MAX_TOKENS: usize = 512;
+
+    pub struct Embedder {
+        name: String,
+        model: BertModel,
+        tokenizer: Tokenizer,
+        device: Device,
+    }
+
+    impl Embedder {
+        pub fn load(dir: &Path) -> Result<Embedder, String> {
+            let device = Device::Cpu;
+            let config = std::fs::read_to_string(dir.join("config.json"))
+                .map_err(|e| format!("读取 config.json 失败: {}", e))?;
+            let config: Config = serde_json::from_str(&config)
+                .map_err(|e| format!("解析 config.json 失败: {}", e))?;
+            let mut tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))
+                .map_err(|e| format!("读取 tokenizer.json 失败: {}", e))?;
+            tokenizer.with_padding(Some(PaddingParams::default()));
+            tokenizer
+                .with_truncation(Some(TruncationParams {
+                    max_length: MAX_TOKENS,
+                    ..Default::default()
+                }))
+                .map_err(|e| format!("设置截断长度失败: {}", e))?;
+            let weights = dir.join("model.safetensors");
+            // 安全性：模型文件在使用期间不会被修改
+            let vb = unsafe { VarBuilder::from_mmaped_safetensors(&[weights], DTYPE, &device) }
+                .map_err(|e| format!("读取 model.safetensors 失败: {}", e))?;
+            let model = BertModel::load(vb, &config).map_err(|e| format!("加载模型失败: {}", e))?;
+            let name = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Ok(Embedder {
+                name,
+                model,
+                tokenizer,
+                device,
+            })
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        /// 计算一组文本的向量（均值池化后 L2 归一化）
+        pub fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+            let encodings = self
+                .tokenizer
+                .encode_batch(texts.to_vec(), true)
+                .map_err(|e| format!("分词失败: {}", e))?;
+            let to_tensor = |rows: Vec<&[u32]>| -> candle_core::Result<Tensor> {
+                let rows = rows
+                    .into_iter()
+                    .map(|row| Tensor::new(row, &self.device))
+                    .collect::<candle_core::Result<Vec<_>>>()?;
+                Tensor::stack(&rows, 0)
+            };
+            let run = || -> candle_core::Result<Vec<Vec<f32>>> {
+                let ids = to_tensor(encodings.iter().map(|e| e.get_ids()).collect())?;
+                let mask = to_tensor(encodings.iter().map(|e| e.get_attention_mask()).collect())?;
+                let type_ids = ids.zeros_like()?;
+                let hidden = self.model.forward(&ids, &type_ids, Some(&mask))?;
+                let mask = mask.to_dtype(DTYPE)?.unsqueeze(2)?;
+                let pooled = hidden
+                    .broadcast_mul(&mask)?
+                    .sum(1)?
+                    .broadcast_div(&mask.sum(1)?)?;
+                let norm = pooled.sqr()?.sum_keepdim(1)?.sqrt()?;
+                pooled.broadcast_div(&norm)?.to_vec2::<f32>()
+            };
+            run().map_err(|e| format!("推理失败: {}", e))
+        }
+    }
+}
+
+#[cfg(not(feature = "local-embeddings"))]
+mod model {
+    use std::path::Path;
+
+    /// 未启用 `local-embeddings` 特性时无法创建
+    pub enum Embedder {}
+
+    impl Embedder {
+        pub fn load(_dir: &Path) -> Result<Embedder, String> {
+            Err("此版本编译时未启用 local-embeddings 特性，不支持本地向量化".to_string())
+        }
+
+        pub fn name(&self) -> &str {
+            match *self {}
+        }
+
+        pub fn embed(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+            match *self {}
+        }
+    }
+}
+
+fn current_model() -> Option<Arc<model::Embedder>> {
+    MODEL.lock().unwrap().clone()
+}
+
+/// 当前是否计算本地向量
+pub fn enabled() -> bool {
+    MODEL.lock().unwrap().is_some()
+}
+
+/// 加载保存的模型并启动后台任务，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+    if QUEUE.set(tx).is_err() {
+        return;
+    }
+    let saved = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(MODEL_DIR_STORE_KEY))
+        .and_then(|value| value.as_str().map(PathBuf::from));
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Some(dir) = saved {
+            match load_model(dir.clone()).await {
+                Ok(()) => info!("[LOCAL_EMBEDDING] 已加载本地向量模型 {:?}", dir),
+                Err(e) => error!("[LOCAL_EMBEDDING] 加载本地向量模型失败 {:?}: {}", dir, e),
+            }
+        }
+        run(app_handle, rx).await;
+    });
+}
+
+// 在阻塞线程上加载模型，成功后替换当前模型
+async fn load_model(dir: PathBuf) -> Result<(), String> {
+    let embedder = tauri::async_runtime::spawn_blocking(move || model::Embedder::load(&dir))
+        .await
+        .map_err(|e| format!("加载任务异常结束: {}", e))??;
+    *MODEL.lock().unwrap() = Some(Arc::new(embedder));
+    Ok(())
+}
+
+/// 文本已发送到 API 的文件加入待计算队列
+pub fn enqueue(files: Vec<(String, String)>) {
+    if !enabled() {
+        return;
+    }
+    let Some(queue) = QUEUE.get() else {
+        return;
+    };
+    for file in files {
+        if queue.try_send(file).is_err() {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+// 后台任务：逐个文件切块、计算向量并发送到 API
+async fn run(app_handle: AppHandle, mut rx: mpsc::Receiver<(String, String)>) {
+    while let Some((file_path, text)) = rx.recv().await {
+        crate::sleep_wake::wait_while_paused().await;
+        // 模型在排队期间被清除时丢弃
+        let Some(embedder) = current_model() else {
+            continue;
+        };
+
+        let chunks = chunk_text(&text);
+        if chunks.is_empty() {
+            continue;
+        }
+        let model_name = embedder.name().to_string();
+        let inputs = chunks.clone();
+        let vectors = tauri::async_runtime::spawn_blocking(move || {
+            let mut vectors = Vec::with_capacity(inputs.len());
+            for group in inputs.chunks(INFERENCE_BATCH) {
+                vectors.extend(embedder.embed(group)?);
+            }
+            Ok::<_, String>(vectors)
+        })
+        .await;
+        let vectors = match vectors {
+            Ok(Ok(vectors)) => vectors,
+            Ok(Err(e)) => {
+                error!("[LOCAL_EMBEDDING] 计算向量失败 {}: {}", file_path, e);
+                continue;
+            }
+            Err(_) => return,
+        };
+
+        let count = vectors.len();
+        let items: Vec<serde_json::Value> = chunks
+            .into_iter()
+            .zip(vectors)
+            .enumerate()
+            .map(|(chunk_index, (text, vector))| {
+                serde_json::json!({
+                    "chunk_index": chunk_index,
+                    "text": text,
+                    "vector": vector
+                })
+            })
+            .collect();
+        match post_embeddings(&app_handle, &file_path, &model_name, items).await {
+            Ok(()) => {
+                EMBEDDED_CHUNKS.fetch_add(count as u64, Ordering::Relaxed);
+                info!("[LOCAL_EMBEDDING] 已发送 {} 的 {} 个向量", file_path, count);
+            }
+            Err(e) => error!("[LOCAL_EMBEDDING] 发送 {} 的向量失败: {}", file_path, e),
+        }
+    }
+}
+
+// 按空行分段后合并为不超过 CHUNK_CHARS 的块，过长的段落按字符切开
+fn chunk_text(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0usize;
+    for paragraph in text.split("\n\n") {
+        let paragraph = paragraph.trim();
+        if paragraph.is_empty() {
+            continue;
+        }
+        let chars: Vec<char> = paragraph.chars().collect();
+        for piece in chars.chunks(CHUNK_CHARS) {
+            if current_chars > 0 && current_chars + piece.len() + 2 > CHUNK_CHARS {
+                chunks.push(std::mem::take(&mut current));
+                current_chars = 0;
+            }
+            if current_chars > 0 {
+                current.push_str("\n\n");
+                current_chars += 2;
+            }
+            current.extend(piece);
+            current_chars += piece.len();
+        }
+        if chunks.len() >= MAX_CHUNKS_PER_FILE {
+            break;
+        }
+    }
+    if current_chars > 0 {
+        chunks.push(current);
+    }
+    chunks.truncate(MAX_CHUNKS_PER_FILE);
+    chunks
+}
+
+// 发送一个文件的全部向量，API 替换该文件原有的向量
+async fn post_embeddings(
+    app_handle: &AppHandle,
+    file_path: &str,
+    model_name: &str,
+    items: Vec<serde_json::Value>,
+) -> Result<(), String> {
+    let (api_host, api_port) = {
+        let api_state = app_handle.state::<crate::ApiState>();
+        let api_state_guard = api_state.0.lock().unwrap();
+        (api_state_guard.host.clone(), api_state_guard.port)
+    };
+    let url = format!("http://{}:{}/file-content/embeddings", api_host, api_port);
+    let response = crate::http_client::shared()
+        .post(&url)
+        .timeout(Duration::from_secs(60))
+        .json(&serde_json::json!({
+            "file_path": file_path,
+            "model": model_name,
+            "items": items
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("请求失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("API返回错误状态: {}", response.status()));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("解析响应失败: {}", e))?;
+    if body["success"].as_bool() != Some(true) {
+        return Err(format!("API未能保存向量: {}", body["message"]));
+    }
+    Ok(())
+}
+
+/// 设置本地向量模型目录，传入空值时停止计算向量
+#[tauri::command(rename_all = "snake_case", async_runtime = "tokio")]
+pub async fn set_local_embedding_model(
+    model_dir: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let model_dir = model_dir.filter(|dir| !dir.trim().is_empty());
+    match &model_dir {
+        Some(dir) => {
+            if !Path::new(dir).is_dir() {
+                return Err(format!("模型目录不存在: {}", dir));
+            }
+            load_model(PathBuf::from(dir)).await?;
+        }
+        None => *MODEL.lock().unwrap() = None,
+    }
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    match &model_dir {
+        Some(dir) => store.set(MODEL_DIR_STORE_KEY, serde_json::json!(dir)),
+        None => {
+            store.delete(MODEL_DIR_STORE_KEY);
+        }
+    }
+    store
+        .save()
+        .map_err(|e| format!("保存本地向量模型设置失败: {}", e))?;
+
+    info!("[LOCAL_EMBEDDING] 本地向量模型已设置为 {:?}", model_dir);
+    Ok(serde_json::json!({
+        "status": "success",
+        "enabled": model_dir.is_some(),
+        "model_dir": model_dir
+    }))
+}
+
+/// 获取本地向量化的状态和统计
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_local_embedding_status() -> Result<serde_json::Value, String> {
+    let model = current_model();
+    Ok(serde_json::json!({
+        "status": "success",
+        "supported": cfg!(feature = "local-embeddings"),
+        "enabled": model.is_some(),
+        "model": model.as_ref().map(|embedder| embedder.name().to_string()),
+        "embedded_chunks": EMBEDDED_CHUNKS.load(Ordering::Relaxed),
+        "dropped_files": DROPPED.load(Ordering::Relaxed)
+    }))
+}
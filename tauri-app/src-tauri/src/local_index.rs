@@ -0,0 +1,367 @@
+//! # 本地文件索引 (Local Index)
+//!
+//! 在应用数据目录下的 `local-index.db`（SQLite）中保存已成功发送到 API 的粗筛文件，
+//! 内容与 API 的粗筛结果表保持一致（发送成功后写入，删除事件时移除），用于：
+//! - 不经过 API 的即时查询：按文件名搜索、最近修改的文件、按哈希查找重复文件
+//! - 与 API 的粗筛结果比对，检查两边是否一致
+//! - 延迟哈希模式下记录尚未计算哈希的文件（`pending_hashes` 表），见 `deferred_hash`
+//!
+//! 写入在阻塞线程中执行，不占用 tokio 工作线程；数据库打开失败时索引不可用，不影响粗筛流程。
+
+use crate::file_monitor::FileMetadata;
+use log::{error, warn};
+use rusqlite::{params, Connection};
+use std::collections::HashSet;
+use std::path::MAIN_SEPARATOR;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// 索引数据库文件名
+const INDEX_FILE: &str = "local-index.db";
+/// 比对时最多列出的差异路径数
+const MAX_DIFF_SAMPLES: usize = 100;
+
+static DB: Mutex<Option<Connection>> = Mutex::new(None);
+
+/// 索引中的一条文件记录
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IndexedFile {
+    pub file_path: String,
+    pub file_name: String,
+    pub extension: Option<String>,
+    pub file_size: u64,
+    pub created_time: u64,
+    pub modified_time: u64,
+    pub file_hash: Option<String>,
+    pub category_id: Option<i32>,
+}
+
+impl IndexedFile {
+    pub fn of(metadata: &FileMetadata) -> IndexedFile {
+        IndexedFile {
+            file_path: metadata.file_path.clone(),
+            file_name: metadata.file_name.clone(),
+            extension: metadata.extension.clone(),
+            file_size: metadata.file_size,
+            created_time: metadata.created_time,
+            modified_time: metadata.modified_time,
+            file_hash: metadata.hash_value.clone(),
+            category_id: metadata.category_id,
+        }
+    }
+
+    pub fn signature(&self) -> crate::seen_cache::FileSignature {
+        crate::seen_cache::FileSignature::new(
+            self.modified_time,
+            self.file_size,
+            self.file_hash.clone(),
+        )
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<IndexedFile> {
+        Ok(IndexedFile {
+            file_path: row.get(0)?,
+            file_name: row.get(1)?,
+            extension: row.get(2)?,
+            file_size: row.get::<_, i64>(3)? as u64,
+            created_time: row.get::<_, i64>(4)? as u64,
+            modified_time: row.get::<_, i64>(5)? as u64,
+            file_hash: row.get(6)?,
+            category_id: row.get(7)?,
+        })
+    }
+}
+
+const SELECT_COLUMNS: &str = "file_path, file_name, extension, file_size, created_time, \
+     modified_time, file_hash, category_id";
+
+fn open(app_handle: &AppHandle) -> Result<Connection, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建应用数据目录失败: {}", e))?;
+    let conn =
+        Connection::open(dir.join(INDEX_FILE)).map_err(|e| format!("打开本地索引失败: {}", e))?;
+    conn.execute_batch(
+        "PRAGMA journal_mode = WAL;
+         PRAGMA synchronous = NORMAL;
+         CREATE TABLE IF NOT EXISTS screened_files (
+             file_path TEXT PRIMARY KEY,
+             file_name TEXT NOT NULL,
+             extension TEXT,
+             file_size INTEGER NOT NULL,
+             created_time INTEGER NOT NULL,
+             modified_time INTEGER NOT NULL,
+             file_hash TEXT,
+             category_id INTEGER,
+             sent_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_screened_files_modified ON screened_files (modified_time DESC);
+         CREATE INDEX IF NOT EXISTS idx_screened_files_hash ON screened_files (file_hash, file_size);
+         CREATE TABLE IF NOT EXISTS pending_hashes (
+             file_path TEXT PRIMARY KEY,
+             queued_at INTEGER NOT NULL
+         );",
+    )
+    .map_err(|e| format!("初始化本地索引失败: {}", e))?;
+    Ok(conn)
+}
+
+/// 打开本地索引，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    match open(app_handle) {
+        Ok(conn) => *DB.lock().unwrap() = Some(conn),
+        Err(e) => warn!("[LOCAL_INDEX] {}，本地索引不可用", e),
+    }
+}
+
+/// 本地索引是否可用
+pub fn available() -> bool {
+    DB.lock().unwrap().is_some()
+}
+
+/// 在索引可用时执行数据库操作
+fn with_db<T>(action: impl FnOnce(&mut Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+    let mut db = DB.lock().unwrap();
+    let conn = db.as_mut().ok_or_else(|| "本地索引不可用".to_string())?;
+    action(conn).map_err(|e| format!("本地索引操作失败: {}", e))
+}
+
+/// 记录已成功发送到API的文件（在阻塞线程中写入）；defer_hashes 为 true 时，
+/// 未计算哈希的非空文件同时加入待计算哈希的队列
+pub fn record(files: Vec<IndexedFile>, defer_hashes: bool) {
+    if files.is_empty() {
+        return;
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let sent_at = chrono::Utc::now().timestamp();
+        let result = with_db(|conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO screened_files (file_path, file_name, extension, \
+                     file_size, created_time, modified_time, file_hash, category_id, sent_at) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                )?;
+                let mut pending = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO pending_hashes (file_path, queued_at) VALUES (?1, ?2)",
+                )?;
+                for file in &files {
+                    stmt.execute(params![
+                        file.file_path,
+                        file.file_name,
+                        file.extension,
+                        file.file_size as i64,
+                        file.created_time as i64,
+                        file.modified_time as i64,
+                        file.file_hash,
+                        file.category_id,
+                        sent_at
+                    ])?;
+                    if defer_hashes && file.file_hash.is_none() && file.file_size > 0 {
+                        pending.execute(params![file.file_path, sent_at])?;
+                    }
+                }
+            }
+            tx.commit()
+        });
+        if let Err(e) = result {
+            error!("[LOCAL_INDEX] 写入 {} 条记录失败: {}", files.len(), e);
+        }
+    });
+}
+
+/// 文件或目录被删除后移除其记录（目录下的记录一并移除）
+pub fn remove(path: &str) {
+    let path = path.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let prefix = format!(
+            "{}{}",
+            path.trim_end_matches(MAIN_SEPARATOR),
+            MAIN_SEPARATOR
+        );
+        let result = with_db(|conn| {
+            conn.execute(
+                "DELETE FROM screened_files WHERE file_path = ?1 OR substr(file_path, 1, length(?2)) = ?2",
+                params![path, prefix],
+            )?;
+            conn.execute(
+                "DELETE FROM pending_hashes WHERE file_path = ?1 OR substr(file_path, 1, length(?2)) = ?2",
+                params![path, prefix],
+            )
+        });
+        if let Err(e) = result {
+            error!("[LOCAL_INDEX] 移除记录失败 {}: {}", path, e);
+        }
+    });
+}
+
+/// 待计算哈希的文件路径，按加入队列的先后顺序（阻塞）
+pub fn pending_hashes(limit: usize) -> Result<Vec<String>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare_cached(
+            "SELECT file_path FROM pending_hashes ORDER BY queued_at, file_path LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| row.get(0))?;
+        rows.collect()
+    })
+}
+
+/// 待计算哈希的文件数
+pub fn pending_hash_count() -> Result<u64, String> {
+    with_db(|conn| {
+        conn.query_row("SELECT COUNT(*) FROM pending_hashes", [], |row| {
+            row.get::<_, i64>(0)
+        })
+    })
+    .map(|count| count as u64)
+}
+
+/// 写入后台计算的哈希并移出待计算队列；哈希为 None（文件已删除或无法读取）时只移出队列（阻塞）
+pub fn complete_hashes(results: &[(String, Option<String>)]) -> Result<(), String> {
+    with_db(|conn| {
+        let tx = conn.transaction()?;
+        {
+            let mut update =
+                tx.prepare_cached("UPDATE screened_files SET file_hash = ?2 WHERE file_path = ?1")?;
+            let mut dequeue =
+                tx.prepare_cached("DELETE FROM pending_hashes WHERE file_path = ?1")?;
+            for (file_path, file_hash) in results {
+                if let Some(file_hash) = file_hash {
+                    update.execute(params![file_path, file_hash])?;
+                }
+                dequeue.execute(params![file_path])?;
+            }
+        }
+        tx.commit()
+    })
+}
+
+fn query_files(sql: &str, params: impl rusqlite::Params) -> Result<Vec<IndexedFile>, String> {
+    with_db(|conn| {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, IndexedFile::from_row)?;
+        rows.collect()
+    })
+}
+
+/// 按文件名搜索本地索引
+#[tauri::command(rename_all = "snake_case", async)]
+pub fn local_index_search(query: String, limit: Option<u32>) -> Result<serde_json::Value, String> {
+    let pattern = format!(
+        "%{}%",
+        query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+    let files = query_files(
+        &format!(
+            "SELECT {} FROM screened_files WHERE file_name LIKE ?1 ESCAPE '\\' \
+             ORDER BY modified_time DESC LIMIT ?2",
+            SELECT_COLUMNS
+        ),
+        params![pattern, limit.unwrap_or(50)],
+    )?;
+    Ok(serde_json::json!({ "status": "success", "files": files }))
+}
+
+/// 本地索引中最近修改的文件
+#[tauri::command(rename_all = "snake_case", async)]
+pub fn local_index_recent_files(limit: Option<u32>) -> Result<serde_json::Value, String> {
+    let files = query_files(
+        &format!(
+            "SELECT {} FROM screened_files ORDER BY modified_time DESC LIMIT ?1",
+            SELECT_COLUMNS
+        ),
+        params![limit.unwrap_or(50)],
+    )?;
+    Ok(serde_json::json!({ "status": "success", "files": files }))
+}
+
+/// 按哈希和大小分组的重复文件（哈希只覆盖文件开头 4KB，结果是候选重复）
+#[tauri::command(rename_all = "snake_case", async)]
+pub fn local_index_duplicates(limit: Option<u32>) -> Result<serde_json::Value, String> {
+    let files = query_files(
+        &format!(
+            "SELECT {} FROM screened_files WHERE (file_hash, file_size) IN ( \
+                 SELECT file_hash, file_size FROM screened_files WHERE file_hash IS NOT NULL \
+                 GROUP BY file_hash, file_size HAVING COUNT(*) > 1 \
+                 ORDER BY file_size DESC LIMIT ?1) \
+             ORDER BY file_size DESC, file_hash",
+            SELECT_COLUMNS
+        ),
+        params![limit.unwrap_or(50)],
+    )?;
+
+    let mut groups: Vec<Vec<IndexedFile>> = Vec::new();
+    for file in files {
+        match groups.last_mut() {
+            Some(group)
+                if group[0].file_hash == file.file_hash && group[0].file_size == file.file_size =>
+            {
+                group.push(file)
+            }
+            _ => groups.push(vec![file]),
+        }
+    }
+    Ok(serde_json::json!({ "status": "success", "groups": groups }))
+}
+
+/// 与API的粗筛结果比对，列出只存在于一边的路径
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn local_index_diff_with_api(
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let local_paths: HashSet<String> = tauri::async_runtime::spawn_blocking(|| {
+        with_db(|conn| {
+            let mut stmt = conn.prepare("SELECT file_path FROM screened_files")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect()
+        })
+    })
+    .await
+    .map_err(|e| format!("读取本地索引失败: {}", e))??;
+
+    let (api_host, api_port) = {
+        let api_state = app_handle.state::<crate::ApiState>();
+        let api_state_guard = api_state.0.lock().unwrap();
+        (api_state_guard.host.clone(), api_state_guard.port)
+    };
+    // 取足够多的结果，覆盖本地索引中的全部记录
+    let url = format!(
+        "http://{}:{}/file-screening/results?limit={}",
+        api_host,
+        api_port,
+        local_paths.len() * 2 + 1000
+    );
+    let response: serde_json::Value = crate::http_client::shared()
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(120))
+        .send()
+        .await
+        .map_err(|e| format!("获取API粗筛结果失败: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("解析API粗筛结果失败: {}", e))?;
+    let api_paths: HashSet<String> = response["data"]
+        .as_array()
+        .ok_or_else(|| "API粗筛结果格式不正确".to_string())?
+        .iter()
+        .filter_map(|item| item["file_path"].as_str().map(str::to_string))
+        .collect();
+
+    let only_local: Vec<&String> = local_paths.difference(&api_paths).collect();
+    let only_api: Vec<&String> = api_paths.difference(&local_paths).collect();
+    Ok(serde_json::json!({
+        "status": "success",
+        "local_count": local_paths.len(),
+        "api_count": api_paths.len(),
+        "consistent": only_local.is_empty() && only_api.is_empty(),
+        "only_local_count": only_local.len(),
+        "only_api_count": only_api.len(),
+        "only_local": only_local.into_iter().take(MAX_DIFF_SAMPLES).collect::<Vec<_>>(),
+        "only_api": only_api.into_iter().take(MAX_DIFF_SAMPLES).collect::<Vec<_>>(),
+    }))
+}
@@ -0,0 +1,283 @@
+//! # 本地规则文件 (Local Rule Overrides)
+//!
+//! 应用数据目录下的 `local_rules.json` 供高级用户手工添加个人过滤规则和扩展名映射，
+//! 每次从 `/config/all` 获取配置后合并到配置之上，因此不受后端数据库重置影响，
+//! API 离线时也可以通过 `reload_local_rules` 重新加载。
+//!
+//! 合并方式：
+//! - 过滤规则：与 API 规则同名时替换 API 规则，否则追加；本地规则使用负数 ID，与 API 规则区分
+//! - 扩展名映射：与 API 映射扩展名相同时替换，否则追加
+//! - Bundle 扩展名：追加到列表中，也可以通过 `add_custom_bundle_extension` 命令添加
+//! - 隐藏文件：位于 `include_hidden_folders` 所列目录（支持 `~/`）中的监控目录开启隐藏文件索引，
+//!   如 Obsidian 仓库的 `.obsidian` 目录
+//!
+//! 删除本地规则后，被它替换的 API 规则在下次从 API 刷新配置时恢复。
+//!
+//! 文件示例：
+//! ```json
+//! {
+//!   "file_filter_rules": [
+//!     { "name": "忽略备份文件", "rule_type": "filename", "pattern": "*.bak",
+//!       "pattern_type": "glob", "action": "exclude", "priority": "high" }
+//!   ],
+//!   "file_extension_maps": [
+//!     { "extension": "org", "category_id": 2 }
+//!   ],
+//!   "include_hidden_folders": ["~/Notes/vault"]
+//! }
+//! ```
+
+use log::{error, info};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager};
+
+use crate::file_monitor::{
+    AllConfigurations, ConfigSnapshot, FileExtensionMapRust, FileFilterRuleRust, RuleActionRust,
+    RulePriorityRust, RuleTypeRust,
+};
+
+/// 本地规则文件名，位于应用数据目录
+const LOCAL_RULES_FILE: &str = "local_rules.json";
+/// 首次启动时创建的空规则文件
+const LOCAL_RULES_TEMPLATE: &str =
+    "{\n  \"file_filter_rules\": [],\n  \"file_extension_maps\": []\n}\n";
+
+/// 本地规则文件路径，None 表示尚未初始化
+static LOCAL_RULES_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_pattern_type() -> String {
+    "keyword".to_string()
+}
+
+fn default_priority() -> RulePriorityRust {
+    RulePriorityRust::Medium
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalFilterRule {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    rule_type: RuleTypeRust,
+    #[serde(default)]
+    category_id: Option<i32>,
+    #[serde(default = "default_priority")]
+    priority: RulePriorityRust,
+    action: RuleActionRust,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    pattern: String,
+    #[serde(default = "default_pattern_type")]
+    pattern_type: String,
+    #[serde(default)]
+    extra_data: Option<JsonValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalExtensionMap {
+    extension: String,
+    category_id: i32,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default = "default_priority")]
+    priority: RulePriorityRust,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LocalRules {
+    #[serde(default)]
+    file_filter_rules: Vec<LocalFilterRule>,
+    #[serde(default)]
+    file_extension_maps: Vec<LocalExtensionMap>,
+    #[serde(default)]
+    bundle_extensions: Vec<String>,
+    #[serde(default)]
+    include_hidden_folders: Vec<String>,
+}
+
+/// 读取本地规则文件，文件不存在时返回空规则
+fn load() -> Result<LocalRules, String> {
+    let Some(path) = LOCAL_RULES_PATH.read().unwrap().clone() else {
+        return Ok(LocalRules::default());
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(LocalRules::default()),
+        Err(e) => return Err(format!("读取本地规则文件 {:?} 失败: {}", path, e)),
+    };
+    serde_json::from_str(&content).map_err(|e| format!("解析本地规则文件 {:?} 失败: {}", path, e))
+}
+
+/// 修改本地规则文件中的 Bundle 扩展名列表，保留文件中的其他内容，返回列表是否有变化
+fn update_bundle_extensions(update: impl FnOnce(&mut Vec<String>)) -> Result<bool, String> {
+    let path = LOCAL_RULES_PATH
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "本地规则文件尚未初始化".to_string())?;
+    let mut content: JsonValue = match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("解析本地规则文件 {:?} 失败: {}", path, e))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::json!({}),
+        Err(e) => return Err(format!("读取本地规则文件 {:?} 失败: {}", path, e)),
+    };
+    let object = content
+        .as_object_mut()
+        .ok_or_else(|| format!("本地规则文件 {:?} 不是 JSON 对象", path))?;
+
+    let mut extensions: Vec<String> = object
+        .get("bundle_extensions")
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or_default();
+    let before = extensions.clone();
+    update(&mut extensions);
+    if extensions == before {
+        return Ok(false);
+    }
+
+    object.insert(
+        "bundle_extensions".to_string(),
+        serde_json::json!(extensions),
+    );
+    let content = serde_json::to_string_pretty(&content).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content + "\n")
+        .map_err(|e| format!("保存本地规则文件 {:?} 失败: {}", path, e))?;
+    Ok(true)
+}
+
+/// 在本地规则文件中添加自定义 Bundle 扩展名（带点的小写形式，如 `.logicx`）
+pub fn add_bundle_extension(extension: &str) -> Result<bool, String> {
+    update_bundle_extensions(|extensions| {
+        if !extensions.iter().any(|existing| existing == extension) {
+            extensions.push(extension.to_string());
+        }
+    })
+}
+
+/// 从本地规则文件中移除自定义 Bundle 扩展名
+pub fn remove_bundle_extension(extension: &str) -> Result<bool, String> {
+    update_bundle_extensions(|extensions| extensions.retain(|existing| existing != extension))
+}
+
+/// 把本地规则合并到从 API 获取的配置之上，返回合并的过滤规则数和扩展名映射数
+///
+/// 可以对同一份配置重复调用：合并前先移除上一次合并的本地规则
+pub fn merge_into(config: &mut AllConfigurations) -> Result<(usize, usize), String> {
+    let local = load()?;
+
+    config.file_filter_rules.retain(|rule| rule.id >= 0);
+    config.file_extension_maps.retain(|map| map.id >= 0);
+
+    for (index, rule) in local.file_filter_rules.iter().enumerate() {
+        config
+            .file_filter_rules
+            .retain(|existing| existing.name != rule.name);
+        config.file_filter_rules.push(FileFilterRuleRust {
+            id: -(index as i32) - 1,
+            name: rule.name.clone(),
+            description: rule.description.clone(),
+            rule_type: rule.rule_type.clone(),
+            category_id: rule.category_id,
+            priority: rule.priority.clone(),
+            action: rule.action.clone(),
+            enabled: rule.enabled,
+            is_system: false,
+            pattern: rule.pattern.clone(),
+            pattern_type: rule.pattern_type.clone(),
+            extra_data: rule.extra_data.clone(),
+        });
+    }
+
+    for (index, map) in local.file_extension_maps.iter().enumerate() {
+        let extension = map.extension.trim_start_matches('.').to_lowercase();
+        config
+            .file_extension_maps
+            .retain(|existing| existing.extension != extension);
+        config.file_extension_maps.push(FileExtensionMapRust {
+            id: -(index as i32) - 1,
+            extension,
+            category_id: map.category_id,
+            description: map.description.clone(),
+            priority: map.priority.clone(),
+        });
+    }
+
+    // API 没有返回 Bundle 扩展名列表时，先填入从规则提取或默认的列表，
+    // 否则追加的本地扩展名会让列表非空，默认的 Bundle 扩展名不再生效
+    if config.bundle_extensions.is_empty() && !local.bundle_extensions.is_empty() {
+        config.bundle_extensions = ConfigSnapshot::bundle_extensions_of(config);
+    }
+    for bundle_extension in local.bundle_extensions {
+        if !config.bundle_extensions.contains(&bundle_extension) {
+            config.bundle_extensions.push(bundle_extension);
+        }
+    }
+
+    for folder in config.monitored_folders.iter_mut() {
+        let folder_path = folder.expanded_path();
+        if local.include_hidden_folders.iter().any(|path| {
+            crate::path_utils::path_starts_with(&folder_path, &crate::path_utils::expand_home(path))
+        }) {
+            folder.include_hidden = true;
+        }
+    }
+
+    let merged = (
+        local.file_filter_rules.len(),
+        local.file_extension_maps.len(),
+    );
+    if merged != (0, 0) {
+        info!(
+            "[LOCAL_RULES] 合并了 {} 条本地过滤规则和 {} 条本地扩展名映射",
+            merged.0, merged.1
+        );
+    }
+    Ok(merged)
+}
+
+/// 确定本地规则文件位置，文件不存在时创建空模板，应在文件监控启动前调用
+pub fn init(app_handle: &AppHandle) {
+    let path = match app_handle.path().app_data_dir() {
+        Ok(dir) => {
+            let _ = std::fs::create_dir_all(&dir);
+            dir.join(LOCAL_RULES_FILE)
+        }
+        Err(e) => {
+            error!("[LOCAL_RULES] 无法获取应用数据目录: {}", e);
+            return;
+        }
+    };
+    if !path.exists() {
+        if let Err(e) = std::fs::write(&path, LOCAL_RULES_TEMPLATE) {
+            error!("[LOCAL_RULES] 创建本地规则文件失败: {}", e);
+        }
+    }
+    *LOCAL_RULES_PATH.write().unwrap() = Some(path);
+}
+
+/// 重新读取本地规则文件并合并到当前配置，不需要 API 在线
+#[tauri::command(rename_all = "snake_case")]
+pub fn reload_local_rules(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<serde_json::Value, String> {
+    let monitor = state
+        .file_monitor
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "文件监控器未初始化".to_string())?;
+    let (rules, extension_maps) = monitor.reload_local_rules()?;
+    let path = LOCAL_RULES_PATH.read().unwrap().clone();
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("已加载 {} 条本地过滤规则和 {} 条本地扩展名映射", rules, extension_maps),
+        "path": path.map(|path| path.to_string_lossy().to_string())
+    }))
+}
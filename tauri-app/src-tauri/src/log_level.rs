@@ -0,0 +1,198 @@
+//! # 日志配置与运行时日志级别 (Logging & Runtime Log Levels)
+//!
+//! 日志以每行一个 JSON 对象（时间、级别、target、消息）的形式写入应用日志目录下的
+//! `knowledge-focus.log`，单个文件超过 10MB 时轮转，最多保留 5 个旧文件，长期运行不会无限增长；
+//! 开发时同样的记录也输出到标准输出。
+//!
+//! tauri_plugin_log 的 `level_for` 过滤器只在启动时生效。这里把插件的全局级别放开到 Trace，
+//! 改由 `filter` 回调按运行时可修改的级别表过滤，用户排查问题时可以临时打开某个模块的详细日志，无需重启。
+//!
+//! 级别表的键是日志 target 前缀（如 `tauri_app_lib::file_monitor`、`notify`），按最长前缀匹配，
+//! 未匹配的记录使用默认级别。修改后保存到 settings.json 的 `log_levels`，下次启动时恢复。
+//! target 为 `api` 时转发给 Python API 的 `set_log_level` 控制命令，只对当前运行的 API 进程生效。
+
+use log::{info, warn, LevelFilter};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+use tauri_plugin_store::StoreExt;
+
+/// 日志文件名（不含扩展名），位于应用日志目录
+pub const LOG_FILE_NAME: &str = "knowledge-focus";
+/// 单个日志文件的最大字节数，超过后轮转
+const MAX_LOG_FILE_SIZE: u128 = 10 * 1024 * 1024;
+/// 轮转后保留的旧日志文件数
+const KEEP_LOG_FILES: usize = 5;
+
+/// settings.json 中保存日志级别的键
+const LOG_LEVELS_STORE_KEY: &str = "log_levels";
+/// 级别表中表示默认级别的键
+const DEFAULT_TARGET: &str = "*";
+/// 转发给 Python API 的 target
+const API_TARGET: &str = "api";
+
+static LOG_LEVELS: RwLock<Option<HashMap<String, LevelFilter>>> = RwLock::new(None);
+
+/// 内置级别：默认 Info，tao 的窗口事件日志过多设为 Warn
+fn default_levels() -> HashMap<String, LevelFilter> {
+    HashMap::from([
+        (DEFAULT_TARGET.to_string(), LevelFilter::Info),
+        ("tao".to_string(), LevelFilter::Warn),
+        ("notify".to_string(), LevelFilter::Info),
+    ])
+}
+
+/// target 匹配级别表中的键：完全相同或以 `键::` 开头
+fn target_matches(key: &str, target: &str) -> bool {
+    target == key
+        || target
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+/// tauri_plugin_log 的过滤回调，按最长前缀匹配的级别决定是否记录
+pub fn is_enabled(metadata: &log::Metadata) -> bool {
+    let guard = LOG_LEVELS.read().unwrap();
+    let Some(levels) = guard.as_ref() else {
+        return metadata.level() <= LevelFilter::Info;
+    };
+    let level = levels
+        .iter()
+        .filter(|(key, _)| key.as_str() != DEFAULT_TARGET && target_matches(key, metadata.target()))
+        .max_by_key(|(key, _)| key.len())
+        .or_else(|| levels.get_key_value(DEFAULT_TARGET))
+        .map_or(LevelFilter::Info, |(_, level)| *level);
+    metadata.level() <= level
+}
+
+/// 把一条日志记录格式化为单行 JSON
+fn json_record(message: &std::fmt::Arguments, record: &log::Record) -> serde_json::Value {
+    serde_json::json!({
+        "ts": chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": message.to_string(),
+    })
+}
+
+/// 创建日志插件：JSON 格式、按大小轮转的日志文件，级别由 `is_enabled` 在运行时过滤
+pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri_plugin_log::Builder::new()
+        .clear_targets()
+        .targets([
+            Target::new(TargetKind::Stdout),
+            Target::new(TargetKind::LogDir {
+                file_name: Some(LOG_FILE_NAME.to_string()),
+            }),
+        ])
+        .max_file_size(MAX_LOG_FILE_SIZE)
+        .rotation_strategy(RotationStrategy::KeepSome(KEEP_LOG_FILES))
+        .format(|out, message, record| out.finish(format_args!("{}", json_record(message, record))))
+        // 全局放开到 Trace，实际级别由级别表过滤
+        .level(LevelFilter::Trace)
+        .filter(is_enabled)
+        .build()
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .parse::<LevelFilter>()
+        .map_err(|_| format!("无效的日志级别: {}", level))
+}
+
+/// 从 settings.json 恢复日志级别，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let mut levels = default_levels();
+    let saved = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(LOG_LEVELS_STORE_KEY));
+    if let Some(serde_json::Value::Object(saved)) = saved {
+        for (target, level) in saved {
+            match level.as_str().map(parse_level) {
+                Some(Ok(level)) => {
+                    levels.insert(target, level);
+                }
+                _ => warn!("[LOG_LEVEL] 忽略无效的日志级别配置: {} = {}", target, level),
+            }
+        }
+    }
+    *LOG_LEVELS.write().unwrap() = Some(levels);
+}
+
+fn levels_json() -> serde_json::Value {
+    let guard = LOG_LEVELS.read().unwrap();
+    let levels = guard.clone().unwrap_or_else(default_levels);
+    serde_json::json!(levels
+        .into_iter()
+        .map(|(target, level)| (target, level.to_string()))
+        .collect::<HashMap<_, _>>())
+}
+
+/// 修改日志级别并保存到 settings.json
+///
+/// `target` 为空或 `*` 时修改默认级别，为 `api` 时修改 Python API 的日志级别；
+/// `level` 为 off / error / warn / info / debug / trace（不区分大小写）
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn set_log_level(
+    target: Option<String>,
+    level: String,
+    app_handle: tauri::AppHandle,
+    api_state: tauri::State<'_, crate::ApiState>,
+) -> Result<serde_json::Value, String> {
+    let level = parse_level(&level)?;
+    let target = target
+        .filter(|target| !target.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_TARGET.to_string());
+    info!("[LOG_LEVEL] 设置日志级别: {} = {}", target, level);
+
+    if target == API_TARGET {
+        let python_level = match level {
+            LevelFilter::Off | LevelFilter::Error => "ERROR",
+            LevelFilter::Warn => "WARNING",
+            LevelFilter::Info => "INFO",
+            LevelFilter::Debug | LevelFilter::Trace => "DEBUG",
+        };
+        crate::api_control::send_control_command(
+            &api_state.0,
+            "set_log_level",
+            serde_json::json!({ "level": python_level }),
+        )
+        .await?;
+        return Ok(serde_json::json!({
+            "status": "success",
+            "message": format!("API日志级别已设置为 {}", python_level)
+        }));
+    }
+
+    LOG_LEVELS
+        .write()
+        .unwrap()
+        .get_or_insert_with(default_levels)
+        .insert(target.clone(), level);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(LOG_LEVELS_STORE_KEY, levels_json());
+    store
+        .save()
+        .map_err(|e| format!("保存日志级别设置失败: {}", e))?;
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("日志级别已设置: {} = {}", target, level),
+        "levels": levels_json()
+    }))
+}
+
+/// 获取当前日志级别表
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_log_levels() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "levels": levels_json()
+    }))
+}
@@ -0,0 +1,214 @@
+//! # 图片文字识别 (OCR)
+//!
+//! 监控文件夹中的图片（尤其是截图）经文本提取流程识别出文字，以 `image` 类型发送到
+//! `/file-content/batch`，截图因此可以按内容检索。
+//!
+//! macOS 使用系统 Vision 框架（无需额外安装）；其他平台调用 PATH 中的 `tesseract` 命令，
+//! 未安装时跳过。识别模式保存在 settings.json 的 `ocr_mode` 中：
+//! - off：不识别
+//! - screenshots：只识别截图（默认），按文件名、所在文件夹和系统截图文件夹判断
+//! - all：识别所有图片
+
+use log::info;
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+/// settings.json 中保存识别模式的键
+const MODE_STORE_KEY: &str = "ocr_mode";
+/// 超过此大小的图片不识别
+pub const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+/// 可识别的图片扩展名（小写，不带点）
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "heic", "heif", "tif", "tiff", "bmp", "gif", "webp",
+];
+/// 各系统截图默认文件名的前缀
+const SCREENSHOT_NAME_PREFIXES: &[&str] = &[
+    "screenshot",
+    "screen shot",
+    "截屏",
+    "屏幕快照",
+    "屏幕截图",
+    "スクリーンショット",
+];
+
+const MODE_OFF: u8 = 0;
+const MODE_SCREENSHOTS: u8 = 1;
+const MODE_ALL: u8 = 2;
+
+static MODE: AtomicU8 = AtomicU8::new(MODE_SCREENSHOTS);
+
+fn mode_name(mode: u8) -> &'static str {
+    match mode {
+        MODE_OFF => "off",
+        MODE_ALL => "all",
+        _ => "screenshots",
+    }
+}
+
+fn parse_mode(name: &str) -> Option<u8> {
+    match name {
+        "off" => Some(MODE_OFF),
+        "screenshots" => Some(MODE_SCREENSHOTS),
+        "all" => Some(MODE_ALL),
+        _ => None,
+    }
+}
+
+/// 扩展名（小写，不带点）是否为可识别的图片
+pub fn is_image_extension(extension: &str) -> bool {
+    IMAGE_EXTENSIONS.contains(&extension)
+}
+
+/// 按文件名和所在文件夹判断是否为截图
+pub fn is_screenshot(path: &Path) -> bool {
+    let name_matches = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .is_some_and(|name| {
+            SCREENSHOT_NAME_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        });
+    let folder_matches = path
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .is_some_and(|name| name == "screenshots" || name == "截图" || name == "截屏");
+    name_matches || folder_matches
+}
+
+/// 按当前识别模式判断是否识别该图片
+pub fn should_recognize(path: &Path) -> bool {
+    match MODE.load(Ordering::SeqCst) {
+        MODE_ALL => true,
+        MODE_SCREENSHOTS => is_screenshot(path) || crate::screenshots::is_screenshot(path),
+        _ => false,
+    }
+}
+
+/// 识别图片中的文字（阻塞），按行拼接
+pub fn recognize_text(path: &Path) -> Result<String, String> {
+    platform::recognize_text(path)
+}
+
+/// 恢复保存的识别模式，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let saved = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(MODE_STORE_KEY))
+        .and_then(|value| value.as_str().and_then(parse_mode));
+    if let Some(mode) = saved {
+        MODE.store(mode, Ordering::SeqCst);
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[allow(unused_unsafe)]
+mod platform {
+    use objc2::rc::{autoreleasepool, Retained};
+    use objc2::AnyThread;
+    use objc2_foundation::{NSArray, NSDictionary, NSString, NSURL};
+    use objc2_vision::{
+        VNImageRequestHandler, VNRecognizeTextRequest, VNRequest, VNRequestTextRecognitionLevel,
+    };
+    use std::path::Path;
+
+    pub fn recognize_text(path: &Path) -> Result<String, String> {
+        autoreleasepool(|_| {
+            let url =
+                unsafe { NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy())) };
+            let handler = unsafe {
+                VNImageRequestHandler::initWithURL_options(
+                    VNImageRequestHandler::alloc(),
+                    &url,
+                    &NSDictionary::new(),
+                )
+            };
+            let request = unsafe { VNRecognizeTextRequest::new() };
+            unsafe {
+                request.setRecognitionLevel(VNRequestTextRecognitionLevel::Accurate);
+                request.setUsesLanguageCorrection(true);
+                request.setRecognitionLanguages(&NSArray::from_retained_slice(&[
+                    NSString::from_str("zh-Hans"),
+                    NSString::from_str("zh-Hant"),
+                    NSString::from_str("en-US"),
+                ]));
+            }
+            let base: Retained<VNRequest> =
+                Retained::into_super(Retained::into_super(request.clone()));
+            unsafe { handler.performRequests_error(&NSArray::from_retained_slice(&[base])) }
+                .map_err(|e| format!("Vision 识别失败: {:?}", e))?;
+
+            let lines: Vec<String> = unsafe { request.results() }
+                .map(|observations| {
+                    observations
+                        .iter()
+                        .filter_map(|observation| unsafe {
+                            observation
+                                .topCandidates(1)
+                                .firstObject()
+                                .map(|candidate| candidate.string().to_string())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            Ok(lines.join("\n"))
+        })
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use std::path::Path;
+
+    pub fn recognize_text(path: &Path) -> Result<String, String> {
+        let output = std::process::Command::new("tesseract")
+            .arg(path)
+            .arg("stdout")
+            .output()
+            .map_err(|e| format!("无法运行 tesseract（未安装？）: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "tesseract 识别失败: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// 设置识别模式：off、screenshots（只识别截图）、all
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_ocr_mode(
+    mode: String,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let value = parse_mode(&mode).ok_or_else(|| format!("未知的识别模式: {}", mode))?;
+    MODE.store(value, Ordering::SeqCst);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(MODE_STORE_KEY, serde_json::json!(mode));
+    store
+        .save()
+        .map_err(|e| format!("保存识别模式失败: {}", e))?;
+
+    info!("[OCR] 图片文字识别模式已设置为: {}", mode);
+    Ok(serde_json::json!({
+        "status": "success",
+        "mode": mode
+    }))
+}
+
+/// 获取图片文字识别模式
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_ocr_mode() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "mode": mode_name(MODE.load(Ordering::SeqCst))
+    }))
+}
@@ -0,0 +1,195 @@
+//! # 路径工具 (Path Utilities)
+//!
+//! 文件监控和文件扫描共用的跨平台路径处理：
+//! - 隐藏文件判断：所有平台检查以 `.` 开头的路径部分；Windows 上还读取
+//!   `FILE_ATTRIBUTE_HIDDEN` / `FILE_ATTRIBUTE_SYSTEM` 属性（如 `AppData`、`desktop.ini`）
+//! - 长路径：`canonicalize`、用户输入或监控事件可能带有 `\\?\` / `\\?\UNC\` 前缀，
+//!   与不带前缀的监控目录、黑名单做前缀比较时会失败，因此内部统一去掉前缀。
+//!   Rust 标准库访问超过 MAX_PATH 的绝对路径时会自动重新加上前缀，文件读写不受影响。
+//! - 大小写：APFS、NTFS 默认不区分大小写，`~/Documents` 与 `~/documents` 是同一目录。
+//!   按卷检测是否区分大小写（结果按挂载点缓存），不区分时路径比较忽略大小写。
+//! - Unicode 规范化：macOS 返回的文件名是 NFD（分解形式），用户输入和 API 中保存的是 NFC，
+//!   带声调字母或部分中日韩字符的目录按字节比较会不一致。比较路径和发送给 API 的路径都统一为 NFC；
+//!   访问文件系统时仍使用原始路径。
+
+use log::info;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf, Prefix};
+use std::sync::Mutex;
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+/// Windows 文件属性：隐藏
+#[cfg(windows)]
+const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+/// Windows 文件属性：系统
+#[cfg(windows)]
+const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+
+/// 各卷（按挂载点）是否不区分大小写
+static CASE_INSENSITIVE_VOLUMES: Mutex<Option<HashMap<PathBuf, bool>>> = Mutex::new(None);
+
+/// 去掉 Windows 的 `\\?\` 长路径前缀：`\\?\C:\a` -> `C:\a`，`\\?\UNC\server\share\a` -> `\\server\share\a`
+///
+/// 其他路径（包括非 Windows 平台上的所有路径）原样返回
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut components = path.components();
+    let Some(Component::Prefix(prefix)) = components.next() else {
+        return path.to_path_buf();
+    };
+    let root = match prefix.kind() {
+        Prefix::VerbatimDisk(disk) => format!("{}:\\", disk as char),
+        Prefix::VerbatimUNC(server, share) => format!(
+            "\\\\{}\\{}\\",
+            server.to_string_lossy(),
+            share.to_string_lossy()
+        ),
+        _ => return path.to_path_buf(),
+    };
+    let mut normalized = PathBuf::from(root);
+    normalized.extend(components.filter(|c| !matches!(c, Component::RootDir)));
+    normalized
+}
+
+/// 展开用户输入或配置中以 `~/` 开头的路径，无法获取 HOME 时原样返回
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// 路径中是否有以 `.` 开头的部分（同时适用于 `/` 和 `\` 分隔符）
+fn has_dot_component(path: &Path) -> bool {
+    path.components().any(|component| match component {
+        Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+        _ => false,
+    })
+}
+
+/// 文件本身是否带有 Windows 隐藏或系统属性
+#[cfg(windows)]
+fn has_hidden_attribute(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    // 驱动器根目录本身带有隐藏和系统属性，不能据此过滤
+    if path.file_name().is_none() {
+        return false;
+    }
+    std::fs::symlink_metadata(path)
+        .map(|metadata| {
+            metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(not(windows))]
+fn has_hidden_attribute(_path: &Path) -> bool {
+    false
+}
+
+/// 检查文件是否隐藏：路径中任何部分以 `.` 开头，或文件本身带有 Windows 隐藏/系统属性
+///
+/// 遍历目录时父目录已经检查过，所以只读取文件本身的属性
+pub fn is_hidden(path: &Path) -> bool {
+    has_dot_component(path) || has_hidden_attribute(path)
+}
+
+/// 在 `is_hidden` 的基础上检查所有上级目录的 Windows 隐藏/系统属性，
+/// 用于单独到达的文件事件（如 `AppData` 下的变动）
+pub fn is_hidden_or_in_hidden_dir(path: &Path) -> bool {
+    is_hidden(path) || (cfg!(windows) && path.ancestors().skip(1).any(has_hidden_attribute))
+}
+
+/// 两个路径是否指向同一个文件
+#[cfg(unix)]
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (std::fs::metadata(a), std::fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_file(_a: &Path, b: &Path) -> bool {
+    b.exists()
+}
+
+/// 用路径上一个名称含字母的已存在部分，检查改变大小写后是否仍指向同一文件
+fn detect_case_insensitive(path: &Path) -> Option<bool> {
+    path.ancestors().find_map(|existing| {
+        let name = existing.file_name()?.to_string_lossy();
+        let swapped = if name.chars().any(char::is_lowercase) {
+            name.to_uppercase()
+        } else {
+            name.to_lowercase()
+        };
+        if swapped == name || !existing.exists() {
+            return None;
+        }
+        Some(is_same_file(existing, &existing.with_file_name(swapped)))
+    })
+}
+
+/// 路径所在的卷是否不区分大小写
+///
+/// 无法检测时（路径不存在或不含字母）按平台默认：macOS、Windows 不区分，其他平台区分
+pub fn is_case_insensitive(path: &Path) -> bool {
+    let volume = crate::volumes::volume_root(path).unwrap_or_default();
+    let mut cache = CASE_INSENSITIVE_VOLUMES.lock().unwrap();
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if let Some(&insensitive) = cache.get(&volume) {
+        return insensitive;
+    }
+    match detect_case_insensitive(path) {
+        Some(insensitive) => {
+            info!(
+                "[PATH_UTILS] 卷 {:?} {}区分大小写",
+                volume,
+                if insensitive { "不" } else { "" }
+            );
+            cache.insert(volume, insensitive);
+            insensitive
+        }
+        None => cfg!(any(target_os = "macos", windows)),
+    }
+}
+
+/// 把字符串转为 Unicode NFC 形式，已经是 NFC 时不做转换
+pub fn to_nfc(s: &str) -> String {
+    match is_nfc_quick(s.chars()) {
+        IsNormalized::Yes => s.to_string(),
+        _ => s.nfc().collect(),
+    }
+}
+
+/// 发送给 API 或前端的路径字符串（NFC 形式）
+pub fn path_to_nfc_string(path: &Path) -> String {
+    to_nfc(&path.to_string_lossy())
+}
+
+/// 比较用的路径部分：统一为 NFC，所在卷不区分大小写时再转为小写
+pub fn comparable_component(name: &str, case_insensitive: bool) -> String {
+    let name = to_nfc(name);
+    if case_insensitive {
+        name.to_lowercase()
+    } else {
+        name
+    }
+}
+
+/// `path` 是否位于 `base` 之内（或就是 `base`），按 NFC 比较，所在卷不区分大小写时忽略大小写
+pub fn path_starts_with(path: &Path, base: &Path) -> bool {
+    if path.starts_with(base) {
+        return true;
+    }
+    let case_insensitive = is_case_insensitive(base);
+    let comparable = |component: Component| {
+        comparable_component(&component.as_os_str().to_string_lossy(), case_insensitive)
+    };
+    let mut path_components = path.components();
+    base.components().all(|base_component| {
+        path_components
+            .next()
+            .is_some_and(|path_component| comparable(path_component) == comparable(base_component))
+    })
+}
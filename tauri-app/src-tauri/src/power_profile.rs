@@ -0,0 +1,226 @@
+//! # 电源模式 (Power Profile)
+//!
+//! 笔记本使用电池（或 macOS 开启低电量模式）时自动进入低功耗模式，降低后台索引的负载：
+//! - 扫描：每处理一定数量的文件暂停片刻，让出 CPU 和磁盘
+//! - 哈希：跳过文件哈希计算，接通电源后重新扫描时补上
+//! - 防抖：文件事件的防抖间隔放大，合并更多变动后再处理
+//!
+//! 电源状态每分钟检测一次，切换时发出 `power-profile-changed` 事件。
+//! `set_power_profile` 可以固定为 normal / low_power，或恢复 auto（按电源状态自动切换），
+//! 选择保存在 settings.json 的 `power_profile` 中。
+
+use log::{error, info};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+/// settings.json 中保存电源模式的键
+const PROFILE_STORE_KEY: &str = "power_profile";
+/// 电源状态检测间隔
+const DETECT_INTERVAL: Duration = Duration::from_secs(60);
+/// 低功耗模式下扫描每处理多少个文件暂停一次
+const SCAN_YIELD_EVERY: u64 = 50;
+/// 低功耗模式下扫描每次暂停的时长
+const SCAN_PAUSE: Duration = Duration::from_millis(20);
+/// 低功耗模式下防抖间隔的放大倍数
+const LOW_POWER_DEBOUNCE_FACTOR: u32 = 5;
+
+const PROFILE_AUTO: u8 = 0;
+const PROFILE_NORMAL: u8 = 1;
+const PROFILE_LOW_POWER: u8 = 2;
+
+/// 用户选择的电源模式
+static PROFILE: AtomicU8 = AtomicU8::new(PROFILE_AUTO);
+/// 检测到正在使用电池或低电量模式
+static ON_BATTERY: AtomicBool = AtomicBool::new(false);
+
+fn profile_name(profile: u8) -> &'static str {
+    match profile {
+        PROFILE_NORMAL => "normal",
+        PROFILE_LOW_POWER => "low_power",
+        _ => "auto",
+    }
+}
+
+fn parse_profile(name: &str) -> Option<u8> {
+    match name {
+        "auto" => Some(PROFILE_AUTO),
+        "normal" => Some(PROFILE_NORMAL),
+        "low_power" => Some(PROFILE_LOW_POWER),
+        _ => None,
+    }
+}
+
+/// 当前是否处于低功耗模式
+pub fn is_low_power() -> bool {
+    match PROFILE.load(Ordering::SeqCst) {
+        PROFILE_NORMAL => false,
+        PROFILE_LOW_POWER => true,
+        _ => ON_BATTERY.load(Ordering::SeqCst),
+    }
+}
+
+/// 是否计算文件哈希，低功耗模式下跳过
+pub fn hashing_enabled() -> bool {
+    !is_low_power()
+}
+
+/// 按电源模式调整防抖间隔
+pub fn debounce_interval(base: Duration) -> Duration {
+    if is_low_power() {
+        base * LOW_POWER_DEBOUNCE_FACTOR
+    } else {
+        base
+    }
+}
+
+/// 扫描循环中调用，低功耗模式下每处理 `SCAN_YIELD_EVERY` 个文件暂停片刻，
+/// 刚从系统睡眠中恢复时等待恢复处理
+pub async fn throttle_scan(processed: u64) {
+    crate::sleep_wake::wait_while_paused().await;
+    if processed % SCAN_YIELD_EVERY == 0 && is_low_power() {
+        tokio::time::sleep(SCAN_PAUSE).await;
+    }
+}
+
+#[cfg(any(target_os = "macos", windows))]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 检测是否正在使用电池或开启了低电量模式
+#[cfg(target_os = "macos")]
+fn detect_on_battery() -> bool {
+    let on_battery = command_output("pmset", &["-g", "batt"])
+        .is_some_and(|output| output.contains("'Battery Power'"));
+    let low_power_mode = command_output("pmset", &["-g"]).is_some_and(|output| {
+        output.lines().any(|line| {
+            let mut parts = line.split_whitespace();
+            parts.next() == Some("lowpowermode") && parts.next() == Some("1")
+        })
+    });
+    on_battery || low_power_mode
+}
+
+#[cfg(target_os = "linux")]
+fn detect_on_battery() -> bool {
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let read = |name: &str| {
+            std::fs::read_to_string(entry.path().join(name))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        read("type") == "Battery" && read("status") == "Discharging"
+    })
+}
+
+#[cfg(windows)]
+fn detect_on_battery() -> bool {
+    // Win32_Battery.BatteryStatus 为 1 表示正在放电
+    command_output(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "(Get-CimInstance Win32_Battery).BatteryStatus",
+        ],
+    )
+    .is_some_and(|output| output.lines().any(|line| line.trim() == "1"))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn detect_on_battery() -> bool {
+    false
+}
+
+fn status_json() -> serde_json::Value {
+    serde_json::json!({
+        "profile": profile_name(PROFILE.load(Ordering::SeqCst)),
+        "on_battery": ON_BATTERY.load(Ordering::SeqCst),
+        "low_power": is_low_power()
+    })
+}
+
+/// 更新电源状态，低功耗模式切换时通知前端
+fn update_on_battery(app_handle: &AppHandle, on_battery: bool) {
+    let was_low_power = is_low_power();
+    ON_BATTERY.store(on_battery, Ordering::SeqCst);
+    if is_low_power() != was_low_power {
+        info!(
+            "[POWER] {}低功耗模式（使用电池: {}）",
+            if is_low_power() { "进入" } else { "退出" },
+            on_battery
+        );
+        let _ = app_handle.emit("power-profile-changed", status_json());
+    }
+}
+
+/// 恢复保存的电源模式并开始定期检测电源状态，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let saved = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(PROFILE_STORE_KEY))
+        .and_then(|value| value.as_str().and_then(parse_profile));
+    if let Some(profile) = saved {
+        PROFILE.store(profile, Ordering::SeqCst);
+    }
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            // 检测需要执行外部命令，放到阻塞线程中
+            match tokio::task::spawn_blocking(detect_on_battery).await {
+                Ok(on_battery) => update_on_battery(&app_handle, on_battery),
+                Err(e) => error!("[POWER] 检测电源状态失败: {}", e),
+            }
+            tokio::time::sleep(DETECT_INTERVAL).await;
+        }
+    });
+}
+
+/// 设置电源模式：auto（按电源状态自动切换）、normal、low_power
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_power_profile(
+    profile: String,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let value = parse_profile(&profile).ok_or_else(|| format!("未知的电源模式: {}", profile))?;
+    let was_low_power = is_low_power();
+    PROFILE.store(value, Ordering::SeqCst);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(PROFILE_STORE_KEY, serde_json::json!(profile));
+    store
+        .save()
+        .map_err(|e| format!("保存电源模式失败: {}", e))?;
+
+    info!("[POWER] 电源模式已设置为: {}", profile);
+    if is_low_power() != was_low_power {
+        let _ = app_handle.emit("power-profile-changed", status_json());
+    }
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("电源模式已设置为 {}", profile),
+        "power": status_json()
+    }))
+}
+
+/// 获取电源模式和当前电源状态
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_power_profile() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "power": status_json()
+    }))
+}
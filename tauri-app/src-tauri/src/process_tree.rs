@@ -0,0 +1,141 @@
+//! # 进程树 (Process Tree)
+//!
+//! 应用通过 uv 启动 Python 进程，Python 是 uv 的子进程，只终止 uv 时 Python 可能残留并继续占用端口。
+//! 这里按应用实际启动的进程 PID 查找并终止整个进程树，不按命令行匹配，不会误杀其他进程；
+//! 同时提供等待进程退出和等待端口释放的工具，替代固定时长的等待。
+
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// 检查进程状态和端口的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn quiet_command(program: &str) -> Command {
+    let mut command = Command::new(program);
+    command.stdout(Stdio::null()).stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW：不弹出控制台窗口
+        command.creation_flags(0x0800_0000);
+    }
+    command
+}
+
+/// 进程是否仍在运行
+#[cfg(unix)]
+pub fn is_alive(pid: u32) -> bool {
+    quiet_command("kill")
+        .args(["-0", &pid.to_string()])
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// 进程是否仍在运行
+#[cfg(windows)]
+pub fn is_alive(pid: u32) -> bool {
+    let mut command = quiet_command("tasklist");
+    command
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .stdout(Stdio::piped());
+    command
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)))
+        .unwrap_or(false)
+}
+
+/// 进程及其所有子孙进程的 PID，子孙进程在前
+///
+/// 应在终止进程前调用：父进程退出后子进程会被过继，无法再按父进程查找。
+#[cfg(unix)]
+pub fn tree_pids(pid: u32) -> Vec<u32> {
+    let mut pids = Vec::new();
+    let children = quiet_command("pgrep")
+        .args(["-P", &pid.to_string()])
+        .stdout(Stdio::piped())
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default();
+    for child in children
+        .lines()
+        .filter_map(|line| line.trim().parse::<u32>().ok())
+    {
+        pids.extend(tree_pids(child));
+    }
+    pids.push(pid);
+    pids
+}
+
+/// 进程及其所有子孙进程的 PID；Windows 上由 taskkill /T 终止整个进程树
+#[cfg(windows)]
+pub fn tree_pids(pid: u32) -> Vec<u32> {
+    vec![pid]
+}
+
+/// 终止进程树；force 为 false 时请求进程自行退出（SIGTERM），为 true 时强制终止
+#[cfg(unix)]
+pub fn terminate(pids: &[u32], force: bool) {
+    if pids.is_empty() {
+        return;
+    }
+    let signal = if force { "-KILL" } else { "-TERM" };
+    let _ = quiet_command("kill")
+        .arg(signal)
+        .args(pids.iter().map(|pid| pid.to_string()))
+        .status();
+}
+
+/// 终止进程树；force 为 false 时请求进程自行退出，为 true 时强制终止
+#[cfg(windows)]
+pub fn terminate(pids: &[u32], force: bool) {
+    for pid in pids {
+        let mut command = quiet_command("taskkill");
+        command.args(["/PID", &pid.to_string(), "/T"]);
+        if force {
+            command.arg("/F");
+        }
+        let _ = command.status();
+    }
+}
+
+/// 等待进程全部退出，返回是否在超时前退出（阻塞，供退出清理使用）
+pub fn wait_for_exit_blocking(pids: &[u32], timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !pids.iter().any(|pid| is_alive(*pid)) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// 等待进程全部退出，返回是否在超时前退出
+pub async fn wait_for_exit(pids: &[u32], timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if !pids.iter().any(|pid| is_alive(*pid)) {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// 等待端口可以重新绑定，返回是否在超时前释放
+pub async fn wait_for_port_free(host: &str, port: u16, timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if std::net::TcpListener::bind((host, port)).is_ok() {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
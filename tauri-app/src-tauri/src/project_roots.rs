@@ -0,0 +1,307 @@
+//! # 项目根目录识别 (Project Root Detection)
+//!
+//! 通过标记文件（`.git`、`Cargo.toml`、`package.json` 等）识别项目根目录，为项目内的文件记录
+//! `project_root` / `project_id`，并跳过项目根目录下的构建产物目录（`target`、`node_modules`、
+//! `build` 等）：这些目录文件数量庞大，内容都可以从源码重新生成。
+//!
+//! 除内置标记外，API 中启用的 Structure 类型规则也作为项目标记：`pattern` 为标记文件名
+//! （`pattern_type` 为 `glob` 时按通配符匹配，如 `*.xcodeproj`），`extra_data.skip_dirs`
+//! 为该类项目的构建产物目录。配置更新时调用 `update_rules`。
+//!
+//! 构建产物目录的排除是优先级最低的内置排除：任何匹配的 Include 规则都可以覆盖它。
+//! 模式中写明构建产物目录名（如 `node_modules/my-notes/**`）或作用范围位于其中的 Include 规则
+//! 会让扫描继续进入该目录，只有规则匹配的文件被包含。
+//!
+//! 每个目录所属的项目只检测一次，结果缓存到下次配置更新。
+
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+use crate::file_monitor::{FileFilterRuleRust, RuleActionRust, RuleTypeRust};
+
+/// 内置的项目标记文件及对应的构建产物目录
+const BUILTIN_MARKERS: &[(&str, &[&str])] = &[
+    ("Cargo.toml", &["target"]),
+    ("package.json", &["node_modules", "dist", "build"]),
+    ("pyproject.toml", &["__pycache__", ".venv", "build", "dist"]),
+    ("pom.xml", &["target"]),
+    ("build.gradle", &["build", ".gradle"]),
+    ("go.mod", &[]),
+    (".git", &[]),
+];
+/// 目录缓存的最大条目数，超过后清空重新检测
+const ROOT_CACHE_LIMIT: usize = 10_000;
+
+#[derive(Debug, Clone)]
+struct ProjectMarker {
+    pattern: String,
+    glob: Option<glob::Pattern>,
+    derived_dirs: Vec<String>,
+    /// 来自 Structure 规则时为规则 ID
+    rule_id: Option<i32>,
+}
+
+impl ProjectMarker {
+    fn builtin(name: &str, derived_dirs: &[&str]) -> Self {
+        Self {
+            pattern: name.to_string(),
+            glob: None,
+            derived_dirs: derived_dirs.iter().map(|dir| dir.to_string()).collect(),
+            rule_id: None,
+        }
+    }
+
+    fn from_rule(rule: &FileFilterRuleRust) -> Option<Self> {
+        let glob = if rule.pattern_type == "glob" {
+            match glob::Pattern::new(&rule.pattern) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    warn!(
+                        "[PROJECT_ROOTS] Structure 规则 '{}' 的通配符无效: {}",
+                        rule.name, e
+                    );
+                    return None;
+                }
+            }
+        } else {
+            None
+        };
+        let derived_dirs = rule
+            .extra_data
+            .as_ref()
+            .and_then(|data| data.get("skip_dirs"))
+            .and_then(|dirs| dirs.as_array())
+            .map(|dirs| {
+                dirs.iter()
+                    .filter_map(|dir| dir.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            pattern: rule.pattern.clone(),
+            glob,
+            derived_dirs,
+            rule_id: Some(rule.id),
+        })
+    }
+
+    /// 目录中是否存在该标记
+    fn is_present_in(&self, dir: &Path) -> bool {
+        match &self.glob {
+            None => dir.join(&self.pattern).exists(),
+            Some(pattern) => std::fs::read_dir(dir)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .any(|entry| pattern.matches(&entry.file_name().to_string_lossy()))
+                })
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// 识别出的项目
+#[derive(Debug, Clone)]
+pub struct ProjectRoot {
+    pub root: PathBuf,
+    pub project_id: String,
+    /// 识别项目所用的标记
+    pub marker: String,
+    /// 标记来自 Structure 规则时为规则 ID
+    pub rule_id: Option<i32>,
+}
+
+impl ProjectRoot {
+    fn new(root: &Path, marker: &ProjectMarker) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            project_id: project_id(root),
+            marker: marker.pattern.clone(),
+            rule_id: marker.rule_id,
+        }
+    }
+}
+
+/// 可能包含构建产物目录中文件的 Include 规则
+#[derive(Debug, Clone)]
+struct IncludeTarget {
+    /// 模式按路径分隔符拆分后的各段（小写）
+    segments: Vec<String>,
+    /// extra_data.scope 中的目录
+    scope: Vec<PathBuf>,
+}
+
+impl IncludeTarget {
+    fn from_rule(rule: &FileFilterRuleRust) -> Self {
+        let segments = rule
+            .pattern
+            .to_lowercase()
+            .split(['/', '\\'])
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .collect();
+        let scope = rule
+            .extra_data
+            .as_ref()
+            .and_then(|data| data.get("scope"))
+            .and_then(|scope| scope.as_array())
+            .map(|dirs| {
+                dirs.iter()
+                    .filter_map(|dir| dir.as_str())
+                    .map(crate::path_utils::expand_home)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { segments, scope }
+    }
+
+    /// 规则是否指向该构建产物目录下的路径
+    fn reaches(&self, dir: &Path, name: &str) -> bool {
+        self.segments.iter().any(|segment| segment == name)
+            || self
+                .scope
+                .iter()
+                .any(|scope| crate::path_utils::path_starts_with(scope, dir))
+    }
+}
+
+/// 当前使用的项目标记：Structure 规则在前，内置标记在后；None 表示尚未收到配置
+static MARKERS: RwLock<Option<Vec<ProjectMarker>>> = RwLock::new(None);
+/// 启用的 Include 规则，用于判断是否需要进入构建产物目录
+static INCLUDE_TARGETS: RwLock<Vec<IncludeTarget>> = RwLock::new(Vec::new());
+/// 目录 -> 所属项目（None 表示不在任何项目中）
+static ROOT_CACHE: Mutex<Option<HashMap<PathBuf, Option<ProjectRoot>>>> = Mutex::new(None);
+
+fn builtin_markers() -> Vec<ProjectMarker> {
+    BUILTIN_MARKERS
+        .iter()
+        .map(|(name, derived_dirs)| ProjectMarker::builtin(name, derived_dirs))
+        .collect()
+}
+
+fn with_markers<R>(f: impl FnOnce(&[ProjectMarker]) -> R) -> R {
+    let guard = MARKERS.read().unwrap();
+    match guard.as_ref() {
+        Some(markers) => f(markers),
+        None => f(&builtin_markers()),
+    }
+}
+
+/// 项目 ID：根目录路径（NFC）的 SHA-256 前 8 字节
+fn project_id(root: &Path) -> String {
+    Sha256::digest(crate::path_utils::path_to_nfc_string(root).as_bytes())
+        .iter()
+        .take(8)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// 文件系统根目录和用户主目录本身不当作项目根目录（主目录下可能有 dotfiles 仓库的 `.git`）
+fn can_be_project_root(dir: &Path) -> bool {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    dir.parent().is_some() && home.is_none_or(|home| dir != Path::new(&home))
+}
+
+/// 根据配置中的 Structure 规则更新项目标记，记录 Include 规则，并清空目录缓存
+pub fn update_rules(rules: &[FileFilterRuleRust]) {
+    *INCLUDE_TARGETS.write().unwrap() = rules
+        .iter()
+        .filter(|rule| {
+            rule.enabled
+                && rule.action == RuleActionRust::Include
+                && rule.rule_type != RuleTypeRust::OSBundle
+        })
+        .map(IncludeTarget::from_rule)
+        .collect();
+
+    let mut markers: Vec<ProjectMarker> = rules
+        .iter()
+        .filter(|rule| rule.enabled && rule.rule_type == RuleTypeRust::Structure)
+        .filter_map(ProjectMarker::from_rule)
+        .collect();
+    if !markers.is_empty() {
+        info!(
+            "[PROJECT_ROOTS] 加载了 {} 条 Structure 规则作为项目标记",
+            markers.len()
+        );
+    }
+    markers.extend(builtin_markers());
+    *MARKERS.write().unwrap() = Some(markers);
+    *ROOT_CACHE.lock().unwrap() = None;
+}
+
+/// 文件所属的项目（最近的带有项目标记的上级目录）
+pub fn project_root_of(file_path: &Path) -> Option<ProjectRoot> {
+    let dir = file_path.parent()?;
+    with_markers(|markers| {
+        let mut guard = ROOT_CACHE.lock().unwrap();
+        let cache = guard.get_or_insert_with(HashMap::new);
+        if cache.len() > ROOT_CACHE_LIMIT {
+            cache.clear();
+        }
+
+        // 向上查找，直到命中缓存或找到标记；沿途的目录都属于同一个结果
+        let mut unresolved = Vec::new();
+        let mut found = None;
+        for ancestor in dir.ancestors() {
+            if let Some(cached) = cache.get(ancestor) {
+                found = cached.clone();
+                break;
+            }
+            unresolved.push(ancestor.to_path_buf());
+            if !can_be_project_root(ancestor) {
+                continue;
+            }
+            if let Some(marker) = markers.iter().find(|marker| marker.is_present_in(ancestor)) {
+                found = Some(ProjectRoot::new(ancestor, marker));
+                break;
+            }
+        }
+        for unresolved_dir in unresolved {
+            cache.insert(unresolved_dir, found.clone());
+        }
+        found
+    })
+}
+
+/// 目录是否为项目根目录下的构建产物目录（如 Cargo 项目的 `target`、npm 项目的 `node_modules`）
+pub fn is_derived_dir(dir: &Path) -> bool {
+    let (Some(name), Some(parent)) = (dir.file_name(), dir.parent()) else {
+        return false;
+    };
+    let name = name.to_string_lossy();
+    with_markers(|markers| {
+        markers.iter().any(|marker| {
+            marker.derived_dirs.iter().any(|derived| *derived == name)
+                && marker.is_present_in(parent)
+        })
+    })
+}
+
+/// 遍历时是否跳过该目录：构建产物目录，且没有 Include 规则指向其中的路径
+pub fn should_skip_dir(dir: &Path) -> bool {
+    if !is_derived_dir(dir) {
+        return false;
+    }
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    !INCLUDE_TARGETS
+        .read()
+        .unwrap()
+        .iter()
+        .any(|target| target.reaches(dir, &name))
+}
+
+/// 文件所在的构建产物目录（任一上级目录），不在构建产物目录中时返回 None
+pub fn derived_dir_of(file_path: &Path) -> Option<PathBuf> {
+    file_path
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| is_derived_dir(ancestor))
+        .map(Path::to_path_buf)
+}
@@ -0,0 +1,150 @@
+//! # 快速搜索窗口 (Quick Search Window)
+//!
+//! 通过全局快捷键在任何应用中呼出一个置顶的紧凑搜索窗口，直接搜索已索引的文件，
+//! 无需先切换到主窗口。窗口首次呼出时创建，失去焦点后隐藏，再次按下快捷键时切换显示。
+//!
+//! 快捷键保存在 settings.json 的 `quick_search_shortcut` 中，可通过 `set_quick_search_shortcut` 修改。
+
+use log::{info, warn};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+use tauri_plugin_store::StoreExt;
+
+/// 快速搜索窗口标签
+pub const QUICK_SEARCH_LABEL: &str = "quick-search";
+/// 默认快捷键
+const DEFAULT_SHORTCUT: &str = "CommandOrControl+Shift+Space";
+/// settings.json 中保存快捷键的键
+const SHORTCUT_STORE_KEY: &str = "quick_search_shortcut";
+
+/// 当前已注册的快捷键，修改时先注销旧的
+static CURRENT_SHORTCUT: Mutex<Option<String>> = Mutex::new(None);
+
+/// 显示快速搜索窗口（不存在时创建），已显示且有焦点时隐藏
+pub fn toggle_window(app_handle: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(QUICK_SEARCH_LABEL) {
+        if window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false) {
+            return window.hide().map_err(|e| e.to_string());
+        }
+    }
+    show_window(app_handle, None)
+}
+
+/// 显示快速搜索窗口（不存在时创建），`query` 不为空时填入搜索框
+pub fn show_window(app_handle: &AppHandle, query: Option<&str>) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(QUICK_SEARCH_LABEL) {
+        if let Some(query) = query {
+            window
+                .emit_to(QUICK_SEARCH_LABEL, "quick-search-query", query)
+                .map_err(|e| e.to_string())?;
+        }
+        window.center().map_err(|e| e.to_string())?;
+        window.show().map_err(|e| e.to_string())?;
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+
+    // 窗口尚未创建时事件无人接收，搜索词通过页面地址传入
+    let mut page_url = tauri::Url::parse("tauri://localhost/index.html").unwrap();
+    page_url
+        .query_pairs_mut()
+        .append_pair("window", QUICK_SEARCH_LABEL);
+    if let Some(query) = query {
+        page_url.query_pairs_mut().append_pair("q", query);
+    }
+    let window = WebviewWindowBuilder::new(
+        app_handle,
+        QUICK_SEARCH_LABEL,
+        WebviewUrl::App(format!("index.html?{}", page_url.query().unwrap_or_default()).into()),
+    )
+    .title("Quick Search")
+    .inner_size(640.0, 420.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .build()
+    .map_err(|e| format!("创建快速搜索窗口失败: {}", e))?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// 注册快速搜索快捷键，替换之前注册的快捷键；注册失败时恢复旧的快捷键
+fn register_shortcut(app_handle: &AppHandle, shortcut: &str) -> Result<(), String> {
+    let mut current = CURRENT_SHORTCUT.lock().unwrap();
+    let global_shortcut = app_handle.global_shortcut();
+    if let Some(previous) = current.as_deref() {
+        let _ = global_shortcut.unregister(previous);
+    }
+
+    let on_pressed = |app: &AppHandle, _: &Shortcut, event: ShortcutEvent| {
+        if event.state == ShortcutState::Pressed {
+            if let Err(e) = toggle_window(app) {
+                warn!("[QUICK_SEARCH] {}", e);
+            }
+        }
+    };
+    match global_shortcut.on_shortcut(shortcut, on_pressed) {
+        Ok(()) => {
+            info!("[QUICK_SEARCH] 已注册快速搜索快捷键: {}", shortcut);
+            *current = Some(shortcut.to_string());
+            Ok(())
+        }
+        Err(e) => {
+            if let Some(previous) = current.as_deref() {
+                let _ = global_shortcut.on_shortcut(previous, on_pressed);
+            }
+            Err(format!("注册快捷键 {} 失败: {}", shortcut, e))
+        }
+    }
+}
+
+fn saved_shortcut(app_handle: &AppHandle) -> String {
+    app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(SHORTCUT_STORE_KEY))
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_SHORTCUT.to_string())
+}
+
+/// 按 settings.json 中保存的快捷键注册，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let shortcut = saved_shortcut(app_handle);
+    if let Err(e) = register_shortcut(app_handle, &shortcut) {
+        warn!("[QUICK_SEARCH] {}", e);
+    }
+}
+
+/// 修改快速搜索快捷键，并保存到 settings.json
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_quick_search_shortcut(
+    shortcut: String,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    register_shortcut(&app_handle, &shortcut)?;
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(SHORTCUT_STORE_KEY, serde_json::json!(shortcut));
+    store
+        .save()
+        .map_err(|e| format!("保存快捷键设置失败: {}", e))?;
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("快速搜索快捷键已设置为 {}", shortcut),
+        "shortcut": shortcut
+    }))
+}
+
+/// 获取当前快速搜索快捷键
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_quick_search_shortcut() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "shortcut": CURRENT_SHORTCUT.lock().unwrap().clone(),
+        "default": DEFAULT_SHORTCUT
+    }))
+}
@@ -0,0 +1,187 @@
+//! # 组合规则条件 (Composite Rule Conditions)
+//!
+//! 过滤规则可以在 `extra_data.conditions` 中附加组合条件，规则本身的模式匹配后，
+//! 组合条件也成立才算匹配（规则的 `pattern` 为空时只看组合条件）。条件可以任意嵌套：
+//! ```json
+//! {
+//!   "conditions": {
+//!     "all": [
+//!       { "field": "extension", "op": "equals", "value": "pdf" },
+//!       { "any": [
+//!         { "field": "folder", "op": "contains", "value": "Invoices" },
+//!         { "field": "filename", "op": "glob", "value": "invoice*" }
+//!       ] },
+//!       { "not": { "field": "size", "op": "gt", "value": 52428800 } }
+//!     ]
+//!   }
+//! }
+//! ```
+//! - `field`：extension、filename、folder（所在文件夹路径）、path（完整路径）、size（字节）
+//! - `op`：equals、contains、starts_with、ends_with、glob、regex（字符串比较不区分大小写），
+//!   gt、lt（仅用于 size）
+//!
+//! glob 和 regex 条件在 [`Condition::parse`] 时编译，配置快照构建时解析一次，评估文件时不再编译。
+
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Extension,
+    Filename,
+    Folder,
+    Path,
+    Size,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    Equals,
+    Contains,
+    StartsWith,
+    EndsWith,
+    Glob,
+    Regex,
+    Gt,
+    Lt,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Condition {
+    All {
+        all: Vec<Condition>,
+    },
+    Any {
+        any: Vec<Condition>,
+    },
+    Not {
+        not: Box<Condition>,
+    },
+    Leaf {
+        field: Field,
+        op: Op,
+        value: JsonValue,
+        /// glob / regex 条件预编译的匹配器，模式无效时为 None（条件不成立）
+        #[serde(skip)]
+        matcher: Option<Matcher>,
+    },
+}
+
+/// 预编译的字符串匹配器
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    Glob(globset::GlobMatcher),
+    Regex(regex::Regex),
+}
+
+impl Matcher {
+    fn compile(op: Op, expected: &str) -> Option<Matcher> {
+        match op {
+            Op::Glob => globset::GlobBuilder::new(expected)
+                .case_insensitive(true)
+                .build()
+                .ok()
+                .map(|glob| Matcher::Glob(glob.compile_matcher())),
+            Op::Regex => regex::Regex::new(expected).ok().map(Matcher::Regex),
+            _ => None,
+        }
+    }
+}
+
+/// 评估条件所需的文件信息
+pub struct FileFacts<'a> {
+    pub extension: Option<&'a str>,
+    pub file_name: &'a str,
+    pub path: &'a str,
+    pub size: u64,
+}
+
+impl FileFacts<'_> {
+    fn folder(&self) -> &str {
+        std::path::Path::new(self.path)
+            .parent()
+            .and_then(|parent| parent.to_str())
+            .unwrap_or("")
+    }
+}
+
+fn compare_str(actual: &str, op: Op, expected: &str, matcher: Option<&Matcher>) -> bool {
+    let actual = actual.to_lowercase();
+    let expected_lower = expected.to_lowercase();
+    match (op, matcher) {
+        (Op::Equals, _) => actual == expected_lower,
+        (Op::Contains, _) => actual.contains(&expected_lower),
+        (Op::StartsWith, _) => actual.starts_with(&expected_lower),
+        (Op::EndsWith, _) => actual.ends_with(&expected_lower),
+        (Op::Glob, Some(Matcher::Glob(glob))) => glob.is_match(actual.replace('\\', "/")),
+        (Op::Regex, Some(Matcher::Regex(regex))) => regex.is_match(&actual),
+        _ => false,
+    }
+}
+
+impl Condition {
+    /// 从规则的 `extra_data.conditions` 解析组合条件，并编译其中的 glob / regex
+    pub fn parse(value: &JsonValue) -> Result<Condition, String> {
+        let mut condition: Condition = serde_json::from_value(value.clone())
+            .map_err(|e| format!("组合条件格式错误: {}", e))?;
+        condition.compile();
+        Ok(condition)
+    }
+
+    fn compile(&mut self) {
+        match self {
+            Condition::All { all: conditions } | Condition::Any { any: conditions } => {
+                conditions.iter_mut().for_each(Condition::compile)
+            }
+            Condition::Not { not } => not.compile(),
+            Condition::Leaf {
+                op, value, matcher, ..
+            } => {
+                *matcher = value
+                    .as_str()
+                    .and_then(|expected| Matcher::compile(*op, expected))
+            }
+        }
+    }
+
+    pub fn evaluate(&self, facts: &FileFacts) -> bool {
+        match self {
+            Condition::All { all } => all.iter().all(|condition| condition.evaluate(facts)),
+            Condition::Any { any } => any.iter().any(|condition| condition.evaluate(facts)),
+            Condition::Not { not } => !not.evaluate(facts),
+            Condition::Leaf {
+                field,
+                op,
+                value,
+                matcher,
+            } => match field {
+                Field::Size => {
+                    let Some(expected) = value.as_u64() else {
+                        return false;
+                    };
+                    match op {
+                        Op::Gt => facts.size > expected,
+                        Op::Lt => facts.size < expected,
+                        Op::Equals => facts.size == expected,
+                        _ => false,
+                    }
+                }
+                _ => {
+                    let Some(expected) = value.as_str() else {
+                        return false;
+                    };
+                    let actual = match field {
+                        Field::Extension => facts.extension.unwrap_or(""),
+                        Field::Filename => facts.file_name,
+                        Field::Folder => facts.folder(),
+                        _ => facts.path,
+                    };
+                    compare_str(actual, *op, expected, matcher.as_ref())
+                }
+            },
+        }
+    }
+}
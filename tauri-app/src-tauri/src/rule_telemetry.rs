@@ -0,0 +1,168 @@
+//! # 规则命中统计上报 (Rule Telemetry)
+//!
+//! 记录每条过滤规则在文件监控和扫描中的命中次数、排除次数以及被更高优先级 Include 规则
+//! 覆盖的次数，定期 POST 到 `/rules/telemetry`，让后端（和用户）看到哪些系统规则真正生效、
+//! 哪些规则排除了过多文件。隐藏文件（9999）和项目构建产物（9998）的自动排除也会统计。
+//!
+//! 只统计真实的监控和扫描，`audit_rules` 试运行不计入。上报失败时保留计数，下次一起上报。
+
+use log::{error, info};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::file_monitor::{AllConfigurations, FileMetadata};
+
+/// 上报间隔
+const REPORT_INTERVAL: Duration = Duration::from_secs(600);
+/// 单次上报请求超时
+const REPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default, Clone)]
+struct RuleCounters {
+    name: String,
+    hits: u64,
+    exclusions: u64,
+    overrides: u64,
+}
+
+#[derive(Debug, Default)]
+struct TelemetryPeriod {
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+    files_evaluated: u64,
+    rules: HashMap<i32, RuleCounters>,
+}
+
+static PERIOD: Mutex<Option<TelemetryPeriod>> = Mutex::new(None);
+
+fn counters<'a>(
+    period: &'a mut TelemetryPeriod,
+    rule_id: i32,
+    rule_name: &str,
+) -> &'a mut RuleCounters {
+    period.rules.entry(rule_id).or_insert_with(|| RuleCounters {
+        name: rule_name.to_string(),
+        ..Default::default()
+    })
+}
+
+/// 记录一个文件的规则评估结果，应在 evaluate_rules 之后调用
+pub fn record(config: &AllConfigurations, metadata: &FileMetadata) {
+    let mut guard = PERIOD.lock().unwrap();
+    let period = guard.get_or_insert_with(TelemetryPeriod::default);
+    period.started_at.get_or_insert_with(chrono::Utc::now);
+    period.files_evaluated += 1;
+
+    if let Some(matches) = &metadata.initial_rule_matches {
+        for rule in config
+            .file_filter_rules
+            .iter()
+            .filter(|rule| matches.contains(&rule.name))
+        {
+            counters(period, rule.id, &rule.name).hits += 1;
+        }
+    }
+
+    let Some(extra) = &metadata.extra_metadata else {
+        return;
+    };
+    let rule_ref = |id_key: &str, name_key: &str| {
+        let id = extra.get(id_key)?.as_i64()? as i32;
+        let name = extra.get(name_key)?.as_str()?.to_string();
+        Some((id, name))
+    };
+    if let Some((id, name)) = rule_ref("excluded_by_rule_id", "excluded_by_rule_name") {
+        counters(period, id, &name).exclusions += 1;
+    }
+    if let Some((id, name)) = rule_ref(
+        "exclude_overridden_by_rule_id",
+        "exclude_overridden_by_rule_name",
+    ) {
+        counters(period, id, &name).overrides += 1;
+    }
+}
+
+/// 把上报失败的统计合并回当前周期
+fn restore(failed: TelemetryPeriod) {
+    let mut guard = PERIOD.lock().unwrap();
+    let period = guard.get_or_insert_with(TelemetryPeriod::default);
+    period.started_at = match (failed.started_at, period.started_at) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    period.files_evaluated += failed.files_evaluated;
+    for (id, failed_counters) in failed.rules {
+        let current = counters(period, id, &failed_counters.name);
+        current.hits += failed_counters.hits;
+        current.exclusions += failed_counters.exclusions;
+        current.overrides += failed_counters.overrides;
+    }
+}
+
+/// 启动定期上报任务，应在API首次就绪后调用
+pub fn start_reporting(api_state_mutex: Arc<Mutex<crate::ApiProcessState>>) {
+    tauri::async_runtime::spawn(async move {
+        let client = crate::http_client::shared();
+        loop {
+            tokio::time::sleep(REPORT_INTERVAL).await;
+
+            let Some(period) = PERIOD.lock().unwrap().take() else {
+                continue;
+            };
+            if period.files_evaluated == 0 {
+                continue;
+            }
+
+            let rules: Vec<serde_json::Value> = period
+                .rules
+                .iter()
+                .map(|(id, counters)| {
+                    serde_json::json!({
+                        "rule_id": id,
+                        "rule_name": counters.name,
+                        "hits": counters.hits,
+                        "exclusions": counters.exclusions,
+                        "overrides": counters.overrides
+                    })
+                })
+                .collect();
+            let body = serde_json::json!({
+                "period_start": period.started_at.map(|time| time.to_rfc3339()),
+                "period_end": chrono::Utc::now().to_rfc3339(),
+                "files_evaluated": period.files_evaluated,
+                "rules": rules
+            });
+
+            let url = {
+                let api_state_guard = api_state_mutex.lock().unwrap();
+                format!(
+                    "http://{}:{}/rules/telemetry",
+                    api_state_guard.host, api_state_guard.port
+                )
+            };
+            match client
+                .post(&url)
+                .json(&body)
+                .timeout(REPORT_TIMEOUT)
+                .send()
+                .await
+            {
+                Ok(response) if response.status().is_success() => {
+                    info!(
+                        "[RULE_TELEMETRY] 已上报 {} 条规则的统计（{} 个文件）",
+                        period.rules.len(),
+                        period.files_evaluated
+                    );
+                }
+                Ok(response) => {
+                    error!("[RULE_TELEMETRY] 上报规则统计失败: {}", response.status());
+                    restore(period);
+                }
+                Err(e) => {
+                    error!("[RULE_TELEMETRY] 上报规则统计失败: {}", e);
+                    restore(period);
+                }
+            }
+        }
+    });
+}
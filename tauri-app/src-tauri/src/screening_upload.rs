@@ -0,0 +1,229 @@
+//! # 粗筛数据流式提交 (Streaming Screening Upload)
+//!
+//! 默认的批量模式下，批处理器把元数据攒成 `Vec` 再整体序列化为一个 JSON 发送到 `/file-screening/batch`，
+//! 全盘扫描时内存中同时存在元数据、JSON 树和请求体三份数据。
+//!
+//! 流式模式下批处理器打开一个分块传输的请求，每收到一条元数据就序列化为一行 NDJSON 写入请求体，
+//! 由 `/file-screening/stream` 边接收边入库。请求在达到记录数上限或批处理间隔时结束，随后开启新的请求。
+//! 写入通道有界，API 处理不过来时批处理器会等待，内存占用保持平稳。
+//!
+//! 模式保存在 settings.json 的 `screening_upload_mode` 中：`batch`（默认）/ `stream`。
+//!
+//! 批量模式下批处理器不等上一批的响应就开始攒下一批，最多同时有 `batch_concurrency` 个
+//! `/file-screening/batch` 请求在进行（保存在 settings.json 的 `batch_send_concurrency` 中，默认 2），
+//! 磁盘快时提高吞吐，同时限制 Python API 的负载。同一文件在相邻批次中的元数据可能乱序到达，
+//! 文件事件经过防抖合并，这种情况很少见。
+
+use log::info;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use tokio::sync::mpsc;
+
+/// settings.json 中保存提交模式的键
+const MODE_STORE_KEY: &str = "screening_upload_mode";
+/// 攒够这么多字节再写入请求体，避免每条记录一个分块
+const CHUNK_BYTES: usize = 64 * 1024;
+/// 请求体通道中最多排队的分块数
+const CHUNK_QUEUE: usize = 16;
+/// 单个流式请求最多包含的记录数，超过后结束请求并开启新的请求
+pub const MAX_RECORDS_PER_REQUEST: usize = 5000;
+
+/// settings.json 中保存批量请求并发数的键
+const CONCURRENCY_STORE_KEY: &str = "batch_send_concurrency";
+/// 默认同时进行的批量请求数
+const DEFAULT_CONCURRENCY: usize = 2;
+/// 同时进行的批量请求数上限
+const MAX_CONCURRENCY: usize = 8;
+
+/// 是否使用流式模式，默认批量模式
+static STREAMING: AtomicBool = AtomicBool::new(false);
+/// 同时进行的批量请求数
+static CONCURRENCY: AtomicUsize = AtomicUsize::new(DEFAULT_CONCURRENCY);
+
+fn mode_name(streaming: bool) -> &'static str {
+    if streaming {
+        "stream"
+    } else {
+        "batch"
+    }
+}
+
+fn parse_mode(name: &str) -> Option<bool> {
+    match name {
+        "batch" => Some(false),
+        "stream" => Some(true),
+        _ => None,
+    }
+}
+
+/// 当前是否使用流式模式提交粗筛数据
+pub fn streaming_enabled() -> bool {
+    STREAMING.load(Ordering::SeqCst)
+}
+
+/// 批量模式下最多同时进行的 `/file-screening/batch` 请求数
+pub fn batch_concurrency() -> usize {
+    CONCURRENCY.load(Ordering::SeqCst)
+}
+
+/// 恢复保存的提交模式和批量请求并发数，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let Ok(store) = app_handle.store("settings.json") else {
+        return;
+    };
+    let saved = store
+        .get(MODE_STORE_KEY)
+        .and_then(|value| value.as_str().and_then(parse_mode));
+    if let Some(streaming) = saved {
+        STREAMING.store(streaming, Ordering::SeqCst);
+    }
+    let concurrency = store
+        .get(CONCURRENCY_STORE_KEY)
+        .and_then(|value| value.as_u64());
+    if let Some(concurrency) = concurrency {
+        CONCURRENCY.store(
+            (concurrency as usize).clamp(1, MAX_CONCURRENCY),
+            Ordering::SeqCst,
+        );
+    }
+}
+
+/// 进行中的 NDJSON 流式请求
+pub struct NdjsonUpload {
+    tx: mpsc::Sender<Vec<u8>>,
+    // 尚未写入请求体的行
+    pending: Vec<u8>,
+    // 已写入的记录数
+    count: usize,
+    response: tokio::task::JoinHandle<reqwest::Result<reqwest::Response>>,
+}
+
+impl NdjsonUpload {
+    /// 开始一个流式请求，请求体随后通过 push 写入
+    pub fn open(client: &reqwest::Client, url: String) -> NdjsonUpload {
+        let (tx, mut rx) = mpsc::channel::<Vec<u8>>(CHUNK_QUEUE);
+        let body = reqwest::Body::wrap_stream(futures::stream::poll_fn(move |cx| {
+            rx.poll_recv(cx)
+                .map(|chunk| chunk.map(Ok::<_, std::io::Error>))
+        }));
+        let request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+            .body(body);
+        NdjsonUpload {
+            tx,
+            pending: Vec::with_capacity(CHUNK_BYTES),
+            count: 0,
+            response: tokio::spawn(request.send()),
+        }
+    }
+
+    /// 已写入的记录数
+    pub fn record_count(&self) -> usize {
+        self.count
+    }
+
+    /// 写入一条记录；请求已失败（如连接被拒绝）时返回错误
+    pub async fn push<T: Serialize>(&mut self, record: &T) -> Result<(), String> {
+        serde_json::to_writer(&mut self.pending, record)
+            .map_err(|e| format!("序列化记录失败: {}", e))?;
+        self.pending.push(b'\n');
+        self.count += 1;
+        if self.pending.len() >= CHUNK_BYTES {
+            self.send_pending().await?;
+        }
+        Ok(())
+    }
+
+    async fn send_pending(&mut self) -> Result<(), String> {
+        let chunk = std::mem::replace(&mut self.pending, Vec::with_capacity(CHUNK_BYTES));
+        self.tx
+            .send(chunk)
+            .await
+            .map_err(|_| "流式请求已中断".to_string())
+    }
+
+    /// 结束请求体并等待API响应
+    pub async fn finish(mut self) -> Result<reqwest::Response, String> {
+        if !self.pending.is_empty() {
+            // 请求已中断时错误会在下面等待响应时返回
+            let _ = self.send_pending().await;
+        }
+        drop(self.tx);
+        self.response
+            .await
+            .map_err(|e| format!("流式请求任务异常退出: {}", e))?
+            .map_err(|e| format!("流式请求失败: {}", e))
+    }
+}
+
+/// 设置粗筛数据提交模式：batch（批量）或 stream（NDJSON 流式）
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_screening_upload_mode(
+    mode: String,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let streaming = parse_mode(&mode).ok_or_else(|| format!("未知的提交模式: {}", mode))?;
+    STREAMING.store(streaming, Ordering::SeqCst);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(MODE_STORE_KEY, serde_json::json!(mode));
+    store
+        .save()
+        .map_err(|e| format!("保存提交模式失败: {}", e))?;
+
+    info!("[SCREENING_UPLOAD] 粗筛数据提交模式已设置为: {}", mode);
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("粗筛数据提交模式已设置为 {}", mode)
+    }))
+}
+
+/// 获取当前粗筛数据提交模式
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_screening_upload_mode() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "mode": mode_name(streaming_enabled())
+    }))
+}
+
+/// 设置批量模式下最多同时进行的请求数（1 表示逐批串行发送）
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_batch_send_concurrency(
+    concurrency: usize,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    if !(1..=MAX_CONCURRENCY).contains(&concurrency) {
+        return Err(format!("并发数应在 1 到 {} 之间", MAX_CONCURRENCY));
+    }
+    CONCURRENCY.store(concurrency, Ordering::SeqCst);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(CONCURRENCY_STORE_KEY, serde_json::json!(concurrency));
+    store
+        .save()
+        .map_err(|e| format!("保存批量请求并发数失败: {}", e))?;
+
+    info!("[SCREENING_UPLOAD] 批量请求并发数已设置为: {}", concurrency);
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("批量请求并发数已设置为 {}", concurrency)
+    }))
+}
+
+/// 获取批量模式下最多同时进行的请求数
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_batch_send_concurrency() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "concurrency": batch_concurrency(),
+        "max_concurrency": MAX_CONCURRENCY
+    }))
+}
@@ -0,0 +1,159 @@
+//! # 截图文件夹 (Screenshot Folder)
+//!
+//! 自动识别系统保存截图的文件夹：
+//! - macOS：`defaults read com.apple.screencapture location`，未设置时为桌面
+//! - Windows：OneDrive 或用户目录下的 `Pictures\Screenshots`
+//! - Linux：`xdg-user-dir PICTURES` 下的 `Screenshots`，不存在时为图片文件夹本身
+//!
+//! 截图文件夹位于监控文件夹中时单独使用最短的防抖间隔（不随事件速率和低功耗模式放大），
+//! 新截图立即加上 `screenshot` 标牌，并发出 `screenshot-captured` 事件供前端展示。
+//! 截图文件夹本身不会被额外监控，未授权的文件夹中的截图仍然被忽略。
+
+use log::info;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// 截图的标牌
+pub const SCREENSHOT_TAG: &str = "screenshot";
+
+struct ScreenshotDirectory {
+    path: PathBuf,
+    // 是否为专门保存截图的文件夹；桌面、图片文件夹等混放其他文件时只按文件名判断
+    dedicated: bool,
+}
+
+static DIRECTORY: OnceLock<Option<ScreenshotDirectory>> = OnceLock::new();
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+fn detected() -> Option<&'static ScreenshotDirectory> {
+    DIRECTORY
+        .get_or_init(|| {
+            let directory = detect();
+            match &directory {
+                Some(directory) => info!(
+                    "[SCREENSHOTS] 截图文件夹: {:?}{}",
+                    directory.path,
+                    if directory.dedicated {
+                        ""
+                    } else {
+                        "（按文件名识别截图）"
+                    }
+                ),
+                None => info!("[SCREENSHOTS] 未找到截图文件夹"),
+            }
+            directory
+        })
+        .as_ref()
+}
+
+#[cfg(target_os = "macos")]
+fn detect() -> Option<ScreenshotDirectory> {
+    let home = home_dir()?;
+    let desktop = home.join("Desktop");
+    let configured = std::process::Command::new("defaults")
+        .args(["read", "com.apple.screencapture", "location"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|location| !location.is_empty())
+        .map(|location| match location.strip_prefix("~/") {
+            Some(relative) => home.join(relative),
+            None => PathBuf::from(location),
+        })
+        .filter(|path| path.is_dir());
+    let path = configured.unwrap_or(desktop.clone());
+    Some(ScreenshotDirectory {
+        dedicated: path != desktop && path != home,
+        path,
+    })
+}
+
+#[cfg(windows)]
+fn detect() -> Option<ScreenshotDirectory> {
+    let candidates = [
+        std::env::var_os("OneDrive").map(PathBuf::from),
+        home_dir().map(|home| home.join("OneDrive")),
+        home_dir(),
+    ];
+    candidates
+        .into_iter()
+        .flatten()
+        .map(|base| base.join("Pictures").join("Screenshots"))
+        .find(|path| path.is_dir())
+        .map(|path| ScreenshotDirectory {
+            path,
+            dedicated: true,
+        })
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn detect() -> Option<ScreenshotDirectory> {
+    let pictures = std::process::Command::new("xdg-user-dir")
+        .arg("PICTURES")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+        .filter(|path| path.is_dir())
+        .or_else(|| home_dir().map(|home| home.join("Pictures")))?;
+    let screenshots = pictures.join("Screenshots");
+    if screenshots.is_dir() {
+        Some(ScreenshotDirectory {
+            path: screenshots,
+            dedicated: true,
+        })
+    } else if pictures.is_dir() {
+        Some(ScreenshotDirectory {
+            path: pictures,
+            dedicated: false,
+        })
+    } else {
+        None
+    }
+}
+
+/// 系统保存截图的文件夹（首次调用时检测）
+pub fn directory() -> Option<&'static Path> {
+    detected().map(|directory| directory.path.as_path())
+}
+
+/// 是否为系统截图文件夹中新保存的截图
+pub fn is_screenshot(path: &Path) -> bool {
+    let Some(directory) = detected() else {
+        return false;
+    };
+    let is_image = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| crate::ocr::is_image_extension(&ext));
+    is_image
+        && path.parent() == Some(directory.path.as_path())
+        && (directory.dedicated || crate::ocr::is_screenshot(path))
+}
+
+/// 截图文件夹位于某个监控目录中（且本身不是监控目录）时返回它，用于单独注册防抖任务
+pub fn nested_in(directories: &[String]) -> Option<String> {
+    let directory = directory()?;
+    let nested = directories.iter().any(|dir| {
+        let dir = Path::new(dir);
+        dir != directory && crate::path_utils::path_starts_with(directory, dir)
+    });
+    let is_root = directories.iter().any(|dir| Path::new(dir) == directory);
+    (nested && !is_root).then(|| crate::path_utils::path_to_nfc_string(directory))
+}
+
+/// 获取检测到的截图文件夹
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_screenshot_directory() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "directory": detected().map(|directory| directory.path.to_string_lossy().into_owned()),
+        "dedicated": detected().is_some_and(|directory| directory.dedicated)
+    }))
+}
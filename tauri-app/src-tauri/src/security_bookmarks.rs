@@ -0,0 +1,229 @@
+//! # 安全范围书签 (Security-Scoped Bookmarks)
+//!
+//! 沙盒化的 macOS 应用（如 Mac App Store 版本）只能访问用户在打开面板中选择过的文件夹，
+//! 且授权在应用退出后失效。为了在没有完全磁盘访问权限时也能跨启动持续监控，
+//! 用户添加白名单文件夹时为其创建安全范围书签，保存到应用数据目录的 `security_bookmarks.json`；
+//! 启动时在文件监控开始前解析所有书签并调用 `startAccessingSecurityScopedResource` 恢复访问。
+//!
+//! 书签过期（文件夹被移动或系统更新后）时用解析出的新位置重新创建。暂时无法解析的书签（例如所在的外接卷
+//! 未挂载）不会被丢弃，而是标记为失效并保留在存储中，卷挂载（`volume-mounted` 事件）时重试。
+//! 非 macOS 平台没有该机制，所有操作均为空操作。
+
+use base64::Engine;
+use log::{info, warn};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Listener, Manager};
+
+/// 书签存储文件名，位于应用数据目录
+const BOOKMARKS_FILE: &str = "security_bookmarks.json";
+
+/// 书签存储的读写锁，避免并发添加/删除时相互覆盖
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 暂时无法解析的书签对应的文件夹，卷挂载时重试
+static STALE: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+fn store_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(BOOKMARKS_FILE))
+        .map_err(|e| format!("无法获取应用数据目录: {}", e))
+}
+
+/// 读取保存的书签：文件夹路径 -> base64 编码的书签数据
+fn load_bookmarks(app_handle: &AppHandle) -> BTreeMap<String, String> {
+    store_path(app_handle)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_bookmarks(
+    app_handle: &AppHandle,
+    bookmarks: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    let path = store_path(app_handle)?;
+    let content = serde_json::to_string_pretty(bookmarks).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("保存安全范围书签失败: {}", e))
+}
+
+#[cfg(target_os = "macos")]
+#[allow(unused_unsafe)]
+mod platform {
+    use objc2::runtime::Bool;
+    use objc2_foundation::{
+        NSData, NSString, NSURLBookmarkCreationOptions, NSURLBookmarkResolutionOptions, NSURL,
+    };
+
+    /// 为文件夹创建安全范围书签
+    pub fn create_bookmark(path: &str) -> Result<Vec<u8>, String> {
+        let url = unsafe { NSURL::fileURLWithPath(&NSString::from_str(path)) };
+        let data = unsafe {
+            url.bookmarkDataWithOptions_includingResourceValuesForKeys_relativeToURL_error(
+                NSURLBookmarkCreationOptions::WithSecurityScope,
+                None,
+                None,
+            )
+        }
+        .map_err(|e| format!("创建安全范围书签失败: {:?}", e))?;
+        Ok(data.to_vec())
+    }
+
+    /// 解析书签并开始访问（访问保持到进程退出），返回解析出的路径和书签是否过期
+    pub fn resolve_and_access(bookmark: &[u8]) -> Result<(String, bool), String> {
+        let data = NSData::with_bytes(bookmark);
+        let mut is_stale = Bool::NO;
+        let url = unsafe {
+            NSURL::URLByResolvingBookmarkData_options_relativeToURL_bookmarkDataIsStale_error(
+                &data,
+                NSURLBookmarkResolutionOptions::WithSecurityScope,
+                None,
+                &mut is_stale,
+            )
+        }
+        .map_err(|e| format!("解析安全范围书签失败: {:?}", e))?;
+        let path = unsafe { url.path() }
+            .map(|path| path.to_string())
+            .ok_or_else(|| "书签没有对应的文件路径".to_string())?;
+        if !unsafe { url.startAccessingSecurityScopedResource() } {
+            return Err(format!("无法访问书签对应的文件夹: {}", path));
+        }
+        Ok((path, is_stale.as_bool()))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    pub fn create_bookmark(_path: &str) -> Result<Vec<u8>, String> {
+        Err("仅 macOS 支持安全范围书签".to_string())
+    }
+
+    pub fn resolve_and_access(_bookmark: &[u8]) -> Result<(String, bool), String> {
+        Err("仅 macOS 支持安全范围书签".to_string())
+    }
+}
+
+/// 为用户授权的文件夹创建并保存书签，在用户添加白名单文件夹时调用
+pub fn save_bookmark(app_handle: &AppHandle, path: &str) -> Result<(), String> {
+    if !cfg!(target_os = "macos") {
+        return Ok(());
+    }
+    let bookmark = platform::create_bookmark(path)?;
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut bookmarks = load_bookmarks(app_handle);
+    bookmarks.insert(
+        path.to_string(),
+        base64::engine::general_purpose::STANDARD.encode(bookmark),
+    );
+    save_bookmarks(app_handle, &bookmarks)?;
+    info!("[BOOKMARKS] 已保存安全范围书签: {}", path);
+    Ok(())
+}
+
+/// 删除文件夹的书签，在用户删除白名单文件夹时调用
+pub fn remove_bookmark(app_handle: &AppHandle, path: &str) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut bookmarks = load_bookmarks(app_handle);
+    STALE.lock().unwrap().remove(path);
+    if bookmarks.remove(path).is_some() {
+        save_bookmarks(app_handle, &bookmarks)?;
+        info!("[BOOKMARKS] 已删除安全范围书签: {}", path);
+    }
+    Ok(())
+}
+
+/// 解析书签并恢复访问，`only_stale` 为 true 时只重试之前无法解析的书签，返回已恢复访问的文件夹
+fn resolve_bookmarks(app_handle: &AppHandle, only_stale: bool) -> Vec<String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let bookmarks = load_bookmarks(app_handle);
+    let mut stale = STALE.lock().unwrap();
+
+    let mut restored = Vec::new();
+    let mut attempted = 0;
+    let mut updated = bookmarks.clone();
+    for (path, encoded) in &bookmarks {
+        if only_stale && !stale.contains(path) {
+            continue;
+        }
+        attempted += 1;
+        let resolved = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("书签数据损坏: {}", e))
+            .and_then(|bookmark| platform::resolve_and_access(&bookmark));
+        match resolved {
+            Ok((resolved_path, is_stale)) => {
+                // 过期的书签用解析出的位置重新创建
+                let encoded = if is_stale {
+                    info!("[BOOKMARKS] 书签已过期，重新创建: {}", resolved_path);
+                    platform::create_bookmark(&resolved_path)
+                        .map(|bookmark| base64::engine::general_purpose::STANDARD.encode(bookmark))
+                        .unwrap_or_else(|_| encoded.clone())
+                } else {
+                    encoded.clone()
+                };
+                stale.remove(path);
+                updated.remove(path);
+                updated.insert(resolved_path.clone(), encoded);
+                restored.push(resolved_path);
+            }
+            // 保留书签，所在的卷挂载后重试；用户删除该文件夹时书签一并删除
+            Err(e) => {
+                if stale.insert(path.clone()) {
+                    warn!(
+                        "[BOOKMARKS] 暂时无法恢复书签 {}，标记为失效，卷挂载时重试: {}",
+                        path, e
+                    );
+                }
+            }
+        }
+    }
+
+    if updated != bookmarks {
+        if let Err(e) = save_bookmarks(app_handle, &updated) {
+            warn!("[BOOKMARKS] {}", e);
+        }
+    }
+    if attempted > 0 {
+        info!(
+            "[BOOKMARKS] 已恢复 {} / {} 个文件夹的访问权限",
+            restored.len(),
+            attempted
+        );
+    }
+    restored
+}
+
+/// 解析所有书签并恢复访问，应在 `volumes::init` 之后、文件监控开始前调用；
+/// 之后每当有卷挂载时重试失效的书签
+pub fn init(app_handle: &AppHandle) {
+    if !cfg!(target_os = "macos") {
+        return;
+    }
+    resolve_bookmarks(app_handle, false);
+
+    let handle = app_handle.clone();
+    app_handle.listen_any("volume-mounted", move |_| {
+        if STALE.lock().unwrap().is_empty() {
+            return;
+        }
+        for path in resolve_bookmarks(&handle, true) {
+            info!("[BOOKMARKS] 卷挂载后已恢复访问: {}", path);
+        }
+    });
+}
+
+/// 列出已保存书签的文件夹，`stale` 为暂时无法恢复访问的文件夹
+#[tauri::command(rename_all = "snake_case")]
+pub fn list_security_bookmarks(app_handle: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let folders: Vec<String> = load_bookmarks(&app_handle).into_keys().collect();
+    let stale: Vec<String> = STALE.lock().unwrap().iter().cloned().collect();
+    Ok(serde_json::json!({
+        "status": "success",
+        "folders": folders,
+        "stale": stale
+    }))
+}
@@ -0,0 +1,95 @@
+//! # 已发送文件缓存 (Seen Cache)
+//!
+//! 重新扫描目录（睡眠唤醒后补扫、文件夹配置刷新、事件通道溢出后补扫）以及编辑器反复保存产生的重复事件，
+//! 会把内容没有变化的文件再次发送到 `/file-screening/batch`。这里记录每个已成功发送的文件的签名
+//! （修改时间、大小、前 4KB 哈希），签名未变的文件由批处理器直接跳过。
+//!
+//! 只在内存中保存；过滤规则或分类配置变化后同一文件的粗筛结果可能不同，每次刷新配置都要调用 [`invalidate`]。
+
+use crate::file_monitor::FileMetadata;
+use std::collections::HashMap;
+use std::path::MAIN_SEPARATOR;
+use std::sync::Mutex;
+
+/// 缓存条目上限，超过后整体清空重新积累
+const MAX_ENTRIES: usize = 200_000;
+
+/// 文件签名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSignature {
+    modified_time: u64,
+    file_size: u64,
+    hash_value: Option<String>,
+}
+
+impl FileSignature {
+    pub fn new(modified_time: u64, file_size: u64, hash_value: Option<String>) -> FileSignature {
+        FileSignature {
+            modified_time,
+            file_size,
+            hash_value,
+        }
+    }
+
+    pub fn of(metadata: &FileMetadata) -> FileSignature {
+        FileSignature::new(
+            metadata.modified_time,
+            metadata.file_size,
+            metadata.hash_value.clone(),
+        )
+    }
+
+    // 修改时间和大小相同；两边都有哈希时哈希也要相同（低功耗模式下不计算哈希）
+    fn matches(&self, other: &FileSignature) -> bool {
+        self.modified_time == other.modified_time
+            && self.file_size == other.file_size
+            && match (&self.hash_value, &other.hash_value) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+    }
+}
+
+/// 文件路径 -> 最近一次成功发送时的签名
+static CACHE: Mutex<Option<HashMap<String, FileSignature>>> = Mutex::new(None);
+
+/// 文件自上次成功发送后是否没有变化
+pub fn is_unchanged(metadata: &FileMetadata) -> bool {
+    let cache = CACHE.lock().unwrap();
+    cache
+        .as_ref()
+        .and_then(|cache| cache.get(&metadata.file_path))
+        .is_some_and(|seen| seen.matches(&FileSignature::of(metadata)))
+}
+
+/// 记录已成功发送的文件签名
+pub fn record(signatures: Vec<(String, FileSignature)>) {
+    let mut cache = CACHE.lock().unwrap();
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if cache.len() + signatures.len() > MAX_ENTRIES {
+        cache.clear();
+    }
+    cache.extend(signatures);
+}
+
+/// 文件或目录被删除后移除其记录，之后重新出现时会再次发送
+pub fn forget(path: &str) {
+    let mut cache = CACHE.lock().unwrap();
+    let Some(cache) = cache.as_mut() else {
+        return;
+    };
+    if cache.remove(path).is_none() {
+        // 不是已发送的文件，可能是目录：移除目录下的所有记录
+        let prefix = format!(
+            "{}{}",
+            path.trim_end_matches(MAIN_SEPARATOR),
+            MAIN_SEPARATOR
+        );
+        cache.retain(|seen_path, _| !seen_path.starts_with(&prefix));
+    }
+}
+
+/// 清空缓存，配置刷新后调用
+pub fn invalidate() {
+    *CACHE.lock().unwrap() = None;
+}
@@ -0,0 +1,83 @@
+//! # 独立设置窗口 (Settings Window)
+//!
+//! 设置界面除了主窗口内的对话框外，也可以在单独的 `settings` 窗口中打开。
+//! 与主窗口不同，设置窗口关闭时真正关闭（不隐藏），下次打开时重新创建；
+//! 窗口位置和大小由 window-state 插件保存，创建时恢复。
+
+use log::error;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_window_state::{StateFlags, WindowExt};
+
+/// 设置窗口标签
+pub const SETTINGS_LABEL: &str = "settings";
+
+/// 设置页面，与前端 App.tsx 中的 SETTINGS_PAGES 保持一致
+const SETTINGS_SECTIONS: &[&str] = &[
+    "general",
+    "authorization",
+    "file_recognition",
+    "aimodels",
+    "theme",
+    "about",
+];
+
+/// 显示设置窗口（不存在时创建）并切换到指定页面
+fn show_window(app_handle: &AppHandle, section: &str) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(SETTINGS_LABEL) {
+        window
+            .emit_to(SETTINGS_LABEL, "settings-window-section", section)
+            .map_err(|e| e.to_string())?;
+        window.unminimize().map_err(|e| e.to_string())?;
+        window.show().map_err(|e| e.to_string())?;
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+
+    let window = WebviewWindowBuilder::new(
+        app_handle,
+        SETTINGS_LABEL,
+        WebviewUrl::App(format!("index.html?window=settings&section={}", section).into()),
+    )
+    .title("Settings")
+    .inner_size(900.0, 640.0)
+    .min_inner_size(720.0, 520.0)
+    .center()
+    // 先隐藏，恢复保存的位置和大小后再显示，避免窗口跳动
+    .visible(false)
+    .build()
+    .map_err(|e| format!("创建设置窗口失败: {}", e))?;
+    if let Err(e) = window.restore_state(StateFlags::all()) {
+        error!("[SETTINGS_WINDOW] 恢复设置窗口状态失败: {}", e);
+    }
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// 打开独立设置窗口，`section` 为要显示的设置页面，默认为 general
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn open_settings_window(
+    section: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let section = section.unwrap_or_else(|| "general".to_string());
+    if !SETTINGS_SECTIONS.contains(&section.as_str()) {
+        return Err(format!("未知的设置页面: {}", section));
+    }
+    show_window(&app_handle, &section)?;
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("设置窗口已打开: {}", section)
+    }))
+}
+
+/// 关闭独立设置窗口
+#[tauri::command(rename_all = "snake_case")]
+pub fn close_settings_window(app_handle: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    if let Some(window) = app_handle.get_webview_window(SETTINGS_LABEL) {
+        window.close().map_err(|e| e.to_string())?;
+    }
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": "设置窗口已关闭"
+    }))
+}
@@ -0,0 +1,80 @@
+//! # 优雅退出 (Graceful Shutdown)
+//!
+//! 托盘菜单"退出"触发的有序退出流程，保证退出前产生的文件变动不会丢失：
+//! 1. 停止所有目录的文件监控，不再产生新的文件事件
+//! 2. 批处理器接收完元数据通道中已排队的数据，发送最后一批到API
+//! 3. 发送缓冲的桥接事件，主窗口不在时保存到磁盘
+//! 4. 最后才请求API关闭并终止 sidecar 进程，然后退出应用
+//!
+//! 第 2、3 步和进程清理复用 `ApiProcessManager::cleanup`。流程完成后 `ExitRequested`
+//! 不再重复清理。
+
+use log::{error, info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{ApiProcessManager, AppState};
+
+/// 退出流程已开始，重复点击"退出"时忽略
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+/// 退出流程的清理已完成
+static SHUTDOWN_COMPLETE: AtomicBool = AtomicBool::new(false);
+
+/// 有序退出流程是否已完成清理
+pub fn has_completed() -> bool {
+    SHUTDOWN_COMPLETE.load(Ordering::SeqCst)
+}
+
+/// 停止所有目录的文件监控
+async fn stop_watchers(app_handle: &AppHandle) {
+    let app_state = app_handle.state::<AppState>();
+    // 退出时不再需要监控实例，取出后再等待停止，避免跨 await 持有锁
+    let debounced_monitor = app_state.debounced_file_monitor.lock().unwrap().take();
+    match debounced_monitor {
+        Some(mut monitor) => {
+            if let Err(e) = monitor.stop_monitoring().await {
+                warn!("[SHUTDOWN] 停止文件监控时出错: {}", e);
+            }
+        }
+        None => info!("[SHUTDOWN] 文件监控未启动，跳过"),
+    }
+}
+
+/// 开始有序退出流程，完成后退出应用
+pub fn request_shutdown(app_handle: &AppHandle) {
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        info!("[SHUTDOWN] 退出流程已在进行中");
+        return;
+    }
+    info!("[SHUTDOWN] 开始有序退出");
+    let _ = app_handle.emit("app-shutting-down", ());
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        info!("[SHUTDOWN] 1/2 停止文件监控");
+        stop_watchers(&app_handle).await;
+
+        // 排空批处理和终止进程都是同步等待，不能占用异步运行时的工作线程
+        let handle_for_cleanup = app_handle.clone();
+        let cleanup = tokio::task::spawn_blocking(move || {
+            match handle_for_cleanup.try_state::<ApiProcessManager>() {
+                Some(api_manager) => {
+                    info!("[SHUTDOWN] 2/2 排空元数据批处理、发送桥接事件并关闭API");
+                    api_manager.cleanup();
+                }
+                None => {
+                    info!("[SHUTDOWN] 无法获取ApiProcessManager，使用静态清理");
+                    ApiProcessManager::cleanup_processes();
+                }
+            }
+        })
+        .await;
+        if let Err(e) = cleanup {
+            error!("[SHUTDOWN] 清理任务异常: {}", e);
+        }
+
+        SHUTDOWN_COMPLETE.store(true, Ordering::SeqCst);
+        info!("[SHUTDOWN] 有序退出完成");
+        app_handle.exit(0);
+    });
+}
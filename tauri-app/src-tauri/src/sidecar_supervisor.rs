@@ -0,0 +1,600 @@
+//! # Sidecar 进程监管 (Sidecar Supervisor)
+//!
+//! 按名称管理应用启动的所有 sidecar 进程，包括主 Python API 和额外的工作进程
+//! （例如独立的向量化或 OCR worker），每个进程有独立的健康检查和生命周期：
+//! - 进程意外退出时按配置自动重启（有次数上限，持续健康运行一段时间后重新计数）
+//! - 配置了 `health_url` 的进程连续健康检查失败后重启
+//! - 应用退出时由 ApiProcessManager 统一终止
+//!
+//! 主 API 以 [`API_NAME`] 登记：启动前需要 uv sync、解析桥接事件输出，仍由 api_startup.rs 启动，
+//! 启动后通过 [`SidecarSupervisor::attach`] 交给监管器持有进程句柄；健康检查和重启由 api_watchdog.rs 负责，
+//! 结果同步到这里，`get_sidecar_status` 和 `restart_sidecar` 对主 API 同样适用。
+//!
+//! 工作进程在 settings.json 的 `sidecars` 数组中配置，例如：
+//! `{"name": "ocr-worker", "sidecar": "uv", "args": ["run", "ocr_worker.py"], "health_url": "http://127.0.0.1:60317/health"}`
+
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::{
+    process::{CommandChild, CommandEvent},
+    ShellExt,
+};
+use tauri_plugin_store::StoreExt;
+
+/// 健康检查间隔
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// 连续失败多少次后重启
+const MAX_CONSECUTIVE_HEALTH_FAILURES: u32 = 3;
+/// 进程意外退出后重启前的等待时间
+const RESTART_DELAY: Duration = Duration::from_secs(2);
+/// 自动重启次数上限，超过后标记为失败，需要手动重启
+const MAX_AUTO_RESTARTS: u32 = 5;
+/// 持续健康运行这么久后重置自动重启次数，偶尔崩溃的进程不会因累计次数而被放弃
+const STABLE_UPTIME: Duration = Duration::from_secs(10 * 60);
+/// 停止 sidecar 时等待进程树自行退出的时间，超时后强制终止
+const STOP_TIMEOUT: Duration = Duration::from_secs(3);
+/// 主 API 在监管器中的名称
+pub const API_NAME: &str = "api";
+
+fn default_true() -> bool {
+    true
+}
+
+/// sidecar 工作进程配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SidecarSpec {
+    /// 唯一名称
+    pub name: String,
+    /// tauri.conf.json 中 externalBin 声明的 sidecar 名称，如 "uv"
+    pub sidecar: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 健康检查地址，不配置则只监测进程是否存活
+    #[serde(default)]
+    pub health_url: Option<String>,
+    #[serde(default = "default_true")]
+    pub auto_restart: bool,
+}
+
+/// sidecar 运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SidecarStatus {
+    Stopped,
+    Running,
+    Unhealthy,
+    Failed,
+}
+
+struct SidecarEntry {
+    spec: SidecarSpec,
+    child: Option<CommandChild>,
+    status: SidecarStatus,
+    restart_count: u32,
+    // 每次启动递增，用于让旧进程的退出事件和健康检查任务失效
+    generation: u64,
+    // 本次连续健康运行的开始时间，健康检查失败时清空
+    healthy_since: Option<Instant>,
+    // 由其他模块启动、只登记在监管器中的进程（主 API），监管器不负责启动和自动重启
+    attached: bool,
+}
+
+impl SidecarEntry {
+    /// 已持续健康运行足够久时重置自动重启次数
+    fn reset_restart_count_if_stable(&mut self) {
+        if self
+            .healthy_since
+            .is_some_and(|since| since.elapsed() >= STABLE_UPTIME)
+        {
+            self.restart_count = 0;
+        }
+    }
+}
+
+/// 按名称管理多个 sidecar 工作进程
+#[derive(Clone, Default)]
+pub struct SidecarSupervisor {
+    entries: Arc<Mutex<HashMap<String, SidecarEntry>>>,
+}
+
+impl SidecarSupervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册 sidecar 配置，名称不能重复
+    pub fn register(&self, spec: SidecarSpec) -> Result<(), String> {
+        if spec.name.is_empty() || spec.name == API_NAME {
+            return Err(format!("无效的 sidecar 名称: '{}'", spec.name));
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.contains_key(&spec.name) {
+            return Err(format!("sidecar '{}' 已注册", spec.name));
+        }
+        info!("[SIDECAR] 注册 sidecar: {} ({})", spec.name, spec.sidecar);
+        entries.insert(
+            spec.name.clone(),
+            SidecarEntry {
+                spec,
+                child: None,
+                status: SidecarStatus::Stopped,
+                restart_count: 0,
+                generation: 0,
+                healthy_since: None,
+                attached: false,
+            },
+        );
+        Ok(())
+    }
+
+    /// 登记由其他模块启动的进程，监管器持有其进程句柄；同名进程已存在时替换句柄
+    pub fn attach(&self, app_handle: &AppHandle, name: &str, sidecar: &str, child: CommandChild) {
+        {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = entries
+                .entry(name.to_string())
+                .or_insert_with(|| SidecarEntry {
+                    spec: SidecarSpec {
+                        name: name.to_string(),
+                        sidecar: sidecar.to_string(),
+                        args: Vec::new(),
+                        health_url: None,
+                        auto_restart: false,
+                    },
+                    child: None,
+                    status: SidecarStatus::Stopped,
+                    restart_count: 0,
+                    generation: 0,
+                    healthy_since: None,
+                    attached: true,
+                });
+            entry.generation += 1;
+            entry.child = Some(child);
+            entry.status = SidecarStatus::Running;
+            entry.healthy_since = Some(Instant::now());
+        }
+        self.emit_status(app_handle, name);
+    }
+
+    /// 登记的进程已退出；pid 与当前进程不同时（已被新进程替换）不处理
+    pub fn detach(&self, app_handle: &AppHandle, name: &str, pid: u32) {
+        {
+            let mut entries = self.entries.lock().unwrap();
+            let Some(entry) = entries.get_mut(name) else {
+                return;
+            };
+            if entry.child.as_ref().map(|child| child.pid()) != Some(pid) {
+                return;
+            }
+            entry.child = None;
+            entry.healthy_since = None;
+            if entry.status != SidecarStatus::Stopped {
+                entry.status = SidecarStatus::Failed;
+            }
+        }
+        self.emit_status(app_handle, name);
+    }
+
+    /// 取出进程句柄，由调用方负责终止（例如先请求优雅关闭）；之后的退出事件不再改变状态
+    pub fn take_child(&self, name: &str) -> Option<CommandChild> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get_mut(name)?;
+        entry.status = SidecarStatus::Stopped;
+        entry.healthy_since = None;
+        entry.child.take()
+    }
+
+    /// 对运行中的进程执行操作（例如写入标准输入），进程不存在时返回 None
+    pub fn with_child<R>(&self, name: &str, f: impl FnOnce(&mut CommandChild) -> R) -> Option<R> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.get_mut(name)?.child.as_mut().map(f)
+    }
+
+    /// 运行中进程的 PID
+    pub fn pid(&self, name: &str) -> Option<u32> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(name)?.child.as_ref().map(|child| child.pid())
+    }
+
+    /// 进程是否在运行
+    pub fn is_running(&self, name: &str) -> bool {
+        self.pid(name).is_some()
+    }
+
+    /// 由外部健康检查（如 API 看门狗）报告健康状态
+    pub fn report_health(&self, app_handle: &AppHandle, name: &str, healthy: bool) {
+        let changed = {
+            let mut entries = self.entries.lock().unwrap();
+            let Some(entry) = entries.get_mut(name) else {
+                return;
+            };
+            if entry.child.is_none() {
+                return;
+            }
+            let status = if healthy {
+                SidecarStatus::Running
+            } else {
+                SidecarStatus::Unhealthy
+            };
+            let changed = entry.status != status;
+            entry.status = status;
+            changed
+        };
+        self.mark_healthy(name, healthy);
+        if changed {
+            self.emit_status(app_handle, name);
+        }
+    }
+
+    /// 启动指定的 sidecar，已在运行时先终止旧进程
+    pub fn start(&self, app_handle: &AppHandle, name: &str) -> Result<(), String> {
+        // 先递增 generation 再终止旧进程，使旧进程的退出事件不会触发自动重启
+        let (spec, generation, old_child) = {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = entries
+                .get_mut(name)
+                .ok_or_else(|| format!("未找到 sidecar: {}", name))?;
+            if entry.attached {
+                return Err(format!("sidecar '{}' 不由监管器启动", name));
+            }
+            entry.generation += 1;
+            (entry.spec.clone(), entry.generation, entry.child.take())
+        };
+        if let Some(child) = old_child {
+            Self::terminate_child(name, child);
+        }
+
+        let (mut rx, child) = app_handle
+            .shell()
+            .sidecar(&spec.sidecar)
+            .map_err(|e| format!("创建 sidecar '{}' 命令失败: {}", name, e))?
+            .args(&spec.args)
+            .spawn()
+            .map_err(|e| format!("启动 sidecar '{}' 失败: {}", name, e))?;
+        info!("[SIDECAR] {} 已启动, PID: {}", name, child.pid());
+
+        {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = entries
+                .get_mut(name)
+                .ok_or_else(|| format!("未找到 sidecar: {}", name))?;
+            entry.child = Some(child);
+            entry.status = SidecarStatus::Running;
+            entry.healthy_since = Some(Instant::now());
+        }
+        self.emit_status(app_handle, name);
+
+        // 监听进程输出和退出
+        let supervisor = self.clone();
+        let app_handle_for_events = app_handle.clone();
+        let name_for_events = name.to_string();
+        tauri::async_runtime::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                match event {
+                    CommandEvent::Stdout(line) => {
+                        info!(
+                            "[SIDECAR:{}] {}",
+                            name_for_events,
+                            String::from_utf8_lossy(&line).trim_end()
+                        );
+                    }
+                    CommandEvent::Stderr(line) => {
+                        warn!(
+                            "[SIDECAR:{}] {}",
+                            name_for_events,
+                            String::from_utf8_lossy(&line).trim_end()
+                        );
+                    }
+                    CommandEvent::Terminated(status) => {
+                        warn!(
+                            "[SIDECAR] {} 进程退出，状态码: {:?}",
+                            name_for_events, status.code
+                        );
+                        supervisor
+                            .handle_exit(&app_handle_for_events, &name_for_events, generation)
+                            .await;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        if spec.health_url.is_some() {
+            self.start_health_check(app_handle.clone(), name.to_string(), generation);
+        }
+        Ok(())
+    }
+
+    /// 进程退出后按配置自动重启
+    async fn handle_exit(&self, app_handle: &AppHandle, name: &str, generation: u64) {
+        let should_restart = {
+            let mut entries = self.entries.lock().unwrap();
+            let Some(entry) = entries.get_mut(name) else {
+                return;
+            };
+            // 主动停止或已被新进程替换时不处理
+            if entry.generation != generation || entry.status == SidecarStatus::Stopped {
+                return;
+            }
+            entry.child = None;
+            entry.reset_restart_count_if_stable();
+            entry.healthy_since = None;
+            if entry.spec.auto_restart && entry.restart_count < MAX_AUTO_RESTARTS {
+                entry.restart_count += 1;
+                true
+            } else {
+                entry.status = SidecarStatus::Failed;
+                false
+            }
+        };
+        self.emit_status(app_handle, name);
+
+        if should_restart {
+            tokio::time::sleep(RESTART_DELAY).await;
+            if let Err(e) = self.start(app_handle, name) {
+                error!("[SIDECAR] 重启 {} 失败: {}", name, e);
+                self.set_status(name, SidecarStatus::Failed);
+                self.emit_status(app_handle, name);
+            }
+        } else {
+            warn!("[SIDECAR] {} 已停止自动重启", name);
+        }
+    }
+
+    /// 独立的健康检查任务，进程被替换或停止后自动退出
+    fn start_health_check(&self, app_handle: AppHandle, name: String, generation: u64) {
+        let supervisor = self.clone();
+        tauri::async_runtime::spawn(async move {
+            // sidecar 的健康检查地址不是主 API，不能走 API 本地套接字
+            let client = crate::http_client::shared_tcp();
+            let mut consecutive_failures: u32 = 0;
+
+            loop {
+                tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+
+                let (health_url, auto_restart) = {
+                    let entries = supervisor.entries.lock().unwrap();
+                    match entries.get(&name) {
+                        Some(entry)
+                            if entry.generation == generation
+                                && entry.status != SidecarStatus::Stopped =>
+                        {
+                            (entry.spec.health_url.clone(), entry.spec.auto_restart)
+                        }
+                        _ => return,
+                    }
+                };
+                let Some(health_url) = health_url else {
+                    return;
+                };
+
+                let healthy = match client
+                    .get(&health_url)
+                    .timeout(Duration::from_secs(3))
+                    .send()
+                    .await
+                {
+                    Ok(response) => response.status().is_success(),
+                    Err(_) => false,
+                };
+                if healthy {
+                    if consecutive_failures > 0 {
+                        info!("[SIDECAR] {} 已恢复健康", name);
+                        supervisor.set_status(&name, SidecarStatus::Running);
+                        supervisor.emit_status(&app_handle, &name);
+                    }
+                    supervisor.mark_healthy(&name, true);
+                    consecutive_failures = 0;
+                    continue;
+                }
+
+                consecutive_failures += 1;
+                error!(
+                    "[SIDECAR] {} 健康检查失败 ({}/{})",
+                    name, consecutive_failures, MAX_CONSECUTIVE_HEALTH_FAILURES
+                );
+                if consecutive_failures == 1 {
+                    supervisor.mark_healthy(&name, false);
+                    supervisor.set_status(&name, SidecarStatus::Unhealthy);
+                    supervisor.emit_status(&app_handle, &name);
+                }
+                if consecutive_failures >= MAX_CONSECUTIVE_HEALTH_FAILURES && auto_restart {
+                    error!("[SIDECAR] {} 连续健康检查失败，重启进程", name);
+                    if let Err(e) = supervisor.start(&app_handle, &name) {
+                        error!("[SIDECAR] 重启 {} 失败: {}", name, e);
+                        supervisor.set_status(&name, SidecarStatus::Failed);
+                        supervisor.emit_status(&app_handle, &name);
+                    }
+                    // 新进程有自己的健康检查任务
+                    return;
+                }
+            }
+        });
+    }
+
+    /// 停止指定的 sidecar，不会触发自动重启
+    pub fn stop(&self, name: &str) -> Result<(), String> {
+        let child = {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = entries
+                .get_mut(name)
+                .ok_or_else(|| format!("未找到 sidecar: {}", name))?;
+            entry.status = SidecarStatus::Stopped;
+            entry.child.take()
+        };
+        if let Some(child) = child {
+            Self::terminate_child(name, child);
+        }
+        Ok(())
+    }
+
+    /// 终止 sidecar 的整个进程树：先请求退出，超时后强制终止
+    ///
+    /// 通过 uv 等启动器运行的 sidecar，只终止直接子进程会让实际的工作进程成为孤儿。
+    fn terminate_child(name: &str, child: CommandChild) {
+        let pid = child.pid();
+        // 先记下整个进程树：启动器退出后子进程会被过继，无法再按父进程查找
+        let pids = crate::process_tree::tree_pids(pid);
+        crate::process_tree::terminate(&pids, false);
+        if crate::process_tree::wait_for_exit_blocking(&pids, STOP_TIMEOUT) {
+            info!("[SIDECAR] 已终止 {}, PID: {}, 进程: {:?}", name, pid, pids);
+            return;
+        }
+        warn!(
+            "[SIDECAR] {} 未在 {:?} 内退出，强制终止, 进程: {:?}",
+            name, STOP_TIMEOUT, pids
+        );
+        crate::process_tree::terminate(&pids, true);
+        if let Err(e) = child.kill() {
+            error!("[SIDECAR] 终止 {} 失败: {}", name, e);
+        }
+    }
+
+    /// 停止所有由监管器启动的 sidecar，应用退出时调用；主 API 由 ApiProcessManager 先请求优雅关闭再终止
+    pub fn stop_all(&self) {
+        let names: Vec<String> = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, entry)| !entry.attached)
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in names {
+            let _ = self.stop(&name);
+        }
+    }
+
+    /// 重置重启计数并重新启动，用于手动恢复已失败的 sidecar
+    pub fn restart(&self, app_handle: &AppHandle, name: &str) -> Result<(), String> {
+        {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = entries
+                .get_mut(name)
+                .ok_or_else(|| format!("未找到 sidecar: {}", name))?;
+            entry.restart_count = 0;
+        }
+        self.start(app_handle, name)
+    }
+
+    /// 记录健康状态变化：恢复健康时开始计算持续健康运行时间，失败时重新计时
+    fn mark_healthy(&self, name: &str, healthy: bool) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(name) {
+            if !healthy {
+                entry.reset_restart_count_if_stable();
+                entry.healthy_since = None;
+            } else if entry.healthy_since.is_none() {
+                entry.healthy_since = Some(Instant::now());
+            }
+        }
+    }
+
+    fn set_status(&self, name: &str, status: SidecarStatus) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(name) {
+            entry.status = status;
+        }
+    }
+
+    fn status_json(entry: &SidecarEntry) -> serde_json::Value {
+        serde_json::json!({
+            "name": entry.spec.name,
+            "status": entry.status,
+            "pid": entry.child.as_ref().map(|child| child.pid()),
+            "restart_count": entry.restart_count,
+            "health_url": entry.spec.health_url,
+        })
+    }
+
+    fn emit_status(&self, app_handle: &AppHandle, name: &str) {
+        let payload = {
+            let entries = self.entries.lock().unwrap();
+            match entries.get(name) {
+                Some(entry) => Self::status_json(entry),
+                None => return,
+            }
+        };
+        let _ = app_handle.emit("sidecar-status", payload);
+    }
+
+    /// 所有 sidecar 的状态
+    pub fn statuses(&self) -> Vec<serde_json::Value> {
+        let entries = self.entries.lock().unwrap();
+        let mut statuses: Vec<serde_json::Value> =
+            entries.values().map(Self::status_json).collect();
+        statuses.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+        statuses
+    }
+}
+
+/// 从 settings.json 的 `sidecars` 数组读取配置，注册并启动所有 sidecar
+pub fn start_configured_sidecars(app_handle: &AppHandle) {
+    let specs: Vec<SidecarSpec> = match app_handle.store("settings.json") {
+        Ok(store) => match store.get("sidecars") {
+            Some(value) => match serde_json::from_value(value) {
+                Ok(specs) => specs,
+                Err(e) => {
+                    error!("[SIDECAR] 解析 sidecars 配置失败: {}", e);
+                    return;
+                }
+            },
+            None => return,
+        },
+        Err(e) => {
+            error!("[SIDECAR] 打开 settings.json 失败: {}", e);
+            return;
+        }
+    };
+
+    let supervisor = app_handle.state::<SidecarSupervisor>();
+    for spec in specs {
+        let name = spec.name.clone();
+        if let Err(e) = supervisor.register(spec) {
+            warn!("[SIDECAR] {}", e);
+            continue;
+        }
+        if let Err(e) = supervisor.start(app_handle, &name) {
+            warn!("[SIDECAR] {}", e);
+        }
+    }
+}
+
+/// 获取所有 sidecar 的运行状态
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_sidecar_status(
+    supervisor: tauri::State<'_, SidecarSupervisor>,
+) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "sidecars": supervisor.statuses()
+    }))
+}
+
+/// 手动重启指定的 sidecar
+#[tauri::command(rename_all = "snake_case")]
+pub fn restart_sidecar(
+    name: String,
+    app_handle: tauri::AppHandle,
+    supervisor: tauri::State<'_, SidecarSupervisor>,
+) -> Result<serde_json::Value, String> {
+    if name == API_NAME {
+        // 主 API 需要走完整的启动流程
+        let api_state = app_handle.state::<crate::ApiState>().0.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = crate::api_startup::restart_python_api(app_handle, api_state).await;
+        });
+        return Ok(serde_json::json!({
+            "status": "success",
+            "message": "API 正在重启"
+        }));
+    }
+    supervisor.restart(&app_handle, &name)?;
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("sidecar '{}' 已重启", name)
+    }))
+}
@@ -0,0 +1,109 @@
+//! # 系统睡眠与唤醒 (System Sleep & Wake)
+//!
+//! 笔记本合盖睡眠或被 App Nap 挂起后恢复时，积压的文件事件会一次性涌入。这里用心跳线程检测
+//! 挂起：心跳间隔内墙钟时间走过的时长远超预期，说明进程刚从睡眠/App Nap 中恢复。检测到后：
+//! 1. 暂停扫描循环、批处理和防抖的定时发送，积压的事件只在缓冲区中合并
+//! 2. 等待系统稳定（网络卷重连、Spotlight 恢复等）后恢复
+//! 3. 对所有监控目录做一次轻量的增量扫描，只处理睡眠开始后修改过的文件
+//!
+//! 恢复时发出 `system-resumed` 事件，包含估计的挂起时长。
+
+use log::{error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::AppState;
+
+/// 心跳间隔
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// 心跳超出预期多久视为发生过睡眠或挂起
+const SLEEP_GAP_THRESHOLD: Duration = Duration::from_secs(30);
+/// 唤醒后暂停多久再恢复处理
+const RESUME_SETTLE_DELAY: Duration = Duration::from_secs(10);
+/// 增量扫描的时间余量，覆盖睡眠前最后一次心跳之前的变动
+const DELTA_SCAN_MARGIN: Duration = Duration::from_secs(60);
+
+/// 刚从睡眠中恢复，暂停处理
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// 是否因刚从睡眠中恢复而暂停处理
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::SeqCst)
+}
+
+/// 扫描循环中调用，暂停期间等待恢复
+pub async fn wait_while_paused() {
+    while is_paused() {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+/// 对所有监控目录做增量扫描，只处理 `since` 之后修改过的文件
+async fn delta_rescan(app_handle: &AppHandle, since: SystemTime) {
+    let Some(app_state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+    let file_monitor = app_state.file_monitor.lock().unwrap().clone();
+    let Some(file_monitor) = file_monitor else {
+        info!("[SLEEP_WAKE] 文件监控未启动，跳过增量扫描");
+        return;
+    };
+    for dir in file_monitor.get_monitored_directories() {
+        if dir.is_blacklist {
+            continue;
+        }
+        if let Err(e) = file_monitor
+            .scan_directory_modified_since(&dir.path, Some(since), Some(app_handle))
+            .await
+        {
+            error!("[SLEEP_WAKE] 增量扫描 {} 失败: {}", dir.path, e);
+        }
+    }
+    info!("[SLEEP_WAKE] 唤醒后的增量扫描完成");
+}
+
+/// 检测到从睡眠中恢复：暂停处理，稍后恢复并增量扫描
+fn on_resumed(app_handle: &AppHandle, slept_since: SystemTime, slept_for: Duration) {
+    if PAUSED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    info!(
+        "[SLEEP_WAKE] 检测到系统睡眠或挂起约 {} 秒，暂停处理 {:?}",
+        slept_for.as_secs(),
+        RESUME_SETTLE_DELAY
+    );
+    let _ = app_handle.emit(
+        "system-resumed",
+        serde_json::json!({ "slept_secs": slept_for.as_secs() }),
+    );
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(RESUME_SETTLE_DELAY).await;
+        PAUSED.store(false, Ordering::SeqCst);
+        info!("[SLEEP_WAKE] 恢复处理，开始增量扫描");
+        let since = slept_since
+            .checked_sub(DELTA_SCAN_MARGIN)
+            .unwrap_or(slept_since);
+        delta_rescan(&app_handle, since).await;
+    });
+}
+
+/// 启动心跳线程检测睡眠和唤醒，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    std::thread::Builder::new()
+        .name("sleep-wake-heartbeat".to_string())
+        .spawn(move || loop {
+            let before = SystemTime::now();
+            std::thread::sleep(HEARTBEAT_INTERVAL);
+            // 睡眠期间线程不运行，但墙钟时间照常前进
+            let elapsed = SystemTime::now().duration_since(before).unwrap_or_default();
+            if elapsed > HEARTBEAT_INTERVAL + SLEEP_GAP_THRESHOLD {
+                on_resumed(&app_handle, before, elapsed);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| error!("[SLEEP_WAKE] 启动心跳线程失败: {}", e));
+}
@@ -0,0 +1,239 @@
+//! # 扫描元数据溢出队列 (Spill Queue)
+//!
+//! 扫描上百万文件的 NAS 共享时，扫描速度远快于批处理器发送到 API 的速度。元数据通道有界，
+//! 扫描在通道满时只能等待 API；如果放大通道，内存占用又会随文件数一起增长。
+//!
+//! 扫描改为通过 [`SpillingSender`] 发送：内存中最多保留通道容量（`METADATA_CHANNEL_CAPACITY`）条元数据，
+//! 通道已满时把元数据按 NDJSON 追加到临时目录下的溢出文件，后台任务按块读回后送入通道。
+//! 溢出文件全部读回后截断复用，扫描结束后删除。溢出文件超过 [`MAX_SPILL_BYTES`] 时退回为等待通道，
+//! 磁盘占用同样有上限。
+//!
+//! 溢出文件只在本次运行中使用：应用退出时尚未读回的元数据会被丢弃，下次启动的初始扫描会重新发现这些文件。
+
+use crate::file_monitor::{FileMetadata, FileMonitor};
+use log::{error, info, warn};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{error::TrySendError, Sender};
+use tokio::sync::Notify;
+
+/// 单个溢出文件的大小上限
+pub const MAX_SPILL_BYTES: u64 = 1024 * 1024 * 1024;
+/// 每次从溢出文件读回的记录数
+const READ_CHUNK: usize = 256;
+
+static NEXT_SPILL_ID: AtomicU64 = AtomicU64::new(0);
+
+// 溢出文件：写入端追加，读取端按顺序读回，两端使用各自打开的句柄（不共享文件偏移）
+struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    reader: BufReader<File>,
+    // 已写入的字节数
+    bytes: u64,
+    // 已写入、尚未读回的记录数
+    pending: usize,
+    // 累计溢出的记录数
+    total: u64,
+}
+
+impl SpillFile {
+    fn create() -> std::io::Result<SpillFile> {
+        let path = std::env::temp_dir().join(format!(
+            "knowledge-focus-spill-{}-{}.ndjson",
+            std::process::id(),
+            NEXT_SPILL_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let writer = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        let reader = File::open(&path)?;
+        Ok(SpillFile {
+            path,
+            writer: BufWriter::new(writer),
+            reader: BufReader::new(reader),
+            bytes: 0,
+            pending: 0,
+            total: 0,
+        })
+    }
+
+    // 追加一条记录，超过大小上限时返回 false
+    fn push(&mut self, metadata: &FileMetadata) -> std::io::Result<bool> {
+        if self.bytes >= MAX_SPILL_BYTES {
+            return Ok(false);
+        }
+        let mut line = serde_json::to_vec(metadata)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        self.bytes += line.len() as u64;
+        self.pending += 1;
+        self.total += 1;
+        Ok(true)
+    }
+
+    // 读回最多 READ_CHUNK 条记录，全部读回后截断文件；返回读回的记录和剩余记录数
+    fn read_chunk(&mut self) -> std::io::Result<(Vec<FileMetadata>, usize)> {
+        if self.pending == 0 {
+            return Ok((Vec::new(), 0));
+        }
+        // 只读取已完整写入的行
+        self.writer.flush()?;
+        let count = self.pending.min(READ_CHUNK);
+        let mut records = Vec::with_capacity(count);
+        let mut line = String::new();
+        for _ in 0..count {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            match serde_json::from_str(&line) {
+                Ok(metadata) => records.push(metadata),
+                Err(e) => warn!("[SPILL] 跳过无法解析的溢出记录: {}", e),
+            }
+        }
+        self.pending -= count;
+        if self.pending == 0 {
+            self.writer.get_ref().set_len(0)?;
+            self.writer.seek(SeekFrom::Start(0))?;
+            self.reader.seek(SeekFrom::Start(0))?;
+            self.bytes = 0;
+        }
+        Ok((records, self.pending))
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// 扫描使用的元数据发送端：通道已满时溢出到磁盘，而不是等待批处理器
+pub struct SpillingSender {
+    tx: Sender<FileMetadata>,
+    // 第一次溢出时创建
+    spill: Arc<Mutex<Option<SpillFile>>>,
+    wake: Arc<Notify>,
+    closed: Arc<AtomicBool>,
+    feeder: Option<tokio::task::JoinHandle<()>>,
+    // 溢出文件无法创建或写入时不再尝试，始终等待通道
+    disabled: bool,
+}
+
+impl SpillingSender {
+    pub fn new(tx: Sender<FileMetadata>) -> SpillingSender {
+        SpillingSender {
+            tx,
+            spill: Arc::new(Mutex::new(None)),
+            wake: Arc::new(Notify::new()),
+            closed: Arc::new(AtomicBool::new(false)),
+            feeder: None,
+            disabled: false,
+        }
+    }
+
+    /// 发送元数据；通道已关闭时返回 false
+    pub async fn send(&mut self, monitor: &FileMonitor, metadata: FileMetadata) -> bool {
+        let metadata = match self.tx.try_send(metadata) {
+            Ok(()) => return true,
+            Err(TrySendError::Closed(_)) => return false,
+            Err(TrySendError::Full(metadata)) => metadata,
+        };
+        if !self.disabled {
+            match self.spill(&metadata) {
+                Ok(true) => {
+                    monitor.update_stats(|stats| stats.spilled_records += 1);
+                    return true;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("[SPILL] 写入溢出文件失败，改为等待批处理器: {}", e);
+                    self.disabled = true;
+                }
+            }
+        }
+        monitor.send_with_backpressure(&self.tx, metadata).await
+    }
+
+    fn spill(&mut self, metadata: &FileMetadata) -> std::io::Result<bool> {
+        if self.feeder.is_none() {
+            *self.spill.lock().unwrap() = Some(SpillFile::create()?);
+            info!("[SPILL] 元数据通道已满，开始将元数据溢出到磁盘");
+            self.feeder = Some(tokio::spawn(feed(
+                self.tx.clone(),
+                self.spill.clone(),
+                self.wake.clone(),
+                self.closed.clone(),
+            )));
+        }
+        let pushed = match self.spill.lock().unwrap().as_mut() {
+            Some(file) => file.push(metadata)?,
+            None => false,
+        };
+        if pushed {
+            self.wake.notify_one();
+        }
+        Ok(pushed)
+    }
+
+    /// 扫描结束：等待溢出的元数据全部送入通道，然后删除溢出文件
+    pub async fn finish(mut self) {
+        self.close();
+        if let Some(feeder) = self.feeder.take() {
+            let _ = feeder.await;
+        }
+        if let Some(file) = self.spill.lock().unwrap().take() {
+            info!("[SPILL] 扫描结束，共溢出 {} 条元数据到磁盘", file.total);
+        }
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+}
+
+impl Drop for SpillingSender {
+    // 未调用 finish 时后台任务仍会把剩余元数据送完再退出
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// 后台任务：把溢出文件中的元数据按块读回并送入通道（通道满时等待）
+async fn feed(
+    tx: Sender<FileMetadata>,
+    spill: Arc<Mutex<Option<SpillFile>>>,
+    wake: Arc<Notify>,
+    closed: Arc<AtomicBool>,
+) {
+    loop {
+        let chunk = spill.lock().unwrap().as_mut().map(|file| file.read_chunk());
+        let (records, remaining) = match chunk {
+            Some(Ok(chunk)) => chunk,
+            Some(Err(e)) => {
+                error!("[SPILL] 读取溢出文件失败，剩余元数据被丢弃: {}", e);
+                return;
+            }
+            None => return,
+        };
+        if records.is_empty() && remaining == 0 {
+            if closed.load(Ordering::SeqCst) {
+                return;
+            }
+            wake.notified().await;
+            continue;
+        }
+        for metadata in records {
+            if tx.send(metadata).await.is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! # 启动阶段报告 (Startup Stage Reporting)
+//!
+//! 首次启动耗时较长，单一的 `api-ready` 信号无法让启动页说明当前在做什么。
+//! 启动过程按阶段推进：uv sync → API 启动 → 配置获取 → 初始扫描 → 就绪，
+//! 每次阶段状态变化都通过 `startup-stage` 事件通知前端，并记录在应用状态中，
+//! 启动页挂载较晚时可通过 `get_startup_stages` 命令补齐错过的事件。
+//! 每个阶段只保留最新的状态，API 重启、重新扫描时再次报告的阶段会覆盖旧记录。
+
+use log::{error, info};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 启动阶段，按推进顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupStage {
+    /// 同步 Python 虚拟环境
+    UvSync,
+    /// 启动 Python API 并等待健康检查通过
+    ApiBoot,
+    /// 获取文件扫描配置
+    ConfigFetch,
+    /// 首次全量扫描
+    InitialScan,
+    /// 启动完成
+    Ready,
+}
+
+/// 阶段状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    Started,
+    Completed,
+    /// 阶段未成功但启动以降级方式继续（例如依赖同步失败后使用已有虚拟环境离线运行）
+    Degraded,
+    Failed,
+    Skipped,
+}
+
+/// 一次阶段状态变化
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupStageEvent {
+    pub stage: StartupStage,
+    pub status: StageStatus,
+    pub message: Option<String>,
+    pub timestamp: String,
+}
+
+/// 各阶段最新的状态
+#[derive(Default)]
+pub struct StartupStageState(Mutex<BTreeMap<StartupStage, StartupStageEvent>>);
+
+/// 报告阶段状态变化，发出 `startup-stage` 事件
+pub fn report_stage(
+    app_handle: &AppHandle,
+    stage: StartupStage,
+    status: StageStatus,
+    message: Option<String>,
+) {
+    let event = StartupStageEvent {
+        stage,
+        status,
+        message,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    info!(
+        "[STARTUP] 阶段 {:?} -> {:?}{}",
+        event.stage,
+        event.status,
+        event
+            .message
+            .as_deref()
+            .map(|m| format!(": {}", m))
+            .unwrap_or_default()
+    );
+
+    if let Some(state) = app_handle.try_state::<StartupStageState>() {
+        state.0.lock().unwrap().insert(event.stage, event.clone());
+    }
+    if let Err(e) = app_handle.emit("startup-stage", &event) {
+        error!("[STARTUP] 发送启动阶段事件失败: {}", e);
+    }
+}
+
+/// 获取各阶段最新的状态，按阶段顺序排列
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_startup_stages(
+    state: tauri::State<'_, StartupStageState>,
+) -> Result<Vec<StartupStageEvent>, String> {
+    Ok(state.0.lock().unwrap().values().cloned().collect())
+}
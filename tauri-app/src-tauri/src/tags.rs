@@ -0,0 +1,47 @@
+//! # 层级标牌 (Hierarchical Tags)
+//!
+//! 标牌可以用 `/` 表示层级（如 `work/clients/acme`），前端按层级展示为标牌树。
+//! 添加标牌时统一规范化和去重：
+//! - `\` 视为分隔符，每一级去掉首尾空白、合并连续空白并转为 NFC，空的层级被丢弃
+//! - 不区分大小写地去重，已有子标牌（`work/clients/acme`）时不再添加祖先标牌（`work/clients`），
+//!   添加子标牌时移除已有的祖先标牌
+
+/// 标牌层级分隔符
+pub const TAG_SEPARATOR: char = '/';
+
+/// 规范化标牌，规范化后为空时返回 None
+pub fn normalize(tag: &str) -> Option<String> {
+    let segments: Vec<String> = tag
+        .split(['/', '\\'])
+        .map(|segment| {
+            crate::path_utils::to_nfc(&segment.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    if segments.is_empty() {
+        None
+    } else {
+        Some(segments.join(&TAG_SEPARATOR.to_string()))
+    }
+}
+
+/// `ancestor` 是否是 `tag` 本身或它的祖先标牌（不区分大小写）
+fn covers(ancestor: &str, tag: &str) -> bool {
+    let ancestor = ancestor.to_lowercase();
+    let tag = tag.to_lowercase();
+    tag == ancestor
+        || (tag.starts_with(&ancestor) && tag[ancestor.len()..].starts_with(TAG_SEPARATOR))
+}
+
+/// 规范化并添加标牌，按层级去重
+pub fn add(labels: &mut Option<Vec<String>>, tag: &str) {
+    let Some(tag) = normalize(tag) else {
+        return;
+    };
+    let labels = labels.get_or_insert_with(Vec::new);
+    if labels.iter().any(|existing| covers(&tag, existing)) {
+        return;
+    }
+    labels.retain(|existing| !covers(existing, &tag));
+    labels.push(tag);
+}
@@ -0,0 +1,399 @@
+//! # 文本提取 (Text Extraction)
+//!
+//! 粗筛结果发送成功后，纯文本、Markdown、源代码和带文本层的 PDF 在 Rust 端提取出规范化文本，
+//! 按块通过 `/file-content/batch` 发送到 API，供全文检索等功能使用，Python 端不再为这些简单格式解析文件。
+//!
+//! 规范化：转为 NFC，去掉 BOM 和控制字符，统一换行，去掉行尾空白，连续空行合并为一行；
+//! Markdown 另外去掉开头的 YAML front matter。单个文件最多读取 [`MAX_FILE_BYTES`]
+//! （PDF 为 [`MAX_PDF_BYTES`]），文本最多保留 [`MAX_TEXT_CHARS`] 个字符，超出时标记为截断。
+//!
+//! 提取在阻塞线程上逐个执行，有自己的并发上限，较慢的 PDF 解析和 OCR 不占用哈希的并发名额。待提取队列有界，队列已满时丢弃，
+//! 文件下次变化或重新扫描时再提取。开关保存在 settings.json 的 `text_extraction` 中，默认开启。
+//!
+//! 图片按 [`crate::ocr`] 的识别模式识别文字，以 `image` 类型一并发送。
+
+use crate::local_index::IndexedFile;
+use log::{error, info};
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::{mpsc, Semaphore};
+use unicode_normalization::UnicodeNormalization;
+
+/// settings.json 中保存开关的键
+const ENABLED_STORE_KEY: &str = "text_extraction";
+/// 文本类文件最多读取的字节数
+pub const MAX_FILE_BYTES: u64 = 8 * 1024 * 1024;
+/// 超过此大小的 PDF 不提取
+pub const MAX_PDF_BYTES: u64 = 64 * 1024 * 1024;
+/// 每个文件最多保留的字符数
+pub const MAX_TEXT_CHARS: usize = 1_000_000;
+/// 待提取队列容量
+const QUEUE_CAPACITY: usize = 10_000;
+/// 每次发送到 API 的文件数和文本字节数上限
+const BATCH_FILES: usize = 20;
+const BATCH_BYTES: usize = 2 * 1024 * 1024;
+/// 队列空闲这么久后发送已提取的文本
+const FLUSH_IDLE: Duration = Duration::from_secs(2);
+/// 同时进行的提取数上限
+const MAX_CONCURRENCY: usize = 1;
+
+/// 是否提取文本，默认开启
+static ENABLED: AtomicBool = AtomicBool::new(true);
+static QUEUE: OnceLock<mpsc::Sender<Job>> = OnceLock::new();
+static PERMITS: Semaphore = Semaphore::const_new(MAX_CONCURRENCY);
+/// 已发送到 API 的文件数
+static EXTRACTED: AtomicU64 = AtomicU64::new(0);
+/// 队列已满时丢弃的文件数
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+
+/// 可提取文本的文件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContentKind {
+    Text,
+    Markdown,
+    Code,
+    Pdf,
+    Image,
+}
+
+impl ContentKind {
+    // 按扩展名（小写，不带点）判断，不读取文件
+    fn of(extension: &str) -> Option<ContentKind> {
+        match extension {
+            "txt" | "text" => Some(ContentKind::Text),
+            "md" | "markdown" | "mdx" => Some(ContentKind::Markdown),
+            "pdf" => Some(ContentKind::Pdf),
+            _ if crate::ocr::is_image_extension(extension) => Some(ContentKind::Image),
+            _ if crate::code_language::language_for_extension(extension).is_some() => {
+                Some(ContentKind::Code)
+            }
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ContentKind::Text => "text",
+            ContentKind::Markdown => "markdown",
+            ContentKind::Code => "code",
+            ContentKind::Pdf => "pdf",
+            ContentKind::Image => "image",
+        }
+    }
+
+    fn max_bytes(self) -> u64 {
+        match self {
+            ContentKind::Pdf => MAX_PDF_BYTES,
+            ContentKind::Image => crate::ocr::MAX_IMAGE_BYTES,
+            _ => MAX_FILE_BYTES,
+        }
+    }
+}
+
+struct Job {
+    file_path: String,
+    modified_time: u64,
+    kind: ContentKind,
+}
+
+/// 提取结果
+#[derive(Debug, serde::Serialize)]
+struct ExtractedText {
+    file_path: String,
+    modified_time: u64,
+    content_kind: &'static str,
+    text: String,
+    char_count: usize,
+    truncated: bool,
+}
+
+/// 当前是否提取文本
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// 恢复保存的开关并启动后台任务，应在启动时调用
+pub fn init(app_handle: &AppHandle) {
+    let saved = app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(ENABLED_STORE_KEY))
+        .and_then(|value| value.as_bool());
+    if let Some(enabled) = saved {
+        ENABLED.store(enabled, Ordering::SeqCst);
+    }
+    let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+    if QUEUE.set(tx).is_ok() {
+        tauri::async_runtime::spawn(run(app_handle.clone(), rx));
+    }
+}
+
+/// 已成功发送到 API 的文件中，可提取文本的加入待提取队列
+pub fn enqueue(files: &[IndexedFile]) {
+    if !enabled() {
+        return;
+    }
+    let Some(queue) = QUEUE.get() else {
+        return;
+    };
+    for file in files {
+        let Some(kind) = file.extension.as_deref().and_then(ContentKind::of) else {
+            continue;
+        };
+        if file.file_size == 0 || file.file_size > kind.max_bytes() {
+            continue;
+        }
+        if kind == ContentKind::Image && !crate::ocr::should_recognize(Path::new(&file.file_path)) {
+            continue;
+        }
+        let job = Job {
+            file_path: file.file_path.clone(),
+            modified_time: file.modified_time,
+            kind,
+        };
+        if queue.try_send(job).is_err() {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+// 后台任务：逐个提取文本，攒够一块或队列空闲时发送到 API
+async fn run(app_handle: AppHandle, mut rx: mpsc::Receiver<Job>) {
+    let mut batch: Vec<ExtractedText> = Vec::new();
+    let mut batch_bytes = 0usize;
+    loop {
+        let job = if batch.is_empty() {
+            rx.recv().await
+        } else {
+            match tokio::time::timeout(FLUSH_IDLE, rx.recv()).await {
+                Ok(job) => job,
+                Err(_) => {
+                    send_batch(&app_handle, std::mem::take(&mut batch)).await;
+                    batch_bytes = 0;
+                    continue;
+                }
+            }
+        };
+        let Some(job) = job else {
+            break;
+        };
+
+        crate::sleep_wake::wait_while_paused().await;
+        let Some(Some(extracted)) = run_blocking(move || extract(job)).await else {
+            continue;
+        };
+        batch_bytes += extracted.text.len();
+        batch.push(extracted);
+        if batch.len() >= BATCH_FILES || batch_bytes >= BATCH_BYTES {
+            send_batch(&app_handle, std::mem::take(&mut batch)).await;
+            batch_bytes = 0;
+        }
+    }
+    if !batch.is_empty() {
+        send_batch(&app_handle, batch).await;
+    }
+}
+
+// 在阻塞线程上执行提取，受提取自己的并发上限限制；PDF 解析库可能 panic，此时返回 None（不写崩溃报告）
+async fn run_blocking<T, F>(task: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let _permit = PERMITS.acquire().await.ok()?;
+    tokio::task::spawn_blocking(move || crate::crash_report::recoverable(task))
+        .await
+        .ok()
+}
+
+// 发送提取的文本，失败时丢弃（文件下次变化时重新提取）
+async fn send_batch(app_handle: &AppHandle, batch: Vec<ExtractedText>) {
+    let count = batch.len();
+    match post_contents(app_handle, &batch).await {
+        Ok(()) => {
+            EXTRACTED.fetch_add(count as u64, Ordering::Relaxed);
+            info!("[TEXT_EXTRACTION] 已发送 {} 个文件的文本", count);
+            crate::local_embedding::enqueue(
+                batch
+                    .into_iter()
+                    .map(|extracted| (extracted.file_path, extracted.text))
+                    .collect(),
+            );
+        }
+        Err(e) => error!("[TEXT_EXTRACTION] 发送 {} 个文件的文本失败: {}", count, e),
+    }
+}
+
+async fn post_contents(app_handle: &AppHandle, batch: &[ExtractedText]) -> Result<(), String> {
+    let (api_host, api_port) = {
+        let api_state = app_handle.state::<crate::ApiState>();
+        let api_state_guard = api_state.0.lock().unwrap();
+        (api_state_guard.host.clone(), api_state_guard.port)
+    };
+    let url = format!("http://{}:{}/file-content/batch", api_host, api_port);
+    let response = crate::http_client::shared()
+        .post(&url)
+        .timeout(Duration::from_secs(60))
+        .json(&serde_json::json!({ "items": batch }))
+        .send()
+        .await
+        .map_err(|e| format!("请求失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("API返回错误状态: {}", response.status()));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("解析响应失败: {}", e))?;
+    if body["success"].as_bool() != Some(true) {
+        return Err(format!("API未能保存文本: {}", body["message"]));
+    }
+    Ok(())
+}
+
+// 提取单个文件的文本（阻塞）；文件无法读取、像二进制文件或没有文本时返回 None
+fn extract(job: Job) -> Option<ExtractedText> {
+    let path = Path::new(&job.file_path);
+    let raw = match job.kind {
+        ContentKind::Pdf => match pdf_extract::extract_text(path) {
+            Ok(text) => text,
+            Err(e) => {
+                info!("[TEXT_EXTRACTION] PDF 没有可提取的文本层 {:?}: {}", path, e);
+                return None;
+            }
+        },
+        ContentKind::Image => match crate::ocr::recognize_text(path) {
+            Ok(text) => text,
+            Err(e) => {
+                info!("[TEXT_EXTRACTION] 图片文字识别失败 {:?}: {}", path, e);
+                return None;
+            }
+        },
+        _ => {
+            let file = std::fs::File::open(path).ok()?;
+            let mut buffer = Vec::new();
+            file.take(MAX_FILE_BYTES).read_to_end(&mut buffer).ok()?;
+            // 含 NUL 字节的文件按二进制文件处理
+            if buffer.contains(&0) {
+                return None;
+            }
+            String::from_utf8_lossy(&buffer).into_owned()
+        }
+    };
+
+    let raw = if job.kind == ContentKind::Markdown {
+        strip_front_matter(&raw)
+    } else {
+        &raw
+    };
+    let (text, truncated) = normalize(raw);
+    if text.is_empty() {
+        return None;
+    }
+    Some(ExtractedText {
+        file_path: job.file_path,
+        modified_time: job.modified_time,
+        content_kind: job.kind.name(),
+        char_count: text.chars().count(),
+        text,
+        truncated,
+    })
+}
+
+// 去掉 Markdown 开头 `---` 包围的 YAML front matter
+fn strip_front_matter(text: &str) -> &str {
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return text;
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return &rest[offset..];
+        }
+    }
+    text
+}
+
+// 规范化文本，返回文本和是否被截断
+fn normalize(raw: &str) -> (String, bool) {
+    let mut text = String::with_capacity(raw.len().min(MAX_TEXT_CHARS));
+    let mut chars = 0usize;
+    let mut blank_lines = 0usize;
+    let mut truncated = false;
+    for line in raw.trim_start_matches('\u{feff}').lines() {
+        let line: String = line
+            .nfc()
+            .filter(|c| !c.is_control() || *c == '\t')
+            .collect();
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        // 连续空行合并为一行，开头的空行直接去掉
+        if !text.is_empty() {
+            text.push('\n');
+            if blank_lines > 0 {
+                text.push('\n');
+            }
+        }
+        blank_lines = 0;
+
+        let remaining = MAX_TEXT_CHARS.saturating_sub(chars);
+        let line_chars = line.chars().count();
+        if line_chars > remaining {
+            text.extend(line.chars().take(remaining));
+            truncated = true;
+            break;
+        }
+        text.push_str(line);
+        chars += line_chars + 1;
+    }
+    (text, truncated)
+}
+
+/// 开启或关闭文本提取
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_text_extraction(
+    enabled: bool,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    ENABLED.store(enabled, Ordering::SeqCst);
+
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(ENABLED_STORE_KEY, serde_json::json!(enabled));
+    store
+        .save()
+        .map_err(|e| format!("保存文本提取设置失败: {}", e))?;
+
+    info!(
+        "[TEXT_EXTRACTION] 文本提取已{}",
+        if enabled { "开启" } else { "关闭" }
+    );
+    Ok(serde_json::json!({
+        "status": "success",
+        "enabled": enabled
+    }))
+}
+
+/// 获取文本提取开关和统计
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_text_extraction() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "enabled": enabled(),
+        "extracted_files": EXTRACTED.load(Ordering::Relaxed),
+        "dropped_files": DROPPED.load(Ordering::Relaxed)
+    }))
+}
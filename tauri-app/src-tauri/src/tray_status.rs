@@ -0,0 +1,246 @@
+//! # 托盘菜单状态 (Tray Status Items)
+//!
+//! 主窗口隐藏时用户无法看到应用在做什么。托盘菜单顶部放置几个禁用的菜单项，
+//! 显示当前扫描状态以及 `MonitorStats` 中已处理、已过滤的文件数，并定期刷新。
+//!
+//! 托盘图标右下角的状态角标同样反映当前状态（空闲 / 扫描中 / 已暂停 / 出错 / API不可用）：
+//! API看门狗的健康检查结果和 `file-monitor-error`、`scan_error` 等监控错误通过
+//! `set_api_down` / `set_monitor_error` 报告到这里，窗口隐藏时问题也能被看到。
+
+use log::{error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::image::Image;
+use tauri::menu::MenuItem;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::file_monitor::MonitorStats;
+use crate::AppState;
+
+/// 刷新间隔
+const REFRESH_INTERVAL: Duration = Duration::from_secs(3);
+/// 托盘基础图标，与前端 main.tsx 中设置的图标相同
+const BASE_ICON_BYTES: &[u8] = include_bytes!("../../mac-tray-icon.png");
+
+static API_DOWN: AtomicBool = AtomicBool::new(false);
+static MONITOR_ERROR: AtomicBool = AtomicBool::new(false);
+/// 当前托盘图标上显示的角标，状态不变时不重复设置图标
+static CURRENT_BADGE: Mutex<Option<TrayBadge>> = Mutex::new(None);
+static BASE_ICON: OnceLock<Option<Image<'static>>> = OnceLock::new();
+
+/// 文件监控的扫描状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    Starting,
+    WaitingToScan,
+    Scanning,
+    Watching,
+    Paused,
+}
+
+impl ScanState {
+    fn label(self) -> &'static str {
+        match self {
+            ScanState::Starting => "Starting…",
+            ScanState::WaitingToScan => "Waiting to scan",
+            ScanState::Scanning => "Scanning",
+            ScanState::Watching => "Watching",
+            ScanState::Paused => "Paused",
+        }
+    }
+}
+
+/// 托盘图标角标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrayBadge {
+    Idle,
+    Scanning,
+    Paused,
+    Error,
+    ApiDown,
+}
+
+impl TrayBadge {
+    /// 角标颜色，空闲时不显示角标
+    fn color(self) -> Option<[u8; 3]> {
+        match self {
+            TrayBadge::Idle => None,
+            TrayBadge::Scanning => Some([0x3b, 0x82, 0xf6]),
+            TrayBadge::Paused => Some([0x9c, 0xa3, 0xaf]),
+            TrayBadge::Error => Some([0xf5, 0x9e, 0x0b]),
+            TrayBadge::ApiDown => Some([0xef, 0x44, 0x44]),
+        }
+    }
+
+    fn tooltip(self) -> &'static str {
+        match self {
+            TrayBadge::Idle => "KnowledgeFocus",
+            TrayBadge::Scanning => "KnowledgeFocus (scanning)",
+            TrayBadge::Paused => "KnowledgeFocus (monitoring paused)",
+            TrayBadge::Error => "KnowledgeFocus (file monitor error)",
+            TrayBadge::ApiDown => "KnowledgeFocus (API unavailable)",
+        }
+    }
+}
+
+/// 读取当前扫描状态和监控统计
+fn current_state(app_handle: &AppHandle) -> (ScanState, MonitorStats) {
+    let app_state = app_handle.state::<AppState>();
+    let file_monitor = app_state.file_monitor.lock().unwrap().clone();
+
+    let scan_state = if app_state.is_monitoring_paused() {
+        ScanState::Paused
+    } else {
+        match &file_monitor {
+            None => ScanState::Starting,
+            Some(monitor) if !monitor.has_initial_scan_started() => ScanState::WaitingToScan,
+            Some(_) if !app_state.is_initial_scan_completed() => ScanState::Scanning,
+            Some(_) => ScanState::Watching,
+        }
+    };
+    let stats = file_monitor
+        .map(|monitor| monitor.get_stats())
+        .unwrap_or_default();
+    (scan_state, stats)
+}
+
+/// 按优先级选择角标：API不可用 > 监控错误 > 暂停 > 扫描中 > 空闲
+fn resolve_badge(scan_state: ScanState) -> TrayBadge {
+    if API_DOWN.load(Ordering::SeqCst) {
+        TrayBadge::ApiDown
+    } else if MONITOR_ERROR.load(Ordering::SeqCst) {
+        TrayBadge::Error
+    } else {
+        match scan_state {
+            ScanState::Paused => TrayBadge::Paused,
+            ScanState::Scanning => TrayBadge::Scanning,
+            _ => TrayBadge::Idle,
+        }
+    }
+}
+
+/// 在基础图标右下角绘制带白边的圆点
+fn badge_icon(color: Option<[u8; 3]>) -> Option<Image<'static>> {
+    let base = BASE_ICON
+        .get_or_init(|| match Image::from_bytes(BASE_ICON_BYTES) {
+            Ok(image) => Some(image.to_owned()),
+            Err(e) => {
+                error!("[TRAY] 解码托盘图标失败: {}", e);
+                None
+            }
+        })
+        .as_ref()?;
+    let Some([r, g, b]) = color else {
+        return Some(base.clone());
+    };
+
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    let radius = width.min(height) as f32 * 0.22;
+    let border = radius * 0.25;
+    let center_x = width as f32 - radius - border;
+    let center_y = height as f32 - radius - border;
+    for y in 0..height {
+        for x in 0..width {
+            let distance =
+                ((x as f32 + 0.5 - center_x).powi(2) + (y as f32 + 0.5 - center_y).powi(2)).sqrt();
+            let pixel = if distance <= radius {
+                [r, g, b, 0xff]
+            } else if distance <= radius + border {
+                [0xff, 0xff, 0xff, 0xff]
+            } else {
+                continue;
+            };
+            let offset = ((y * width + x) * 4) as usize;
+            rgba[offset..offset + 4].copy_from_slice(&pixel);
+        }
+    }
+    Some(Image::new_owned(rgba, width, height))
+}
+
+/// 按当前状态更新托盘图标角标，状态未变化时不做任何操作
+pub fn update_badge(app_handle: &AppHandle) {
+    let (scan_state, _) = current_state(app_handle);
+    apply_badge(app_handle, resolve_badge(scan_state));
+}
+
+fn apply_badge(app_handle: &AppHandle, badge: TrayBadge) {
+    // 设置图标会切换到主线程执行，不能持有锁，否则与主线程上的菜单事件互相等待
+    if *CURRENT_BADGE.lock().unwrap() == Some(badge) {
+        return;
+    }
+    // 托盘尚未创建时不记录，下次刷新时重试
+    let Some(tray) = app_handle.tray_by_id(crate::TRAY_ICON_ID) else {
+        return;
+    };
+    if let Some(icon) = badge_icon(badge.color()) {
+        if let Err(e) = tray.set_icon(Some(icon)) {
+            error!("[TRAY] 更新托盘图标失败: {}", e);
+            return;
+        }
+    }
+    let _ = tray.set_tooltip(Some(badge.tooltip()));
+    info!("[TRAY] 托盘状态角标: {:?}", badge);
+    *CURRENT_BADGE.lock().unwrap() = Some(badge);
+}
+
+/// 报告API健康状态（看门狗健康检查、API启动失败）
+pub fn set_api_down(app_handle: &AppHandle, down: bool) {
+    API_DOWN.store(down, Ordering::SeqCst);
+    update_badge(app_handle);
+}
+
+/// 报告文件监控错误，扫描成功完成后清除
+pub fn set_monitor_error(app_handle: &AppHandle, error: bool) {
+    MONITOR_ERROR.store(error, Ordering::SeqCst);
+    update_badge(app_handle);
+}
+
+/// 托盘菜单中的状态项（只用于展示，不可点击）
+#[derive(Clone)]
+pub struct TrayStatusItems {
+    pub scan_state: MenuItem<Wry>,
+    pub processed: MenuItem<Wry>,
+    pub filtered: MenuItem<Wry>,
+}
+
+impl TrayStatusItems {
+    pub fn new(app_handle: &AppHandle) -> tauri::Result<Self> {
+        let item =
+            |id: &str, text: &str| MenuItem::with_id(app_handle, id, text, false, None::<&str>);
+        Ok(Self {
+            scan_state: item("status_scan_state", "Status: Starting…")?,
+            processed: item("status_processed", "Processed: 0")?,
+            filtered: item("status_filtered", "Filtered: 0")?,
+        })
+    }
+
+    /// 按当前监控状态刷新菜单项文字和托盘角标
+    pub fn refresh(&self, app_handle: &AppHandle) {
+        let (scan_state, stats) = current_state(app_handle);
+
+        let _ = self
+            .scan_state
+            .set_text(format!("Status: {}", scan_state.label()));
+        let _ = self
+            .processed
+            .set_text(format!("Processed: {}", stats.processed_files));
+        let _ = self.filtered.set_text(format!(
+            "Filtered: {} (bundles: {})",
+            stats.filtered_files, stats.filtered_bundles
+        ));
+        apply_badge(app_handle, resolve_badge(scan_state));
+    }
+}
+
+/// 启动后台任务定期刷新托盘状态项
+pub fn start_refresh(app_handle: AppHandle, items: TrayStatusItems) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            ticker.tick().await;
+            items.refresh(&app_handle);
+        }
+    });
+}
@@ -0,0 +1,212 @@
+//! # 更新通道与下载策略 (Update Channel & Download Policy)
+//!
+//! 在 updater 插件之上增加两项设置，保存在 settings.json 中：
+//! - 更新通道 `stable` / `beta`：检查更新时按通道构造 latest.json 的地址，
+//!   代替 tauri.conf.json 中固定的 endpoints
+//! - 下载策略 `auto` / `ask` / `never`：定期检查发现更新时，`auto` 在后台静默下载安装，
+//!   `ask` 只通知前端由用户决定，`never` 不做定期检查（手动检查不受影响）
+//!
+//! 下载进度通过 `update-download-progress` 事件发出，安装完成后发出 `update-ready`，重启后生效。
+
+use log::{error, info};
+use tauri::{AppHandle, Emitter, Url};
+use tauri_plugin_store::StoreExt;
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+/// settings.json 中保存更新通道的键
+const CHANNEL_STORE_KEY: &str = "update_channel";
+/// settings.json 中保存下载策略的键
+const POLICY_STORE_KEY: &str = "update_download_policy";
+
+const STABLE_ENDPOINT: &str =
+    "https://github.com/huozhong-in/knowledge-focus/releases/latest/download/latest.json";
+/// beta 版本发布在固定的 beta 标签下（GitHub 的 latest 不包含预发布版本）
+const BETA_ENDPOINT: &str =
+    "https://github.com/huozhong-in/knowledge-focus/releases/download/beta/latest.json";
+
+const CHANNELS: &[&str] = &["stable", "beta"];
+const POLICIES: &[&str] = &["auto", "ask", "never"];
+
+fn read_setting(app_handle: &AppHandle, key: &str, allowed: &[&str], default: &str) -> String {
+    app_handle
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(key))
+        .and_then(|value| value.as_str().map(str::to_string))
+        .filter(|value| allowed.contains(&value.as_str()))
+        .unwrap_or_else(|| default.to_string())
+}
+
+fn write_setting(app_handle: &AppHandle, key: &str, value: &str) -> Result<(), String> {
+    let store = app_handle
+        .store("settings.json")
+        .map_err(|e| format!("打开 settings.json 失败: {}", e))?;
+    store.set(key, serde_json::json!(value));
+    store.save().map_err(|e| format!("保存更新设置失败: {}", e))
+}
+
+fn current_channel(app_handle: &AppHandle) -> String {
+    read_setting(app_handle, CHANNEL_STORE_KEY, CHANNELS, "stable")
+}
+
+fn current_policy(app_handle: &AppHandle) -> String {
+    read_setting(app_handle, POLICY_STORE_KEY, POLICIES, "ask")
+}
+
+/// 按当前通道检查更新
+async fn check_channel(app_handle: &AppHandle) -> Result<Option<Update>, String> {
+    let channel = current_channel(app_handle);
+    let endpoint = if channel == "beta" {
+        BETA_ENDPOINT
+    } else {
+        STABLE_ENDPOINT
+    };
+    let url = Url::parse(endpoint).map_err(|e| e.to_string())?;
+    info!("[UPDATER] 检查更新，通道: {}，地址: {}", channel, endpoint);
+    app_handle
+        .updater_builder()
+        .endpoints(vec![url])
+        .map_err(|e| format!("设置更新地址失败: {}", e))?
+        .build()
+        .map_err(|e| format!("创建更新检查器失败: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("检查更新失败: {}", e))
+}
+
+/// 下载并安装更新，下载进度通过事件发给前端
+async fn download_and_install(app_handle: &AppHandle, update: Update) -> Result<(), String> {
+    info!("[UPDATER] 开始下载更新: {}", update.version);
+    let mut downloaded: u64 = 0;
+    let handle_for_progress = app_handle.clone();
+    update
+        .download_and_install(
+            move |chunk_length, content_length| {
+                downloaded += chunk_length as u64;
+                let _ = handle_for_progress.emit(
+                    "update-download-progress",
+                    serde_json::json!({
+                        "downloaded": downloaded,
+                        "content_length": content_length
+                    }),
+                );
+            },
+            || info!("[UPDATER] 更新下载完成"),
+        )
+        .await
+        .map_err(|e| format!("下载安装更新失败: {}", e))?;
+    let _ = app_handle.emit("update-ready", &update.version);
+    Ok(())
+}
+
+fn update_json(update: &Update) -> serde_json::Value {
+    serde_json::json!({
+        "version": update.version,
+        "notes": update.body,
+        "date": update.date.map(|date| date.to_string())
+    })
+}
+
+/// 按当前通道检查更新
+///
+/// `manual` 为 false 表示定期检查：策略为 never 时跳过，为 auto 时发现更新后在后台下载安装
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn check_for_update(
+    manual: Option<bool>,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let manual = manual.unwrap_or(true);
+    let policy = current_policy(&app_handle);
+    if !manual && policy == "never" {
+        return Ok(serde_json::json!({
+            "status": "skipped",
+            "message": "下载策略为 never，跳过定期更新检查"
+        }));
+    }
+
+    let Some(update) = check_channel(&app_handle).await? else {
+        return Ok(serde_json::json!({
+            "status": "success",
+            "available": false
+        }));
+    };
+    let info = update_json(&update);
+    info!("[UPDATER] 发现新版本: {}", update.version);
+
+    let auto_download = !manual && policy == "auto";
+    if auto_download {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = download_and_install(&app_handle, update).await {
+                error!("[UPDATER] 后台更新失败: {}", e);
+            }
+        });
+    }
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "available": true,
+        "update": info,
+        "auto_download": auto_download
+    }))
+}
+
+/// 按当前通道下载并安装更新，安装完成后需要重启应用
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn download_and_install_update(
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    let update = check_channel(&app_handle)
+        .await?
+        .ok_or_else(|| "未找到可用更新".to_string())?;
+    let version = update.version.clone();
+    download_and_install(&app_handle, update).await?;
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("更新 {} 已安装，重启后生效", version)
+    }))
+}
+
+/// 设置更新通道（stable / beta）
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_update_channel(
+    channel: String,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    if !CHANNELS.contains(&channel.as_str()) {
+        return Err(format!("未知的更新通道: {}", channel));
+    }
+    write_setting(&app_handle, CHANNEL_STORE_KEY, &channel)?;
+    info!("[UPDATER] 更新通道已设置为: {}", channel);
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("更新通道已设置为 {}", channel)
+    }))
+}
+
+/// 设置更新下载策略（auto / ask / never）
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_update_policy(
+    policy: String,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    if !POLICIES.contains(&policy.as_str()) {
+        return Err(format!("未知的更新下载策略: {}", policy));
+    }
+    write_setting(&app_handle, POLICY_STORE_KEY, &policy)?;
+    info!("[UPDATER] 更新下载策略已设置为: {}", policy);
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("更新下载策略已设置为 {}", policy)
+    }))
+}
+
+/// 获取更新通道和下载策略
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_update_settings(app_handle: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "status": "success",
+        "channel": current_channel(&app_handle),
+        "policy": current_policy(&app_handle)
+    }))
+}
@@ -0,0 +1,135 @@
+//! # 卷挂载感知 (Volume Mount Awareness)
+//!
+//! 定期读取系统的挂载点列表，卷挂载/卸载时发出 `volume-mounted` / `volume-unmounted` 事件。
+//! 文件监控线程启动时记录监控目录所在的卷，卷被卸载（移动硬盘弹出、网络卷断开）后暂停该目录的
+//! 监控，不再不断报错；重新挂载后恢复监控并重新扫描该目录，补上离线期间的变动。
+//!
+//! 挂载点来源：macOS 为 `/Volumes` 下的条目，Linux 为 `/proc/self/mounts`，Windows 为可用的盘符。
+
+use log::{error, info};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 挂载点检测间隔，监控线程检查所在卷的间隔与此相同
+pub const VOLUME_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 最近一次检测到的挂载点，None 表示尚未检测
+static MOUNT_POINTS: RwLock<Option<BTreeSet<PathBuf>>> = RwLock::new(None);
+
+#[cfg(target_os = "macos")]
+fn list_mount_points() -> BTreeSet<PathBuf> {
+    std::fs::read_dir("/Volumes")
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+fn list_mount_points() -> BTreeSet<PathBuf> {
+    // 挂载点中的空格等字符以八进制转义，如 `\040`
+    fn unescape(field: &str) -> String {
+        let mut result = String::new();
+        let mut chars = field.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                let digits: String = chars.by_ref().take(3).collect();
+                match u8::from_str_radix(&digits, 8) {
+                    Ok(byte) => result.push(byte as char),
+                    Err(_) => {
+                        result.push(c);
+                        result.push_str(&digits);
+                    }
+                }
+            } else {
+                result.push(c);
+            }
+        }
+        result
+    }
+
+    std::fs::read_to_string("/proc/self/mounts")
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| line.split_whitespace().nth(1))
+                .map(|mount_point| PathBuf::from(unescape(mount_point)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(windows)]
+fn list_mount_points() -> BTreeSet<PathBuf> {
+    (b'A'..=b'Z')
+        .map(|letter| PathBuf::from(format!("{}:\\", letter as char)))
+        .filter(|drive| drive.exists())
+        .collect()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn list_mount_points() -> BTreeSet<PathBuf> {
+    BTreeSet::new()
+}
+
+/// 路径所在卷的挂载点（最长匹配），无法判断时返回 None
+pub fn volume_root(path: &Path) -> Option<PathBuf> {
+    let guard = MOUNT_POINTS.read().unwrap();
+    guard
+        .as_ref()?
+        .iter()
+        .filter(|mount_point| path.starts_with(mount_point))
+        .max_by_key(|mount_point| mount_point.components().count())
+        .cloned()
+}
+
+/// 当前检测到的所有挂载点
+pub fn mount_points() -> Vec<PathBuf> {
+    let guard = MOUNT_POINTS.read().unwrap();
+    guard
+        .as_ref()
+        .map(|mount_points| mount_points.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// 挂载点当前是否已挂载
+pub fn is_mounted(mount_point: &Path) -> bool {
+    let guard = MOUNT_POINTS.read().unwrap();
+    guard
+        .as_ref()
+        .is_none_or(|mount_points| mount_points.contains(mount_point))
+}
+
+/// 更新挂载点列表，通知前端新挂载和已卸载的卷
+fn refresh(app_handle: &AppHandle, current: BTreeSet<PathBuf>) {
+    let previous = MOUNT_POINTS.write().unwrap().replace(current.clone());
+    let Some(previous) = previous else {
+        return;
+    };
+    for mount_point in current.difference(&previous) {
+        info!("[VOLUMES] 卷已挂载: {}", mount_point.display());
+        let _ = app_handle.emit("volume-mounted", mount_point.to_string_lossy());
+    }
+    for mount_point in previous.difference(&current) {
+        info!("[VOLUMES] 卷已卸载: {}", mount_point.display());
+        let _ = app_handle.emit("volume-unmounted", mount_point.to_string_lossy());
+    }
+}
+
+/// 读取初始挂载点并开始定期检测，应在文件监控启动前调用
+pub fn init(app_handle: &AppHandle) {
+    *MOUNT_POINTS.write().unwrap() = Some(list_mount_points());
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(VOLUME_POLL_INTERVAL).await;
+            // 网络卷可能响应很慢，放到阻塞线程中读取
+            match tokio::task::spawn_blocking(list_mount_points).await {
+                Ok(current) => refresh(&app_handle, current),
+                Err(e) => error!("[VOLUMES] 读取挂载点失败: {}", e),
+            }
+        }
+    });
+}
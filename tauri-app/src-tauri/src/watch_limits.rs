@@ -0,0 +1,64 @@
+//! # 监控数量上限 (Watch Limits)
+//!
+//! Linux 上 inotify 为每个子目录占用一个监控，数量受 `fs.inotify.max_user_watches` 限制。
+//! 大目录耗尽上限时，该目录改用轮询监控（见 `watch_registry`），并向前端发出
+//! `watch-limit-reached` 事件，说明当前上限以及如何临时和永久提高上限。
+
+use log::warn;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// inotify 耗尽时轮询监控的间隔
+pub const POLL_FALLBACK_INTERVAL: Duration = Duration::from_secs(10);
+/// 建议提高到的监控数量上限
+const SUGGESTED_MAX_USER_WATCHES: u64 = 524288;
+
+/// 读取当前的 inotify 监控数量上限
+fn current_max_user_watches() -> Option<u64> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    std::fs::read_to_string("/proc/sys/fs/inotify/max_user_watches")
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// 监控数量耗尽、目录已改用轮询时调用，通知前端如何提高上限
+pub fn report_exhausted(app_handle: Option<&AppHandle>, directory: &str) {
+    let current_limit = current_max_user_watches();
+    warn!(
+        "[WATCH_LIMIT] inotify 监控数量已达上限 ({}), 目录改用轮询监控: {}",
+        current_limit.map_or("未知".to_string(), |limit| limit.to_string()),
+        directory
+    );
+
+    let Some(app_handle) = app_handle else {
+        return;
+    };
+    let temporary = format!(
+        "sudo sysctl fs.inotify.max_user_watches={}",
+        SUGGESTED_MAX_USER_WATCHES
+    );
+    let permanent = format!(
+        "echo fs.inotify.max_user_watches={} | sudo tee /etc/sysctl.d/60-knowledge-focus.conf && sudo sysctl --system",
+        SUGGESTED_MAX_USER_WATCHES
+    );
+    let _ = app_handle.emit(
+        "watch-limit-reached",
+        serde_json::json!({
+            "directory": directory,
+            "current_limit": current_limit,
+            "suggested_limit": SUGGESTED_MAX_USER_WATCHES,
+            "poll_interval_secs": POLL_FALLBACK_INTERVAL.as_secs(),
+            "message": format!(
+                "The folder {} has too many subfolders for the system's file watch limit, so it is now checked for changes every {} seconds instead. Raise fs.inotify.max_user_watches and restart Knowledge Focus to get instant updates.",
+                directory,
+                POLL_FALLBACK_INTERVAL.as_secs()
+            ),
+            "commands": {
+                "temporary": temporary,
+                "permanent": permanent
+            }
+        }),
+    );
+}
@@ -0,0 +1,467 @@
+//! # 监控注册表 (Watch Registry)
+//!
+//! 以前每个监控目录各自创建一个 notify watcher，并占用一个永不退出的 OS 线程来保持 watcher 存活、
+//! 检测所在卷是否卸载；停止监控时线程和 watcher 都不会释放。
+//!
+//! 现在所有目录共用一个 watcher，注册表记录每个监控根目录及其防抖队列，notify 回调按最长前缀
+//! 把事件分发到所属的根目录。inotify 监控数量耗尽的目录改由一个共用的轮询 watcher 监控。
+//! 卷的挂载状态由一个异步任务统一检测。
+//!
+//! 嵌套的根目录（如同时监控 `~/Documents` 和 `~/Documents/Projects`）不再各自添加递归监控：
+//! 内层根目录由外层的 OS 监控覆盖，只保留自己的防抖队列，事件仍按最长前缀分发给它。
+//! 外层根目录移除后，内层根目录重新添加自己的 OS 监控。
+//!
+//! 移除根目录时释放其 OS 监控并关闭防抖队列；注册表被释放时 watcher 随之释放，所有监控停止。
+
+use crate::file_monitor::FileMonitor;
+use crate::path_utils::path_starts_with;
+use log::{error, info};
+use notify::event::{CreateKind, RemoveKind};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, SystemTime};
+use tauri::Emitter;
+use tokio::sync::mpsc::{self, Sender};
+
+/// 从 notify 收到、等待防抖的原始事件
+pub type RawEvent = (PathBuf, EventKind);
+
+// 补扫时向前放宽的时间，覆盖文件系统时间戳精度和事件延迟
+const OVERFLOW_RESCAN_MARGIN: Duration = Duration::from_secs(2);
+
+/// 已注册的监控根目录
+struct WatchedRoot {
+    path_str: String,
+    mode: RecursiveMode,
+    // inotify 监控数量耗尽，由轮询 watcher 监控
+    polling: bool,
+    // 所在卷已卸载，暂停监控
+    suspended: bool,
+    // 位于另一个递归监控的根目录下，没有自己的 OS 监控
+    covered: bool,
+    volume_root: Option<PathBuf>,
+    // 该目录的防抖队列，移除根目录时随之关闭
+    events: Sender<RawEvent>,
+    // 防抖队列已满、丢弃事件的最早时间，防抖任务据此补扫目录
+    overflow_since: Arc<Mutex<Option<SystemTime>>>,
+}
+
+type Roots = Arc<RwLock<HashMap<PathBuf, WatchedRoot>>>;
+
+// 路径是否位于另一个递归监控的根目录下
+fn has_recursive_ancestor(roots: &HashMap<PathBuf, WatchedRoot>, path: &Path) -> bool {
+    roots.iter().any(|(root_path, root)| {
+        root_path.as_path() != path
+            && path_starts_with(path, root_path)
+            && matches!(root.mode, RecursiveMode::Recursive)
+    })
+}
+
+/// notify 回调：把事件转发到所属根目录的防抖队列
+#[derive(Clone)]
+struct Dispatcher {
+    roots: Roots,
+    file_monitor: Arc<FileMonitor>,
+}
+
+impl Dispatcher {
+    fn dispatch(&self, path: PathBuf, kind: &EventKind) {
+        // 简化事件种类: Create, Remove 或 Modify；其他事件按文件是否存在归类
+        let processed_kind = match kind {
+            EventKind::Create(_) | EventKind::Remove(_) => kind.clone(),
+            _ => {
+                if path.exists() && path.is_file() {
+                    EventKind::Create(CreateKind::File)
+                } else {
+                    EventKind::Remove(RemoveKind::File)
+                }
+            }
+        };
+
+        let roots = self.roots.read().unwrap();
+        // 路径属于最长匹配的根目录
+        let Some(root) = roots
+            .iter()
+            .filter(|(root_path, _)| path_starts_with(path, root_path))
+            .max_by_key(|(root_path, _)| root_path.as_os_str().len())
+            .map(|(_, root)| root)
+        else {
+            return;
+        };
+
+        // 通道已满时丢弃新事件（不阻塞 notify 的回调线程），记录最早丢弃的时间，随后补扫该目录
+        match root.events.try_send((path, processed_kind)) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                self.file_monitor
+                    .update_stats(|stats| stats.dropped_events += 1);
+                root.overflow_since
+                    .lock()
+                    .unwrap()
+                    .get_or_insert_with(|| SystemTime::now() - OVERFLOW_RESCAN_MARGIN);
+            }
+            // 根目录正在移除，防抖任务已退出
+            Err(mpsc::error::TrySendError::Closed(_)) => {}
+        }
+    }
+}
+
+impl notify::EventHandler for Dispatcher {
+    fn handle_event(&mut self, event: notify::Result<notify::Event>) {
+        match event {
+            Ok(event) => {
+                for path in event.paths {
+                    self.dispatch(path, &event.kind);
+                }
+            }
+            Err(e) => error!("[WATCH_REGISTRY] 监控错误: {:?}", e),
+        }
+    }
+}
+
+struct Watchers {
+    native: Box<dyn Watcher + Send>,
+    // 第一次有目录耗尽 inotify 监控数量时创建
+    poll: Option<notify::PollWatcher>,
+}
+
+/// 所有监控目录共用的 watcher 和根目录注册表
+pub struct WatchRegistry {
+    roots: Roots,
+    watchers: Mutex<Watchers>,
+    dispatcher: Dispatcher,
+    config: notify::Config,
+    app_handle: Option<tauri::AppHandle>,
+}
+
+impl WatchRegistry {
+    /// 创建共用的 watcher，并启动卷挂载状态检测任务
+    pub fn new(
+        file_monitor: Arc<FileMonitor>,
+        app_handle: Option<tauri::AppHandle>,
+    ) -> Result<Arc<WatchRegistry>, String> {
+        let roots: Roots = Arc::new(RwLock::new(HashMap::new()));
+        let dispatcher = Dispatcher {
+            roots: roots.clone(),
+            file_monitor,
+        };
+        // 递归监控时是否跟随链接目录取决于链接策略
+        let config =
+            notify::Config::default().with_follow_symlinks(crate::link_policy::follow_links());
+        let native = notify::RecommendedWatcher::new(dispatcher.clone(), config)
+            .map_err(|e| format!("Failed to create watcher: {:?}", e))?;
+        info!(
+            "[WATCH_REGISTRY] 已创建共用 watcher (后端: {})",
+            crate::file_monitor_debounced::watcher_backend()
+        );
+
+        let registry = Arc::new(WatchRegistry {
+            roots,
+            watchers: Mutex::new(Watchers {
+                native: Box::new(native),
+                poll: None,
+            }),
+            dispatcher,
+            config,
+            app_handle,
+        });
+        tokio::spawn(Self::watch_volumes(Arc::downgrade(&registry)));
+        Ok(registry)
+    }
+
+    /// 开始监控目录，事件发送到 events；目录已在监控中时返回错误
+    ///
+    /// 目录位于已递归监控的根目录下时复用外层的 OS 监控；目录包含已监控的根目录时，
+    /// 释放这些内层根目录的 OS 监控
+    pub fn watch(
+        &self,
+        dir_path: &str,
+        events: Sender<RawEvent>,
+        overflow_since: Arc<Mutex<Option<SystemTime>>>,
+    ) -> Result<(), String> {
+        let watch_path = PathBuf::from(dir_path);
+        if self.roots.read().unwrap().contains_key(&watch_path) {
+            return Err(format!("目录已在监控中: {}", dir_path));
+        }
+
+        // macOS bundle 文件夹使用非递归模式监控
+        let mode = if FileMonitor::is_macos_bundle_folder(&watch_path) {
+            RecursiveMode::NonRecursive
+        } else {
+            RecursiveMode::Recursive
+        };
+
+        // 先注册再添加监控，添加过程中产生的事件也能分发
+        let covered = {
+            let mut roots = self.roots.write().unwrap();
+            let covered = has_recursive_ancestor(&roots, &watch_path);
+            roots.insert(
+                watch_path.clone(),
+                WatchedRoot {
+                    path_str: dir_path.to_string(),
+                    mode,
+                    polling: false,
+                    suspended: false,
+                    covered,
+                    volume_root: crate::volumes::volume_root(&watch_path),
+                    events,
+                    overflow_since,
+                },
+            );
+            covered
+        };
+        if covered {
+            info!(
+                "[WATCH_REGISTRY] ✅ {} 位于已监控的目录下，复用外层目录的监控",
+                dir_path
+            );
+            return Ok(());
+        }
+
+        let mut watchers = self.watchers.lock().unwrap();
+        let result = match watchers.native.watch(&watch_path, mode) {
+            // Linux 上 inotify 监控数量达到 fs.inotify.max_user_watches 上限，该目录改用轮询
+            Err(e) if matches!(e.kind, notify::ErrorKind::MaxFilesWatch) => {
+                crate::watch_limits::report_exhausted(self.app_handle.as_ref(), dir_path);
+                // 释放已添加的部分 inotify 监控
+                let _ = watchers.native.unwatch(&watch_path);
+                self.poll_watcher(&mut watchers)
+                    .and_then(|poll| poll.watch(&watch_path, mode))
+                    .map(|()| {
+                        if let Some(root) = self.roots.write().unwrap().get_mut(&watch_path) {
+                            root.polling = true;
+                        }
+                    })
+            }
+            result => result,
+        };
+
+        match result {
+            Ok(()) => {
+                info!(
+                    "[WATCH_REGISTRY] ✅ 成功设置监控: {} (模式: {:?})",
+                    dir_path, mode
+                );
+                drop(watchers);
+                if matches!(mode, RecursiveMode::Recursive) {
+                    self.cover_nested(&watch_path);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.roots.write().unwrap().remove(&watch_path);
+                Err(format!("Failed to watch: {:?}", e))
+            }
+        }
+    }
+
+    fn poll_watcher<'a>(
+        &self,
+        watchers: &'a mut Watchers,
+    ) -> notify::Result<&'a mut notify::PollWatcher> {
+        if watchers.poll.is_none() {
+            let config = self
+                .config
+                .with_poll_interval(crate::watch_limits::POLL_FALLBACK_INTERVAL);
+            watchers.poll = Some(notify::PollWatcher::new(self.dispatcher.clone(), config)?);
+        }
+        Ok(watchers.poll.as_mut().unwrap())
+    }
+
+    /// 停止监控目录：释放 OS 监控并关闭其防抖队列；目录不在监控中时返回 false
+    pub fn unwatch(&self, dir_path: &str) -> bool {
+        let watch_path = PathBuf::from(dir_path);
+        let Some(root) = self.roots.write().unwrap().remove(&watch_path) else {
+            return false;
+        };
+        if !root.suspended && !root.covered {
+            let mut watchers = self.watchers.lock().unwrap();
+            let result = if root.polling {
+                watchers
+                    .poll
+                    .as_mut()
+                    .map_or(Ok(()), |poll| poll.unwatch(&watch_path))
+            } else {
+                watchers.native.unwatch(&watch_path)
+            };
+            if let Err(e) = result {
+                error!("[WATCH_REGISTRY] 释放监控失败: {} - {:?}", dir_path, e);
+            }
+        }
+        info!("[WATCH_REGISTRY] 已停止监控: {}", dir_path);
+        if !root.covered && matches!(root.mode, RecursiveMode::Recursive) {
+            self.uncover_nested(&watch_path);
+        }
+        true
+    }
+
+    // 新的递归根目录包含已监控的根目录：释放内层根目录的 OS 监控，由外层覆盖
+    fn cover_nested(&self, parent: &Path) {
+        let nested: Vec<(PathBuf, String, RecursiveMode, bool, bool)> = self
+            .roots
+            .write()
+            .unwrap()
+            .iter_mut()
+            .filter(|(path, root)| {
+                path.as_path() != parent && path_starts_with(path, parent) && !root.covered
+            })
+            .map(|(path, root)| {
+                root.covered = true;
+                (
+                    path.clone(),
+                    root.path_str.clone(),
+                    root.mode,
+                    root.polling,
+                    root.suspended,
+                )
+            })
+            .collect();
+
+        for (watch_path, path_str, mode, polling, suspended) in nested {
+            if !suspended {
+                if let Err(e) = self.set_watching(&watch_path, mode, polling, false) {
+                    error!("[WATCH_REGISTRY] 释放监控失败: {} - {:?}", path_str, e);
+                }
+            }
+            info!(
+                "[WATCH_REGISTRY] {} 已由外层目录 {:?} 覆盖，释放其监控",
+                path_str, parent
+            );
+        }
+    }
+
+    // 外层根目录移除后，不再被任何根目录覆盖的内层根目录重新添加 OS 监控
+    fn uncover_nested(&self, parent: &Path) {
+        let orphaned: Vec<(PathBuf, String, RecursiveMode, bool)> = {
+            let roots = self.roots.read().unwrap();
+            roots
+                .iter()
+                .filter(|(path, root)| {
+                    root.covered
+                        && path_starts_with(path, parent)
+                        && !has_recursive_ancestor(&roots, path)
+                })
+                .map(|(path, root)| (path.clone(), root.path_str.clone(), root.mode, root.polling))
+                .collect()
+        };
+
+        for (watch_path, path_str, mode, polling) in orphaned {
+            let result = self.set_watching(&watch_path, mode, polling, true);
+            if let Err(ref e) = result {
+                // 交给卷检测任务稍后重试
+                error!(
+                    "[WATCH_REGISTRY] 恢复内层目录监控失败，稍后重试: {} - {:?}",
+                    path_str, e
+                );
+            } else {
+                info!("[WATCH_REGISTRY] 外层目录已移除，恢复监控: {}", path_str);
+            }
+            if let Some(root) = self.roots.write().unwrap().get_mut(&watch_path) {
+                root.covered = false;
+                root.suspended = result.is_err();
+            }
+        }
+    }
+
+    /// 正在监控的根目录
+    pub fn watched_roots(&self) -> Vec<String> {
+        self.roots
+            .read()
+            .unwrap()
+            .values()
+            .map(|root| root.path_str.clone())
+            .collect()
+    }
+
+    // 对单个根目录添加或释放 OS 监控（卷卸载和重新挂载时使用）
+    fn set_watching(
+        &self,
+        watch_path: &Path,
+        mode: RecursiveMode,
+        polling: bool,
+        on: bool,
+    ) -> notify::Result<()> {
+        let mut watchers = self.watchers.lock().unwrap();
+        let watcher: &mut dyn Watcher = if polling {
+            self.poll_watcher(&mut watchers)?
+        } else {
+            watchers.native.as_mut()
+        };
+        if on {
+            watcher.watch(watch_path, mode)
+        } else {
+            watcher.unwatch(watch_path)
+        }
+    }
+
+    /// 定期检测各根目录所在的卷，卷被卸载时暂停监控，重新挂载后恢复并补扫；注册表释放后退出
+    async fn watch_volumes(registry: Weak<WatchRegistry>) {
+        loop {
+            tokio::time::sleep(crate::volumes::VOLUME_POLL_INTERVAL).await;
+            let Some(registry) = registry.upgrade() else {
+                return;
+            };
+
+            let roots: Vec<(PathBuf, String, RecursiveMode, bool, bool, Option<PathBuf>)> =
+                registry
+                    .roots
+                    .read()
+                    .unwrap()
+                    .iter()
+                    // 被覆盖的根目录随外层根目录暂停和恢复
+                    .filter(|(_, root)| !root.covered)
+                    .map(|(path, root)| {
+                        (
+                            path.clone(),
+                            root.path_str.clone(),
+                            root.mode,
+                            root.polling,
+                            root.suspended,
+                            root.volume_root.clone(),
+                        )
+                    })
+                    .collect();
+
+            for (watch_path, path_str, mode, polling, suspended, volume_root) in roots {
+                let mounted = volume_root
+                    .as_deref()
+                    .is_none_or(crate::volumes::is_mounted);
+                let resume = if !suspended && !mounted {
+                    info!(
+                        "[WATCH_REGISTRY] 所在卷已卸载，暂停监控: {} (卷: {:?})",
+                        path_str, volume_root
+                    );
+                    let _ = registry.set_watching(&watch_path, mode, polling, false);
+                    false
+                } else if suspended && mounted && watch_path.exists() {
+                    match registry.set_watching(&watch_path, mode, polling, true) {
+                        Ok(()) => true,
+                        Err(e) => {
+                            error!("[WATCH_REGISTRY] 恢复监控失败，稍后重试: {:?}", e);
+                            continue;
+                        }
+                    }
+                } else {
+                    continue;
+                };
+
+                if let Some(root) = registry.roots.write().unwrap().get_mut(&watch_path) {
+                    root.suspended = !resume;
+                }
+                let Some(ref app_handle) = registry.app_handle else {
+                    continue;
+                };
+                if resume {
+                    info!("[WATCH_REGISTRY] 所在卷已重新挂载，恢复监控: {}", path_str);
+                    let _ = app_handle.emit("watch-resumed", &path_str);
+                    // 重新扫描，补上卷离线期间的变动
+                    crate::launch_args::queue_scan(app_handle, &path_str);
+                } else {
+                    let _ = app_handle.emit("watch-suspended", &path_str);
+                }
+            }
+        }
+    }
+}
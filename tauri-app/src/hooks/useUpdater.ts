@@ -1,11 +1,20 @@
 import { useCallback, useEffect } from 'react';
-import { check } from '@tauri-apps/plugin-updater';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { relaunch } from '@tauri-apps/plugin-process';
 import { useAppStore } from '@/main';
 import { toast } from 'sonner';
 
 const UPDATE_CHECK_INTERVAL = 24 * 60 * 60 * 1000; // 24小时（毫秒）
 
+// 更新检查和下载由后端按更新通道（stable/beta）和下载策略（auto/ask/never）执行，见 src-tauri/src/update_channel.rs
+interface UpdateCheckResult {
+  status: 'success' | 'skipped';
+  available?: boolean;
+  update?: { version: string; notes: string | null; date: string | null };
+  auto_download?: boolean;
+}
+
 export const useUpdater = () => {
   const {
     updateAvailable,
@@ -29,26 +38,18 @@ export const useUpdater = () => {
   const checkForUpdates = useCallback(async (manual = false) => {
     try {
       console.log('[更新检查] 开始检查更新...');
-      console.log('[更新检查] 配置信息:', {
-        endpoints: ['https://github.com/huozhong-in/knowledge-focus/releases/latest/download/latest.json'],
-        timeout: 30000
-      });
       
       // 重置之前的状态
       if (manual) {
         resetUpdateState();
       }
       
-      // 配置代理和超时
-      const update = await check({
-        timeout: 30000, // 30秒超时
-        headers: {
-          'User-Agent': 'KnowledgeFocus-Updater/1.0',
-          'Cache-Control': 'no-cache'
-        }
-        // 如果需要代理，可以添加这个配置
-        // proxy: 'http://127.0.0.1:7890'
-      });
+      const result = await invoke<UpdateCheckResult>('check_for_update', { manual });
+      if (result.status === 'skipped') {
+        console.log('[更新检查] 下载策略为 never，跳过定期检查');
+        return null;
+      }
+      const update = result.available ? result.update ?? null : null;
       const now = Date.now();
       
       console.log('[更新检查] 检查完成，结果:', update ? '发现更新' : '无更新');
@@ -59,9 +60,13 @@ export const useUpdater = () => {
       if (update) {
         console.log(`[更新检查] 发现新版本: ${update.version}`);
         console.log(`[更新检查] 发布日期: ${update.date}`);
-        console.log(`[更新检查] 更新说明: ${update.body}`);
+        console.log(`[更新检查] 更新说明: ${update.notes}`);
         
-        setUpdateAvailable(true, update.version, update.body);
+        setUpdateAvailable(true, update.version, update.notes ?? undefined);
+        if (result.auto_download) {
+          console.log('[更新检查] 下载策略为 auto，后台下载更新');
+          setIsDownloading(true);
+        }
         
         if (manual) {
             toast.success(`New version ${update.version} found!`);
@@ -109,7 +114,7 @@ export const useUpdater = () => {
       
       return null;
     }
-  }, [setUpdateAvailable, setLastUpdateCheck, setUpdateError, resetUpdateState]);
+  }, [setUpdateAvailable, setIsDownloading, setLastUpdateCheck, setUpdateError, resetUpdateState]);
 
   // 下载并安装更新
   const downloadAndInstall = useCallback(async () => {
@@ -119,40 +124,8 @@ export const useUpdater = () => {
       setDownloadProgress(0);
       setUpdateError(null);
       
-      const update = await check();
-      if (!update) {
-        throw new Error('未找到可用更新');
-      }
-      
-      let downloaded = 0;
-      let contentLength = 0;
-      
-      // 开始下载并安装
-      await update.downloadAndInstall((event) => {
-        switch (event.event) {
-          case 'Started':
-            contentLength = event.data.contentLength || 0;
-            console.log(`[更新下载] 开始下载 ${Math.round(contentLength / 1024 / 1024 * 100) / 100} MB`);
-            break;
-            
-          case 'Progress':
-            downloaded += event.data.chunkLength;
-            const progress = Math.round((downloaded / contentLength) * 100);
-            setDownloadProgress(progress);
-            console.log(`[更新下载] 进度: ${progress}% (${Math.round(downloaded / 1024 / 1024 * 100) / 100}MB / ${Math.round(contentLength / 1024 / 1024 * 100) / 100}MB)`);
-            break;
-            
-          case 'Finished':
-            console.log('[更新下载] 下载完成');
-            setDownloadProgress(100);
-            break;
-        }
-      });
-      
-      console.log('[更新安装] 更新安装完成');
-      setIsDownloading(false);
-      setIsReadyToInstall(true);
-      toast.success('Update downloaded successfully! Click restart to complete the update.');
+      // 进度和完成状态由 update-download-progress / update-ready 事件更新
+      await invoke('download_and_install_update');
       
     } catch (error) {
       console.error('[更新下载] 下载更新失败:', error);
@@ -163,6 +136,29 @@ export const useUpdater = () => {
     }
   }, [setIsDownloading, setDownloadProgress, setIsReadyToInstall, setUpdateError]);
 
+  // 下载进度和安装完成（手动下载和 auto 策略的后台下载共用）
+  useEffect(() => {
+    const unlistenProgress = listen<{ downloaded: number; content_length: number | null }>(
+      'update-download-progress',
+      ({ payload }) => {
+        if (payload.content_length) {
+          setDownloadProgress(Math.round((payload.downloaded / payload.content_length) * 100));
+        }
+      }
+    );
+    const unlistenReady = listen<string>('update-ready', ({ payload }) => {
+      console.log(`[更新安装] 更新 ${payload} 安装完成`);
+      setDownloadProgress(100);
+      setIsDownloading(false);
+      setIsReadyToInstall(true);
+      toast.success('Update downloaded successfully! Click restart to complete the update.');
+    });
+    return () => {
+      unlistenProgress.then((fn) => fn());
+      unlistenReady.then((fn) => fn());
+    };
+  }, [setDownloadProgress, setIsDownloading, setIsReadyToInstall]);
+
   // 重启应用
   const restartApp = useCallback(async () => {
     try {
@@ -5,6 +5,8 @@ import { load } from '@tauri-apps/plugin-store';
 import { TrayIcon } from '@tauri-apps/api/tray';
 import { resourceDir, join, appDataDir } from '@tauri-apps/api/path';
 import App from "./App";
+import { QuickSearch } from "./quick-search";
+import { SettingsWindow } from "./settings-window";
 import { setupI18nWithStore } from './i18n';
 import { ThemeProvider } from "./tweakcn/components/theme-provider";
 
@@ -153,11 +155,18 @@ export const useAppStore = create<AppGlobalState>((set, _get) => ({
   })
 }));
 
+// 快速搜索窗口（由全局快捷键呼出）只渲染搜索框，独立设置窗口只渲染设置界面
+const windowKind = new URLSearchParams(window.location.search).get('window');
+const isQuickSearchWindow = windowKind === 'quick-search';
+const isSettingsWindow = windowKind === 'settings';
+
 // 初始化检查是否首次启动
 const initializeApp = async () => {
   try {
     // 初始化系统托盘图标
-    await setTrayIcon();
+    if (!isQuickSearchWindow && !isSettingsWindow) {
+      await setTrayIcon();
+    }
     
     const appDataPath = await appDataDir();
     const storePath = await join(appDataPath, 'settings.json');
@@ -188,7 +197,7 @@ const initializeApp = async () => {
     ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
       <React.StrictMode>
         <ThemeProvider>
-          <App />
+          {isQuickSearchWindow ? <QuickSearch /> : isSettingsWindow ? <SettingsWindow /> : <App />}
         </ThemeProvider>
       </React.StrictMode>
     );
@@ -0,0 +1,91 @@
+import { useEffect, useRef, useState } from "react";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { listen } from "@tauri-apps/api/event";
+import { revealItemInDir } from "@tauri-apps/plugin-opener";
+import { Input } from "./components/ui/input";
+import { FileService } from "./api/file-service";
+import { TaggedFile } from "./types/file-types";
+
+/**
+ * 快速搜索窗口：由全局快捷键呼出（见 src-tauri/src/quick_search.rs），
+ * 按路径关键字搜索已索引的文件，回车在访达/资源管理器中显示选中的文件，Esc 隐藏窗口。
+ * knowledgefocus://search?q= 深度链接通过页面地址的 q 参数或 quick-search-query 事件传入关键字。
+ */
+export function QuickSearch() {
+  const [query, setQuery] = useState(() => new URLSearchParams(window.location.search).get("q") ?? "");
+  const [results, setResults] = useState<TaggedFile[]>([]);
+  const [selected, setSelected] = useState(0);
+  const inputRef = useRef<HTMLInputElement>(null);
+
+  // 每次窗口获得焦点时选中输入框内容，方便直接输入新的关键字
+  useEffect(() => {
+    const unlisten = getCurrentWindow().onFocusChanged(({ payload: focused }) => {
+      if (focused) {
+        inputRef.current?.focus();
+        inputRef.current?.select();
+      }
+    });
+    const unlistenQuery = listen<string>("quick-search-query", ({ payload }) => setQuery(payload));
+    return () => {
+      unlisten.then((fn) => fn());
+      unlistenQuery.then((fn) => fn());
+    };
+  }, []);
+
+  useEffect(() => {
+    const keyword = query.trim();
+    if (!keyword) {
+      setResults([]);
+      return;
+    }
+    const timer = setTimeout(async () => {
+      try {
+        setResults(await FileService.searchFilesByPath(keyword, 20));
+        setSelected(0);
+      } catch (error) {
+        console.error("快速搜索失败:", error);
+      }
+    }, 200);
+    return () => clearTimeout(timer);
+  }, [query]);
+
+  const handleKeyDown = async (event: React.KeyboardEvent) => {
+    if (event.key === "Escape") {
+      await getCurrentWindow().hide();
+    } else if (event.key === "ArrowDown") {
+      event.preventDefault();
+      setSelected((index) => Math.min(index + 1, results.length - 1));
+    } else if (event.key === "ArrowUp") {
+      event.preventDefault();
+      setSelected((index) => Math.max(index - 1, 0));
+    } else if (event.key === "Enter" && results[selected]) {
+      await revealItemInDir(results[selected].path);
+      await getCurrentWindow().hide();
+    }
+  };
+
+  return (
+    <div className="flex h-screen flex-col gap-2 bg-background p-3" onKeyDown={handleKeyDown}>
+      <Input
+        ref={inputRef}
+        autoFocus
+        value={query}
+        placeholder="Search indexed files…"
+        onChange={(event) => setQuery(event.target.value)}
+      />
+      <ul className="flex-1 overflow-y-auto">
+        {results.map((file, index) => (
+          <li
+            key={file.id}
+            className={`cursor-pointer rounded px-2 py-1 text-sm ${index === selected ? "bg-accent" : ""}`}
+            onMouseEnter={() => setSelected(index)}
+            onClick={() => revealItemInDir(file.path)}
+          >
+            <div className="truncate font-medium">{file.file_name}</div>
+            <div className="truncate text-xs text-muted-foreground">{file.path}</div>
+          </li>
+        ))}
+      </ul>
+    </div>
+  );
+}
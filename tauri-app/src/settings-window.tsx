@@ -0,0 +1,40 @@
+import { useEffect, useRef } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { useSettingsStore, SETTINGS_PAGES, SettingsPageId } from "./App";
+import { SettingsDialog } from "./settings-dialog";
+
+const SETTINGS_PAGE_IDS = Object.values(SETTINGS_PAGES) as string[];
+
+function toSettingsPage(section: string | null): SettingsPageId {
+  return SETTINGS_PAGE_IDS.includes(section ?? "") ? (section as SettingsPageId) : SETTINGS_PAGES.GENERAL;
+}
+
+/**
+ * 独立设置窗口（由 open_settings_window 命令创建，见 src-tauri/src/settings_window.rs）。
+ * 复用设置对话框，对话框关闭时关闭整个窗口；窗口已打开时通过 settings-window-section 事件切换页面。
+ */
+export function SettingsWindow() {
+  const { isSettingsOpen, openSettingsPage } = useSettingsStore();
+  const wasOpen = useRef(false);
+
+  useEffect(() => {
+    openSettingsPage(toSettingsPage(new URLSearchParams(window.location.search).get("section")));
+    const unlisten = listen<string>("settings-window-section", ({ payload }) => {
+      openSettingsPage(toSettingsPage(payload));
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [openSettingsPage]);
+
+  useEffect(() => {
+    if (isSettingsOpen) {
+      wasOpen.current = true;
+    } else if (wasOpen.current) {
+      getCurrentWindow().close();
+    }
+  }, [isSettingsOpen]);
+
+  return <SettingsDialog />;
+}